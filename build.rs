@@ -24,11 +24,14 @@ Alternatively, the `GITHUB_TOKEN` environment variable can be set to a personal
 However, use of `GITHUB_TOKEN_PATH` is recommended as it is less likely to leak the token.
 
 If neither `GITHUB_TOKEN_PATH` nor `GITHUB_TOKEN` is set, but a file exists at {TOKEN_PATH}, \
-cargo-unmaintained will use that file's contents as a personal access token.
+cargo-unmaintained will use that file's contents as a personal access token. Failing that, if the \
+GitHub CLI is installed, cargo-unmaintained will use the token reported by `gh auth token`, and \
+then, if built with the `keyring` feature, a token stored in the system keychain. Pass \
+--token-source to use only one of these sources, or none of them.
 
 Unless --no-exit-code is passed, the exit status is 0 if no unmaintained packages were found and \
-no irrecoverable errors occurred, 1 if unmaintained packages were found, and 2 if an irrecoverable \
-error occurred.";
+no errors occurred, 1 if unmaintained packages were found or some packages could not be checked \
+(e.g., because of an error specific to those packages), and 2 if an irrecoverable error occurred.";
 "#,
     );
     write(path_buf, contents).unwrap();
@@ -19,7 +19,7 @@ use rustsec_util::{
 
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
 fn main() -> Result<()> {
-    github_util::load_token(octocrab_util::load_token)?;
+    github_util::load_token(github_util::TokenSource::Auto, octocrab_util::load_token)?;
 
     let mut issues = Vec::new();
     octocrab_util::RT.block_on(async {
@@ -56,7 +56,7 @@ fn main() -> Result<()> {
                     .any(|label| label.name == "Unmaintained")
             {
                 return None;
-            };
+            }
             let mut urls = issue.body.as_deref().map(extract_urls).unwrap_or_default();
             if urls.is_empty() {
                 return None;
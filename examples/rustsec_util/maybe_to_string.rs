@@ -1,5 +1,6 @@
 use strum_macros::EnumIter;
 
+#[allow(dead_code)]
 #[derive(EnumIter, Eq, PartialEq)]
 pub enum Unit {
     Unit,
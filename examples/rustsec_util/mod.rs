@@ -91,7 +91,7 @@ static CARGO_UNMAINTAINED: Lazy<PathBuf> = Lazy::new(|| {
 #[must_use]
 pub fn cargo_unmaintained(name: &str) -> Command {
     let mut command = Command::new(&*CARGO_UNMAINTAINED);
-    command.args(["unmaintained", "--fail-fast", "-p", name]);
+    command.args(["unmaintained", "--fail-fast", "--latest", "-p", name]);
     command
 }
 
@@ -0,0 +1,196 @@
+//! `--compare-rustsec`: compare findings with the advisory database's unmaintained advisories
+
+use super::{from_crates_io, ignored_advisories, ignored_packages, opts, UnmaintainedPkg};
+use anyhow::{anyhow, Result};
+use cargo_metadata::{semver::Version, Metadata};
+use rustsec::{advisory::Informational, Database};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+const ADVISORY_URL_PREFIX: &str = "https://rustsec.org/advisories/";
+
+#[derive(Default, Serialize)]
+struct Comparison {
+    both: Vec<Entry>,
+    advisory_only: Vec<Entry>,
+    heuristic_only: Vec<Entry>,
+    /// Advisories disregarded because of `--ignore-advisory` or
+    /// `workspace.metadata.unmaintained.ignore_advisories`
+    suppressed: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    pub(crate) version: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) advisory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) repository: Option<String>,
+}
+
+/// Shows which of the workspace's packages are flagged by an unmaintained advisory, by
+/// cargo-unmaintained, or by both
+pub(crate) fn compare_rustsec(
+    metadata: &Metadata,
+    unmaintained_pkgs: &[UnmaintainedPkg],
+) -> Result<()> {
+    let database = if let Some(path) = &opts::get().rustsec_db {
+        Database::open(path)
+    } else {
+        Database::fetch()
+    }
+    .map_err(|error| anyhow!("failed to load advisory database: {error}"))?;
+
+    let mut ignored_advisories = ignored_advisories(metadata)?;
+    ignored_advisories.extend(opts::get().ignore_advisory.iter().cloned());
+
+    let (advised, suppressed, unused) =
+        suppress(advised_pkgs(&database, metadata)?, &ignored_advisories);
+    for id in unused {
+        crate::warn!(
+            "advisory `{}` is ignored, but applies to none of the workspace's packages",
+            id
+        );
+    }
+    let flagged = unmaintained_pkgs
+        .iter()
+        .map(|unmaintained_pkg| Entry {
+            name: unmaintained_pkg.pkg.name.clone(),
+            version: unmaintained_pkg.pkg.version.clone(),
+            advisory: None,
+            repository: unmaintained_pkg
+                .repo_age
+                .url()
+                .map(|url| url.as_str().to_owned()),
+        })
+        .collect();
+
+    let mut comparison = compare(advised, flagged);
+    comparison.suppressed = suppressed;
+
+    if opts::get().json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+    } else {
+        display_section("Flagged by both", &comparison.both);
+        display_section("Flagged by an advisory only", &comparison.advisory_only);
+        display_section(
+            "Flagged by cargo-unmaintained only",
+            &comparison.heuristic_only,
+        );
+        // smoelius: Suppressed advisories are listed so that they do not go unnoticed, e.g., after
+        // a package's repository is archived.
+        if !comparison.suppressed.is_empty() {
+            display_section("Suppressed advisories", &comparison.suppressed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the workspace's packages that an unmaintained advisory applies to
+///
+/// As with a scan, workspace members and ignored packages are not considered. Advisories concern
+/// crates.io packages. So packages from other sources are not considered either.
+fn advised_pkgs(database: &Database, metadata: &Metadata) -> Result<Vec<Entry>> {
+    let ignored_packages = ignored_packages(metadata)?;
+    let mut advised = BTreeSet::new();
+    for pkg in &metadata.packages {
+        if metadata.workspace_members.contains(&pkg.id)
+            || ignored_packages.contains(&pkg.name)
+            || !from_crates_io(pkg)
+        {
+            continue;
+        }
+        for advisory in database.iter() {
+            if advisory.metadata.package.as_str() == pkg.name
+                && advisory.metadata.informational == Some(Informational::Unmaintained)
+                && advisory.metadata.withdrawn.is_none()
+                && advisory.versions.is_vulnerable(&pkg.version)
+            {
+                advised.insert(Entry {
+                    name: pkg.name.clone(),
+                    version: pkg.version.clone(),
+                    advisory: Some(format!("{ADVISORY_URL_PREFIX}{}", advisory.id())),
+                    repository: None,
+                });
+            }
+        }
+    }
+    Ok(advised.into_iter().collect())
+}
+
+/// Partitions `advised` by whether their advisories are in `ignored_advisories`, and returns the
+/// ignored advisories that apply to none of them
+///
+/// Only the advisories are suppressed. A package with a suppressed advisory can still be flagged by
+/// cargo-unmaintained.
+pub(crate) fn suppress(
+    advised: Vec<Entry>,
+    ignored_advisories: &BTreeSet<String>,
+) -> (Vec<Entry>, Vec<Entry>, Vec<&str>) {
+    let is_ignored = |entry: &Entry, id: &str| {
+        entry
+            .advisory
+            .as_deref()
+            .and_then(|url| url.strip_prefix(ADVISORY_URL_PREFIX))
+            == Some(id)
+    };
+    let unused = ignored_advisories
+        .iter()
+        .map(String::as_str)
+        .filter(|id| !advised.iter().any(|entry| is_ignored(entry, id)))
+        .collect();
+    let (suppressed, advised) = advised
+        .into_iter()
+        .partition(|entry| ignored_advisories.iter().any(|id| is_ignored(entry, id)));
+    (advised, suppressed, unused)
+}
+
+/// Partitions packages by whether they are `advised`, `flagged`, or both
+///
+/// Packages are matched by name, because cargo-unmaintained checks only the latest version of each
+/// package that a workspace uses, whereas an advisory can apply to any of them.
+fn compare(advised: Vec<Entry>, flagged: Vec<Entry>) -> Comparison {
+    let mut comparison = Comparison::default();
+    for entry in advised {
+        if let Some(flagged_entry) = flagged
+            .iter()
+            .find(|flagged_entry| flagged_entry.name == entry.name)
+        {
+            comparison.both.push(Entry {
+                repository: flagged_entry.repository.clone(),
+                ..entry
+            });
+        } else {
+            comparison.advisory_only.push(entry);
+        }
+    }
+    for entry in flagged {
+        if !comparison
+            .both
+            .iter()
+            .any(|both_entry| both_entry.name == entry.name)
+        {
+            comparison.heuristic_only.push(entry);
+        }
+    }
+    comparison.heuristic_only.sort();
+    comparison
+}
+
+fn display_section(title: &str, entries: &[Entry]) {
+    println!("{title} ({}):", entries.len());
+    for entry in entries {
+        let urls = [&entry.advisory, &entry.repository]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if urls.is_empty() {
+            println!("    {}@{}", entry.name, entry.version);
+        } else {
+            println!("    {}@{} ({})", entry.name, entry.version, urls.join(", "));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Allowlists of repository patterns
+//!
+//! A package whose repository matches an allowlisted pattern is not reported solely because its
+//! repository is stale. The built-in allowlist is compiled in from `allowlist.txt`, and can be
+//! disabled with `--no-builtin-allowlist`. A workspace can give further patterns in
+//! `workspace.metadata.unmaintained.allowlist`.
+//!
+//! Packages not reported because of an allowlist are recorded, so that a JSON report can list them.
+
+use super::{canonical_url, opts};
+use anyhow::{bail, ensure, Result};
+use cargo_metadata::semver::Version;
+use once_cell::sync::Lazy;
+use std::{
+    fmt::{Display, Formatter},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+const BUILTIN: &str = include_str!("allowlist.txt");
+
+#[allow(clippy::unwrap_used)]
+static BUILTIN_PATTERNS: Lazy<Vec<Pattern>> = Lazy::new(|| parse(BUILTIN).unwrap());
+
+static ALLOWLISTED: Mutex<Vec<Allowlisted>> = Mutex::new(Vec::new());
+
+/// A package not reported because its repository matched an allowlisted pattern
+#[derive(Clone, Debug)]
+pub(crate) struct Allowlisted {
+    pub name: String,
+    pub version: Version,
+    pub repository: String,
+    pub pattern: String,
+    /// Where the pattern came from, e.g., "the built-in allowlist"
+    pub origin: &'static str,
+}
+
+/// A url followed by `/*`, which matches the repositories under the url
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Pattern {
+    /// The pattern as written
+    pattern: String,
+    /// The canonical form of the url preceding `/*`
+    prefix: String,
+}
+
+impl Pattern {
+    pub(crate) fn new(pattern: &str) -> Result<Self> {
+        let Some(url) = pattern.strip_suffix("/*") else {
+            bail!("allowlist pattern `{}` does not end with `/*`", pattern);
+        };
+        ensure!(
+            url.contains("://"),
+            "allowlist pattern `{}` does not begin with a url",
+            pattern
+        );
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            prefix: canonical_url(url),
+        })
+    }
+
+    pub(crate) fn matches(&self, url: &str) -> bool {
+        canonical_url(url)
+            .strip_prefix(&self.prefix)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .is_some_and(|rest| !rest.is_empty())
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Parses one pattern per line, ignoring blank lines and lines beginning with `#`
+pub(crate) fn parse(contents: &str) -> Result<Vec<Pattern>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Pattern::new)
+        .collect()
+}
+
+/// Returns true if the built-in allowlist is in effect
+pub(crate) fn builtin_enabled() -> bool {
+    !opts::get().no_builtin_allowlist
+}
+
+/// Returns the first pattern that `url` matches, and where the pattern came from
+///
+/// The built-in patterns are considered only if [`builtin_enabled`] returns true.
+pub(crate) fn matching_pattern<'a>(
+    workspace_patterns: &'a [Pattern],
+    url: &str,
+) -> Option<(&'a Pattern, &'static str)> {
+    let builtin_patterns: &'static [Pattern] = if builtin_enabled() {
+        &BUILTIN_PATTERNS
+    } else {
+        &[]
+    };
+    builtin_patterns
+        .iter()
+        .map(|pattern| (pattern, "the built-in allowlist"))
+        .chain(
+            workspace_patterns
+                .iter()
+                .map(|pattern| (pattern, "workspace metadata")),
+        )
+        .find(|(pattern, _)| pattern.matches(url))
+}
+
+/// Records that a package was not reported because of an allowlist
+pub(crate) fn record(allowlisted: Allowlisted) {
+    lock().push(allowlisted);
+}
+
+/// Returns the packages recorded so far
+pub(crate) fn collected() -> Vec<Allowlisted> {
+    lock().clone()
+}
+
+/// Forgets the packages recorded so far, e.g., before a scan that `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    lock().clear();
+}
+
+fn lock() -> MutexGuard<'static, Vec<Allowlisted>> {
+    ALLOWLISTED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+pub(crate) fn builtin() -> Result<Vec<Pattern>> {
+    parse(BUILTIN)
+}
@@ -0,0 +1,280 @@
+//! `--publish-anomalies`: find packages whose crates.io publish histories warrant a closer look
+//!
+//! A finding does not mean that a package is compromised. The findings are hints for supply-chain
+//! reviewers, and they do not affect the exit status.
+
+use super::{days, from_crates_io, opts, serialize::SerializablePublishAnomaly, versions};
+use anyhow::Result;
+use cargo_metadata::{semver::Version, Package};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write, time::SystemTime};
+
+/// The number of earlier releases with known publishers needed to detect a publisher change
+///
+/// With fewer, a "change" is too likely to be, e.g., a second maintainer's first release.
+const PUBLISHER_HISTORY_MIN: usize = 2;
+
+const SECS_PER_MINUTE: u64 = 60;
+
+pub(crate) struct PublishAnomaly<'a> {
+    pub pkg: &'a Package,
+    pub anomaly: Anomaly,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// The latest releases were published within `window` minutes of each other, after no release
+    /// for `gap` days
+    Burst {
+        versions: Vec<Version>,
+        window: u64,
+        gap: u64,
+    },
+    /// `version` was published by `publisher`, who published none of the earlier versions
+    PublisherChange {
+        version: Version,
+        publisher: String,
+        usual_publisher: String,
+    },
+    /// `version` was published `window` minutes after `yanked`, which was yanked
+    Republished {
+        version: Version,
+        yanked: Version,
+        window: u64,
+    },
+}
+
+/// A release, as far as the heuristics are concerned
+pub(crate) struct Release {
+    pub version: Version,
+    pub created_at: SystemTime,
+    pub publisher: Option<String>,
+    pub yanked: bool,
+}
+
+pub(crate) struct Thresholds {
+    pub burst_size: usize,
+    pub burst_gap: u64, // seconds
+    pub window: u64,    // seconds
+}
+
+impl Thresholds {
+    fn from_opts() -> Self {
+        Self {
+            burst_size: usize::try_from(opts::get().publish_burst_size).unwrap_or(usize::MAX),
+            burst_gap: days::to_secs(opts::get().publish_burst_gap),
+            window: opts::get().publish_burst_window * SECS_PER_MINUTE,
+        }
+    }
+}
+
+/// Returns the anomalies in the publish histories of `packages`
+pub(crate) fn publish_anomalies<'a>(packages: &[&'a Package]) -> Result<Vec<PublishAnomaly<'a>>> {
+    let thresholds = Thresholds::from_opts();
+
+    let mut publish_anomalies = Vec::new();
+    for &pkg in packages {
+        // smoelius: Only crates.io records publish histories.
+        if !from_crates_io(pkg) {
+            continue;
+        }
+        let versions = match versions(&pkg.name) {
+            Ok(versions) => versions,
+            Err(error) if !opts::get().require_crates_io => {
+                crate::warn!(
+                    "failed to fetch versions of `{}`; cannot check its publish history: {}",
+                    pkg.name,
+                    error
+                );
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let releases = versions
+            .iter()
+            .filter_map(|version| {
+                Some(Release {
+                    version: Version::parse(&version.num).ok()?,
+                    created_at: version.created_at,
+                    publisher: version.published_by.clone(),
+                    yanked: version.yanked,
+                })
+            })
+            .collect::<Vec<_>>();
+        publish_anomalies.extend(
+            anomalies(&releases, &pkg.version, &thresholds)
+                .into_iter()
+                .map(|anomaly| PublishAnomaly { pkg, anomaly }),
+        );
+    }
+
+    Ok(publish_anomalies)
+}
+
+/// Returns the anomalies in `releases`, a package's publish history, given that `version_used` is
+/// the version in use
+pub(crate) fn anomalies(
+    releases: &[Release],
+    version_used: &Version,
+    thresholds: &Thresholds,
+) -> Vec<Anomaly> {
+    let mut releases = releases.iter().collect::<Vec<_>>();
+    releases.sort_by_key(|release| release.created_at);
+
+    let mut anomalies = Vec::new();
+
+    anomalies.extend(burst(&releases, thresholds));
+
+    // smoelius: Check the version in use, and the version that `cargo update` would bring in.
+    let version_latest = releases
+        .iter()
+        .filter(|release| !release.yanked)
+        .map(|release| &release.version)
+        .max();
+    for version in [Some(version_used), version_latest].into_iter().flatten() {
+        if anomalies.iter().any(|anomaly| {
+            matches!(anomaly, Anomaly::PublisherChange { version: other, .. } if other == version)
+        }) {
+            continue;
+        }
+        anomalies.extend(publisher_change(&releases, version));
+    }
+
+    anomalies.extend(republished(&releases, version_used, thresholds));
+
+    anomalies
+}
+
+/// Returns a [`Anomaly::Burst`] if the latest `burst_size` releases were published within
+/// `window` of each other, and the release before them was published at least `burst_gap`
+/// before the first of them
+fn burst(releases: &[&Release], thresholds: &Thresholds) -> Option<Anomaly> {
+    // smoelius: A gap requires a release before the burst.
+    let first = releases
+        .len()
+        .checked_sub(thresholds.burst_size)
+        .filter(|&first| first >= 1)?;
+    let before = releases[first - 1];
+    let burst = &releases[first..];
+    let window = elapsed(burst.first()?.created_at, burst.last()?.created_at);
+    let gap = elapsed(before.created_at, burst.first()?.created_at);
+    if window > thresholds.window || gap < thresholds.burst_gap {
+        return None;
+    }
+    Some(Anomaly::Burst {
+        versions: burst
+            .iter()
+            .map(|release| release.version.clone())
+            .collect(),
+        window: window / SECS_PER_MINUTE,
+        gap: days::from_secs(gap),
+    })
+}
+
+/// Returns a [`Anomaly::PublisherChange`] if `version`'s publisher published none of the releases
+/// before it
+fn publisher_change(releases: &[&Release], version: &Version) -> Option<Anomaly> {
+    let index = releases
+        .iter()
+        .position(|release| release.version == *version)?;
+    let publisher = releases[index].publisher.as_ref()?;
+    // smoelius: Older releases do not record their publishers.
+    let earlier_publishers = releases[..index]
+        .iter()
+        .filter_map(|release| release.publisher.as_ref())
+        .collect::<Vec<_>>();
+    if earlier_publishers.len() < PUBLISHER_HISTORY_MIN || earlier_publishers.contains(&publisher) {
+        return None;
+    }
+    let mut counts = BTreeMap::<_, usize>::new();
+    for earlier_publisher in earlier_publishers {
+        *counts.entry(earlier_publisher).or_default() += 1;
+    }
+    // smoelius: `max_by_key` returns the last maximum. Reverse the iterator so that ties are broken
+    // in favor of the alphabetically first publisher.
+    let (usual_publisher, _) = counts.into_iter().rev().max_by_key(|&(_, count)| count)?;
+    Some(Anomaly::PublisherChange {
+        version: version.clone(),
+        publisher: publisher.clone(),
+        usual_publisher: usual_publisher.clone(),
+    })
+}
+
+/// Returns a [`Anomaly::Republished`] if the release immediately before `version_used` was yanked
+/// and published no more than `window` before it
+fn republished(
+    releases: &[&Release],
+    version_used: &Version,
+    thresholds: &Thresholds,
+) -> Option<Anomaly> {
+    let index = releases
+        .iter()
+        .position(|release| release.version == *version_used)?;
+    let before = releases[..index].last()?;
+    let window = elapsed(before.created_at, releases[index].created_at);
+    if !before.yanked || window > thresholds.window {
+        return None;
+    }
+    Some(Anomaly::Republished {
+        version: version_used.clone(),
+        yanked: before.version.clone(),
+        window: window / SECS_PER_MINUTE,
+    })
+}
+
+fn elapsed(earlier: SystemTime, later: SystemTime) -> u64 {
+    later.duration_since(earlier).unwrap_or_default().as_secs()
+}
+
+impl Anomaly {
+    /// Describes the anomaly as it appears in text output
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Burst {
+                versions,
+                window,
+                gap,
+            } => {
+                let mut s = String::new();
+                for (i, version) in versions.iter().enumerate() {
+                    let _: std::fmt::Result =
+                        write!(s, "{}{version}", if i == 0 { "" } else { ", " });
+                }
+                format!(
+                    "latest {} releases ({s}) published within {window} minutes, after {gap} days \
+                     without a release",
+                    versions.len()
+                )
+            }
+            Self::PublisherChange {
+                version,
+                publisher,
+                usual_publisher,
+            } => format!(
+                "{version} published by `{publisher}`, who published no earlier version; earlier \
+                 versions were mostly published by `{usual_publisher}`"
+            ),
+            Self::Republished {
+                version,
+                yanked,
+                window,
+            } => format!("{version} published {window} minutes after {yanked}, which was yanked"),
+        }
+    }
+}
+
+pub(crate) fn display_publish_anomalies(publish_anomalies: &[SerializablePublishAnomaly]) {
+    if publish_anomalies.is_empty() {
+        return;
+    }
+    println!("\nPublish anomalies (informational):");
+    for publish_anomaly in publish_anomalies {
+        println!(
+            "    {} {}: {}",
+            publish_anomaly.name,
+            publish_anomaly.version,
+            publish_anomaly.anomaly.describe()
+        );
+    }
+}
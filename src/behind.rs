@@ -0,0 +1,60 @@
+//! How far behind the newest release a used version is
+//!
+//! A version is behind by the number of releases published after it, and by the time between its
+//! publication and that of the newest release. Yanked releases are not counted. The release list is
+//! the one [`crate::versions`] returns, so nothing is fetched that an outdated-dependency check
+//! would not fetch anyway.
+
+use super::{days, on_disk_cache::VersionSummary};
+use cargo_metadata::semver::Version;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Behind {
+    /// The number of non-yanked releases published after the used version
+    pub releases: u64,
+    /// The number of days between the used version's publication and the newest non-yanked
+    /// release's
+    pub days: u64,
+}
+
+/// Returns how far `used` is behind the newest of `versions`, or `None` if `used` is not among them
+pub(crate) fn behind(versions: &[VersionSummary], used: &Version) -> Option<Behind> {
+    let used_created_at = versions
+        .iter()
+        .find(|version| Version::parse(&version.num).is_ok_and(|num| num == *used))?
+        .created_at;
+    let newer = versions
+        .iter()
+        .filter(|version| !version.yanked && version.created_at > used_created_at)
+        .map(|version| version.created_at)
+        .collect::<Vec<_>>();
+    let secs = newer
+        .iter()
+        .max()
+        .and_then(|newest| newest.duration_since(used_created_at).ok())
+        .unwrap_or_default()
+        .as_secs();
+    Some(Behind {
+        releases: newer.len() as u64,
+        days: days::from_secs(secs),
+    })
+}
+
+/// Describes how far behind a version is, e.g., `14 releases / 3.2y behind`, or returns `None` if
+/// it is not behind
+///
+/// A gap of less than a year is given in days, e.g., `2 releases / 45d behind`.
+pub(crate) fn describe(releases: u64, days: u64) -> Option<String> {
+    if releases == 0 {
+        return None;
+    }
+    let noun = if releases == 1 { "release" } else { "releases" };
+    // smoelius: Years are given to one decimal place, rounded down, as days are.
+    let gap = if days >= 365 {
+        let tenths = days * 10 / 365;
+        format!("{}.{}y", tenths / 10, tenths % 10)
+    } else {
+        format!("{days}d")
+    };
+    Some(format!("{releases} {noun} / {gap} behind"))
+}
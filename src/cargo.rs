@@ -0,0 +1,27 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use std::{env::var_os, process::Command};
+
+/// Returns a [`Command`] that runs the same `cargo` that ran cargo-unmaintained, if any
+///
+/// When cargo runs an external subcommand, it sets `CARGO` to the path of its own executable.
+/// Honoring `CARGO` ensures the right toolchain is used under rustup shims. Note that
+/// [`cargo_metadata::MetadataCommand`] already honors `CARGO`.
+pub(crate) fn command() -> Command {
+    Command::new(var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+}
+
+/// Returns the host's target triple, as reported by `rustc -vV`
+pub(crate) fn host() -> Result<String> {
+    let mut command = Command::new(var_os("RUSTC").unwrap_or_else(|| "rustc".into()));
+    command.arg("-vV");
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    ensure!(output.status.success(), "command failed: {command:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("failed to determine host from `rustc -vV` output: {stdout:?}"))
+}
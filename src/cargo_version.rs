@@ -0,0 +1,134 @@
+//! The version of the cargo that cargo-unmaintained runs, and the features that require newer ones
+//!
+//! Each feature of cargo that cargo-unmaintained relies upon and that is newer than some toolchains
+//! still in use is listed in [`Feature`], along with the minimum cargo version that supports
+//! it. If the cargo in use is too old for a feature, the feature is either done without (with a
+//! warning saying so) or, if there is no way to do without it, an error names the version required.
+//! A new flag passed to cargo should be registered here.
+
+use super::cargo;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use cargo_metadata::semver::Version;
+use once_cell::sync::OnceCell;
+use std::{cell::RefCell, collections::HashSet};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Feature {
+    FilterPlatform,
+    LocateProjectWorkspace,
+    LockfileV4,
+}
+
+pub(crate) struct Requirement {
+    /// The minimum cargo version (major, minor) that supports the feature
+    minimum: (u64, u64),
+    /// A description of the feature, e.g., "`cargo metadata --filter-platform`"
+    description: &'static str,
+    /// What happens when the feature is unsupported, or `None` if it is required
+    fallback: Option<&'static str>,
+}
+
+impl Feature {
+    pub(crate) fn requirement(self) -> Requirement {
+        match self {
+            Self::FilterPlatform => Requirement {
+                minimum: (1, 41),
+                description: "`cargo metadata --filter-platform`",
+                fallback: Some("dependencies for all targets will be checked"),
+            },
+            Self::LocateProjectWorkspace => Requirement {
+                minimum: (1, 48),
+                description: "`cargo locate-project --workspace`",
+                fallback: Some("a missing `Cargo.lock` will not be warned about"),
+            },
+            Self::LockfileV4 => Requirement {
+                minimum: (1, 78),
+                description: "version 4 `Cargo.lock` files",
+                fallback: None,
+            },
+        }
+    }
+}
+
+static VERSION: OnceCell<Option<Version>> = OnceCell::new();
+
+thread_local! {
+    static WARNED: RefCell<HashSet<Feature>> = RefCell::new(HashSet::new());
+}
+
+/// Forgets which features were warned about, e.g., before a scan that `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    WARNED.with_borrow_mut(HashSet::clear);
+}
+
+/// Returns true if the cargo in use supports `feature`
+///
+/// If it does not, and `feature` can be done without, a warning is emitted (once per feature) and
+/// false is returned. Otherwise, an error naming the required version is returned. If the cargo
+/// version cannot be determined, the feature is assumed to be supported.
+pub(crate) fn supports(feature: Feature) -> Result<bool> {
+    let Some(version) = version() else {
+        return Ok(true);
+    };
+    let requirement = feature.requirement();
+    let supported = check(version, &requirement)?;
+    if !supported && WARNED.with_borrow_mut(|warned| warned.insert(feature)) {
+        crate::warn!(
+            "cargo {} does not support {} (requires {}.{} or newer); {}",
+            version,
+            requirement.description,
+            requirement.minimum.0,
+            requirement.minimum.1,
+            requirement.fallback.unwrap_or_default()
+        );
+    }
+    Ok(supported)
+}
+
+/// Returns true if `version` supports `requirement`'s feature, false if it does not but the
+/// feature can be done without, and an error otherwise
+pub(crate) fn check(version: &Version, requirement: &Requirement) -> Result<bool> {
+    if (version.major, version.minor) >= requirement.minimum {
+        return Ok(true);
+    }
+    if requirement.fallback.is_some() {
+        return Ok(false);
+    }
+    bail!(
+        "cargo {} does not support {} (requires {}.{} or newer); please use a newer toolchain",
+        version,
+        requirement.description,
+        requirement.minimum.0,
+        requirement.minimum.1
+    );
+}
+
+/// Parses the output of `cargo --version`, e.g., `cargo 1.80.0 (376290515 2024-07-16)`
+pub(crate) fn parse(output: &str) -> Result<Version> {
+    let version = output
+        .strip_prefix("cargo ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or_else(|| anyhow!("unexpected `cargo --version` output: {output:?}"))?;
+    Version::parse(version).with_context(|| format!("failed to parse cargo version `{version}`"))
+}
+
+fn version() -> Option<&'static Version> {
+    VERSION
+        .get_or_init(|| {
+            detect()
+                .map_err(|error| log::debug!("failed to determine cargo version: {error}"))
+                .ok()
+        })
+        .as_ref()
+}
+
+fn detect() -> Result<Version> {
+    let mut command = cargo::command();
+    command.arg("--version");
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    ensure!(output.status.success(), "command failed: {command:?}");
+    parse(String::from_utf8(output.stdout)?.trim_end())
+}
@@ -0,0 +1,48 @@
+//! The time against which ages are computed
+//!
+//! [`now`] returns the time given by `--now` or `CARGO_UNMAINTAINED_NOW` (in RFC 3339 format), if
+//! either is set, and the current time otherwise. Pinning the time allows a report to be
+//! reproduced.
+//!
+//! Some times are deliberately not pinned, and use [`real_now`] instead. For example, whether
+//! on-disk cache data is fresh depends on when the data was fetched, not on the time a report is
+//! about.
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use once_cell::sync::OnceCell;
+use std::{env::var, time::SystemTime};
+
+const NOW_VAR: &str = "CARGO_UNMAINTAINED_NOW";
+
+static PINNED: OnceCell<Option<SystemTime>> = OnceCell::new();
+
+/// Pins the time to `now`, if set, or to the value of `CARGO_UNMAINTAINED_NOW`, if set
+pub(crate) fn init(now: Option<&str>) -> Result<()> {
+    let (what, value) = if let Some(now) = now {
+        ("--now", now.to_owned())
+    } else if let Ok(value) = var(NOW_VAR) {
+        (NOW_VAR, value)
+    } else {
+        let _: Result<_, _> = PINNED.set(None);
+        return Ok(());
+    };
+    let pinned = DateTime::parse_from_rfc3339(&value)
+        .with_context(|| format!("failed to parse {what} value `{value}` as an RFC 3339 time"))?;
+    let _: Result<_, _> = PINNED.set(Some(pinned.into()));
+    Ok(())
+}
+
+/// Returns the time against which ages are computed
+pub(crate) fn now() -> SystemTime {
+    PINNED
+        .get()
+        .copied()
+        .flatten()
+        .unwrap_or_else(SystemTime::now)
+}
+
+/// Returns the current time, regardless of whether the time is pinned
+pub(crate) fn real_now() -> SystemTime {
+    SystemTime::now()
+}
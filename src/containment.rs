@@ -0,0 +1,94 @@
+//! Containment of errors that occur while checking a single package
+//!
+//! An error (or panic) while checking one package does not abort the scan. Instead, the package is
+//! reported as not checked, the scan continues, and the exit status is set as though the package
+//! were unmaintained. Errors that concern the scan as a whole (e.g., the network appearing to be
+//! unavailable, or a crates.io failure under `--require-crates-io`) still abort it.
+
+use super::{network, serialize::SerializablePackageError};
+use anyhow::Result;
+use cargo_metadata::Package;
+use std::{
+    any::Any,
+    fmt::{Debug, Display, Formatter},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+pub(crate) struct PackageError<'a> {
+    pub pkg: &'a Package,
+    pub error: String,
+}
+
+/// Wraps an error that should abort the scan even if it occurs while checking a single package
+pub(crate) struct Irrecoverable(anyhow::Error);
+
+impl Debug for Irrecoverable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for Irrecoverable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for Irrecoverable {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+pub(crate) fn irrecoverable(error: anyhow::Error) -> anyhow::Error {
+    anyhow::Error::new(Irrecoverable(error))
+}
+
+/// Calls `f`, which checks `pkg`
+///
+/// Returns `Ok(Err(..))` with a one-line summary of the cause if `f` returns a recoverable error or
+/// panics. Returns `Err(..)` if `f` returns an irrecoverable error.
+pub(crate) fn contain<T>(
+    pkg: &Package,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<Result<T, String>> {
+    let summary = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return Ok(Ok(value)),
+        Ok(Err(error)) => {
+            if error.is::<Irrecoverable>() {
+                return Err(error);
+            }
+            // smoelius: If the error was caused by the network being unavailable, abort.
+            network::check()?;
+            format!("{error:#}")
+        }
+        Err(payload) => format!("panicked: {}", panic_message(payload.as_ref())),
+    };
+    crate::warn!(
+        "failed to check `{}`; continuing with the next package: {}",
+        pkg.name,
+        summary
+    );
+    Ok(Err(summary))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+pub(crate) fn display_package_errors(package_errors: &[SerializablePackageError]) {
+    if package_errors.is_empty() {
+        return;
+    }
+    println!("\nPackages that could not be checked:");
+    for package_error in package_errors {
+        println!(
+            "    {} {}: error: {}",
+            package_error.name, package_error.version, package_error.error
+        );
+    }
+}
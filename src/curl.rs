@@ -1,22 +1,79 @@
-use super::{RepoStatus, Url};
+use super::{evidence, network, provenance, RepoStatus, Url};
 use anyhow::{anyhow, Result};
 use curl::easy::Easy;
-use std::time::Duration;
+use std::{fs::File, io::Write, path::Path, time::Duration};
 
 const TIMEOUT: u64 = 60; // seconds
 
 pub(crate) fn existence(url: Url) -> Result<RepoStatus<()>> {
     let mut handle = handle(url)?;
     let result = handle.transfer().perform();
-    match result.and_then(|()| handle.response_code()) {
+    network::record(match &result {
+        Ok(()) => network::Outcome::Reached,
+        Err(err) => {
+            let connected = handle
+                .connect_time()
+                .is_ok_and(|connect_time| !connect_time.is_zero());
+            network::classify_curl_error(err, connected)
+        }
+    });
+    let result = result.and_then(|()| handle.response_code());
+    if let Ok(response_code) = result {
+        evidence::record_api_response(
+            url.as_str(),
+            "HTTP request",
+            &serde_json::json!({ "url": url.as_str(), "response_code": response_code }),
+        );
+    }
+    match result {
         Ok(200) => Ok(RepoStatus::Success(url, ())),
         Ok(404) => Ok(RepoStatus::Nonexistent(url)),
-        Err(err) if err.is_operation_timedout() => Ok(RepoStatus::Nonexistent(url)),
+        Err(err) if err.is_operation_timedout() => {
+            provenance::record_network_failure(url);
+            Ok(RepoStatus::Nonexistent(url))
+        }
         Ok(response_code) => Err(anyhow!("unexpected response code: {response_code}")),
         Err(err) => Err(err.into()),
     }
 }
 
+/// Downloads `url` to the file at `path`
+pub(crate) fn download(url: Url, path: &Path) -> Result<()> {
+    let mut file = File::create(path)?;
+    let mut write_error = None;
+    let mut handle = handle(url)?;
+    let result = {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            if let Err(error) = file.write_all(data) {
+                write_error = Some(error);
+                // smoelius: Returning fewer bytes than were given aborts the transfer.
+                return Ok(0);
+            }
+            Ok(data.len())
+        })?;
+        transfer.perform()
+    };
+    if let Some(error) = write_error {
+        return Err(error.into());
+    }
+    network::record(match &result {
+        Ok(()) => network::Outcome::Reached,
+        Err(err) => {
+            let connected = handle
+                .connect_time()
+                .is_ok_and(|connect_time| !connect_time.is_zero());
+            network::classify_curl_error(err, connected)
+        }
+    });
+    match result.and_then(|()| handle.response_code())? {
+        200 => Ok(()),
+        response_code => Err(anyhow!(
+            "unexpected response code downloading `{url}`: {response_code}"
+        )),
+    }
+}
+
 pub(crate) fn handle(url: Url) -> Result<Easy> {
     let mut handle = Easy::new();
     handle.url(url.as_str())?;
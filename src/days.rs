@@ -0,0 +1,28 @@
+//! Day arithmetic for ages
+//!
+//! Ages are measured in seconds, but thresholds (e.g., `--max-age`) are given in days, and ages are
+//! displayed and serialized in days. Every conversion goes through [`from_secs`], which rounds
+//! down, i.e., counts whole days. So an age is compared against a threshold exactly as it is
+//! displayed: a repository shown as "updated 365 days ago" is compared as 365 days old.
+//!
+//! An age exceeds a threshold if its number of whole days is strictly greater than the threshold.
+//! So with `--max-age=365`, a repository 365 days (and some hours) old is current, and one 366 days
+//! old is stale. A threshold of 0 is the exception: nothing is within it, which is how
+//! `--max-age=0` disables the staleness check.
+
+use super::SECS_PER_DAY;
+
+/// Returns the number of whole days in `secs` seconds
+pub(crate) fn from_secs(secs: u64) -> u64 {
+    secs / SECS_PER_DAY
+}
+
+/// Returns the number of seconds in `days` days
+pub(crate) fn to_secs(days: u64) -> u64 {
+    days.saturating_mul(SECS_PER_DAY)
+}
+
+/// Returns true if an age of `secs` seconds exceeds `threshold` days
+pub(crate) fn exceeds(secs: u64, threshold: u64) -> bool {
+    threshold == 0 || from_secs(secs) > threshold
+}
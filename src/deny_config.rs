@@ -0,0 +1,194 @@
+//! Ignores imported from a cargo-deny configuration (see `--import-ignores-from`)
+//!
+//! Only the parts of the configuration that name packages not to worry about are read:
+//! - `advisories.ignore`: advisory ids (e.g., `RUSTSEC-2021-0139`) and package specs (e.g.,
+//!   `foo@1.2.3`), each either a string or a table with an `id` or `crate` key
+//! - `bans.skip` and `bans.skip-tree`: package specs, each either a string or a table with a
+//!   `crate` or `name` key
+//!
+//! Everything else is disregarded, as cargo-deny's format evolves. An advisory id is mapped to the
+//! package it concerns using the advisory database, which requires the `rustsec` feature.
+
+use super::ignores::Ignore;
+#[cfg(feature = "rustsec")]
+use super::opts;
+use anyhow::{Context, Result};
+#[cfg(feature = "rustsec")]
+use rustsec::{advisory::Id, Database};
+use std::{fs::read_to_string, path::Path};
+use toml::{Table, Value};
+
+/// The ignores named in a cargo-deny configuration, each with its reason, if any
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct DenyIgnores {
+    pub advisories: Vec<(String, Option<String>)>,
+    pub packages: Vec<(String, Option<String>)>,
+}
+
+/// Parses the ignores in the cargo-deny configuration `contents`
+pub(crate) fn parse(contents: &str) -> Result<DenyIgnores> {
+    let table = contents.parse::<Table>()?;
+    let mut deny_ignores = DenyIgnores::default();
+
+    for entry in entries(&table, "advisories", "ignore") {
+        match entry {
+            Value::String(s) if is_advisory_id(s) => {
+                deny_ignores.advisories.push((s.clone(), None));
+            }
+            Value::Table(table) if table.contains_key("id") => {
+                if let Some(id) = string(table, "id") {
+                    deny_ignores.advisories.push((id, string(table, "reason")));
+                }
+            }
+            _ => deny_ignores.packages.extend(package(entry)),
+        }
+    }
+
+    for key in ["skip", "skip-tree"] {
+        for entry in entries(&table, "bans", key) {
+            deny_ignores.packages.extend(package(entry));
+        }
+    }
+
+    Ok(deny_ignores)
+}
+
+/// Returns the ignores in the cargo-deny configuration at `path`, with advisory ids mapped to
+/// packages
+pub(crate) fn import(path: &Path) -> Result<Vec<Ignore>> {
+    let contents =
+        read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let deny_ignores =
+        parse(&contents).with_context(|| format!("failed to parse `{}`", path.display()))?;
+    let via = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |file_name| file_name.to_string_lossy().into_owned(),
+    );
+
+    let mut ignores = deny_ignores
+        .packages
+        .into_iter()
+        .map(|(name, reason)| Ignore {
+            name,
+            reason,
+            until: None,
+            via: Some(via.clone()),
+        })
+        .collect::<Vec<_>>();
+
+    for (id, reason, name) in advisory_packages(&via, deny_ignores.advisories) {
+        ignores.push(Ignore {
+            name,
+            reason: reason.or_else(|| Some(format!("advisory {id}"))),
+            until: None,
+            via: Some(via.clone()),
+        });
+    }
+
+    Ok(ignores)
+}
+
+/// Maps each advisory id to the package the advisory concerns, warning about ids that cannot be
+/// mapped
+#[cfg(feature = "rustsec")]
+fn advisory_packages(
+    via: &str,
+    advisories: Vec<(String, Option<String>)>,
+) -> Vec<(String, Option<String>, String)> {
+    if advisories.is_empty() {
+        return Vec::new();
+    }
+
+    let database = if let Some(path) = &opts::get().rustsec_db {
+        Database::open(path)
+    } else {
+        Database::fetch()
+    };
+    let database = match database {
+        Ok(database) => database,
+        Err(error) => {
+            crate::warn!(
+                "failed to load advisory database; advisories ignored by `{}` are disregarded: {}",
+                via,
+                error
+            );
+            return Vec::new();
+        }
+    };
+
+    advisories
+        .into_iter()
+        .filter_map(|(id, reason)| {
+            let advisory = id
+                .parse::<Id>()
+                .ok()
+                .and_then(|parsed| database.get(&parsed));
+            let Some(advisory) = advisory else {
+                crate::warn!(
+                    "`{}` ignores advisory `{}`, which is not in the advisory database",
+                    via,
+                    id
+                );
+                return None;
+            };
+            let name = advisory.metadata.package.as_str().to_owned();
+            Some((id, reason, name))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "rustsec"))]
+fn advisory_packages(
+    via: &str,
+    advisories: Vec<(String, Option<String>)>,
+) -> Vec<(String, Option<String>, String)> {
+    for (id, _) in advisories {
+        crate::warn!(
+            "`{}` ignores advisory `{}`, but mapping advisories to packages requires the \
+             `rustsec` feature; disregarding it",
+            via,
+            id
+        );
+    }
+    Vec::new()
+}
+
+fn entries<'a>(table: &'a Table, section: &str, key: &str) -> impl Iterator<Item = &'a Value> {
+    table
+        .get(section)
+        .and_then(|section| section.get(key))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// Returns the name of the package named by a package spec (e.g., `foo@1.2.3`), given as a string
+/// or as a table with a `crate` or `name` key, and the spec's reason, if any
+fn package(entry: &Value) -> Option<(String, Option<String>)> {
+    let (spec, reason) = match entry {
+        Value::String(spec) => (spec.clone(), None),
+        Value::Table(table) => (
+            string(table, "crate").or_else(|| string(table, "name"))?,
+            string(table, "reason"),
+        ),
+        _ => return None,
+    };
+    let name = spec.split(['@', ':']).next().unwrap_or_default().trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_owned(), reason))
+}
+
+fn is_advisory_id(s: &str) -> bool {
+    ["RUSTSEC-", "GHSA-", "CVE-"]
+        .iter()
+        .any(|prefix| s.starts_with(prefix))
+}
+
+fn string(table: &Table, key: &str) -> Option<String> {
+    table
+        .get(key)
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+}
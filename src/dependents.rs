@@ -0,0 +1,116 @@
+//! Reverse dependencies in the resolved dependency graph (see `--blast-radius` and `--tree`)
+
+use cargo_metadata::{Metadata, Package, PackageId};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The packages that depend directly upon each package
+///
+/// Built once per run and shared by the packages whose dependents are needed.
+pub(crate) struct Dependents<'a> {
+    metadata: &'a Metadata,
+    packages: HashMap<&'a PackageId, &'a Package>,
+    direct: HashMap<&'a PackageId, Vec<&'a PackageId>>,
+}
+
+impl<'a> Dependents<'a> {
+    pub(crate) fn new(metadata: &'a Metadata) -> Self {
+        let packages = metadata.packages.iter().map(|pkg| (&pkg.id, pkg)).collect();
+        let mut direct = HashMap::<_, Vec<_>>::new();
+        for node in metadata.resolve.iter().flat_map(|resolve| &resolve.nodes) {
+            for node_dep in &node.deps {
+                direct.entry(&node_dep.pkg).or_default().push(&node.id);
+            }
+        }
+        Self {
+            metadata,
+            packages,
+            direct,
+        }
+    }
+
+    /// Returns the packages other than workspace members that depend upon `id`, directly or
+    /// transitively, nearest first
+    ///
+    /// Packages at the same distance from `id` are ordered by name and version.
+    pub(crate) fn transitive(&self, id: &'a PackageId) -> Vec<&'a Package> {
+        let mut visited = HashSet::from([id]);
+        let mut frontier = vec![id];
+        let mut transitive = Vec::new();
+        while !frontier.is_empty() {
+            let mut next = self.next(&frontier, &mut visited);
+            next.sort_by_key(|id| self.packages.get(id).map(|pkg| (&pkg.name, &pkg.version)));
+            transitive.extend(
+                next.iter()
+                    .filter(|id| !self.metadata.workspace_members.contains(id))
+                    .filter_map(|id| self.packages.get(id).copied()),
+            );
+            frontier = next;
+        }
+        transitive
+    }
+
+    /// Returns up to `k` shortest paths from workspace members to `id`, shortest first
+    ///
+    /// Each path starts with a workspace member and ends with `id`'s package. Paths of the same
+    /// length are ordered by the names and versions of the packages nearest `id`.
+    pub(crate) fn paths(&self, id: &'a PackageId, k: usize) -> Vec<Vec<&'a Package>> {
+        // smoelius: A breadth-first search over reversed edges finds paths in order of length. Each
+        // package is expanded at most `k` times, because no more than `k` of the shortest paths can
+        // pass through it. This bounds the search even when the graph has many paths.
+        let mut expansions = HashMap::<&PackageId, usize>::new();
+        let mut queue = VecDeque::from([vec![id]]);
+        let mut paths = Vec::new();
+        while paths.len() < k {
+            let Some(path) = queue.pop_front() else {
+                break;
+            };
+            let Some(&last) = path.last() else {
+                continue;
+            };
+            if self.metadata.workspace_members.contains(last) {
+                paths.push(
+                    path.iter()
+                        .rev()
+                        .filter_map(|id| self.packages.get(id).copied())
+                        .collect(),
+                );
+                continue;
+            }
+            let count = expansions.entry(last).or_default();
+            if *count >= k {
+                continue;
+            }
+            *count += 1;
+            let mut next = self
+                .direct
+                .get(last)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|id| !path.contains(id))
+                .collect::<Vec<_>>();
+            next.sort_by_key(|id| self.packages.get(id).map(|pkg| (&pkg.name, &pkg.version)));
+            next.dedup();
+            for id in next {
+                let mut extended = path.clone();
+                extended.push(id);
+                queue.push_back(extended);
+            }
+        }
+        paths
+    }
+
+    /// Returns the not yet `visited` packages that depend directly upon one of `ids`
+    fn next(
+        &self,
+        ids: &[&'a PackageId],
+        visited: &mut HashSet<&'a PackageId>,
+    ) -> Vec<&'a PackageId> {
+        ids.iter()
+            .filter_map(|id| self.direct.get(id))
+            .flatten()
+            .copied()
+            .filter(|id| visited.insert(id))
+            .collect()
+    }
+}
@@ -0,0 +1,376 @@
+//! The `doctor` subcommand: check that the environment is suitable for a scan
+//!
+//! Each check is independent of the others, and reports PASS, WARN, or FAIL, along with a hint for
+//! addressing anything other than a PASS. Only a FAIL makes the exit status nonzero. A scan can
+//! proceed in spite of a WARN (e.g., a missing GitHub token), though it may report less.
+
+use super::{clock, curl, git, github, on_disk_cache::USER_AGENT, opts, theme};
+use anyhow::{anyhow, Result};
+use cargo_metadata::MetadataCommand;
+use serde::Serialize;
+use std::{env::current_dir, io::Write, path::Path, time::Duration};
+use termcolor::{StandardStream, WriteColor};
+
+// smoelius: The index is the one `crates_index::GitIndex` fetches (unless the crates.io source is
+// replaced). The API url names a small response.
+const CRATES_IO_INDEX_URL: &str =
+    "https://github.com/rust-lang/crates.io-index/info/refs?service=git-upload-pack";
+const CRATES_IO_API_URL: &str = "https://crates.io/api/v1/crates/cargo-unmaintained";
+
+// smoelius: Network checks are meant to be quick. A host that takes longer than this to respond is
+// reported as unreachable.
+const TIMEOUT: u64 = 15; // seconds
+
+// smoelius: Older versions of git are not tested against.
+const MIN_GIT_VERSION: (u64, u64) = (2, 20);
+
+/// `doctor` subcommand: check the environment
+#[derive(Debug, clap::Parser)]
+pub(crate) struct Opts {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// The result of one check
+#[derive(Debug, Serialize)]
+pub(crate) struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a WARN or FAIL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            detail,
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: String, hint: &str) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail,
+            hint: Some(hint.to_owned()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: String, hint: &str) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail,
+            hint: Some(hint.to_owned()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report {
+    checks: Vec<Check>,
+}
+
+/// Runs the checks and displays their results, and returns true if any check failed
+pub(crate) fn run() -> Result<bool> {
+    let checks = checks()?;
+    let failed = checks.iter().any(|check| check.status == Status::Fail);
+
+    if opts::get().json {
+        serde_json::to_writer_pretty(std::io::stdout(), &Report { checks })?;
+        println!();
+    } else {
+        display(&checks)?;
+    }
+
+    Ok(failed)
+}
+
+fn checks() -> Result<Vec<Check>> {
+    #[allow(unused_mut)]
+    let mut checks = vec![
+        git_version(git_version_output()),
+        reachability(
+            "crates.io index",
+            CRATES_IO_INDEX_URL,
+            response_code(CRATES_IO_INDEX_URL),
+        ),
+        reachability(
+            "crates.io API",
+            CRATES_IO_API_URL,
+            response_code(CRATES_IO_API_URL),
+        ),
+        github_token(),
+    ];
+    #[cfg(all(feature = "on-disk-cache", not(windows)))]
+    checks.push(cache_directory(
+        &xdg::BaseDirectories::new()?
+            .get_cache_home()
+            .join("cargo-unmaintained/v2"),
+    ));
+    #[cfg(feature = "lock-index")]
+    checks.push(index_lock(&super::INDEX_PATH));
+    checks.push(workspace(&current_dir()?));
+    Ok(checks)
+}
+
+fn display(checks: &[Check]) -> Result<()> {
+    let mut stdout = StandardStream::stdout(theme::color_choice());
+    for check in checks {
+        let (label, severity) = match check.status {
+            Status::Pass => ("PASS", None),
+            Status::Warn => ("WARN", Some(0)),
+            Status::Fail => ("FAIL", Some(u8::MAX)),
+        };
+        stdout.set_color(&theme::spec(severity))?;
+        write!(stdout, "{label}")?;
+        stdout.reset()?;
+        writeln!(stdout, "  {}: {}", check.name, check.detail)?;
+        if let Some(hint) = &check.hint {
+            writeln!(stdout, "      hint: {hint}")?;
+        }
+    }
+    Ok(())
+}
+
+fn git_version_output() -> Result<String> {
+    let output = git::command().arg("--version").output()?;
+    if !output.status.success() {
+        return Err(anyhow!("`git --version` failed: {}", output.status));
+    }
+    String::from_utf8(output.stdout).map_err(Into::into)
+}
+
+/// Checks the output of `git --version`, e.g., `git version 2.39.2 (Apple Git-143)`
+pub(crate) fn git_version(output: Result<String>) -> Check {
+    const NAME: &str = "git";
+    let output = match output {
+        Ok(output) => output,
+        Err(error) => {
+            return Check::fail(
+                NAME,
+                format!("failed to run git: {error}"),
+                "install git and ensure that it is in your PATH",
+            );
+        }
+    };
+    let version = output.trim();
+    let parsed = version.strip_prefix("git version ").and_then(|rest| {
+        let mut parts = rest.split(|c: char| !c.is_ascii_digit());
+        let major = parts.next()?.parse::<u64>().ok()?;
+        let minor = parts.next()?.parse::<u64>().ok()?;
+        Some((major, minor))
+    });
+    match parsed {
+        None => Check::warn(
+            NAME,
+            format!("could not determine git's version from `{version}`"),
+            "ensure that `git` in your PATH is git",
+        ),
+        Some(parsed) if parsed < MIN_GIT_VERSION => Check::warn(
+            NAME,
+            format!(
+                "{version} is older than {}.{}",
+                MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+            ),
+            "upgrade git",
+        ),
+        Some(_) => Check::pass(NAME, version.to_owned()),
+    }
+}
+
+fn response_code(url: &str) -> Result<u32> {
+    let mut handle = curl::handle(url.into())?;
+    handle.useragent(USER_AGENT)?;
+    handle.timeout(Duration::from_secs(TIMEOUT))?;
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| Ok(data.len()))?;
+        transfer.perform()?;
+    }
+    handle.response_code().map_err(Into::into)
+}
+
+/// Checks the result of requesting `url`
+pub(crate) fn reachability(name: &'static str, url: &str, result: Result<u32>) -> Check {
+    match result {
+        Ok(200) => Check::pass(name, format!("reached {url}")),
+        Ok(response_code) => Check::warn(
+            name,
+            format!("{url} responded with {response_code}"),
+            "retry later; if the problem persists, check for a proxy that blocks the request",
+        ),
+        Err(error) => Check::fail(
+            name,
+            format!("failed to reach {url}: {error}"),
+            "check your network connection and proxy settings (e.g., `HTTPS_PROXY`)",
+        ),
+    }
+}
+
+fn github_token() -> Check {
+    let mut verification = None;
+    let origin = github::load_token(opts::get().token_source, |token| {
+        verification = Some(github::verify_token(token));
+        Ok(())
+    });
+    github_token_status(origin, verification)
+}
+
+/// Checks where a GitHub token was read from, if anywhere, and whether GitHub accepted it
+pub(crate) fn github_token_status(
+    origin: Result<Option<String>>,
+    verification: Option<Result<github::RateLimit>>,
+) -> Check {
+    const NAME: &str = "GitHub token";
+    const HINT: &str = "set `GITHUB_TOKEN`, or save a personal access token with `cargo \
+                        unmaintained --save-token`";
+    let origin = match origin {
+        Ok(Some(origin)) => origin,
+        Ok(None) => {
+            return Check::warn(
+                NAME,
+                String::from("no token found; archival statuses will not be checked"),
+                HINT,
+            );
+        }
+        Err(error) => {
+            return Check::fail(NAME, format!("failed to read token: {error}"), HINT);
+        }
+    };
+    match verification {
+        Some(Ok(rate_limit)) if rate_limit.remaining == 0 => {
+            let reset = rate_limit
+                .reset
+                .duration_since(clock::real_now())
+                .unwrap_or_default();
+            Check::warn(
+                NAME,
+                format!(
+                    "token read from {origin} has no API requests remaining (resets in {} minutes)",
+                    reset.as_secs().div_ceil(60)
+                ),
+                "wait for the rate limit to reset",
+            )
+        }
+        Some(Ok(rate_limit)) => Check::pass(
+            NAME,
+            format!(
+                "token read from {origin} accepted; {} of {} API requests remaining",
+                rate_limit.remaining, rate_limit.limit
+            ),
+        ),
+        Some(Err(error)) if error.is::<github::TokenRejected>() => Check::fail(
+            NAME,
+            format!("token read from {origin} was rejected: {error}"),
+            "generate a new personal access token; an expired or revoked one is rejected",
+        ),
+        Some(Err(error)) => Check::warn(
+            NAME,
+            format!("failed to verify token read from {origin}: {error}"),
+            "check your network connection and proxy settings (e.g., `HTTPS_PROXY`)",
+        ),
+        None => Check::warn(
+            NAME,
+            format!("token read from {origin} was not verified"),
+            HINT,
+        ),
+    }
+}
+
+/// Number of clones listed by [`cache_directory`]
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+const LARGEST_CLONES: usize = 3;
+
+/// Checks that `dir` can be created and written to, and reports its size and the sizes of its
+/// largest clones
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+pub(crate) fn cache_directory(dir: &Path) -> Check {
+    const NAME: &str = "cache directory";
+    let writable = std::fs::create_dir_all(dir)
+        .and_then(|()| tempfile::NamedTempFile::new_in(dir))
+        .map(drop);
+    if let Err(error) = writable {
+        return Check::fail(
+            NAME,
+            format!("`{}` is not writable: {error}", dir.display()),
+            "make the directory writable, or set `XDG_CACHE_HOME` to a writable directory",
+        );
+    }
+    match super::on_disk_cache::disk_usage(dir) {
+        Ok(size) => {
+            let mut detail = format!("`{}` is writable and occupies {size} bytes", dir.display());
+            // smoelius: The largest clones are the likeliest candidates for `--max-repo-size`.
+            let largest_clones = super::on_disk_cache::recorded_clone_sizes(dir)
+                .into_iter()
+                .take(LARGEST_CLONES)
+                .map(|(url, size)| format!("`{url}` ({size} bytes)"))
+                .collect::<Vec<_>>();
+            if !largest_clones.is_empty() {
+                detail.push_str("; largest clones: ");
+                detail.push_str(&largest_clones.join(", "));
+            }
+            Check::pass(NAME, detail)
+        }
+        Err(error) => Check::warn(
+            NAME,
+            format!(
+                "`{}` is writable, but its size could not be determined: {error}",
+                dir.display()
+            ),
+            "check the directory's permissions",
+        ),
+    }
+}
+
+/// Checks that the lock on the index at `path` can be acquired
+#[cfg(feature = "lock-index")]
+pub(crate) fn index_lock(path: &Path) -> Check {
+    const NAME: &str = "index lock";
+    match super::flock::try_lock_path(path) {
+        Ok(_lock) => Check::pass(NAME, format!("acquired lock on `{}`", path.display())),
+        Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => Check::warn(
+            NAME,
+            format!("`{}` is locked by another process", path.display()),
+            "a scan waits for the lock; if it is never released, look for a stuck cargo process",
+        ),
+        Err(error) => Check::fail(
+            NAME,
+            format!("failed to lock `{}`: {error}", path.display()),
+            "run `cargo fetch` in any package to create the crates.io index",
+        ),
+    }
+}
+
+/// Checks whether `dir` is in a cargo workspace
+pub(crate) fn workspace(dir: &Path) -> Check {
+    const NAME: &str = "cargo workspace";
+    match MetadataCommand::new().current_dir(dir).no_deps().exec() {
+        Ok(metadata) => {
+            let n = metadata.workspace_members.len();
+            Check::pass(
+                NAME,
+                format!(
+                    "`{}` has {n} member{}",
+                    metadata.workspace_root,
+                    if n == 1 { "" } else { "s" }
+                ),
+            )
+        }
+        Err(error) => Check::warn(
+            NAME,
+            format!("`{}` is not in a cargo workspace: {error}", dir.display()),
+            "run `cargo unmaintained` in a directory with a `Cargo.toml` file, or pass --package",
+        ),
+    }
+}
@@ -0,0 +1,116 @@
+//! `--download-trend`: whether a package's downloads have collapsed
+
+use super::{
+    clock,
+    metrics::{self, Phase},
+    on_disk_cache, verbose,
+};
+use anyhow::Result;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use crates_io_api::CrateDownloads;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Number of days in the "current" window; the prior window is twice as long
+const WINDOW: u64 = 30; // days
+
+/// Changes smaller than this are considered stable
+const STABLE_PERCENT: u64 = 20;
+
+/// Change in downloads over the current window relative to the average over the prior two windows
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Trend {
+    Down(u64), // percent
+    Stable,
+    Up(u64), // percent
+}
+
+impl std::fmt::Display for Trend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Down(percent) => write!(f, "downloads trending down {percent}%"),
+            Self::Stable => write!(f, "downloads stable"),
+            Self::Up(percent) => write!(f, "downloads trending up {percent}%"),
+        }
+    }
+}
+
+/// Returns `name`'s download trend, or `None` if the prior window had no downloads
+pub(crate) fn download_trend(name: &str) -> Result<Option<Trend>> {
+    let downloads = metrics::time(Phase::Downloads, || {
+        on_disk_cache::with_cache(|cache| -> Result<_> {
+            verbose::wrap!(
+                || { cache.fetch_downloads(name) },
+                "downloads of `{}` using crates.io API",
+                name
+            )
+        })
+    })?;
+    Ok(trend(
+        &daily_downloads(&downloads),
+        DateTime::<Utc>::from(clock::real_now()).date_naive(),
+    ))
+}
+
+/// Sums the downloads of all versions by date
+///
+/// crates.io reports downloads for a crate's most popular versions individually, and the rest as
+/// "extra" downloads.
+pub(crate) fn daily_downloads(downloads: &CrateDownloads) -> BTreeMap<NaiveDate, u64> {
+    let version_downloads = downloads
+        .version_downloads
+        .iter()
+        .map(|version_downloads| (version_downloads.date, version_downloads.downloads));
+    let extra_downloads = downloads
+        .meta
+        .extra_downloads
+        .iter()
+        .map(|extra_downloads| (extra_downloads.date, extra_downloads.downloads));
+    version_downloads.chain(extra_downloads).fold(
+        BTreeMap::new(),
+        |mut daily, (date, downloads)| {
+            *daily.entry(date).or_default() += downloads;
+            daily
+        },
+    )
+}
+
+/// Compares the total downloads of the `WINDOW` days ending on `today` to the average of the two
+/// `WINDOW`-day periods before that
+///
+/// Days missing from `daily` are treated as having no downloads.
+pub(crate) fn trend(daily: &BTreeMap<NaiveDate, u64>, today: NaiveDate) -> Option<Trend> {
+    let current_start = today.checked_sub_days(Days::new(WINDOW - 1))?;
+    let prior_start = current_start.checked_sub_days(Days::new(2 * WINDOW))?;
+
+    let current = daily
+        .range(current_start..=today)
+        .map(|(_, &n)| n)
+        .sum::<u64>();
+    let prior = daily
+        .range(prior_start..current_start)
+        .map(|(_, &n)| n)
+        .sum::<u64>();
+
+    if prior == 0 {
+        return None;
+    }
+
+    // smoelius: `prior` covers two windows. So compare `current` to `prior / 2`, i.e., compare
+    // `2 * current` to `prior`.
+    let doubled = 2 * current;
+    let (change, down) = if doubled < prior {
+        (prior - doubled, true)
+    } else {
+        (doubled - prior, false)
+    };
+    let percent = change * 100 / prior;
+
+    Some(if percent < STABLE_PERCENT {
+        Trend::Stable
+    } else if down {
+        Trend::Down(percent)
+    } else {
+        Trend::Up(percent)
+    })
+}
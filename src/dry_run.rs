@@ -0,0 +1,118 @@
+//! `--dry-run`: predict what a scan would do without doing it
+//!
+//! The prediction is based on the on-disk cache and the workspace's metadata alone. No repositories
+//! are cloned or fetched, the crates.io index is not updated, and no requests are made to crates.io
+//! or GitHub. (`cargo metadata` is still run, though.)
+//!
+//! The number of crates.io version fetches is an upper bound. Versions are fetched only for
+//! dependencies that turn out to be outdated, which cannot be determined without the index.
+
+use super::{
+    canonical_url, find_packages, from_crates_io, metadata, on_disk_cache,
+    on_disk_cache::RepositoryState, optional_dependency_is_enabled, opts, outdated_dep_grace,
+    packages, published, url, Kind,
+};
+use anyhow::Result;
+use std::collections::{BTreeSet, HashSet};
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Plan {
+    n_packages: usize,
+    /// `None` if repositories would not be cloned at all
+    repositories: Option<RepositoryCounts>,
+    n_version_fetches: usize,
+    index_update: bool,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct RepositoryCounts {
+    fresh: usize,
+    stale: usize,
+    missing: usize,
+}
+
+pub(crate) fn display_plan() -> Result<()> {
+    let metadata = metadata()?;
+    let (packages, _) = packages(&metadata)?;
+
+    let only = opts::get().only;
+    let clones = only.is_none_or(|kind| kind == Kind::Stale);
+    let fetches_versions =
+        only.is_none_or(|kind| kind == Kind::OutdatedDeps) && outdated_dep_grace() != 0;
+
+    let repositories = clones.then(|| {
+        let mut counts = RepositoryCounts::default();
+        let mut seen = HashSet::new();
+        for pkg in packages.iter().filter(|pkg| !url::is_assumed_fresh(pkg)) {
+            let Some(url_string) = url::repository(pkg) else {
+                continue;
+            };
+            if !seen.insert(canonical_url(url_string)) {
+                continue;
+            }
+            match on_disk_cache::with_cache(|cache| cache.repository_state(pkg)) {
+                RepositoryState::Fresh => counts.fresh += 1,
+                RepositoryState::Stale => counts.stale += 1,
+                RepositoryState::Missing => counts.missing += 1,
+            }
+        }
+        counts
+    });
+
+    let mut names = BTreeSet::new();
+    if fetches_versions {
+        for pkg in packages.iter().filter(|pkg| published(pkg)) {
+            for dep in &pkg.dependencies {
+                if dep.path.is_some()
+                    || (dep.optional && !optional_dependency_is_enabled(&metadata, pkg, dep))
+                {
+                    continue;
+                }
+                let Some(dep_pkg) = find_packages(&metadata, dep.into()).next() else {
+                    continue;
+                };
+                if from_crates_io(dep_pkg) {
+                    names.insert(dep_pkg.name.as_str());
+                }
+            }
+        }
+    }
+    let n_version_fetches = names
+        .into_iter()
+        .filter(|name| !on_disk_cache::with_cache(|cache| cache.versions_are_cached(name)))
+        .count();
+
+    let plan = Plan {
+        n_packages: packages.len(),
+        repositories,
+        n_version_fetches,
+        index_update: only.is_none_or(Kind::uses_index),
+    };
+
+    println!("{}", describe(&plan));
+
+    Ok(())
+}
+
+fn describe(plan: &Plan) -> String {
+    let repositories = plan.repositories.as_ref().map_or_else(
+        || String::from("no repos would be cloned"),
+        |counts| {
+            format!(
+                "{} repos cached (fresh), {} cached (stale, would refresh), {} would be cloned",
+                counts.fresh, counts.stale, counts.missing
+            )
+        },
+    );
+    format!(
+        "{} packages; {}; {} crates.io version fetches needed; index update: {}",
+        plan.n_packages,
+        repositories,
+        plan.n_version_fetches,
+        if plan.index_update {
+            "required"
+        } else {
+            "not required"
+        }
+    )
+}
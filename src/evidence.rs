@@ -0,0 +1,384 @@
+//! Evidence behind findings (see `--evidence-dir`)
+//!
+//! While packages are checked, the raw material consulted is recorded: API responses, the clones'
+//! latest commits, the manifests that name packages, and the versions fetched from crates.io. Once
+//! the scan completes, a directory is written for each flagged package containing the material
+//! relevant to it, along with a `summary.json` tying the pieces together.
+//!
+//! Nothing is recorded unless `--evidence-dir` is given. Everything written is passed through
+//! [`redact`], so that tokens and paths within the user's home directory do not end up in a bundle.
+
+use super::{
+    github,
+    on_disk_cache::VersionSummary,
+    opts,
+    serialize::{rfc3339, SerializableRepoStatus},
+    url::canonical_url,
+    UnmaintainedPkg, Url,
+};
+use anyhow::{Context, Result};
+use cargo_metadata::Package;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::{create_dir_all, write},
+    path::Path,
+    process::Stdio,
+    time::SystemTime,
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+#[allow(clippy::unwrap_used)]
+static AUTHORIZATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(authorization\s*:\s*)[^\r\n\x22]*").unwrap());
+
+#[allow(clippy::unwrap_used)]
+static BEARER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(bearer\s+)[A-Za-z0-9_.~+/=-]+").unwrap());
+
+struct ApiResponse {
+    api: &'static str,
+    recorded_at: SystemTime,
+    body: Value,
+}
+
+struct Commit {
+    head: String,
+    log: String,
+    recorded_at: SystemTime,
+}
+
+struct Manifest {
+    rev: String,
+    path: String,
+    contents: String,
+    recorded_at: SystemTime,
+}
+
+struct Versions {
+    versions: Vec<VersionSummary>,
+    recorded_at: SystemTime,
+}
+
+thread_local! {
+    // smoelius: Keyed by canonical url, like the url-keyed caches in lib.rs.
+    static API_RESPONSES: RefCell<HashMap<String, Vec<ApiResponse>>> = RefCell::new(HashMap::new());
+    static CLONES: RefCell<HashMap<String, Commit>> = RefCell::new(HashMap::new());
+    // smoelius: Keyed by package id, because a repository can contain several packages.
+    static MANIFESTS: RefCell<HashMap<String, Manifest>> = RefCell::new(HashMap::new());
+    // smoelius: Keyed by crate name, because crates.io is queried by name.
+    static VERSIONS: RefCell<HashMap<String, Versions>> = RefCell::new(HashMap::new());
+}
+
+/// Forgets the evidence recorded so far, e.g., before a scan that `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    API_RESPONSES.with_borrow_mut(HashMap::clear);
+    CLONES.with_borrow_mut(HashMap::clear);
+    MANIFESTS.with_borrow_mut(HashMap::clear);
+    VERSIONS.with_borrow_mut(HashMap::clear);
+}
+
+/// Returns true if evidence should be recorded, i.e., if `--evidence-dir` was given
+pub(crate) fn enabled() -> bool {
+    opts::get().evidence_dir.is_some()
+}
+
+/// Records `body`, as returned by `api` for the repository at `url`
+pub(crate) fn record_api_response(url: &str, api: &'static str, body: &Value) {
+    if !enabled() {
+        return;
+    }
+    API_RESPONSES.with_borrow_mut(|api_responses| {
+        api_responses
+            .entry(canonical_url(url))
+            .or_default()
+            .push(ApiResponse {
+                api,
+                recorded_at: super::clock::now(),
+                body: body.clone(),
+            });
+    });
+}
+
+/// Records the latest commit of the repository cloned from `url` at `repo_dir`
+pub(crate) fn record_clone(url: Url, repo_dir: &Path) -> Result<()> {
+    if !enabled() || CLONES.with_borrow(|clones| clones.contains_key(&url.canonical())) {
+        return Ok(());
+    }
+    let head = git_output(repo_dir, &["rev-parse", "HEAD"])?;
+    let log = git_output(repo_dir, &["log", "-1"])?;
+    CLONES.with_borrow_mut(|clones| {
+        clones.insert(
+            url.canonical(),
+            Commit {
+                head: head.trim_end().to_owned(),
+                log,
+                recorded_at: super::clock::now(),
+            },
+        );
+    });
+    Ok(())
+}
+
+/// Records `contents`, the manifest at `path` in revision `rev` that was found to name `pkg`
+///
+/// Only the first such manifest is recorded, as that is the one that established membership.
+pub(crate) fn record_manifest(pkg: &Package, rev: &str, path: &Path, contents: &str) {
+    if !enabled() {
+        return;
+    }
+    MANIFESTS.with_borrow_mut(|manifests| {
+        manifests
+            .entry(pkg.id.repr.clone())
+            .or_insert_with(|| Manifest {
+                rev: rev.to_owned(),
+                path: path.to_string_lossy().into_owned(),
+                contents: contents.to_owned(),
+                recorded_at: super::clock::now(),
+            });
+    });
+}
+
+/// Records the versions of crate `name` fetched from crates.io
+pub(crate) fn record_versions(name: &str, versions: &[VersionSummary]) {
+    if !enabled() {
+        return;
+    }
+    VERSIONS.with_borrow_mut(|versions_map| {
+        versions_map.insert(
+            name.to_owned(),
+            Versions {
+                versions: versions.to_vec(),
+                recorded_at: super::clock::now(),
+            },
+        );
+    });
+}
+
+/// Writes a directory beneath `dir` for each of `unmaintained_pkgs`
+pub(crate) fn write_bundles(dir: &Path, unmaintained_pkgs: &[UnmaintainedPkg]) -> Result<()> {
+    create_dir_all(dir).with_context(|| format!("failed to create `{}`", dir.display()))?;
+    for unmaintained_pkg in unmaintained_pkgs {
+        write_bundle(dir, unmaintained_pkg)?;
+    }
+    Ok(())
+}
+
+fn write_bundle(dir: &Path, unmaintained_pkg: &UnmaintainedPkg) -> Result<()> {
+    let pkg = unmaintained_pkg.pkg;
+    let bundle = dir.join(format!("{}-{}", pkg.name, pkg.version));
+    create_dir_all(&bundle).with_context(|| format!("failed to create `{}`", bundle.display()))?;
+
+    let url = unmaintained_pkg
+        .repo_age
+        .url()
+        .map(|url| url.as_str().to_owned())
+        .or_else(|| super::url::repository(pkg).map(ToOwned::to_owned));
+    let key = url.as_deref().map(canonical_url).unwrap_or_default();
+
+    let mut summary = Map::new();
+    summary.insert("package".to_owned(), json!(pkg.name));
+    summary.insert("version".to_owned(), json!(pkg.version.to_string()));
+    summary.insert("repository".to_owned(), json!(url));
+    summary.insert(
+        "status".to_owned(),
+        serde_json::to_value(SerializableRepoStatus::from(unmaintained_pkg.repo_age))?,
+    );
+    summary.insert(
+        "generated_at".to_owned(),
+        json!(rfc3339(super::clock::now())),
+    );
+
+    // smoelius: Each piece of evidence is a file in the bundle, and an entry in the summary
+    // describing it.
+    let pieces = [
+        ("api.json", "api", api_evidence(&key)?),
+        ("git_log.txt", "clone", commit_evidence(&key)),
+        ("Cargo.toml", "manifest", manifest_evidence(pkg)),
+        (
+            "versions.json",
+            "versions",
+            versions_evidence(unmaintained_pkg)?,
+        ),
+    ];
+    let mut files = Vec::new();
+    for (file_name, summary_key, piece) in pieces {
+        let Some((contents, description)) = piece else {
+            continue;
+        };
+        write_redacted(&bundle, file_name, &contents)?;
+        summary.insert(summary_key.to_owned(), description);
+        files.push(file_name);
+    }
+
+    summary.insert("files".to_owned(), json!(files));
+    write_redacted(&bundle, "summary.json", &to_json(&Value::from(summary))?)?;
+
+    Ok(())
+}
+
+fn api_evidence(key: &str) -> Result<Option<(String, Value)>> {
+    API_RESPONSES.with_borrow(|api_responses| {
+        let Some(api_responses) = api_responses.get(key) else {
+            return Ok(None);
+        };
+        let contents = to_json(&Value::from(
+            api_responses
+                .iter()
+                .map(|api_response| {
+                    json!({
+                        "api": api_response.api,
+                        "recorded_at": rfc3339(api_response.recorded_at),
+                        "body": api_response.body,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        ))?;
+        let description = api_responses
+            .iter()
+            .map(|api_response| {
+                json!({
+                    "api": api_response.api,
+                    "recorded_at": rfc3339(api_response.recorded_at),
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(Some((contents, Value::from(description))))
+    })
+}
+
+fn commit_evidence(key: &str) -> Option<(String, Value)> {
+    CLONES.with_borrow(|clones| {
+        clones.get(key).map(|commit| {
+            (
+                commit.log.clone(),
+                json!({ "head": commit.head, "recorded_at": rfc3339(commit.recorded_at) }),
+            )
+        })
+    })
+}
+
+fn manifest_evidence(pkg: &Package) -> Option<(String, Value)> {
+    MANIFESTS.with_borrow(|manifests| {
+        manifests.get(&pkg.id.repr).map(|manifest| {
+            (
+                manifest.contents.clone(),
+                json!({
+                    "rev": manifest.rev,
+                    "path": manifest.path,
+                    "recorded_at": rfc3339(manifest.recorded_at),
+                }),
+            )
+        })
+    })
+}
+
+fn versions_evidence(unmaintained_pkg: &UnmaintainedPkg) -> Result<Option<(String, Value)>> {
+    // smoelius: The versions relevant to a finding are those of the package itself (its newest
+    // release's age) and those of its outdated dependencies (their incompatible releases' ages).
+    let names = std::iter::once(unmaintained_pkg.pkg.name.as_str()).chain(
+        unmaintained_pkg
+            .outdated_deps
+            .iter()
+            .map(|outdated_dep| outdated_dep.dep.name.as_str()),
+    );
+    let mut versions = Map::new();
+    let mut description = Map::new();
+    VERSIONS.with_borrow(|versions_map| {
+        for name in names {
+            let Some(recorded) = versions_map.get(name) else {
+                continue;
+            };
+            versions.insert(
+                name.to_owned(),
+                Value::from(
+                    recorded
+                        .versions
+                        .iter()
+                        .map(|version| {
+                            json!({
+                                "num": version.num,
+                                "created_at": rfc3339(version.created_at),
+                                "yanked": version.yanked,
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+            );
+            description.insert(
+                name.to_owned(),
+                json!({ "recorded_at": rfc3339(recorded.recorded_at) }),
+            );
+        }
+    });
+    if versions.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((
+        to_json(&Value::from(versions))?,
+        Value::from(description),
+    )))
+}
+
+fn to_json(value: &Value) -> Result<String> {
+    let mut json = serde_json::to_string_pretty(value)?;
+    json.push('\n');
+    Ok(json)
+}
+
+fn write_redacted(bundle: &Path, file_name: &str, contents: &str) -> Result<()> {
+    let path = bundle.join(file_name);
+    write(&path, redact(contents)?).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+fn git_output(repo_dir: &Path, args: &[&str]) -> Result<String> {
+    let mut command = super::git::command();
+    command.args(args);
+    command.current_dir(repo_dir);
+    command.stdout(Stdio::piped());
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    anyhow::ensure!(output.status.success(), "command failed: {command:?}");
+    String::from_utf8(output.stdout).map_err(Into::into)
+}
+
+/// Removes sensitive information from `text`: the GitHub token, if any, the values of
+/// `Authorization` headers, and the user's home directory
+pub(crate) fn redact(text: &str) -> Result<String> {
+    redact_with(text, github::personal_token(), home::home_dir().as_deref())
+}
+
+pub(crate) fn redact_with(
+    text: &str,
+    token: Option<&str>,
+    home_dir: Option<&Path>,
+) -> Result<String> {
+    let mut text = text.to_owned();
+    if let Some(token) = token.filter(|token| !token.is_empty()) {
+        text = text.replace(token, REDACTED);
+    }
+    text = AUTHORIZATION_RE
+        .replace_all(&text, format!("${{1}}{REDACTED}"))
+        .into_owned();
+    text = BEARER_RE
+        .replace_all(&text, format!("${{1}}{REDACTED}"))
+        .into_owned();
+    // smoelius: A home directory of `/` would match every absolute path.
+    if let Some(home_dir) = home_dir
+        .map(|home_dir| home_dir.to_string_lossy())
+        .map(|home_dir| home_dir.trim_end_matches(['/', '\\']).to_owned())
+        .filter(|home_dir| !home_dir.is_empty())
+    {
+        // smoelius: Match only whole path components, so that, e.g., `/home/alice` does not match
+        // `/home/alicesmith`.
+        let home_dir_re = Regex::new(&format!(r"{}([/\\\s\x22']|$)", regex::escape(&home_dir)))?;
+        text = home_dir_re.replace_all(&text, "~$1").into_owned();
+    }
+    Ok(text)
+}
@@ -0,0 +1,134 @@
+//! `--fast`: a cheap profile for, e.g., pre-commit hooks
+//!
+//! In fast mode:
+//! - Only repositories' general statuses (i.e., archival status and existence) are checked. No
+//!   repositories are cloned, and the crates.io index is not updated.
+//! - On-disk cache data is used regardless of its age.
+//! - Packages that are new since the last fast run are checked first, and at most
+//!   `--fast-max-requests` general status requests are made. Packages whose checks would require
+//!   more are not checked.
+//!
+//! Each fast run records a baseline for the workspace: the packages that were checked, and, if
+//! every package was checked and none was found unmaintained, a digest of `Cargo.lock`. If
+//! `Cargo.lock` is unchanged since such a run, nothing is checked.
+
+use super::{on_disk_cache, opts};
+use anyhow::Result;
+use cargo_metadata::{Metadata, Package};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+    fs::read,
+};
+
+pub(crate) const HEADER: &str = "fast mode: partial checks";
+
+#[derive(Default, Deserialize, Serialize)]
+pub(crate) struct Baseline {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lockfile_digest: Option<String>,
+    #[serde(default)]
+    packages: BTreeSet<String>,
+}
+
+thread_local! {
+    static REQUESTS: Cell<u64> = const { Cell::new(0) };
+    static UNCHECKED: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+/// Packages to scan in fast mode, along with what is needed to record the next baseline
+pub(crate) struct Plan<'a> {
+    pub packages: Vec<&'a Package>,
+    pub n_new: usize,
+    baseline: Baseline,
+    lockfile_digest: Option<String>,
+}
+
+/// Returns `None` if `Cargo.lock` is unchanged since the last fast run that found nothing;
+/// otherwise, returns `packages` with those new since the last fast run first
+pub(crate) fn plan<'a>(metadata: &Metadata, packages: &[&'a Package]) -> Option<Plan<'a>> {
+    let lockfile_digest = read(metadata.workspace_root.join("Cargo.lock"))
+        .ok()
+        .map(|contents| sha1_smol::Sha1::from(contents).hexdigest());
+
+    let baseline =
+        on_disk_cache::with_cache(|cache| cache.baseline(metadata.workspace_root.as_str()))
+            .unwrap_or_default();
+
+    if lockfile_digest.is_some() && baseline.lockfile_digest == lockfile_digest {
+        return None;
+    }
+
+    let (mut packages, known): (Vec<_>, Vec<_>) = packages
+        .iter()
+        .partition(|pkg| !baseline.packages.contains(&key(pkg)));
+    let n_new = packages.len();
+    packages.extend(known);
+
+    Some(Plan {
+        packages,
+        n_new,
+        baseline,
+        lockfile_digest,
+    })
+}
+
+/// Forgets the requests made and the packages left unchecked so far, e.g., before a scan that
+/// `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    REQUESTS.set(0);
+    UNCHECKED.with_borrow_mut(BTreeSet::clear);
+}
+
+/// Returns true if a general status request may be made, and counts it
+pub(crate) fn take_request() -> bool {
+    REQUESTS.with(|requests| {
+        if requests.get() >= opts::get().fast_max_requests {
+            return false;
+        }
+        requests.set(requests.get() + 1);
+        true
+    })
+}
+
+/// Records that `pkg` was not checked because no more requests could be made
+pub(crate) fn record_unchecked(pkg: &Package) {
+    UNCHECKED.with_borrow_mut(|unchecked| unchecked.insert(key(pkg)));
+}
+
+/// Records the baseline for the next fast run, and returns the number of packages not checked
+pub(crate) fn finish(metadata: &Metadata, plan: Plan, found: bool) -> Result<usize> {
+    let Plan {
+        packages,
+        n_new: _,
+        mut baseline,
+        lockfile_digest,
+    } = plan;
+
+    let unchecked = UNCHECKED.with_borrow(Clone::clone);
+
+    baseline.packages.extend(
+        packages
+            .iter()
+            .map(|pkg| key(pkg))
+            .filter(|key| !unchecked.contains(key)),
+    );
+    // smoelius: Skipping the next run is safe only if this run left nothing to report.
+    baseline.lockfile_digest = if unchecked.is_empty() && !found {
+        lockfile_digest
+    } else {
+        None
+    };
+
+    on_disk_cache::with_cache(|cache| {
+        cache.write_baseline(metadata.workspace_root.as_str(), &baseline)
+    })?;
+
+    Ok(unchecked.len())
+}
+
+fn key(pkg: &Package) -> String {
+    format!("{}@{}", pkg.name, pkg.version)
+}
@@ -19,6 +19,27 @@ pub fn try_lock_path(path: &Path) -> Result<File> {
     Ok(file)
 }
 
+/// Locks the regular file at `path`, creating it if it does not exist
+pub fn lock_file(path: &Path) -> Result<File> {
+    let file = open_lock_file(path)?;
+    sys::lock_exclusive(&file)?;
+    Ok(file)
+}
+
+pub fn try_lock_file(path: &Path) -> Result<File> {
+    let file = open_lock_file(path)?;
+    sys::try_lock_exclusive(&file)?;
+    Ok(file)
+}
+
+fn open_lock_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+}
+
 fn open_lockable_file(path: &Path) -> Result<File> {
     if cfg!(windows) {
         OpenOptions::new()
@@ -0,0 +1,13 @@
+use std::process::Command;
+
+/// Returns a `git` [`Command`] that will neither fetch LFS objects nor recurse into submodules
+///
+/// All of cargo-unmaintained's git invocations should be created with this function. Otherwise,
+/// a repository with mandatory LFS hooks could cause, e.g., a fetch to fail.
+pub(crate) fn command() -> Command {
+    let mut command = Command::new("git");
+    command
+        .args(["-c", "lfs.fetchexclude=*", "-c", "submodule.recurse=false"])
+        .env("GIT_LFS_SKIP_SMUDGE", "1");
+    command
+}
@@ -0,0 +1,185 @@
+//! Batched repository lookups using GitHub's GraphQL API
+//!
+//! Fetching repositories' metadata with the REST API requires one request per repository. The
+//! GraphQL API allows up to [`BATCH_SIZE`] repositories to be fetched in a single request, using
+//! one alias per repository.
+//!
+//! Batching is only an optimization. A repository that a batch does not resolve (e.g., because it
+//! does not exist, or because the GraphQL rate limit was reached) is left uncached, so that it is
+//! fetched with the REST API, as it would have been without batching.
+
+use super::{
+    call_graphql, map_ext::MapExt, match_github_url, parse_timestamp, repo_info::GraphQlRepoInfo,
+    RepoInfo, Url, REPOSITORY_CACHE,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{collections::BTreeMap, fmt::Write, rc::Rc, time::SystemTime};
+
+/// Maximum number of repositories fetched by one query
+const BATCH_SIZE: usize = 100;
+
+const FRAGMENT: &str = "fragment RepoInfo on Repository { nameWithOwner isArchived isFork \
+                        pushedAt defaultBranchRef { name } parent { nameWithOwner url } \
+                        description homepageUrl }";
+
+/// The GraphQL API's rate limit, as reported in a query's response
+///
+/// Unlike the REST API's rate limit, which counts requests, the GraphQL API's rate limit counts
+/// points. A query's cost depends upon the number of repositories it fetches.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct RateLimit {
+    pub cost: u64,
+    pub remaining: u64,
+    pub reset_at: SystemTime,
+}
+
+/// Fetches the repositories named by `urls` in batches, and caches those that are resolved
+///
+/// Returns the number of repositories cached. Urls that are not GitHub urls, and repositories that
+/// are already cached, are ignored.
+pub(crate) fn prefetch_repositories<'a>(urls: impl IntoIterator<Item = Url<'a>>) -> Result<usize> {
+    // smoelius: The mock's fixtures are looked up one repository at a time.
+    #[cfg(feature = "testing-mock")]
+    if super::mock::enabled() {
+        return Ok(0);
+    }
+
+    let mut repositories = BTreeMap::new();
+    for url in urls {
+        let Ok((_, owner_slash_repo, owner, repo)) = match_github_url(url) else {
+            continue;
+        };
+        if REPOSITORY_CACHE
+            .with_borrow(|repository_cache| repository_cache.contains_key(owner_slash_repo))
+        {
+            continue;
+        }
+        repositories
+            .entry(owner_slash_repo.to_owned())
+            .or_insert_with(|| (owner.to_owned(), repo.to_owned()));
+    }
+    let repositories = repositories.into_iter().collect::<Vec<_>>();
+
+    let mut n_cached = 0;
+    for batch in repositories.chunks(BATCH_SIZE) {
+        let owners_and_repos = batch
+            .iter()
+            .map(|(_, (owner, repo))| (owner.as_str(), repo.as_str()))
+            .collect::<Vec<_>>();
+        let value = call_graphql(&query(&owners_and_repos))?;
+        let (repo_infos, rate_limit) = parse_response(&value, batch.len())?;
+        for (i, (_, (owner, repo))) in batch.iter().enumerate() {
+            if let Some(repository) = value
+                .get("data")
+                .and_then(|data| data.get(format!("r{i}")))
+                .filter(|repository| !repository.is_null())
+            {
+                crate::evidence::record_api_response(
+                    &format!("https://github.com/{owner}/{repo}"),
+                    "GitHub GraphQL API",
+                    repository,
+                );
+            }
+        }
+        for ((owner_slash_repo, _), repo_info) in batch.iter().zip(repo_infos) {
+            let Some(repo_info) = repo_info else {
+                continue;
+            };
+            REPOSITORY_CACHE.with_borrow_mut(|repository_cache| {
+                repository_cache.insert(owner_slash_repo.clone(), Some(Rc::new(repo_info)));
+            });
+            n_cached += 1;
+        }
+        // smoelius: If another batch could exceed the rate limit, leave the remaining repositories
+        // to the REST API, whose rate limit is separate.
+        if rate_limit.remaining < rate_limit.cost {
+            log::debug!(
+                "GraphQL rate limit nearly reached (resets at {:?}); not fetching further batches",
+                rate_limit.reset_at
+            );
+            break;
+        }
+    }
+
+    Ok(n_cached)
+}
+
+/// Returns a query that fetches each of `owners_and_repos`, along with the rate limit
+///
+/// The repository `owners_and_repos[i]` is fetched under the alias `r{i}`.
+pub(crate) fn query(owners_and_repos: &[(&str, &str)]) -> String {
+    let mut query = String::from("query {\n");
+    for (i, (owner, repo)) in owners_and_repos.iter().enumerate() {
+        // smoelius: A JSON string literal is also a GraphQL string literal.
+        #[allow(clippy::unwrap_used)]
+        writeln!(
+            query,
+            "  r{i}: repository(owner: {}, name: {}) {{ ...RepoInfo }}",
+            serde_json::Value::from(*owner),
+            serde_json::Value::from(*repo)
+        )
+        .unwrap();
+    }
+    query.push_str("  rateLimit { cost remaining resetAt }\n}\n");
+    query.push_str(FRAGMENT);
+    query
+}
+
+/// Parses the response to a query returned by [`query`] for `n` repositories
+///
+/// The `i`th element of the returned vector is `None` if the repository under alias `r{i}` was not
+/// resolved.
+pub(crate) fn parse_response(
+    value: &serde_json::Value,
+    n: usize,
+) -> Result<(Vec<Option<RepoInfo>>, RateLimit)> {
+    let map = value
+        .as_object()
+        .ok_or_else(|| anyhow!("GraphQL response is not an object"))?;
+
+    // smoelius: A repository that does not exist produces a `NOT_FOUND` error alongside the data.
+    // Any other kind of error (e.g., `RATE_LIMITED`) means the data cannot be relied upon.
+    if let Some(errors) = map.get_array("errors") {
+        if let Some(error) = errors.iter().find(|error| {
+            error
+                .as_object()
+                .and_then(|map| map.get_str("type"))
+                .is_none_or(|r#type| r#type != "NOT_FOUND")
+        }) {
+            bail!("GraphQL query failed: {error}");
+        }
+    }
+
+    let data = map
+        .get_object("data")
+        .ok_or_else(|| anyhow!("GraphQL response has no data"))?;
+
+    let repo_infos = (0..n)
+        .map(|i| {
+            data.get(&format!("r{i}"))
+                .filter(|value| !value.is_null())
+                .and_then(|value| serde_json::from_value::<GraphQlRepoInfo>(value.clone()).ok())
+                .map(RepoInfo::from)
+        })
+        .collect();
+
+    let rate_limit = data
+        .get_object("rateLimit")
+        .ok_or_else(|| anyhow!("GraphQL response has no rate limit"))?;
+    let (Some(cost), Some(remaining), Some(reset_at)) = (
+        rate_limit.get_u64("cost"),
+        rate_limit.get_u64("remaining"),
+        rate_limit.get_str("resetAt"),
+    ) else {
+        bail!("GraphQL response's rate limit is incomplete");
+    };
+
+    Ok((
+        repo_infos,
+        RateLimit {
+            cost,
+            remaining,
+            reset_at: parse_timestamp(reset_at)?,
+        },
+    ))
+}
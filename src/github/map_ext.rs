@@ -6,6 +6,7 @@ pub(crate) trait MapExt {
     fn get_bool(&self, key: &str) -> Option<bool>;
     fn get_object(&self, key: &str) -> Option<&Map<String, Value>>;
     fn get_str(&self, key: &str) -> Option<&str>;
+    fn get_u64(&self, key: &str) -> Option<u64>;
 }
 
 impl MapExt for Map<String, Value> {
@@ -21,4 +22,7 @@ impl MapExt for Map<String, Value> {
     fn get_str(&self, key: &str) -> Option<&str> {
         self.get(key).and_then(Value::as_str)
     }
+    fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(Value::as_u64)
+    }
 }
@@ -0,0 +1,192 @@
+//! Controllable GitHub behavior for tests
+//!
+//! The `testing-mock` feature compiles the mock in, but does not enable it. The mock is enabled
+//! once a fixture is set in-process with [`set_fixture`], or when the [`FIXTURES_VAR`] environment
+//! variable names a JSON file of fixtures. While the mock is enabled, no requests are made to
+//! GitHub's API. Instead, repositories' metadata is looked up in the fixtures. The file maps
+//! `owner/repo` to a [`RepoFixture`], e.g.:
+//!
+//! ```json
+//! { "owner/repo": { "archived": true } }
+//! ```
+//!
+//! A repository with no fixture does not exist. Token verification succeeds unless the token is
+//! [`REJECTED_TOKEN`]. A request for a repository's forks returns the repositories named by its
+//! fixture's `forks`. A request for a repository's latest release or a user's public events
+//! receives a 404, as GitHub responds when there is no release or the user does not exist. Other
+//! requests (e.g., for branches) return no results.
+//!
+//! The mock affects only GitHub's API. Repositories are still cloned with `git`.
+
+use super::{RateLimit, RepoInfo, TokenRejected, UnexpectedResponseCode};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env::{var, var_os},
+    fs::read_to_string,
+    time::SystemTime,
+};
+
+/// Environment variable naming a JSON file of fixtures
+pub const FIXTURES_VAR: &str = "CARGO_UNMAINTAINED_GITHUB_FIXTURES";
+
+/// A token that the mock rejects, as GitHub rejects an expired or revoked token
+pub const REJECTED_TOKEN: &str = "mock-rejected-token";
+
+/// The state of a mock GitHub repository
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RepoFixture {
+    #[serde(default)]
+    pub archived: bool,
+    /// If false, the repository is treated as though it had no fixture
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Time of the last push, in RFC 3339 format (e.g., `2024-01-01T00:00:00Z`)
+    #[serde(default)]
+    pub pushed_at: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub stargazers_count: u64,
+    /// The forks of the repository, each as `owner/repo`, with fixtures of their own
+    #[serde(default)]
+    pub forks: Vec<String>,
+}
+
+impl Default for RepoFixture {
+    fn default() -> Self {
+        Self {
+            archived: false,
+            exists: true,
+            default_branch: None,
+            pushed_at: None,
+            description: None,
+            homepage: None,
+            stargazers_count: 0,
+            forks: Vec::new(),
+        }
+    }
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+thread_local! {
+    static FIXTURES: RefCell<Option<HashMap<String, RepoFixture>>> = const { RefCell::new(None) };
+}
+
+/// Sets the fixture for the repository `owner_repo` (e.g., `rust-lang/cargo`)
+///
+/// Fixtures set this way take precedence over those read from [`FIXTURES_VAR`]'s file.
+pub fn set_fixture(owner_repo: &str, fixture: RepoFixture) -> Result<()> {
+    with_fixtures(|fixtures| {
+        fixtures.insert(owner_repo.to_owned(), fixture);
+    })
+}
+
+/// Returns true if the mock is enabled, i.e., if a fixture was set or [`FIXTURES_VAR`] is set
+pub(super) fn enabled() -> bool {
+    var_os(FIXTURES_VAR).is_some() || FIXTURES.with_borrow(Option::is_some)
+}
+
+pub(super) fn verify_token(token: &str) -> Result<RateLimit> {
+    if token == REJECTED_TOKEN {
+        return Err(TokenRejected(401).into());
+    }
+    Ok(RateLimit {
+        limit: 5000,
+        remaining: 5000,
+        reset: SystemTime::now(),
+    })
+}
+
+pub(super) fn repository(owner: &str, repo: &str) -> Result<RepoInfo> {
+    let owner_repo = format!("{owner}/{repo}");
+    let fixture = with_fixtures(|fixtures| fixtures.get(&owner_repo).cloned())?;
+    let Some(RepoFixture {
+        archived,
+        exists: true,
+        default_branch,
+        pushed_at,
+        description,
+        homepage,
+        ..
+    }) = fixture
+    else {
+        return Err(UnexpectedResponseCode(404).into());
+    };
+    let value = serde_json::json!({
+        "full_name": owner_repo,
+        "archived": archived,
+        "default_branch": default_branch,
+        "pushed_at": pushed_at,
+        "description": description,
+        "homepage": homepage,
+    });
+    serde_json::from_value(value).map_err(Into::into)
+}
+
+/// Responds to a request for `path` (e.g., `repos/owner/repo/forks`)
+pub(super) fn call_api(path: &str) -> Result<serde_json::Value> {
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+    let components = path.split('/').collect::<Vec<_>>();
+    match components.as_slice() {
+        ["repos", owner, repo, "forks"] => forks(owner, repo),
+        ["repos", _, _, "releases", "latest"] | ["users", _, "events", "public"] => {
+            Err(UnexpectedResponseCode(404).into())
+        }
+        _ => Ok(serde_json::Value::Array(Vec::new())),
+    }
+}
+
+fn forks(owner: &str, repo: &str) -> Result<serde_json::Value> {
+    with_fixtures(|fixtures| {
+        let forks = fixtures
+            .get(&format!("{owner}/{repo}"))
+            .map(|fixture| fixture.forks.as_slice())
+            .unwrap_or_default();
+        let mut forks = forks
+            .iter()
+            .filter_map(|owner_repo| Some((owner_repo, fixtures.get(owner_repo)?)))
+            .filter(|(_, fixture)| fixture.exists)
+            .collect::<Vec<_>>();
+        forks.sort_by_key(|(_, fixture)| std::cmp::Reverse(fixture.stargazers_count));
+        serde_json::Value::Array(
+            forks
+                .into_iter()
+                .map(|(owner_repo, fixture)| {
+                    serde_json::json!({
+                        "html_url": format!("https://github.com/{owner_repo}"),
+                        "stargazers_count": fixture.stargazers_count,
+                        "pushed_at": fixture.pushed_at,
+                    })
+                })
+                .collect(),
+        )
+    })
+}
+
+fn with_fixtures<T>(f: impl FnOnce(&mut HashMap<String, RepoFixture>) -> T) -> Result<T> {
+    FIXTURES.with_borrow_mut(|fixtures| {
+        if fixtures.is_none() {
+            *fixtures = Some(read_fixtures()?);
+        }
+        #[allow(clippy::unwrap_used)]
+        Ok(f(fixtures.as_mut().unwrap()))
+    })
+}
+
+fn read_fixtures() -> Result<HashMap<String, RepoFixture>> {
+    let Ok(path) = var(FIXTURES_VAR) else {
+        return Ok(HashMap::new());
+    };
+    let contents = read_to_string(&path).with_context(|| format!("failed to read `{path}`"))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse `{path}`"))
+}
@@ -1,4 +1,4 @@
-use super::{curl, RepoStatus, Url};
+use super::{curl, retry, RepoStatus, Url};
 use anyhow::{anyhow, bail, Result};
 use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
@@ -11,19 +11,51 @@ use std::{
     time::{Duration, SystemTime},
 };
 
+mod graphql;
+pub(crate) use graphql::prefetch_repositories;
+#[cfg(test)]
+pub(crate) use graphql::{parse_response, query};
+
 mod map_ext;
 use map_ext::MapExt;
 
+#[cfg(feature = "testing-mock")]
+pub mod mock;
+
+mod repo_info;
+pub(crate) use repo_info::RepoInfo;
+
+mod successor;
+#[cfg(test)]
+pub(crate) use successor::extract_candidates;
+pub(crate) use successor::successor_candidates;
+
 pub mod util;
+#[cfg(all(test, feature = "keyring"))]
+pub(crate) use util::keychain;
 use util::PERSONAL_TOKEN;
-pub(crate) use util::{load_token, save_token};
+pub(crate) use util::{load_token, save_token, SaveTokenDestination, TokenSource};
 
 #[allow(clippy::unwrap_used)]
 static RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^https://github\.com/(([^/]*)/([^/]*))").unwrap());
 
+/// Minimum number of stars a fork must have to be suggested
+const MIN_FORK_STARS: u64 = 10;
+
+/// Maximum number of forks to request (the forks are sorted by stars)
+const FORKS_PER_PAGE: u32 = 30;
+
+/// A fork that appears to be more actively maintained than its parent
+#[derive(Clone, Debug)]
+pub(crate) struct Fork {
+    pub url: String,
+    pub stars: u64,
+    pub pushed_at: SystemTime,
+}
+
 thread_local! {
-    static REPOSITORY_CACHE: RefCell<HashMap<String, Option<Rc<serde_json::Value>>>> = RefCell::new(HashMap::new());
+    static REPOSITORY_CACHE: RefCell<HashMap<String, Option<Rc<RepoInfo>>>> = RefCell::new(HashMap::new());
 }
 
 pub(crate) fn archival_status(url: Url) -> Result<RepoStatus<()>> {
@@ -33,17 +65,179 @@ pub(crate) fn archival_status(url: Url) -> Result<RepoStatus<()>> {
         return Ok(RepoStatus::Nonexistent(url));
     };
 
-    if repository
-        .as_object()
-        .and_then(|map| map.get_bool("archived"))
-        .unwrap_or_default()
-    {
+    if repository.archived() {
         Ok(RepoStatus::Archived(url))
     } else {
         Ok(RepoStatus::Success(url, ()))
     }
 }
 
+/// Returns the url of `owner`'s repository named `name`, if there is one
+///
+/// The url uses the repository's name as GitHub reports it, which can differ from `owner/name`,
+/// e.g., if the repository was transferred.
+pub(crate) fn infer_repository(owner: &str, name: &str) -> Result<Option<String>> {
+    match repository(&format!("{owner}/{name}"), owner, name) {
+        Ok(repository) => {
+            Ok(repository
+                .map(|repository| format!("https://github.com/{}", repository.full_name())))
+        }
+        Err(error)
+            if error
+                .downcast_ref::<UnexpectedResponseCode>()
+                .is_some_and(|&UnexpectedResponseCode(code)| code == 404) =>
+        {
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns forks of `url`'s repository that were pushed to more recently than the repository
+/// itself, and that have at least [`MIN_FORK_STARS`] stars, ordered by stars
+pub(crate) fn maintained_forks(url: Url) -> Result<Vec<Fork>> {
+    let (_, owner_slash_repo, owner, repo) = match_github_url(url)?;
+
+    let Some(repository) = repository(owner_slash_repo, owner, repo)? else {
+        return Ok(Vec::new());
+    };
+
+    let pushed_at = repository.pushed_at()?;
+
+    let forks = call_api(
+        owner,
+        repo,
+        Some(&format!("forks?sort=stargazers&per_page={FORKS_PER_PAGE}")),
+        &[],
+    )?;
+
+    select_forks(&forks, pushed_at)
+}
+
+pub(crate) fn select_forks(forks: &serde_json::Value, pushed_at: SystemTime) -> Result<Vec<Fork>> {
+    let array = forks
+        .as_array()
+        .ok_or_else(|| anyhow!("forks response is not an array"))?;
+
+    let mut selected = Vec::new();
+    for fork in array {
+        let Some(map) = fork.as_object() else {
+            continue;
+        };
+        let (Some(url), Some(stars), Some(fork_pushed_at)) = (
+            map.get_str("html_url"),
+            map.get_u64("stargazers_count"),
+            map.get_str("pushed_at"),
+        ) else {
+            continue;
+        };
+        let fork_pushed_at = parse_timestamp(fork_pushed_at)?;
+        if stars < MIN_FORK_STARS || fork_pushed_at <= pushed_at {
+            continue;
+        }
+        selected.push(Fork {
+            url: url.to_owned(),
+            stars,
+            pushed_at: fork_pushed_at,
+        });
+    }
+
+    selected.sort_by_key(|fork| std::cmp::Reverse(fork.stars));
+
+    Ok(selected)
+}
+
+/// Returns the tag name and publication time of `url`'s repository's latest release, if it has one
+pub(crate) fn latest_release(url: Url) -> Result<Option<(String, SystemTime)>> {
+    let (_, _, owner, repo) = match_github_url(url)?;
+
+    let release = match call_api(owner, repo, Some("releases/latest"), &[]) {
+        Ok(release) => release,
+        // smoelius: GitHub responds with 404 if the repository has no releases.
+        Err(error)
+            if error
+                .downcast_ref::<UnexpectedResponseCode>()
+                .is_some_and(|&UnexpectedResponseCode(code)| code == 404) =>
+        {
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+
+    let map = release
+        .as_object()
+        .ok_or_else(|| anyhow!("release response is not an object"))?;
+    let tag_name = map
+        .get_str("tag_name")
+        .ok_or_else(|| anyhow!("{url} release has no tag name"))?;
+    let published_at = map
+        .get_str("published_at")
+        .ok_or_else(|| anyhow!("{url} release has no publication time"))?;
+
+    Ok(Some((tag_name.to_owned(), parse_timestamp(published_at)?)))
+}
+
+/// Maximum number of branches to request
+const BRANCHES_PER_PAGE: u32 = 100;
+
+/// Returns the names of (up to [`BRANCHES_PER_PAGE`] of) `url`'s repository's branches
+pub(crate) fn branches(url: Url) -> Result<Vec<String>> {
+    let (_, _, owner, repo) = match_github_url(url)?;
+
+    let branches = call_api(
+        owner,
+        repo,
+        Some(&format!("branches?per_page={BRANCHES_PER_PAGE}")),
+        &[],
+    )?;
+
+    let array = branches
+        .as_array()
+        .ok_or_else(|| anyhow!("branches response is not an array"))?;
+
+    Ok(array
+        .iter()
+        .filter_map(|branch| branch.as_object().and_then(|map| map.get_str("name")))
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Returns the time of `login`'s most recent public event, if there is one
+///
+/// GitHub reports only events from the past 90 days. So `None` means that `login` has had no
+/// public activity in that time (or does not exist).
+pub(crate) fn latest_public_event(login: &str) -> Result<Option<SystemTime>> {
+    let events = match call_api_path(&format!("users/{login}/events/public?per_page=1"), &[]) {
+        Ok(events) => events,
+        Err(error)
+            if error
+                .downcast_ref::<UnexpectedResponseCode>()
+                .is_some_and(|&UnexpectedResponseCode(code)| code == 404) =>
+        {
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+
+    latest_event(&events)
+}
+
+pub(crate) fn latest_event(events: &serde_json::Value) -> Result<Option<SystemTime>> {
+    let array = events
+        .as_array()
+        .ok_or_else(|| anyhow!("events response is not an array"))?;
+    array
+        .iter()
+        .filter_map(|event| event.as_object().and_then(|map| map.get_str("created_at")))
+        .map(parse_timestamp)
+        .try_fold(None, |latest: Option<SystemTime>, timestamp| {
+            let timestamp = timestamp?;
+            Ok(Some(
+                latest.map_or(timestamp, |latest| latest.max(timestamp)),
+            ))
+        })
+}
+
 // smoelius: Since --imprecise was removed, timestamps are no longer checked using the GitHub API.
 #[allow(dead_code)]
 pub(crate) fn timestamp(url: Url) -> Result<Option<(Url, SystemTime)>> {
@@ -53,10 +247,7 @@ pub(crate) fn timestamp(url: Url) -> Result<Option<(Url, SystemTime)>> {
         return Ok(None);
     };
 
-    let default_branch = repository
-        .as_object()
-        .and_then(|map| map.get_str("default_branch"))
-        .ok_or_else(|| anyhow!("{url} repository has no default branch"))?;
+    let default_branch = repository.default_branch()?;
 
     let page = {
         let json = serde_json::json!({
@@ -81,21 +272,21 @@ pub(crate) fn timestamp(url: Url) -> Result<Option<(Url, SystemTime)>> {
         .and_then(|map| map.get_str("date"))
         .ok_or_else(|| anyhow!("{url} committer has no date"))?;
 
-    let date_time = date.parse::<DateTime<Utc>>()?;
-    let secs = date_time.timestamp().try_into()?;
-    let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    let timestamp = parse_timestamp(date)?;
 
     Ok(Some((url, timestamp)))
 }
 
+fn parse_timestamp(date: &str) -> Result<SystemTime> {
+    let date_time = date.parse::<DateTime<Utc>>()?;
+    let secs = date_time.timestamp().try_into()?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
 // smoelius: `owner_slash_repo` is a hack to avoid calling `to_owned` on `owner` and `repo` just to
 // perform a cache lookup.
-fn repository(
-    owner_slash_repo: &str,
-    owner: &str,
-    repo: &str,
-) -> Result<Option<Rc<serde_json::Value>>> {
+fn repository(owner_slash_repo: &str, owner: &str, repo: &str) -> Result<Option<Rc<RepoInfo>>> {
     REPOSITORY_CACHE.with_borrow_mut(|repository_cache| {
         if let Some(repo) = repository_cache.get(owner_slash_repo) {
             return Ok(repo.clone());
@@ -114,11 +305,36 @@ fn repository(
     })
 }
 
-fn repository_uncached(owner: &str, repo: &str) -> Result<serde_json::Value> {
-    call_api(owner, repo, None, &[])
+fn repository_uncached(owner: &str, repo: &str) -> Result<RepoInfo> {
+    #[cfg(feature = "testing-mock")]
+    if mock::enabled() {
+        return mock::repository(owner, repo);
+    }
+
+    let value = call_api(owner, repo, None, &[])?;
+    crate::evidence::record_api_response(
+        &format!("https://github.com/{owner}/{repo}"),
+        "GitHub REST API",
+        &value,
+    );
+    serde_json::from_value(value).map_err(Into::into)
+}
+
+/// Returns the url of a tarball of the default branch of the GitHub repository at `url`, if `url`
+/// is a GitHub url
+pub(crate) fn codeload_url(url: Url) -> Option<String> {
+    let (_, _, owner, repo) = match_github_url(url).ok()?;
+    Some(format!(
+        "https://codeload.github.com/{owner}/{repo}/tar.gz/HEAD"
+    ))
 }
 
-fn match_github_url(url: Url) -> Result<(Url, &str, &str, &str)> {
+/// Returns the personal access token, if one was loaded
+pub(crate) fn personal_token() -> Option<&'static str> {
+    PERSONAL_TOKEN.get().map(String::as_str)
+}
+
+fn match_github_url(url: Url<'_>) -> Result<(Url<'_>, &str, &str, &str)> {
     let (url_string, owner_slash_repo, owner, repo) = {
         #[allow(clippy::unwrap_used)]
         if let Some(captures) = RE.captures(url.as_str()) {
@@ -139,26 +355,152 @@ fn match_github_url(url: Url) -> Result<(Url, &str, &str, &str)> {
     Ok((url_string.into(), owner_slash_repo, owner, repo))
 }
 
+/// The GitHub API's core rate limit, as reported when a token is verified
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: SystemTime,
+}
+
+/// Error returned by [`verify_token`] when GitHub does not accept a token
+#[derive(Debug)]
+pub(crate) struct TokenRejected(pub u32);
+
+impl std::fmt::Display for TokenRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GitHub token rejected ({})", self.0)
+    }
+}
+
+impl std::error::Error for TokenRejected {}
+
+/// Verifies `token` by requesting the rate limit, which does not count against the rate limit
+///
+/// If GitHub does not accept `token`, the returned error is a [`TokenRejected`].
+pub(crate) fn verify_token(token: &str) -> Result<RateLimit> {
+    #[cfg(feature = "testing-mock")]
+    if mock::enabled() {
+        return mock::verify_token(token);
+    }
+
+    // smoelius: Do not retry. A failure here is reported, and the run proceeds.
+    let value =
+        call_api_once("rate_limit", Some(token), &[]).map_err(|error| match error
+            .downcast_ref::<UnexpectedResponseCode>(
+        ) {
+            Some(&UnexpectedResponseCode(code @ 401)) => TokenRejected(code).into(),
+            _ => error,
+        })?;
+    parse_rate_limit(&value)
+}
+
+pub(crate) fn parse_rate_limit(value: &serde_json::Value) -> Result<RateLimit> {
+    let core = value
+        .as_object()
+        .and_then(|map| map.get_object("resources"))
+        .and_then(|map| map.get_object("core"))
+        .ok_or_else(|| anyhow!("rate limit response has no core resource"))?;
+    let (Some(limit), Some(remaining), Some(reset)) = (
+        core.get_u64("limit"),
+        core.get_u64("remaining"),
+        core.get_u64("reset"),
+    ) else {
+        bail!("rate limit response's core resource is incomplete");
+    };
+    Ok(RateLimit {
+        limit,
+        remaining,
+        reset: SystemTime::UNIX_EPOCH + Duration::from_secs(reset),
+    })
+}
+
+#[derive(Debug)]
+pub(crate) struct UnexpectedResponseCode(pub u32);
+
+impl std::fmt::Display for UnexpectedResponseCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unexpected response code: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnexpectedResponseCode {}
+
 fn call_api(
     owner: &str,
     repo: &str,
     endpoint: Option<&str>,
-    mut data: &[u8],
+    data: &[u8],
 ) -> Result<serde_json::Value> {
-    let url_string = format!(
-        "https://api.github.com/repos/{owner}/{repo}{}",
+    let path = format!(
+        "repos/{owner}/{repo}{}",
         endpoint
             .map(|endpoint| String::from("/") + endpoint)
             .unwrap_or_default(),
     );
+    call_api_path(&path, data)
+}
+
+fn call_api_path(path: &str, data: &[u8]) -> Result<serde_json::Value> {
+    #[cfg(feature = "testing-mock")]
+    if mock::enabled() {
+        return mock::call_api(path);
+    }
 
+    retry::with_backoff(
+        "GitHub API request",
+        || call_api_once(path, PERSONAL_TOKEN.get().map(String::as_str), data),
+        is_transient,
+    )
+}
+
+// smoelius: Timeouts, rate limiting, and server errors are worth retrying. Anything else (e.g., a
+// 404, or a host that could not be resolved) will not be fixed by retrying.
+pub(crate) fn is_transient(error: &anyhow::Error) -> bool {
+    if let Some(error) = error.downcast_ref::<::curl::Error>() {
+        return retry::is_transient_curl_error(error);
+    }
+    error
+        .downcast_ref::<UnexpectedResponseCode>()
+        .is_some_and(|&UnexpectedResponseCode(code)| code == 429 || code >= 500)
+}
+
+fn call_api_once(path: &str, token: Option<&str>, data: &[u8]) -> Result<serde_json::Value> {
+    let url_string = format!("https://api.github.com/{path}");
+
+    let handle = curl::handle(url_string.as_str().into())?;
+
+    perform(handle, token, data)
+}
+
+fn call_graphql(query: &str) -> Result<serde_json::Value> {
+    let token = PERSONAL_TOKEN
+        .get()
+        .ok_or_else(|| anyhow!("the GraphQL API requires a token"))?;
+    let body = serde_json::json!({ "query": query }).to_string();
+    retry::with_backoff(
+        "GitHub GraphQL API request",
+        || {
+            let mut handle = curl::handle("https://api.github.com/graphql".into())?;
+            handle.post(true)?;
+            handle.post_field_size(body.len().try_into()?)?;
+            perform(handle, Some(token), body.as_bytes())
+        },
+        is_transient,
+    )
+}
+
+fn perform(
+    mut handle: ::curl::easy::Easy,
+    token: Option<&str>,
+    mut data: &[u8],
+) -> Result<serde_json::Value> {
     let mut list = ::curl::easy::List::new();
     list.append("User-Agent: cargo-unmaintained")?;
-    if let Some(token) = PERSONAL_TOKEN.get() {
+    if let Some(token) = token {
         list.append(&format!("Authorization: Bearer {token}"))?;
     }
 
-    let mut handle = curl::handle(url_string.as_str().into())?;
     handle.http_headers(list)?;
     let mut response = Vec::new();
     {
@@ -179,7 +521,7 @@ fn call_api(
 
     // smoelius: Should the next statement handle 404s, like `curl::existence` does?
     if response_code != 200 {
-        bail!("unexpected response code: {response_code}");
+        return Err(UnexpectedResponseCode(response_code).into());
     }
 
     let value = serde_json::from_slice::<serde_json::Value>(&response)?;
@@ -0,0 +1,148 @@
+use super::parse_timestamp;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::time::SystemTime;
+
+/// The parts of a GitHub repository's metadata that cargo-unmaintained uses
+///
+/// A repository's metadata is fetched and deserialized at most once per run. Lookups that need
+/// other fields should add them here rather than fetching the metadata again.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RepoInfo {
+    full_name: String,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    parent: Option<Parent>,
+    #[serde(default)]
+    default_branch: Option<String>,
+    #[serde(default)]
+    pushed_at: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    homepage: Option<String>,
+}
+
+/// The repository from which a fork was created
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub(crate) struct Parent {
+    pub full_name: String,
+    pub html_url: String,
+}
+
+/// A repository's metadata as fetched by the GraphQL API (see [`super::graphql`])
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GraphQlRepoInfo {
+    name_with_owner: String,
+    #[serde(default)]
+    is_archived: bool,
+    #[serde(default)]
+    is_fork: bool,
+    #[serde(default)]
+    parent: Option<GraphQlParent>,
+    #[serde(default)]
+    default_branch_ref: Option<GraphQlRef>,
+    #[serde(default)]
+    pushed_at: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    homepage_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlParent {
+    name_with_owner: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlRef {
+    name: String,
+}
+
+impl From<GraphQlRepoInfo> for RepoInfo {
+    fn from(value: GraphQlRepoInfo) -> Self {
+        let GraphQlRepoInfo {
+            name_with_owner,
+            is_archived,
+            is_fork,
+            parent,
+            default_branch_ref,
+            pushed_at,
+            description,
+            homepage_url,
+        } = value;
+        Self {
+            full_name: name_with_owner,
+            archived: is_archived,
+            fork: is_fork,
+            parent: parent.map(
+                |GraphQlParent {
+                     name_with_owner,
+                     url,
+                 }| Parent {
+                    full_name: name_with_owner,
+                    html_url: url,
+                },
+            ),
+            default_branch: default_branch_ref.map(|GraphQlRef { name }| name),
+            pushed_at,
+            description,
+            homepage: homepage_url,
+        }
+    }
+}
+
+// smoelius: Not every accessor is used yet. But the fields are deserialized regardless, so that
+// future lookups (e.g., of whether a repository is a fork) do not require additional API calls.
+#[allow(dead_code)]
+impl RepoInfo {
+    /// Returns the repository's name in `owner/repo` form, as GitHub reports it
+    ///
+    /// This can differ from the name used to look up the repository, e.g., if the repository was
+    /// renamed or transferred.
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    pub fn archived(&self) -> bool {
+        self.archived
+    }
+
+    pub fn fork(&self) -> bool {
+        self.fork
+    }
+
+    pub fn parent(&self) -> Option<&Parent> {
+        self.parent.as_ref()
+    }
+
+    pub fn default_branch(&self) -> Result<&str> {
+        self.default_branch
+            .as_deref()
+            .ok_or_else(|| anyhow!("{} repository has no default branch", self.full_name))
+    }
+
+    pub fn pushed_at(&self) -> Result<SystemTime> {
+        let pushed_at = self
+            .pushed_at
+            .as_deref()
+            .ok_or_else(|| anyhow!("{} repository has no push time", self.full_name))?;
+        parse_timestamp(pushed_at)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
+    }
+}
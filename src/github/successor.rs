@@ -0,0 +1,127 @@
+//! Successors of archived repositories
+//!
+//! An archived repository's description or homepage often names the repository that replaced it,
+//! e.g., "Moved to github.com/owner/foo2" or "Use `foo2` instead". A candidate is a
+//! repository with the same owner that is named by url, by `owner/name`, or by a name in backticks.
+//! Candidates are ordered by where they are named, the description before the homepage. Only the
+//! first candidate is checked, with a single API call.
+
+use super::{match_github_url, repository, RepoInfo, UnexpectedResponseCode, Url};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::rc::Rc;
+
+#[allow(clippy::unwrap_used)]
+static URL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:https?://)?(?:www\.)?github\.com/([A-Za-z0-9-]+)/([A-Za-z0-9._-]+)")
+        .unwrap()
+});
+
+#[allow(clippy::unwrap_used)]
+static OWNER_SLASH_NAME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[\s(])([A-Za-z0-9-]+)/([A-Za-z0-9._-]+)").unwrap());
+
+#[allow(clippy::unwrap_used)]
+static BACKTICKED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"`([A-Za-z0-9._-]+)`").unwrap());
+
+/// Returns the urls of the possible successors of `url`'s repository, if it is archived
+///
+/// Nothing is returned unless the first candidate exists and is not archived. If it does, it is
+/// returned under the name GitHub reports for it, followed by the remaining (unchecked) candidates.
+pub(crate) fn successor_candidates(url: Url) -> Result<Vec<String>> {
+    let (_, owner_slash_repo, owner, repo) = match_github_url(url)?;
+
+    let Some(repository) = repository(owner_slash_repo, owner, repo)? else {
+        return Ok(Vec::new());
+    };
+
+    if !repository.archived() {
+        return Ok(Vec::new());
+    }
+
+    let mut candidates = extract_candidates(
+        owner,
+        repo,
+        [repository.description(), repository.homepage()]
+            .into_iter()
+            .flatten(),
+    );
+
+    let Some(first) = candidates.first_mut() else {
+        return Ok(Vec::new());
+    };
+
+    let name = first
+        .split_once('/')
+        .map_or(first.as_str(), |(_, name)| name);
+    let Some(successor) = unarchived_repository(owner, name)? else {
+        return Ok(Vec::new());
+    };
+
+    successor.full_name().clone_into(first);
+
+    Ok(candidates
+        .into_iter()
+        .map(|candidate| format!("https://github.com/{candidate}"))
+        .collect())
+}
+
+/// Returns the candidate successors of `owner`'s repository `repo` named in `texts`, each in
+/// `owner/name` form
+pub(crate) fn extract_candidates<'a>(
+    owner: &str,
+    repo: &str,
+    texts: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut names = Vec::<&str>::new();
+    for text in texts {
+        let mut found = Vec::new();
+        for re in [&*URL_RE, &*OWNER_SLASH_NAME_RE] {
+            for captures in re.captures_iter(text) {
+                if captures[1].eq_ignore_ascii_case(owner) {
+                    #[allow(clippy::unwrap_used)]
+                    let name = captures.get(2).unwrap();
+                    found.push((name.start(), name.as_str()));
+                }
+            }
+        }
+        for captures in BACKTICKED_RE.captures_iter(text) {
+            #[allow(clippy::unwrap_used)]
+            let name = captures.get(1).unwrap();
+            found.push((name.start(), name.as_str()));
+        }
+        found.sort_by_key(|&(start, _)| start);
+        for (_, name) in found {
+            // smoelius: A name at the end of a sentence is followed by a period.
+            let name = name.trim_end_matches('.');
+            let name = name.strip_suffix(".git").unwrap_or(name);
+            if name.is_empty()
+                || name.eq_ignore_ascii_case(repo)
+                || names.iter().any(|other| other.eq_ignore_ascii_case(name))
+            {
+                continue;
+            }
+            names.push(name);
+        }
+    }
+    names
+        .into_iter()
+        .map(|name| format!("{owner}/{name}"))
+        .collect()
+}
+
+/// Returns `owner`'s repository `name`, if it exists and is not archived
+fn unarchived_repository(owner: &str, name: &str) -> Result<Option<Rc<RepoInfo>>> {
+    match repository(&format!("{owner}/{name}"), owner, name) {
+        Ok(repository) => Ok(repository.filter(|repository| !repository.archived())),
+        Err(error)
+            if error
+                .downcast_ref::<UnexpectedResponseCode>()
+                .is_some_and(|&UnexpectedResponseCode(code)| code == 404) =>
+        {
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
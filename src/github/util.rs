@@ -1,11 +1,15 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
 use once_cell::sync::Lazy;
 use std::{
-    env::var,
+    env::{split_paths, var, var_os},
     fs::{create_dir_all, read_to_string, File, OpenOptions},
     io::{stdin, Write},
     path::PathBuf,
+    process::{Command, Stdio},
     sync::OnceLock,
+    thread::sleep,
+    time::{Duration, Instant},
 };
 
 #[allow(clippy::unwrap_used)]
@@ -26,44 +30,179 @@ static TOKEN_PATH: Lazy<PathBuf> = Lazy::new(|| CONFIG_DIRECTORY.join("token.txt
 
 pub(super) static PERSONAL_TOKEN: OnceLock<String> = OnceLock::new();
 
-pub fn load_token(f: impl FnOnce(&str) -> Result<()>) -> Result<bool> {
-    let token_untrimmed = if let Ok(path) = var("GITHUB_TOKEN_PATH") {
-        read_to_string(&path).with_context(|| format!("failed to read {path:?}"))?
-    } else if let Ok(token) = var("GITHUB_TOKEN") {
-        // smoelius: Suppress warning if `CI` is set, i.e., if running on GitHub.
-        if var("CI").is_err() {
-            #[cfg(__warnings)]
+/// Where a personal access token can be read from
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum TokenSource {
+    #[default]
+    Auto,
+    Env,
+    File,
+    Gh,
+    Keyring,
+    None,
+}
+
+/// Where `--save-token` can save a personal access token
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum SaveTokenDestination {
+    #[default]
+    File,
+    #[cfg(feature = "keyring")]
+    Keyring,
+}
+
+/// How long to wait for `gh auth token` before giving up on it
+const GH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads a token from the sources that `source` allows, passes it to `f`, and returns a description
+/// of where it was read from
+pub fn load_token(
+    source: TokenSource,
+    f: impl FnOnce(&str) -> Result<()>,
+) -> Result<Option<String>> {
+    let Some((token_untrimmed, origin)) = read_token(source)? else {
+        #[cfg(__warnings)]
+        if source == TokenSource::Auto {
+            crate::warn!(
+                "`GITHUB_TOKEN_PATH` and `GITHUB_TOKEN` are not set, no file was found at {}, and \
+                 no token was obtained from `gh auth token`{}; archival statuses will not be \
+                 checked",
+                TOKEN_PATH.display(),
+                if cfg!(feature = "keyring") {
+                    " or the system keychain"
+                } else {
+                    ""
+                }
+            );
+        } else if source != TokenSource::None {
             crate::warn!(
-                "found a token in `GITHUB_TOKEN`; consider using the more secure method of \
-                 setting `GITHUB_TOKEN_PATH` to the path of a file containing the token",
+                "no token was found using --token-source={}; archival statuses will not be checked",
+                source
+                    .to_possible_value()
+                    .map_or_else(String::new, |value| value.get_name().to_owned())
             );
         }
-        token
-    } else if TOKEN_PATH.try_exists().with_context(|| {
-        format!(
-            "failed to determine whether `{}` exists",
-            TOKEN_PATH.display()
-        )
-    })? {
-        read_to_string(&*TOKEN_PATH).with_context(|| format!("failed to read {TOKEN_PATH:?}"))?
-    } else {
-        #[cfg(__warnings)]
-        crate::warn!(
-            "`GITHUB_TOKEN_PATH` and `GITHUB_TOKEN` are not set and no file was found at {}; \
-             archival statuses will not be checked",
-            TOKEN_PATH.display()
-        );
-        return Ok(false);
+        return Ok(None);
     };
     let token = token_untrimmed.trim_end().to_owned();
     PERSONAL_TOKEN
         .set(token.clone())
         .map_err(|_| anyhow!("`load_token` was already called"))?;
     f(&token)?;
-    Ok(true)
+    Ok(Some(origin))
+}
+
+/// Returns a token and a description of where it was read from, trying the sources that `source`
+/// allows in order
+fn read_token(source: TokenSource) -> Result<Option<(String, String)>> {
+    let allows = |other: TokenSource| source == TokenSource::Auto || source == other;
+
+    if allows(TokenSource::Env) {
+        if let Ok(path) = var("GITHUB_TOKEN_PATH") {
+            let token =
+                read_to_string(&path).with_context(|| format!("failed to read {path:?}"))?;
+            return Ok(Some((token, format!("`GITHUB_TOKEN_PATH` ({path})"))));
+        }
+        if let Ok(token) = var("GITHUB_TOKEN") {
+            // smoelius: Suppress warning if `CI` is set, i.e., if running on GitHub.
+            if var("CI").is_err() {
+                #[cfg(__warnings)]
+                crate::warn!(
+                    "found a token in `GITHUB_TOKEN`; consider using the more secure method of \
+                     setting `GITHUB_TOKEN_PATH` to the path of a file containing the token",
+                );
+            }
+            return Ok(Some((token, String::from("`GITHUB_TOKEN`"))));
+        }
+    }
+
+    if allows(TokenSource::File)
+        && TOKEN_PATH.try_exists().with_context(|| {
+            format!(
+                "failed to determine whether `{}` exists",
+                TOKEN_PATH.display()
+            )
+        })?
+    {
+        let token = read_to_string(&*TOKEN_PATH)
+            .with_context(|| format!("failed to read {TOKEN_PATH:?}"))?;
+        return Ok(Some((token, TOKEN_PATH.display().to_string())));
+    }
+
+    if allows(TokenSource::Gh) {
+        if let Some(token) = gh_auth_token()? {
+            return Ok(Some((token, String::from("`gh auth token`"))));
+        }
+    }
+
+    if source == TokenSource::Keyring && !cfg!(feature = "keyring") {
+        bail!(
+            "--token-source=keyring requires cargo-unmaintained to be built with the `keyring` \
+             feature"
+        );
+    }
+
+    #[cfg(feature = "keyring")]
+    if allows(TokenSource::Keyring) {
+        if let Some(token) = keychain::read()? {
+            return Ok(Some((token, String::from("the system keychain"))));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Runs `gh auth token`, if `gh` is installed, and returns its output
+///
+/// A missing, failing, or slow `gh` is not an error; it just does not provide a token.
+fn gh_auth_token() -> Result<Option<String>> {
+    let Some(gh) = find_executable("gh") else {
+        return Ok(None);
+    };
+    let mut child = Command::new(&gh)
+        .args(["auth", "token"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("failed to spawn `{}`", gh.display()))?;
+    let deadline = Instant::now() + GH_TIMEOUT;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            // smoelius: Ignore errors. The child may have exited in the meantime.
+            let _: std::io::Result<()> = child.kill();
+            let _: std::io::Result<_> = child.wait();
+            log::debug!("`gh auth token` did not finish within {GH_TIMEOUT:?}");
+            return Ok(None);
+        }
+        sleep(Duration::from_millis(10));
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        log::debug!("`gh auth token` failed: {:?}", output.status);
+        return Ok(None);
+    }
+    let token = String::from_utf8(output.stdout)?;
+    if token.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(token))
 }
 
-pub(crate) fn save_token() -> Result<()> {
+/// Returns the first file named `name` (or, on Windows, `name.exe`) in a `PATH` directory
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = var_os("PATH")?;
+    let file_name = if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_owned()
+    };
+    split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+pub(crate) fn save_token(destination: SaveTokenDestination) -> Result<()> {
     println!("Please paste a personal access token below. The token needs no scopes.");
 
     let mut buf = String::new();
@@ -75,6 +214,26 @@ pub(crate) fn save_token() -> Result<()> {
         assert_eq!(buf.len(), n);
     }
 
+    match super::verify_token(buf.trim_end()) {
+        Ok(_) => {}
+        Err(error) if error.is::<super::TokenRejected>() => {
+            return Err(error.context("not saving token"));
+        }
+        // smoelius: The token may be fine. Perhaps the network is unavailable.
+        Err(error) => {
+            crate::warn!("failed to verify token; saving it anyway: {}", error);
+        }
+    }
+
+    #[cfg(feature = "keyring")]
+    if destination == SaveTokenDestination::Keyring {
+        keychain::write(buf.trim_end())?;
+        println!("Personal access token written to the system keychain");
+        return Ok(());
+    }
+    #[cfg(not(feature = "keyring"))]
+    let SaveTokenDestination::File = destination;
+
     create_dir_all(&*CONFIG_DIRECTORY).with_context(|| "failed to create config directory")?;
 
     let mut file = OpenOptions::new()
@@ -95,6 +254,55 @@ pub(crate) fn save_token() -> Result<()> {
     Ok(())
 }
 
+// smoelius: The system keychain is accessed through the `keyring` crate, i.e., through the Security
+// framework on macOS, the Secret Service on Linux and the BSDs, and the Credential Manager on
+// Windows.
+#[cfg(feature = "keyring")]
+pub(crate) mod keychain {
+    use anyhow::{anyhow, Context, Result};
+    use keyring::{Entry, Error};
+    use once_cell::sync::Lazy;
+
+    const SERVICE: &str = "cargo-unmaintained";
+    const ACCOUNT: &str = "github";
+
+    // smoelius: Reads and writes go through the same entry. So the `keyring` crate's mock
+    // credentials, which are not shared between entries, can be used in tests.
+    static ENTRY: Lazy<keyring::Result<Entry>> = Lazy::new(|| Entry::new(SERVICE, ACCOUNT));
+
+    /// Returns the token stored in the system keychain, if any
+    pub fn read() -> Result<Option<String>> {
+        let entry = match &*ENTRY {
+            Ok(entry) => entry,
+            Err(error) => {
+                log::debug!("failed to access the system keychain: {error}");
+                return Ok(None);
+            }
+        };
+        match entry.get_password() {
+            Ok(token) => Ok(Some(token).filter(|token| !token.trim().is_empty())),
+            // smoelius: A missing entry or an unavailable keychain just means the keychain has no
+            // token.
+            Err(Error::NoEntry) => Ok(None),
+            Err(error @ (Error::PlatformFailure(_) | Error::NoStorageAccess(_))) => {
+                log::debug!("failed to read from the system keychain: {error}");
+                Ok(None)
+            }
+            Err(error) => Err(error).context("failed to read token from the system keychain"),
+        }
+    }
+
+    /// Stores `token` in the system keychain
+    pub fn write(token: &str) -> Result<()> {
+        let entry = ENTRY
+            .as_ref()
+            .map_err(|error| anyhow!("failed to access the system keychain: {error}"))?;
+        entry
+            .set_password(token)
+            .context("failed to write token to the system keychain")
+    }
+}
+
 type CargoResult<T> = Result<T>;
 
 // smoelius: The below definitions of `set_permissions` were copied from:
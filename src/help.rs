@@ -0,0 +1,120 @@
+//! `--generate-man` and `--help-json`: renderings of the command line interface for packagers and
+//! doc tooling
+//!
+//! Both are produced from the same [`Command`] as `--help`. So they reflect exactly the options
+//! compiled into the binary generating them (e.g., `--save-token`'s Windows or non-Windows help).
+//! The man page is rendered by `clap_mangen`. Hidden options and subcommands are left out of it, as
+//! they are left out of `--help`.
+
+use anyhow::Result;
+use clap::{builder::PossibleValue, ArgAction, Command};
+use clap_mangen::Man;
+use serde_json::{json, Value};
+
+/// Renders `command` as a section 1 man page
+pub(crate) fn man_page(command: Command) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    Man::new(command).render(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Describes `command`'s options and subcommands as JSON
+///
+/// Unlike the man page, the schema includes hidden options, marked as such.
+pub(crate) fn schema(command: &Command) -> Value {
+    let options = command
+        .get_arguments()
+        .map(|arg| {
+            // smoelius: A conflict is declared on only one of the two arguments. So look in both
+            // directions.
+            let mut conflicts_with = command
+                .get_arguments()
+                .filter(|other| {
+                    command.get_arg_conflicts_with(arg).contains(other)
+                        || command.get_arg_conflicts_with(other).contains(&arg)
+                })
+                .map(|other| other.get_id().as_str().to_owned())
+                .collect::<Vec<_>>();
+            conflicts_with.sort();
+            json!({
+                "id": arg.get_id().as_str(),
+                "long": arg.get_long(),
+                "short": arg.get_short(),
+                "help": arg.get_help().map(ToString::to_string),
+                "type": value_type(arg),
+                "value_names": value_names(arg),
+                "possible_values": possible_values(arg),
+                "default": default_values(arg),
+                "conflicts_with": conflicts_with,
+                "global": arg.is_global_set(),
+                "hidden": arg.is_hide_set(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| {
+            json!({
+                "name": subcommand.get_name(),
+                "about": subcommand.get_about().map(ToString::to_string),
+                "hidden": subcommand.is_hide_set(),
+                "options": schema(subcommand)["options"],
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "name": command.get_name(),
+        "version": command.get_version(),
+        "about": command.get_about().map(ToString::to_string),
+        "options": options,
+        "subcommands": subcommands,
+    })
+}
+
+/// Returns `flag` for an option that takes no value, `count` for one that may be repeated to
+/// increase a count, `list` for one that may be given multiple times, and `value` otherwise
+fn value_type(arg: &clap::Arg) -> &'static str {
+    match arg.get_action() {
+        ArgAction::Append => "list",
+        ArgAction::Count => "count",
+        action if takes_value(action) => "value",
+        _ => "flag",
+    }
+}
+
+fn takes_value(action: &ArgAction) -> bool {
+    matches!(action, ArgAction::Set | ArgAction::Append)
+}
+
+fn value_names(arg: &clap::Arg) -> Vec<String> {
+    if !takes_value(arg.get_action()) {
+        return Vec::new();
+    }
+    arg.get_value_names()
+        .unwrap_or_default()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn default_values(arg: &clap::Arg) -> Vec<String> {
+    if !takes_value(arg.get_action()) {
+        return Vec::new();
+    }
+    arg.get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn possible_values(arg: &clap::Arg) -> Vec<String> {
+    if !takes_value(arg.get_action()) {
+        return Vec::new();
+    }
+    arg.get_possible_values()
+        .iter()
+        .filter(|possible_value| !possible_value.is_hide_set())
+        .map(PossibleValue::get_name)
+        .map(ToOwned::to_owned)
+        .collect()
+}
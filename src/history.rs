@@ -0,0 +1,214 @@
+//! How long findings have been outstanding, across runs (see `--history`)
+//!
+//! The on-disk cache holds a findings history for each workspace. A finding is identified by its
+//! finding id (see [`crate::serialize::finding_id`]). When a scan produces a finding, the finding's
+//! first-seen time is recorded if absent, and its last-seen time is updated. When a scan does not
+//! produce a previously recorded finding, the finding is marked resolved. A resolved finding that
+//! recurs is recorded anew.
+//!
+//! Scans with different scopes (e.g., with and without `--only`) would disagree about which
+//! findings are outstanding. So each scope has its own history, identified by a fingerprint of the
+//! workspace root and the options that determine the scope.
+//!
+//! Times are those against which ages are computed (see [`crate::clock::now`]).
+
+use super::{
+    clock, days, on_disk_cache, opts, prefilter,
+    serialize::{self, rfc3339},
+    UnmaintainedPkg,
+};
+use anyhow::Result;
+use cargo_metadata::Metadata;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, SystemTime},
+};
+
+#[derive(Default, Deserialize, Serialize)]
+pub(crate) struct History {
+    findings: BTreeMap<String, Record>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub(crate) struct Record {
+    /// The name of the package that produced the finding when it was first seen
+    pub name: String,
+    /// The finding's family (see [`serialize::SerializableRepoStatus::family`])
+    pub family: String,
+    /// Seconds since the Unix epoch
+    pub first_seen: u64,
+    pub last_seen: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<u64>,
+}
+
+/// A finding produced by a scan
+pub(crate) struct Finding<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub family: &'a str,
+}
+
+impl History {
+    /// Records the findings that a scan produced at `now`, and returns the time at which each was
+    /// first seen
+    ///
+    /// Findings not produced are marked resolved, unless `complete` is false (e.g., because the
+    /// scan stopped early) or they were produced by a package named in `unchecked`.
+    pub(crate) fn update(
+        &mut self,
+        findings: &[Finding],
+        unchecked: &BTreeSet<&str>,
+        complete: bool,
+        now: u64,
+    ) -> BTreeMap<String, u64> {
+        let mut first_seen = BTreeMap::new();
+        for finding in findings {
+            let record = self
+                .findings
+                .entry(finding.id.to_owned())
+                .and_modify(|record| {
+                    if record.resolved.is_some() {
+                        record.first_seen = now;
+                        record.resolved = None;
+                    }
+                })
+                .or_insert_with(|| Record {
+                    name: finding.name.to_owned(),
+                    family: finding.family.to_owned(),
+                    first_seen: now,
+                    last_seen: now,
+                    resolved: None,
+                });
+            record.last_seen = now;
+            first_seen.insert(finding.id.to_owned(), record.first_seen);
+        }
+        if complete {
+            for (id, record) in &mut self.findings {
+                if record.resolved.is_none()
+                    && !first_seen.contains_key(id)
+                    && !unchecked.contains(record.name.as_str())
+                {
+                    record.resolved = Some(now);
+                }
+            }
+        }
+        first_seen
+    }
+
+    /// Returns the findings resolved no more than `days` days before `now`, most recent first
+    pub(crate) fn resolved_within(&self, days: u64, now: u64) -> Vec<Record> {
+        let since = now.saturating_sub(days::to_secs(days));
+        let mut resolved = self
+            .findings
+            .values()
+            .filter(|record| record.resolved.is_some_and(|resolved| resolved >= since))
+            .cloned()
+            .collect::<Vec<_>>();
+        resolved.sort_by_key(|record| std::cmp::Reverse(record.resolved));
+        resolved
+    }
+}
+
+impl Record {
+    /// Describes the record as it appears in `--history` output, e.g., `foo (archived): resolved
+    /// on 2024-05-01, after 94 days`
+    pub(crate) fn describe(&self) -> String {
+        let resolved = self.resolved.unwrap_or(self.last_seen);
+        let date = rfc3339(from_secs(resolved));
+        format!(
+            "{} ({}): resolved on {}, after {} days",
+            self.name,
+            self.family,
+            date.split_once('T').map_or(date.as_str(), |(date, _)| date),
+            days::from_secs(resolved.saturating_sub(self.first_seen))
+        )
+    }
+}
+
+/// Records the findings among `unmaintained_pkgs` in the workspace's history, sets each package's
+/// first-seen time, and returns the findings resolved within `--history` days
+pub(crate) fn record(
+    metadata: &Metadata,
+    unmaintained_pkgs: &mut [UnmaintainedPkg],
+    unchecked: &BTreeSet<&str>,
+) -> Result<Vec<Record>> {
+    let now = to_secs(clock::now());
+    let serializable_unmaintained_pkgs = unmaintained_pkgs
+        .iter()
+        .map(serialize::SerializableUnmaintainedPkg::new)
+        .collect::<Vec<_>>();
+    let findings = serializable_unmaintained_pkgs
+        .iter()
+        .map(|unmaintained_pkg| Finding {
+            id: &unmaintained_pkg.finding_id,
+            name: &unmaintained_pkg.name,
+            family: unmaintained_pkg.repo_status.family(),
+        })
+        .collect::<Vec<_>>();
+    // smoelius: A scan that stopped early did not produce the findings it would have produced
+    // after stopping. Nor does a fast scan produce findings for the packages it skips.
+    let complete = !opts::get().fast
+        && unmaintained_pkgs
+            .iter()
+            .all(|unmaintained_pkg| unmaintained_pkg.fail_fast.is_none());
+    let (first_seen, resolved) = on_disk_cache::with_cache(|cache| -> Result<_> {
+        cache.update_history(&fingerprint(metadata), |history| {
+            let first_seen = history.update(&findings, unchecked, complete, now);
+            let resolved = opts::get()
+                .history
+                .map(|days| history.resolved_within(days, now))
+                .unwrap_or_default();
+            (first_seen, resolved)
+        })
+    })?;
+    for (unmaintained_pkg, serializable) in unmaintained_pkgs
+        .iter_mut()
+        .zip(&serializable_unmaintained_pkgs)
+    {
+        unmaintained_pkg.first_seen = first_seen
+            .get(&serializable.finding_id)
+            .copied()
+            .map(from_secs);
+    }
+    Ok(resolved)
+}
+
+/// Displays the findings resolved within `--history` days
+pub(crate) fn display(resolved: &[Record]) {
+    let Some(days) = opts::get().history else {
+        return;
+    };
+    if resolved.is_empty() {
+        println!("No findings resolved in the last {days} days");
+        return;
+    }
+    println!("Findings resolved in the last {days} days:");
+    for record in resolved {
+        println!("    {}", record.describe());
+    }
+}
+
+/// Returns a fingerprint of the workspace and the options that determine a scan's scope
+fn fingerprint(metadata: &Metadata) -> String {
+    let opts = opts::get();
+    serde_json::json!({
+        "workspace_root": metadata.workspace_root,
+        "only": opts.only,
+        "package": opts.package,
+        "prefilter": prefilter::enabled(),
+        "target": opts.target,
+    })
+    .to_string()
+}
+
+fn to_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn from_secs(secs: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+}
@@ -0,0 +1,152 @@
+//! Entries of `workspace.metadata.unmaintained.ignore`
+//!
+//! An entry is either a package name or a table giving a name, and optionally a reason and a date
+//! until which the package is ignored, e.g.:
+//!
+//! ```toml
+//! ignore = ["foo", { name = "bar", reason = "replacing in Q3", until = "2025-09-01" }]
+//! ```
+//!
+//! An entry whose date has passed (relative to [`crate::clock::now`]) has expired. A package named
+//! by an expired entry is scanned as though it were not ignored, and if it is flagged, the finding
+//! says so.
+
+use super::clock;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::cell::RefCell;
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Entry {
+    Name(String),
+    Table {
+        name: String,
+        reason: Option<String>,
+        until: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Ignore {
+    pub name: String,
+    pub reason: Option<String>,
+    pub until: Option<NaiveDate>,
+    /// The file the entry was imported from (see [`crate::deny_config`]), if any
+    pub via: Option<String>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Vec<Ignore>> = const { RefCell::new(Vec::new()) };
+    static EXPIRED: RefCell<Vec<Ignore>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Ignore {
+    /// Returns true if the entry's date is before `today`
+    pub fn expired(&self, today: NaiveDate) -> bool {
+        self.until.is_some_and(|until| until < today)
+    }
+
+    /// Describes an expired entry as it appears in a finding, e.g., `ignore entry expired on
+    /// 2025-09-01 (reason: replacing in Q3)`
+    pub fn describe_expired(&self) -> Option<String> {
+        let until = self.until?;
+        Some(match &self.reason {
+            Some(reason) => format!("ignore entry expired on {until} (reason: {reason})"),
+            None => format!("ignore entry expired on {until}"),
+        })
+    }
+
+    /// Describes an active entry as it appears in the summary, e.g., `bar (reason: replacing in
+    /// Q3; until 2025-09-01)` or `baz (ignored via deny.toml)`
+    pub fn describe_active(&self) -> String {
+        let details = self
+            .reason
+            .iter()
+            .map(|reason| format!("reason: {reason}"))
+            .chain(self.until.iter().map(|until| format!("until {until}")))
+            .chain(self.via.iter().map(|via| format!("ignored via {via}")))
+            .collect::<Vec<_>>();
+        if details.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, details.join("; "))
+        }
+    }
+}
+
+/// Parses the entries of `workspace.metadata.unmaintained.ignore`
+pub(crate) fn parse(entries: Vec<Entry>) -> Result<Vec<Ignore>> {
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Entry::Name(name) => Ok(Ignore {
+                name,
+                reason: None,
+                until: None,
+                via: None,
+            }),
+            Entry::Table {
+                name,
+                reason,
+                until,
+            } => {
+                let until = until
+                    .map(|until| {
+                        NaiveDate::parse_from_str(&until, "%Y-%m-%d").with_context(|| {
+                            format!(
+                                "failed to parse `until` value `{until}` of ignore entry for \
+                                 `{name}` as a YYYY-MM-DD date"
+                            )
+                        })
+                    })
+                    .transpose()?;
+                Ok(Ignore {
+                    name,
+                    reason,
+                    until,
+                    via: None,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Returns the date against which entries' dates are compared
+pub(crate) fn today() -> NaiveDate {
+    DateTime::<Utc>::from(clock::now()).date_naive()
+}
+
+/// Records the entries for packages that the workspace depends upon, separating the expired ones
+/// from the active ones
+pub(crate) fn set(ignores: Vec<Ignore>) {
+    let today = today();
+    let (expired, active) = ignores
+        .into_iter()
+        .partition(|ignore| ignore.expired(today));
+    ACTIVE.set(active);
+    EXPIRED.set(expired);
+}
+
+/// Returns the description of the expired entry for the package `name`, if any
+pub(crate) fn expired_note(name: &str) -> Option<String> {
+    EXPIRED.with_borrow(|expired| {
+        expired
+            .iter()
+            .find(|ignore| ignore.name == name)
+            .and_then(Ignore::describe_expired)
+    })
+}
+
+/// Lists the active entries, with their reasons
+pub(crate) fn display_active() {
+    ACTIVE.with_borrow(|active| {
+        if active.is_empty() {
+            return;
+        }
+        eprintln!("Ignored by `workspace.metadata.unmaintained.ignore`:");
+        for ignore in active {
+            eprintln!("    {}", ignore.describe_active());
+        }
+    });
+}
@@ -0,0 +1,70 @@
+//! How long ago the crates.io index was last updated
+//!
+//! Updating the index can take a minute or more on a slow connection. So the update is skipped if
+//! the index was updated recently, e.g., by another cargo command (see `--index-max-age`).
+//!
+//! A git index was last updated when its `FETCH_HEAD` was written or when its head commit was made,
+//! whichever is later. An index with neither (e.g., a sparse index's cache directory) was last
+//! updated when its directory was last modified.
+
+use super::SECS_PER_DAY;
+use once_cell::sync::OnceCell;
+use std::{
+    fs::metadata,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+// smoelius: If the index cannot be updated and is at least this old, latest-version results are
+// likely to be wrong for some packages, and the warning says so.
+pub(crate) const STALE_THRESHOLD: Duration = Duration::from_secs(SECS_PER_DAY);
+
+static AGE: OnceCell<Option<Duration>> = OnceCell::new();
+
+/// Returns when the index at `path` was last updated, given the time of its head commit, if any
+pub(crate) fn last_updated(
+    path: &Path,
+    head_commit_time: Option<SystemTime>,
+) -> Option<SystemTime> {
+    let fetch_head_time = modified(&path.join("FETCH_HEAD"));
+    fetch_head_time
+        .max(head_commit_time)
+        .or_else(|| modified(path))
+}
+
+/// Returns how long before `now` the index was last updated
+///
+/// An update time in the future (e.g., because of clock skew) is treated as `now`.
+pub(crate) fn age(last_updated: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(last_updated).unwrap_or_default()
+}
+
+/// Describes an age in the largest whole unit that fits it, e.g., `3 hours`
+pub(crate) fn describe(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (n, unit) = if secs < 60 {
+        (secs, "second")
+    } else if secs < 60 * 60 {
+        (secs / 60, "minute")
+    } else if secs < SECS_PER_DAY {
+        (secs / (60 * 60), "hour")
+    } else {
+        (secs / SECS_PER_DAY, "day")
+    };
+    format!("{n} {unit}{}", if n == 1 { "" } else { "s" })
+}
+
+/// Records the index's age as of when it was opened, for the report's metadata
+pub(crate) fn set_age(age: Option<Duration>) {
+    let _: Result<_, _> = AGE.set(age);
+}
+
+/// Returns the index's age as of when it was opened, or `None` if it was not opened or its age
+/// could not be determined
+pub(crate) fn recorded_age() -> Option<Duration> {
+    AGE.get().copied().flatten()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,163 @@
+//! Warnings about missing or stale `Cargo.lock` files
+//!
+//! The versions in a stale lockfile can be much older than those `cargo update` would select. Many
+//! findings about such versions reflect the lockfile rather than upstream maintenance.
+
+use super::{cargo, clock, days, from_crates_io, opts, versions};
+use anyhow::{bail, ensure, Context, Result};
+use cargo_metadata::Metadata;
+use std::{
+    fs::{metadata as fs_metadata, read_to_string},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Age in days beyond which a lockfile is considered stale, absent `--require-fresh-lockfile`
+const STALE_DAYS: u64 = 365;
+
+/// Number of packages whose release dates are fetched to estimate a lockfile's age
+const SAMPLE_SIZE: usize = 8;
+
+/// How a lockfile's age was determined
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Age {
+    /// Days since the newest release among a sample of locked versions
+    NewestRelease(u64),
+    /// Days since the lockfile was last modified
+    Modified(u64),
+}
+
+impl Age {
+    fn days(&self) -> u64 {
+        match self {
+            Self::NewestRelease(days) | Self::Modified(days) => *days,
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Self::NewestRelease(days) => format!("newest locked release is {days} days old"),
+            Self::Modified(days) => format!("last modified {days} days ago"),
+        }
+    }
+}
+
+/// Returns the path of the current workspace's `Cargo.lock`
+///
+/// `cargo metadata` creates a missing lockfile. So the path must be determined without it.
+pub(crate) fn path() -> Result<PathBuf> {
+    let mut command = cargo::command();
+    command.args(["locate-project", "--workspace", "--message-format=plain"]);
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    ensure!(output.status.success(), "command failed: {command:?}");
+    let manifest_path = PathBuf::from(String::from_utf8(output.stdout)?.trim_end());
+    Ok(manifest_path.with_file_name("Cargo.lock"))
+}
+
+/// Returns the format version of the lockfile at `path`, if it exists and records one
+pub(crate) fn format_version(path: &Path) -> Option<u64> {
+    let contents = read_to_string(path).ok()?;
+    contents
+        .lines()
+        .take_while(|line| !line.starts_with('['))
+        .find_map(|line| line.strip_prefix("version = "))?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Warns if the lockfile at `path` did not exist before `cargo metadata` was run
+pub(crate) fn warn_if_missing(path: &Path) -> Result<bool> {
+    if path.try_exists()? {
+        return Ok(false);
+    }
+    crate::warn!(
+        "`{}` does not exist; versions will be resolved as of now, which may differ from the \
+         versions you build with",
+        path.display()
+    );
+    Ok(true)
+}
+
+/// Warns if `metadata`'s lockfile appears stale, or fails if `--require-fresh-lockfile` was passed
+pub(crate) fn check_freshness(metadata: &Metadata) -> Result<()> {
+    let Some(age) = age(metadata) else {
+        return Ok(());
+    };
+    let threshold = opts::get().require_fresh_lockfile.unwrap_or(STALE_DAYS);
+    if age.days() <= threshold {
+        return Ok(());
+    }
+    if opts::get().require_fresh_lockfile.is_some() {
+        bail!(
+            "`Cargo.lock` is stale ({}, which exceeds --require-fresh-lockfile={}); consider \
+             `cargo update`",
+            age.describe(),
+            threshold
+        );
+    }
+    crate::warn!(
+        "`Cargo.lock` appears stale ({}); findings may reflect your lockfile rather than upstream \
+         maintenance; consider `cargo update`",
+        age.describe()
+    );
+    Ok(())
+}
+
+fn age(metadata: &Metadata) -> Option<Age> {
+    let now = clock::now();
+    let newest_release = if opts::get().fast {
+        // smoelius: Fast mode does not spend requests on this check.
+        None
+    } else {
+        newest_release(metadata)
+    };
+    let modified = fs_metadata(metadata.workspace_root.join("Cargo.lock"))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    choose_age(newest_release, modified, now)
+}
+
+/// Returns the age of the newest release, if known, and the age of the lockfile's modification
+/// otherwise
+///
+/// A modification time says little on its own, e.g., because checking out a repository updates
+/// it. So a release date is preferred.
+pub(crate) fn choose_age(
+    newest_release: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    now: SystemTime,
+) -> Option<Age> {
+    let days =
+        |time: SystemTime| days::from_secs(now.duration_since(time).unwrap_or_default().as_secs());
+    newest_release
+        .map(|time| Age::NewestRelease(days(time)))
+        .or_else(|| modified.map(|time| Age::Modified(days(time))))
+}
+
+/// Returns the newest release date among a sample of `metadata`'s locked crates.io packages
+fn newest_release(metadata: &Metadata) -> Option<SystemTime> {
+    let mut packages = metadata
+        .packages
+        .iter()
+        .filter(|pkg| from_crates_io(pkg) && !metadata.workspace_members.contains(&pkg.id))
+        .collect::<Vec<_>>();
+    packages.sort_by(|lhs, rhs| (&lhs.name, &lhs.version).cmp(&(&rhs.name, &rhs.version)));
+
+    packages
+        .into_iter()
+        .take(SAMPLE_SIZE)
+        .filter_map(|pkg| {
+            let versions = versions(&pkg.name)
+                .map_err(|error| log::debug!("failed to fetch versions of `{}`: {error}", pkg.name))
+                .ok()?;
+            let version = pkg.version.to_string();
+            versions
+                .iter()
+                .find(|other| other.num == version)
+                .map(|other| other.created_at)
+        })
+        .max()
+}
@@ -0,0 +1,69 @@
+//! A map that holds a bounded number of entries, evicting the least recently used
+//!
+//! Used for in-memory caches whose entries can be recomputed cheaply, e.g., because they are
+//! backed by the on-disk cache.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+pub(crate) struct Lru<K, V> {
+    capacity: usize,
+    tick: u64,
+    map: HashMap<K, (u64, V)>,
+}
+
+impl<K: Clone + Eq + Hash, V> Lru<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            map: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+        self.map.get_mut(key).map(|(last_used, value)| {
+            *last_used = tick;
+            &*value
+        })
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if self.map.len() >= self.capacity && !self.map.contains_key(&key) {
+            self.evict();
+        }
+        self.map.insert(key, (self.tick, value));
+    }
+
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.map.remove(key).map(|(_, value)| value)
+    }
+
+    #[cfg(any(feature = "server", test))]
+    pub(crate) fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    // smoelius: Eviction is linear in the number of entries. But it happens at most once per
+    // insertion, and the capacity is small compared to the cost of computing an entry.
+    fn evict(&mut self) {
+        if let Some(key) = self
+            .map
+            .iter()
+            .min_by_key(|(_, &(last_used, _))| last_used)
+            .map(|(key, _)| key.clone())
+        {
+            self.map.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Guards that keep a membership check from examining every manifest in a pathological repository
+//!
+//! A manifest larger than `--max-manifest-size` (e.g., a generated fuzzing artifact) is skipped.
+//! So are manifests under vendored directories, unless `--scan-vendored` is passed or the path
+//! names the package being checked. At most `--max-manifests` manifests are examined per
+//! repository; manifests whose paths name the package are examined first.
+
+use super::{
+    git,
+    metrics::{self, Count},
+    opts,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    ffi::OsStr,
+    path::{Component, Path, PathBuf},
+};
+
+const VENDORED_DIRS: [&str; 3] = ["vendor", "third_party", "node_modules"];
+
+pub(crate) struct Manifest {
+    /// The path of the manifest, relative to the repository's root
+    pub path: PathBuf,
+    /// The size of the manifest in bytes
+    pub size: u64,
+}
+
+/// Returns the manifests in `rev` of the repository cloned at `repo_dir`
+pub(crate) fn in_rev(repo_dir: &Path, rev: &str) -> Result<Vec<Manifest>> {
+    let mut command = git::command();
+    command.args(["ls-tree", "-r", "-l", "-z", rev]);
+    command.current_dir(repo_dir);
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    if !output.status.success() {
+        let error = String::from_utf8(output.stderr)?;
+        bail!(
+            "failed to list `{}` in `{}`: {}",
+            rev,
+            repo_dir.display(),
+            error
+        );
+    }
+    let stdout = String::from_utf8(output.stdout)?;
+    let mut manifests = Vec::new();
+    // smoelius: Each entry has the form `<mode> <type> <object> <size>\t<path>`, where the size is
+    // padded with spaces.
+    for entry in stdout.split_terminator('\0') {
+        let Some((info, path)) = entry.split_once('\t') else {
+            bail!("unexpected `git ls-tree` output: {entry:?}");
+        };
+        let path = Path::new(path);
+        if path.file_name() != Some(OsStr::new("Cargo.toml")) {
+            continue;
+        }
+        // smoelius: The size of a submodule is `-`. Such entries are not manifests.
+        let Some(Ok(size)) = info.split_whitespace().nth(3).map(str::parse::<u64>) else {
+            continue;
+        };
+        manifests.push(Manifest {
+            path: path.to_path_buf(),
+            size,
+        });
+    }
+    Ok(manifests)
+}
+
+/// Returns the paths of the manifests among `manifests` that should be examined for `name`
+///
+/// `repo` describes the repository for use in messages.
+pub(crate) fn select(name: &str, repo: &str, manifests: Vec<Manifest>) -> Vec<PathBuf> {
+    let opts = opts::get();
+    let mut oversized = 0;
+    let mut vendored = 0;
+    let mut paths = Vec::new();
+    for Manifest { path, size } in manifests {
+        if size > opts.max_manifest_size {
+            crate::verbose::note!(
+                "skipping `{}` in `{}`: {} bytes exceeds --max-manifest-size",
+                path.display(),
+                repo,
+                size
+            );
+            oversized += 1;
+            continue;
+        }
+        if !opts.scan_vendored && is_vendored(&path) && !names(&path, name) {
+            vendored += 1;
+            continue;
+        }
+        paths.push(path);
+    }
+    metrics::count(Count::OversizedManifests, oversized);
+    metrics::count(Count::VendoredManifests, vendored);
+
+    if paths.len() > opts.max_manifests {
+        crate::warn!(
+            "`{}` has {} manifests; examining only {} of them for `{}` (see --max-manifests)",
+            repo,
+            paths.len(),
+            opts.max_manifests,
+            name
+        );
+        metrics::count(Count::TruncatedScans, 1);
+        // smoelius: `sort_by_key` is stable. So, otherwise, the order of the manifests is kept.
+        paths.sort_by_key(|path| !names(path, name));
+        paths.truncate(opts.max_manifests);
+    }
+    paths
+}
+
+/// Returns true if `path` is under one of [`VENDORED_DIRS`]
+pub(crate) fn is_vendored(path: &Path) -> bool {
+    path.parent().is_some_and(|parent| {
+        parent.components().any(|component| {
+            VENDORED_DIRS
+                .iter()
+                .any(|dir| component.as_os_str() == *dir)
+        })
+    })
+}
+
+/// Returns true if a directory in `path` has `name`, treating `-` and `_` as equivalent
+pub(crate) fn names(path: &Path, name: &str) -> bool {
+    let name = name.replace('-', "_");
+    path.parent().is_some_and(|parent| {
+        parent.components().any(|component| {
+            matches!(component, Component::Normal(_))
+                && component.as_os_str().to_string_lossy().replace('-', "_") == name
+        })
+    })
+}
@@ -0,0 +1,293 @@
+//! Per-phase timings, cache-hit counts, and counts of other events (e.g., manifests skipped)
+//!
+//! Totals are accumulated only if `--timings` or `--verbose` is passed, or if the `prime`
+//! subcommand is run (which reports some of them), and are displayed at the end of the run if
+//! either option is passed. Phases can nest (e.g., a timestamp requires a clone), in which case the
+//! outer phase's total includes the inner phase's.
+
+use super::{opts, UnmaintainedSubCommand};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Phase {
+    IndexLookup,
+    CratesIo,
+    Downloads,
+    Resolution,
+    GeneralStatus,
+    Clone,
+    Membership,
+    Timestamp,
+    Prefilter,
+    Check,
+}
+
+impl Phase {
+    fn name(self) -> &'static str {
+        match self {
+            Self::IndexLookup => "index lookup",
+            Self::CratesIo => "crates.io",
+            Self::Downloads => "downloads",
+            Self::Resolution => "temporary package resolution",
+            Self::GeneralStatus => "general status",
+            Self::Clone => "clone",
+            Self::Membership => "membership",
+            Self::Timestamp => "timestamp",
+            Self::Prefilter => "pre-filter (first phase)",
+            Self::Check => "package check (second phase)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Cache {
+    GeneralStatus,
+    LatestVersion,
+    MembershipBranch,
+    MembershipTag,
+    Repository,
+    Timestamp,
+    OnDiskDownloads,
+    OnDiskRecordedRepositories,
+    OnDiskRepositories,
+    OnDiskTempPackageMetadata,
+    OnDiskVersions,
+}
+
+impl Cache {
+    fn name(self) -> &'static str {
+        match self {
+            Self::GeneralStatus => "general status (in-memory)",
+            Self::LatestVersion => "latest version (in-memory)",
+            Self::MembershipBranch => "membership branch (in-memory)",
+            Self::MembershipTag => "membership tag (in-memory)",
+            Self::Repository => "repository (in-memory)",
+            Self::Timestamp => "timestamp (in-memory)",
+            Self::OnDiskDownloads => "downloads (on-disk)",
+            Self::OnDiskRecordedRepositories => "recorded repositories (on-disk)",
+            Self::OnDiskRepositories => "repositories (on-disk)",
+            Self::OnDiskTempPackageMetadata => "temporary package metadata (on-disk)",
+            Self::OnDiskVersions => "versions (on-disk)",
+        }
+    }
+
+    /// The phase whose calls this cache can answer
+    fn phase(self) -> Phase {
+        match self {
+            Self::GeneralStatus => Phase::GeneralStatus,
+            Self::LatestVersion => Phase::IndexLookup,
+            Self::MembershipBranch | Self::MembershipTag => Phase::Membership,
+            Self::Repository | Self::OnDiskRepositories => Phase::Clone,
+            Self::Timestamp => Phase::Timestamp,
+            Self::OnDiskDownloads => Phase::Downloads,
+            Self::OnDiskTempPackageMetadata => Phase::Resolution,
+            Self::OnDiskRecordedRepositories | Self::OnDiskVersions => Phase::CratesIo,
+        }
+    }
+}
+
+/// Events that are counted rather than timed
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Count {
+    ExaminedManifests,
+    OversizedManifests,
+    VendoredManifests,
+    TruncatedScans,
+    PrefilteredPackages,
+    ClonedRepositories,
+    RefreshedRepositories,
+    FetchedVersions,
+}
+
+impl Count {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ExaminedManifests => "manifests examined",
+            Self::OversizedManifests => "manifests skipped for size",
+            Self::VendoredManifests => "manifests skipped as vendored",
+            Self::TruncatedScans => "repositories with too many manifests",
+            Self::PrefilteredPackages => "packages ruled out by pre-filter",
+            Self::ClonedRepositories => "repositories cloned",
+            Self::RefreshedRepositories => "repositories refreshed",
+            Self::FetchedVersions => "version lists fetched from crates.io",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct PhaseTotals {
+    pub calls: u64,
+    pub hits: u64,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct CacheTotals {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    phases: BTreeMap<Phase, PhaseTotals>,
+    caches: BTreeMap<Cache, CacheTotals>,
+    counts: BTreeMap<Count, u64>,
+}
+
+thread_local! {
+    static METRICS: RefCell<Metrics> = RefCell::new(Metrics::default());
+}
+
+impl Metrics {
+    pub fn record_call(&mut self, phase: Phase, elapsed: Duration) {
+        let totals = self.phases.entry(phase).or_default();
+        totals.calls += 1;
+        totals.elapsed += elapsed;
+    }
+
+    /// Records a cache hit, which is also a hit for the phase that the cache answers
+    ///
+    /// A hit should be recorded only during a call to [`time`] for the cache's phase, so that a
+    /// phase never has more hits than calls.
+    pub fn record_hit(&mut self, cache: Cache) {
+        self.caches.entry(cache).or_default().hits += 1;
+        self.phases.entry(cache.phase()).or_default().hits += 1;
+    }
+
+    pub fn record_miss(&mut self, cache: Cache) {
+        self.caches.entry(cache).or_default().misses += 1;
+    }
+
+    pub fn record_count(&mut self, count: Count, n: u64) {
+        *self.counts.entry(count).or_default() += n;
+    }
+
+    #[cfg(test)]
+    pub fn phase(&self, phase: Phase) -> PhaseTotals {
+        self.phases.get(&phase).copied().unwrap_or_default()
+    }
+
+    #[cfg(test)]
+    pub fn cache(&self, cache: Cache) -> CacheTotals {
+        self.caches.get(&cache).copied().unwrap_or_default()
+    }
+
+    pub fn count(&self, count: Count) -> u64 {
+        self.counts.get(&count).copied().unwrap_or_default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.phases.is_empty() && self.caches.is_empty() && self.counts.is_empty()
+    }
+}
+
+impl Display for Metrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Timings:")?;
+        for (phase, totals) in &self.phases {
+            writeln!(
+                f,
+                "    {}: {} calls, {} cache hits, {} total",
+                phase.name(),
+                totals.calls,
+                totals.hits,
+                format_duration(totals.elapsed)
+            )?;
+        }
+        writeln!(f, "Caches:")?;
+        for (cache, totals) in &self.caches {
+            writeln!(
+                f,
+                "    {}: {} hits, {} misses",
+                cache.name(),
+                totals.hits,
+                totals.misses
+            )?;
+        }
+        if !self.counts.is_empty() {
+            writeln!(f, "Counts:")?;
+            for (count, n) in &self.counts {
+                writeln!(f, "    {}: {}", count.name(), n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn enabled() -> bool {
+    displayed() || matches!(opts::get().subcmd, Some(UnmaintainedSubCommand::Prime(_)))
+}
+
+fn displayed() -> bool {
+    let opts = opts::get();
+    opts.timings || opts.verbose
+}
+
+/// Calls `f`, recording a call to `phase` and the time `f` took
+pub(crate) fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    METRICS.with_borrow_mut(|metrics| metrics.record_call(phase, elapsed));
+    result
+}
+
+pub(crate) fn hit(cache: Cache) {
+    if enabled() {
+        METRICS.with_borrow_mut(|metrics| metrics.record_hit(cache));
+    }
+}
+
+pub(crate) fn miss(cache: Cache) {
+    if enabled() {
+        METRICS.with_borrow_mut(|metrics| metrics.record_miss(cache));
+    }
+}
+
+/// Adds `n` to `count`'s total
+pub(crate) fn count(count: Count, n: u64) {
+    if enabled() && n != 0 {
+        METRICS.with_borrow_mut(|metrics| metrics.record_count(count, n));
+    }
+}
+
+/// Returns `count`'s total
+pub(crate) fn total(count: Count) -> u64 {
+    METRICS.with_borrow(|metrics| metrics.count(count))
+}
+
+/// Displays the accumulated totals, if any, to standard error
+pub(crate) fn display() {
+    if !displayed() {
+        return;
+    }
+    METRICS.with_borrow(|metrics| {
+        if metrics.is_empty() {
+            return;
+        }
+        crate::verbose::newline!();
+        eprint!("{metrics}");
+    });
+}
+
+/// Formats `duration` as, e.g., `350ms`, `44s`, or `11m32s`
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m{:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
@@ -0,0 +1,91 @@
+//! Detection of network unavailability
+//!
+//! In a sandbox with no network access, every repository would otherwise be reported as
+//! nonexistent or uncloneable, each only after a timeout. So consecutive connection failures are
+//! counted, and once `--network-failure-threshold` of them are seen, the scan is aborted (unless
+//! `--keep-going` was passed). Failures that imply the network was reached (e.g., a 404) reset the
+//! count.
+
+use super::opts;
+use anyhow::{bail, Result};
+use std::cell::Cell;
+
+// smoelius: Substrings of git's stderr that indicate a host could not be reached. Some come from
+// git's use of curl, others from ssh.
+const GIT_CONNECTION_FAILURES: &[&str] = &[
+    "Could not resolve host",
+    "Could not resolve hostname",
+    "Could not resolve proxy",
+    "Failed to connect to",
+    "Connection refused",
+    "Connection timed out",
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+];
+
+thread_local! {
+    static CONSECUTIVE_FAILURES: Cell<u64> = const { Cell::new(0) };
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Outcome {
+    /// The remote host was reached, whether or not the request succeeded
+    Reached,
+    /// The remote host could not be resolved or connected to
+    ConnectionFailure,
+}
+
+/// Classifies a failed curl transfer
+///
+/// A timeout counts as a connection failure only if no connection was established before it.
+pub(crate) fn classify_curl_error(error: &curl::Error, connected: bool) -> Outcome {
+    if error.is_couldnt_resolve_host()
+        || error.is_couldnt_resolve_proxy()
+        || error.is_couldnt_connect()
+        || (error.is_operation_timedout() && !connected)
+    {
+        Outcome::ConnectionFailure
+    } else {
+        Outcome::Reached
+    }
+}
+
+/// Classifies a failed git command by its stderr
+pub(crate) fn classify_git_stderr(stderr: &str) -> Outcome {
+    if GIT_CONNECTION_FAILURES
+        .iter()
+        .any(|pattern| stderr.contains(pattern))
+    {
+        Outcome::ConnectionFailure
+    } else {
+        Outcome::Reached
+    }
+}
+
+pub(crate) fn record(outcome: Outcome) {
+    CONSECUTIVE_FAILURES.with(|consecutive_failures| match outcome {
+        Outcome::Reached => consecutive_failures.set(0),
+        Outcome::ConnectionFailure => consecutive_failures.set(consecutive_failures.get() + 1),
+    });
+}
+
+/// Forgets the connection failures counted so far, e.g., before a scan that `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    CONSECUTIVE_FAILURES.set(0);
+}
+
+/// Returns an error if the network appears to be unavailable
+pub(crate) fn check() -> Result<()> {
+    let threshold = opts::get().network_failure_threshold;
+    let consecutive_failures = CONSECUTIVE_FAILURES.with(Cell::get);
+    if opts::get().keep_going || threshold == 0 || consecutive_failures < threshold {
+        return Ok(());
+    }
+    bail!(
+        "the network appears to be unavailable ({} consecutive connection failures); allow access \
+         to crates.io and to the hosts of your dependencies' repositories, raise \
+         --network-failure-threshold, or pass --keep-going to scan anyway",
+        consecutive_failures
+    )
+}
@@ -1,19 +1,46 @@
 //! On-disk cache
 //!
 //! The on-disk cache consists of the following subdirectories:
-//! - `entries`: JSON-encoded [`Entry`]. Each file's name is the associated package's name.
-//! - `repositories`: Cloned repositories. Each subdirectory's name is the hash of the url that was
-//!   cloned.
+//! - `accessed`: Number of seconds between the Unix epoch and the time when a cloned repository was
+//!   last used. Filenames are the same as those of the cloned repositories.
+//! - `baselines`: JSON-encoded [`crate::fast::Baseline`]. Each file's name is the hash of the
+//!   associated workspace's root.
+//! - `branches`: Name of a branch other than the default on which a repository's packages were
+//!   found. Filenames are the same as those of the cloned repositories.
+//! - `downloads`: JSON-encoded [`crates_io_api::CrateDownloads`]. Each file's name is the
+//!   associated package's filename (see below).
+//! - `downloads_timestamps`: Number of seconds between the Unix epoch and the time when the
+//!   downloads were fetched. Filenames are the same as those of the fetched downloads.
+//! - `entries`: JSON-encoded [`Entry`]. Each file's name is the associated package's filename (see
+//!   below).
+//! - `histories`: JSON-encoded [`crate::history::History`]. Each file's name is the hash of the
+//!   associated workspace's root and the options that determine a scan's scope.
+//! - `locks`: Empty files used to lock cloned repositories (see below). Filenames are the same as
+//!   those of the cloned repositories.
+//! - `recorded_repositories`: JSON-encoded repository url (or `null`) that crates.io records for a
+//!   package. Each file's name is the associated package's filename (see below).
+//! - `repositories`: Cloned repositories, or, with `--allow-tarball-fallback`, unpacked tarballs of
+//!   repositories that could not be cloned. Each subdirectory's name is the hash of the canonical
+//!   form (see [`crate::canonical_url`]) of the url that was cloned.
+//! - `temp_package_metadata`: JSON-encoded [`cargo_metadata::Metadata`] of a temporary package (see
+//!   [`crate::packaging`]). Each file's name is the hash of the package depended upon, its version,
+//!   and its features.
+//! - `temp_package_metadata_timestamps`: Number of seconds between the Unix epoch and the time when
+//!   the metadata was produced. Filenames are the same as those of the metadata.
 //! - `timestamps`: Number of seconds between the Unix epoch and the time when the repository was
 //!   cloned. Filenames are the same as those of the cloned repositories.
 //! - `versions`: JSON-encoded array of [`crates_io_api::Version`]. Each file's name is the
-//!   associated package's name.
+//!   associated package's filename (see below).
 //! - `versions_timestamps`: Number of seconds between the Unix epoch and the time when the versions
 //!   were fetched. Filenames are the same as those of the fetched versions.
+//! - `version_repositories`: JSON-encoded map from versions to the repository url (or `null`) named
+//!   in each version's manifest. Each file's name is the associated package's filename (see below).
 //!
-//! A package's entry is considered current if both of the following conditions are met:
-//! - A url associated with the package was successfully cloned.
-//! - The clone was performed no more than `refresh_age` days ago.
+//! A package's filename is its name, sanitized and followed by a hash of the exact name (see
+//! [`package_filename`]). Package names from git or path sources need not be valid crates.io names,
+//! and names that differ only by case would collide on a case-insensitive filesystem. Files written
+//! by earlier versions, which used the package's name as the filename, are read if no file with the
+//! new name exists, and are removed when the new file is written.
 //!
 //! A package's entry is considered current if both of the following conditions are met:
 //! - A url associated with the package was successfully cloned.
@@ -21,46 +48,186 @@
 //!
 //! If either of the above conditions are not met, an attempt is made to refresh the entry.
 //!
-//! A similar statement applies to versions.
+//! If a package's repository changes between runs and the new repository is cloned, the package's
+//! entry is replaced, and the clone of the previous url is removed unless another package's entry
+//! refers to it.
+//!
+//! A similar statement applies to versions. Recorded repositories are fetched along with versions
+//! and are refreshed with them. Downloads are refreshed after
+//! [`DOWNLOADS_REFRESH_AGE`] days, regardless of `refresh_age`. Version repositories are never
+//! refreshed, because a published version's manifest cannot change. Temporary package metadata is
+//! refreshed after `refresh_age` days, because the dependencies of a published version can resolve
+//! differently as new versions of them are published.
+//!
+//! If a size limit is given (see `--cache-size-limit`), then at the end of a run, the least
+//! recently used clones are evicted until the clones occupy no more than the limit. Clones used
+//! during the run are never evicted.
+//!
+//! Each clone is locked separately while it is cloned or fetched into, and while the entry and
+//! timestamp that refer to it are written. So concurrent runs that use different repositories do
+//! not wait on one another. The cache as a whole is locked only while its structure changes, i.e.,
+//! while clones are evicted or removed, and while the `entries` directory is created. A process
+//! holding the cache's lock never waits on a clone's lock. Rather, a clone whose lock is held by
+//! another process is left alone.
 //!
 //! The on-disk cache resides at `$HOME/.cache/cargo-unmaintained/v2`.
 
-use super::{urls, SECS_PER_DAY};
+use super::{
+    canonical_url, clock, days, fast::Baseline, git, github, history::History, metrics, network,
+    retry, scheduler, snapshot, url, urls, Url,
+};
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use cargo_metadata::Package;
-use crates_io_api::{SyncClient, Version};
+use cargo_metadata::{semver, Metadata, Package};
+use crates_io_api::{CrateDownloads, CrateResponse, CratesQuery, Sort, SyncClient, Version};
 use once_cell::{sync::Lazy, unsync::OnceCell};
 use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
-    collections::HashMap,
-    fs::{create_dir_all, read_to_string, write, File},
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{
+        create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, symlink_metadata,
+        write, File,
+    },
     path::{Path, PathBuf},
     process::{Command, Stdio},
     str::FromStr,
+    thread::sleep,
     time::{Duration, SystemTime},
 };
 use tempfile::{tempdir, TempDir};
 
 const DEFAULT_REFRESH_AGE: u64 = 30; // days
 
-const USER_AGENT: &str = "cargo-unmaintained (github.com/trailofbits/cargo-unmaintained)";
+// smoelius: Download counts change daily. But a week-old trend is still a reasonable trend.
+const DOWNLOADS_REFRESH_AGE: u64 = 7; // days
+
+const CRATES_API_URL: &str = "https://crates.io/api/v1/crates/";
+
+pub(crate) const USER_AGENT: &str =
+    "cargo-unmaintained (github.com/trailofbits/cargo-unmaintained)";
 
 const RATE_LIMIT: Duration = Duration::from_secs(1);
 
+/// How long to wait before retrying a clone or fetch that failed to connect
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+const CACHE_SIZE_LIMIT_VAR: &str = "CARGO_UNMAINTAINED_CACHE_SIZE_LIMIT";
+
+/// A url that was cloned, the directory it was cloned into, and the clone's lock (see
+/// [`Cache::clone_repository_uncached`])
+type Cloned = ((String, PathBuf), Option<RepositoryLock>);
+
 #[derive(Clone, Deserialize, Serialize)]
 struct Entry {
     named_url: String,
     cloned_url: String,
+    /// Urls that the package previously named, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    previous_urls: Vec<String>,
+    /// Bytes that the clone occupied when it was last cloned or fetched into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+/// The error returned when a clone exceeds `--max-repo-size` and `--deny-oversize` was passed
+#[derive(Debug)]
+pub(crate) struct TooLarge {
+    pub url: String,
+    pub size: u64,
+}
+
+impl std::fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "clone of `{}` occupies {} bytes, which exceeds --max-repo-size",
+            self.url, self.size
+        )
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+/// What to do about a clone, given its size
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum SizeCheck {
+    Within,
+    Warn,
+    Deny,
+}
+
+/// Whether the on-disk cache contained any entries when a scan began
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum CacheState {
+    Cold,
+    Warm,
+}
+
+/// Whether a package's repository would be used as is, refreshed, or cloned
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum RepositoryState {
+    Fresh,
+    Stale,
+    Missing,
+}
+
+/// A clone removed by [`Cache::evict`]
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+pub(crate) struct Eviction {
+    /// The url that was cloned, if an entry referred to the clone
+    pub url: Option<String>,
+    pub repo_dir: PathBuf,
+    pub size: u64,
+    pub accessed: SystemTime,
+}
+
+/// The fields of a [`Version`] that are used
+///
+/// Versions stay in memory for the whole run, and some crates have hundreds of them. So only these
+/// fields are kept in memory. The on-disk cache still stores complete versions.
+#[derive(Clone, Debug)]
+pub struct VersionSummary {
+    pub num: String,
+    pub created_at: SystemTime,
+    pub yanked: bool,
+    /// The login of the user who published the version, if known
+    pub published_by: Option<String>,
+}
+
+impl From<Version> for VersionSummary {
+    fn from(version: Version) -> Self {
+        Self {
+            num: version.num,
+            created_at: version.created_at.into(),
+            yanked: version.yanked,
+            published_by: version.published_by.map(|user| user.login),
+        }
+    }
+}
+
+/// A lock on a single clone, released when dropped
+///
+/// A temporary cache's clones are not locked.
+struct RepositoryLock {
+    _file: Option<File>,
 }
 
 pub(crate) struct Cache {
     tempdir: Option<TempDir>,
     refresh_age: u64, // days
+    /// Digests of the clones used during this run
+    used: HashSet<String>,
+    branches: HashMap<String, String>,
+    downloads: HashMap<String, CrateDownloads>,
+    downloads_timestamps: HashMap<String, SystemTime>,
     entries: HashMap<String, Entry>,
+    recorded_repositories: HashMap<String, Option<String>>,
     repository_timestamps: HashMap<String, SystemTime>,
-    versions: HashMap<String, Vec<Version>>,
+    versions: HashMap<String, Vec<VersionSummary>>,
     versions_timestamps: HashMap<String, SystemTime>,
+    version_repositories: HashMap<String, BTreeMap<String, Option<String>>>,
 }
 
 thread_local! {
@@ -80,6 +247,57 @@ static CACHE_DIRECTORY: Lazy<PathBuf> = Lazy::new(|| {
 static CRATES_IO_SYNC_CLIENT: Lazy<SyncClient> =
     Lazy::new(|| SyncClient::new(USER_AGENT, RATE_LIMIT).unwrap());
 
+/// A crates.io owner of a crate
+pub(crate) struct Owner {
+    pub login: String,
+    pub id: u64,
+    /// Whether the owner is a team (e.g., `github:rust-lang:libs`) rather than a user
+    pub team: bool,
+}
+
+/// Fetches the crates.io owners of `name`
+///
+/// Owners change rarely, and are needed only with `--owner-activity`. So they are not stored on
+/// disk.
+pub(crate) fn fetch_owners(name: &str) -> Result<Vec<Owner>> {
+    let users = retry::with_backoff(
+        "crates.io API request",
+        || CRATES_IO_SYNC_CLIENT.crate_owners(name).map_err(Into::into),
+        is_transient,
+    )?;
+    Ok(users
+        .into_iter()
+        .map(|user| Owner {
+            team: user.kind.as_deref() == Some("team") || user.login.contains(':'),
+            login: user.login,
+            id: user.id,
+        })
+        .collect())
+}
+
+/// Fetches the time that the most recently updated crate owned by the crates.io user with id
+/// `user_id` was updated
+pub(crate) fn fetch_latest_update_by(user_id: u64) -> Result<Option<SystemTime>> {
+    let query = CratesQuery::builder()
+        .user_id(user_id)
+        .sort(Sort::RecentUpdates)
+        .page_size(1)
+        .build();
+    let page = retry::with_backoff(
+        "crates.io API request",
+        || {
+            CRATES_IO_SYNC_CLIENT
+                .crates(query.clone())
+                .map_err(Into::into)
+        },
+        is_transient,
+    )?;
+    Ok(page
+        .crates
+        .first()
+        .map(|krate| SystemTime::from(krate.updated_at)))
+}
+
 pub fn with_cache<T>(f: impl FnOnce(&mut Cache) -> T) -> T {
     CACHE_ONCE_CELL.with_borrow_mut(|once_cell| {
         let _: &Cache = once_cell.get_or_init(|| {
@@ -89,12 +307,17 @@ pub fn with_cache<T>(f: impl FnOnce(&mut Cache) -> T) -> T {
             #[cfg(any(not(feature = "on-disk-cache"), windows))]
             let temporary = true;
 
+            // smoelius: In fast mode, cached data is used regardless of its age.
+            let refresh_age = if crate::opts::get().fast {
+                u64::MAX
+            } else {
+                std::cmp::min(DEFAULT_REFRESH_AGE, crate::opts::get().max_age)
+            };
+
             #[allow(clippy::panic)]
-            Cache::new(
-                temporary,
-                std::cmp::min(DEFAULT_REFRESH_AGE, crate::opts::get().max_age),
-            )
-            .unwrap_or_else(|error| panic!("failed to create on-disk repository cache: {error}"))
+            Cache::new(temporary, refresh_age).unwrap_or_else(|error| {
+                panic!("failed to create on-disk repository cache: {error}")
+            })
         });
 
         #[allow(clippy::unwrap_used)]
@@ -116,122 +339,822 @@ impl Cache {
         Ok(Self {
             tempdir,
             refresh_age,
+            used: HashSet::new(),
+            branches: HashMap::new(),
+            downloads: HashMap::new(),
+            downloads_timestamps: HashMap::new(),
             entries: HashMap::new(),
+            recorded_repositories: HashMap::new(),
             repository_timestamps: HashMap::new(),
             versions: HashMap::new(),
+            version_repositories: HashMap::new(),
             versions_timestamps: HashMap::new(),
         })
     }
 
+    pub fn state(&self) -> CacheState {
+        // smoelius: A temporary cache is always cold.
+        if self.tempdir.is_some() {
+            return CacheState::Cold;
+        }
+        let warm = std::fs::read_dir(self.entries_dir())
+            .is_ok_and(|mut read_dir| read_dir.next().is_some());
+        if warm {
+            CacheState::Warm
+        } else {
+            CacheState::Cold
+        }
+    }
+
+    /// Returns what [`Cache::clone_repository`] would do for `pkg`, without cloning or fetching
+    /// anything
+    pub fn repository_state(&mut self, pkg: &Package) -> RepositoryState {
+        // smoelius: Ignore any errors that may occur while reading/deserializing.
+        if let Ok(entry) = self.entry(pkg) {
+            if url::repository(pkg) == Some(entry.named_url.as_str())
+                && self
+                    .repository_is_current(&entry.cloned_url)
+                    .unwrap_or_default()
+            {
+                return RepositoryState::Fresh;
+            }
+        }
+        let exists = urls(pkg).into_iter().any(|url| {
+            self.repositories_dir()
+                .join(url_digest(url.as_str()))
+                .try_exists()
+                .unwrap_or_default()
+        });
+        if exists {
+            RepositoryState::Stale
+        } else {
+            RepositoryState::Missing
+        }
+    }
+
     #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
     pub fn clone_repository(&mut self, pkg: &Package) -> Result<(String, PathBuf)> {
+        let mut previous_urls = Vec::new();
+        let mut replaced_url = None;
+
         // smoelius: Ignore any errors that may occur while reading/deserializing.
         if let Ok(entry) = self.entry(pkg) {
-            if self
-                .repository_is_current(&entry.cloned_url)
-                .unwrap_or_default()
-            {
-                let repo_dir = self.repositories_dir().join(url_digest(&entry.cloned_url));
-                return Ok((entry.cloned_url, repo_dir));
+            if url::repository(pkg) == Some(entry.named_url.as_str()) {
+                if self
+                    .repository_is_current(&entry.cloned_url)
+                    .unwrap_or_default()
+                {
+                    // smoelius: The clone already occupies its space. So it is not removed, even
+                    // with `--deny-oversize`.
+                    if let Some(size) = entry.size {
+                        check_size(&entry.cloned_url, size)?;
+                    }
+                    metrics::hit(metrics::Cache::OnDiskRepositories);
+                    let digest = url_digest(&entry.cloned_url);
+                    self.record_access(&digest)?;
+                    let repo_dir = self.repositories_dir().join(digest);
+                    return Ok((entry.cloned_url, repo_dir));
+                }
+                previous_urls = entry.previous_urls;
+            } else {
+                log::info!(
+                    "repository of `{}` changed from `{}` to `{}`",
+                    pkg.name,
+                    entry.named_url,
+                    url::repository(pkg).unwrap_or_default()
+                );
+                // smoelius: Do not remove the previous clone until the new url has been cloned.
+                // The previous clone may still be needed, e.g., if the new url cannot be cloned
+                // and the previous url is substituted for it.
+                replaced_url = Some(entry.cloned_url);
+                previous_urls = entry.previous_urls;
+                previous_urls.push(entry.named_url);
             }
         }
 
-        let url_and_dir = self.clone_repository_uncached(pkg)?;
+        metrics::miss(metrics::Cache::OnDiskRepositories);
+
+        let (url_and_dir, lock) = self.clone_repository_uncached(urls(pkg))?;
+
+        let digest = url_digest(&url_and_dir.0);
+        self.record_access(&digest)?;
+
+        // smoelius: If the fetch failed but an existing clone was used, do not update the entry or
+        // the timestamp, so that the refresh is retried on the next run.
+        let Some(_lock) = lock else {
+            return Ok(url_and_dir);
+        };
+
+        let size = self.check_repository_size(&url_and_dir.0, &url_and_dir.1)?;
 
         #[allow(clippy::unwrap_used)]
         let entry = Entry {
-            named_url: pkg.repository.clone().unwrap(),
+            named_url: url::repository(pkg).unwrap().to_owned(),
             cloned_url: url_and_dir.0.as_str().to_owned(),
+            previous_urls,
+            size: Some(size),
         };
         self.write_entry(&pkg.name, &entry)?;
         self.entries.insert(pkg.name.clone(), entry);
 
-        let digest = url_digest(&url_and_dir.0);
-        let timestamp = SystemTime::now();
+        if let Some(replaced_url) = replaced_url {
+            if canonical_url(&replaced_url) != canonical_url(&url_and_dir.0) {
+                self.remove_unreferenced_repository(&pkg.name, &replaced_url)?;
+            }
+        }
+
+        let timestamp = clock::real_now();
         self.write_repository_timestamp(&digest, timestamp)?;
         self.repository_timestamps.insert(digest, timestamp);
 
         Ok(url_and_dir)
     }
 
-    fn clone_repository_uncached(&self, pkg: &Package) -> Result<(String, PathBuf)> {
-        // smoelius: The next `lock_path` locks the entire cache. This is needed for the `snapbox`
-        // tests, because they run concurrently. I am not sure how much contention this locking
-        // causes.
-        let _lock: File;
-        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
-        if self.tempdir.is_none() {
-            _lock = crate::flock::lock_path(&CACHE_DIRECTORY)
-                .with_context(|| format!("failed to lock {:?}", &*CACHE_DIRECTORY))?;
+    /// Clones or refreshes the repository at `url`, independent of any package
+    ///
+    /// Unlike [`Cache::clone_repository`], no entry is written. The clone is keyed by `url`, as
+    /// all clones are, and so is shared with any package whose repository is `url`.
+    pub fn clone_url(&mut self, url: &str) -> Result<(String, PathBuf)> {
+        let digest = url_digest(url);
+        if self.repository_is_current(url).unwrap_or_default() {
+            metrics::hit(metrics::Cache::OnDiskRepositories);
+            self.record_access(&digest)?;
+            let repo_dir = self.repositories_dir().join(digest);
+            return Ok((url.to_owned(), repo_dir));
         }
 
+        metrics::miss(metrics::Cache::OnDiskRepositories);
+
+        let (url_and_dir, lock) = self.clone_repository_uncached([Url::from(url)])?;
+
+        self.record_access(&digest)?;
+
+        let Some(_lock) = lock else {
+            return Ok(url_and_dir);
+        };
+
+        self.check_repository_size(&url_and_dir.0, &url_and_dir.1)?;
+
+        let timestamp = clock::real_now();
+        self.write_repository_timestamp(&digest, timestamp)?;
+        self.repository_timestamps.insert(digest, timestamp);
+
+        Ok(url_and_dir)
+    }
+
+    /// Measures the clone of `url` at `repo_dir`, which must be locked, and returns its size
+    ///
+    /// If the clone exceeds `--max-repo-size` and `--deny-oversize` was passed, the clone is
+    /// removed, and a [`TooLarge`] error is returned.
+    fn check_repository_size(&mut self, url: &str, repo_dir: &Path) -> Result<u64> {
+        let size = disk_usage(repo_dir)?;
+        if let Err(error) = check_size(url, size) {
+            remove_dir_all(repo_dir)
+                .with_context(|| format!("failed to remove `{}`", repo_dir.display()))?;
+            // smoelius: A previous clone's timestamp would make the removed clone appear current.
+            let digest = url_digest(url);
+            let _ = remove_file(self.repository_timestamps_dir().join(&digest));
+            self.repository_timestamps.remove(&digest);
+            return Err(error);
+        }
+        Ok(size)
+    }
+
+    /// Clones or refreshes the repository at the first of `urls` that can be cloned or fetched
+    ///
+    /// Each url is tried as given, then with a `.git` suffix appended or stripped. If no url can be
+    /// cloned or fetched, and `--allow-tarball-fallback` was passed, a tarball of the first GitHub
+    /// url's default branch is unpacked in place of a clone.
+    ///
+    /// If the repository was successfully cloned or fetched, the returned lock is `Some` and is
+    /// held on the clone. If fetching failed but an existing (stale) clone is usable, the returned
+    /// lock is `None`.
+    fn clone_repository_uncached<'a>(
+        &self,
+        urls: impl IntoIterator<Item = Url<'a>>,
+    ) -> Result<Cloned> {
+        let urls = urls.into_iter().collect::<Vec<_>>();
         let mut errors = Vec::new();
-        for url in urls(pkg) {
-            let repo_dir = self.repositories_dir().join(url_digest(url.as_str()));
-            let exists = repository_existence(&repo_dir)?;
-            let mut command = if exists {
-                let branch_name = branch_name(&repo_dir)?;
-                let mut command = Command::new("git");
-                command.args([
-                    "fetch",
-                    "--update-head-ok",
-                    "origin",
-                    &format!("{branch_name}:{branch_name}"),
-                ]);
-                command.current_dir(&repo_dir);
-                command
-            } else {
-                let mut command = Command::new("git");
-                // smoelius: The full repository is no longer checked out.
-                command.args([
-                    "clone",
-                    "--depth=1",
-                    "--no-checkout",
-                    "--quiet",
-                    url.as_str(),
-                    &repo_dir.to_string_lossy(),
-                ]);
-                command
-            };
-            command
-                .env("GCM_INTERACTIVE", "never")
-                .env("GIT_ASKPASS", "echo")
-                .env("GIT_TERMINAL_PROMPT", "0")
-                .stderr(Stdio::piped());
-            let output = command
-                .output()
-                .with_context(|| format!("failed to run command: {command:?}"))?;
-            if output.status.success() {
-                return Ok((url.as_str().to_owned(), repo_dir));
+        let mut stale = None;
+        let mut reached = Vec::new();
+        for url in &urls {
+            if let Some(lock) = self.try_clone_or_fetch(url.as_str(), &mut errors, &mut stale)? {
+                return Ok(lock);
+            }
+            // smoelius: If the host could not be reached, there is no point in trying the url
+            // with a different suffix.
+            if errors.last().is_some_and(|error| {
+                network::classify_git_stderr(error) == network::Outcome::Reached
+            }) {
+                reached.push(url.as_str());
+            }
+        }
+        for alternative in reached
+            .into_iter()
+            .filter_map(toggle_git_suffix)
+            .filter(|alternative| urls.iter().all(|url| url.as_str() != alternative))
+        {
+            if let Some(lock) = self.try_clone_or_fetch(&alternative, &mut errors, &mut stale)? {
+                return Ok(lock);
             }
-            let error = String::from_utf8(output.stderr)?;
-            errors.push(error);
         }
         // smoelius: Don't emit duplicate errors.
         errors.dedup();
+        if let Some(url_and_dir) = stale {
+            crate::warn!(
+                "failed to refresh `{}`; using existing clone: {:#?}",
+                url_and_dir.0,
+                errors
+            );
+            return Ok((url_and_dir, None));
+        }
+        if crate::opts::get().allow_tarball_fallback {
+            if let Some(url) = urls
+                .iter()
+                .find(|url| github::codeload_url(**url).is_some())
+            {
+                let digest = url_digest(url.as_str());
+                #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+                let lock = self.lock_repository(&digest)?;
+                #[cfg(not(all(feature = "on-disk-cache", feature = "lock-index", not(windows))))]
+                let lock = RepositoryLock { _file: None };
+                let repo_dir = self.repositories_dir().join(digest);
+                match unpack_tarball(*url, &repo_dir) {
+                    Ok(()) => {
+                        crate::warn!(
+                            "failed to clone `{}`; using a tarball of its default branch: {:#?}",
+                            url,
+                            errors
+                        );
+                        return Ok(((url.as_str().to_owned(), repo_dir), Some(lock)));
+                    }
+                    Err(error) => errors.push(format!("{error:#}")),
+                }
+            }
+        }
         Err(anyhow!("{:#?}", errors))
     }
 
+    /// Clones or fetches `url_string`, and returns the url, the clone's directory, and the clone's
+    /// lock if successful
+    ///
+    /// On failure, git's stderr is pushed onto `errors`, and if a (stale) clone of `url_string`
+    /// exists, it is stored in `stale`, unless `stale` already holds one.
+    fn try_clone_or_fetch(
+        &self,
+        url_string: &str,
+        errors: &mut Vec<String>,
+        stale: &mut Option<(String, PathBuf)>,
+    ) -> Result<Option<Cloned>> {
+        let digest = url_digest(url_string);
+        // smoelius: The lock is needed for the `snapbox` tests, because they run concurrently.
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        let lock = self.lock_repository(&digest)?;
+        #[cfg(not(all(feature = "on-disk-cache", feature = "lock-index", not(windows))))]
+        let lock = RepositoryLock { _file: None };
+        let repo_dir = self.repositories_dir().join(digest);
+        // smoelius: An unpacked tarball cannot be fetched into. So it is replaced by a clone.
+        if snapshot::is_tarball(&repo_dir) {
+            remove_dir_all(&repo_dir)
+                .with_context(|| format!("failed to remove `{}`", repo_dir.display()))?;
+        }
+        let exists = repository_existence(&repo_dir)?;
+        let Some(error) = clone_or_fetch(url_string, &repo_dir, exists)? else {
+            metrics::count(
+                if exists {
+                    metrics::Count::RefreshedRepositories
+                } else {
+                    metrics::Count::ClonedRepositories
+                },
+                1,
+            );
+            return Ok(Some(((url_string.to_owned(), repo_dir), Some(lock))));
+        };
+        errors.push(error);
+        // smoelius: A failed fetch does not make an existing clone unusable. Remember the first
+        // such clone in case no url can be cloned or fetched.
+        if exists && stale.is_none() {
+            *stale = Some((url_string.to_owned(), repo_dir));
+        }
+        Ok(None)
+    }
+
+    /// Returns the branch recorded for `url` by [`Cache::set_branch`], if any
+    pub fn branch(&mut self, url: &str) -> Option<String> {
+        let digest = url_digest(url);
+        if !self.branches.contains_key(&digest) {
+            // smoelius: Ignore any errors that may occur while reading.
+            let path_buf = self.branches_dir().join(&digest);
+            let branch = read_to_string(path_buf).ok()?;
+            self.branches.insert(digest.clone(), branch);
+        }
+        self.branches.get(&digest).cloned()
+    }
+
+    pub fn set_branch(&mut self, url: &str, branch: &str) -> Result<()> {
+        create_dir_all(self.branches_dir())
+            .with_context(|| "failed to create branches directory")?;
+        let digest = url_digest(url);
+        let path_buf = self.branches_dir().join(&digest);
+        write(&path_buf, branch)
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        self.branches.insert(digest, branch.to_owned());
+        Ok(())
+    }
+
+    /// Returns the repository named in the manifest of version `version` of `name`, as recorded by
+    /// [`Cache::set_version_repository`]
+    ///
+    /// Returns an error if no repository was recorded for the version.
+    pub fn version_repository(
+        &mut self,
+        name: &str,
+        version: &semver::Version,
+    ) -> Result<Option<String>> {
+        self.version_repositories(name)
+            .get(&version.to_string())
+            .cloned()
+            .ok_or_else(|| anyhow!("no repository recorded for `{}` {}", name, version))
+    }
+
+    pub fn set_version_repository(
+        &mut self,
+        name: &str,
+        version: &semver::Version,
+        repository: Option<&str>,
+    ) -> Result<()> {
+        let mut version_repositories = self.version_repositories(name).clone();
+        version_repositories.insert(version.to_string(), repository.map(ToOwned::to_owned));
+        create_dir_all(self.version_repositories_dir())
+            .with_context(|| "failed to create version repositories directory")?;
+        let json = serde_json::to_string_pretty(&version_repositories)?;
+        write_package_file(&self.version_repositories_dir(), name, json)?;
+        self.version_repositories
+            .insert(name.to_owned(), version_repositories);
+        Ok(())
+    }
+
+    fn version_repositories(&mut self, name: &str) -> &BTreeMap<String, Option<String>> {
+        let path_buf = package_file(&self.version_repositories_dir(), name);
+        self.version_repositories
+            .entry(name.to_owned())
+            .or_insert_with(|| {
+                // smoelius: Ignore any errors that may occur while reading.
+                read_to_string(path_buf)
+                    .ok()
+                    .and_then(|contents| serde_json::from_str(&contents).ok())
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Shallowly fetches `branch` of the repository cloned at `repo_dir`, and returns a revision
+    /// that refers to it
+    ///
+    /// If `branch` was fetched previously, it is not fetched again.
+    pub fn fetch_branch(&self, repo_dir: &Path, branch: &str) -> Result<String> {
+        self.fetch_ref(
+            repo_dir,
+            &format!("refs/heads/{branch}"),
+            &format!("refs/remotes/origin/{branch}"),
+        )
+    }
+
+    /// Shallowly fetches `tag` of the repository cloned at `repo_dir`, and returns a revision that
+    /// refers to it
+    ///
+    /// If `tag` was fetched previously, it is not fetched again.
+    pub fn fetch_tag(&self, repo_dir: &Path, tag: &str) -> Result<String> {
+        let rev = format!("refs/tags/{tag}");
+        self.fetch_ref(repo_dir, &rev, &rev)
+    }
+
+    // smoelius: `self` is used only to lock the clone, which requires the `lock-index` feature.
+    #[cfg_attr(
+        not(all(feature = "on-disk-cache", feature = "lock-index", not(windows))),
+        allow(clippy::unused_self)
+    )]
+    fn fetch_ref(&self, repo_dir: &Path, remote_ref: &str, rev: &str) -> Result<String> {
+        let mut command = git::command();
+        command.args(["rev-parse", "--quiet", "--verify", rev]);
+        command.current_dir(repo_dir).stdout(Stdio::null());
+        let status = command
+            .status()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        if status.success() {
+            return Ok(rev.to_owned());
+        }
+
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        let _lock = self.lock_repository_dir(repo_dir)?;
+
+        let mut command = git::command();
+        command.args([
+            "fetch",
+            "--depth=1",
+            "--no-recurse-submodules",
+            "--quiet",
+            "origin",
+            &format!("+{remote_ref}:{rev}"),
+        ]);
+        command
+            .current_dir(repo_dir)
+            .env("GCM_INTERACTIVE", "never")
+            .env("GIT_ASKPASS", "echo")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stderr(Stdio::piped());
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        if !output.status.success() {
+            let error = String::from_utf8(output.stderr)?;
+            let name = remote_ref
+                .strip_prefix("refs/heads/")
+                .or_else(|| remote_ref.strip_prefix("refs/tags/"))
+                .unwrap_or(remote_ref);
+            bail!("failed to fetch `{}`: {}", name, error.trim_end());
+        }
+        Ok(rev.to_owned())
+    }
+
+    /// Fetches the tags and full history of the repository cloned at `repo_dir`, if the clone is
+    /// shallow
+    ///
+    /// Once the clone is no longer shallow, refreshing it fetches any new tags that point into the
+    /// fetched history. So nothing more is fetched.
+    // smoelius: `self` is used only to lock the clone, which requires the `lock-index` feature.
+    #[cfg_attr(
+        not(all(feature = "on-disk-cache", feature = "lock-index", not(windows))),
+        allow(clippy::unused_self)
+    )]
+    pub fn fetch_tags(&self, repo_dir: &Path) -> Result<()> {
+        let mut command = git::command();
+        command.args(["rev-parse", "--is-shallow-repository"]);
+        command.current_dir(repo_dir);
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        ensure!(
+            output.status.success(),
+            "failed to run command: {command:?}"
+        );
+        if String::from_utf8(output.stdout)?.trim_end() == "false" {
+            return Ok(());
+        }
+
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        let _lock = self.lock_repository_dir(repo_dir)?;
+
+        let mut command = git::command();
+        command.args([
+            "fetch",
+            "--unshallow",
+            "--tags",
+            "--no-recurse-submodules",
+            "--quiet",
+            "origin",
+        ]);
+        command
+            .current_dir(repo_dir)
+            .env("GCM_INTERACTIVE", "never")
+            .env("GIT_ASKPASS", "echo")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stderr(Stdio::piped());
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        if !output.status.success() {
+            let error = String::from_utf8(output.stderr)?;
+            bail!(
+                "failed to fetch tags of `{}`: {}",
+                repo_dir.display(),
+                error.trim_end()
+            );
+        }
+        Ok(())
+    }
+
+    /// Fetches history for the repository cloned at `repo_dir` until its `HEAD` has at least
+    /// `depth` commits, or until the repository is no longer shallow
+    // smoelius: `self` is used only to lock the clone, which requires the `lock-index` feature.
+    #[cfg_attr(
+        not(all(feature = "on-disk-cache", feature = "lock-index", not(windows))),
+        allow(clippy::unused_self)
+    )]
+    pub fn deepen(&self, repo_dir: &Path, depth: usize) -> Result<()> {
+        let mut command = git::command();
+        command.args(["rev-parse", "--is-shallow-repository"]);
+        command.current_dir(repo_dir);
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        ensure!(
+            output.status.success(),
+            "failed to run command: {command:?}"
+        );
+        if String::from_utf8(output.stdout)?.trim_end() == "false" {
+            return Ok(());
+        }
+
+        let mut command = git::command();
+        command.args(["rev-list", "--count", "HEAD"]);
+        command.current_dir(repo_dir);
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        ensure!(
+            output.status.success(),
+            "failed to run command: {command:?}"
+        );
+        let count = String::from_utf8(output.stdout)?
+            .trim_end()
+            .parse::<usize>()?;
+        if count >= depth {
+            return Ok(());
+        }
+
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        let _lock = self.lock_repository_dir(repo_dir)?;
+
+        let mut command = git::command();
+        command.args([
+            "fetch",
+            &format!("--depth={depth}"),
+            "--no-recurse-submodules",
+            "--quiet",
+            "origin",
+        ]);
+        command
+            .current_dir(repo_dir)
+            .env("GCM_INTERACTIVE", "never")
+            .env("GIT_ASKPASS", "echo")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stderr(Stdio::piped());
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        if !output.status.success() {
+            let error = String::from_utf8(output.stderr)?;
+            bail!(
+                "failed to deepen `{}`: {}",
+                repo_dir.display(),
+                error.trim_end()
+            );
+        }
+        Ok(())
+    }
+
     fn entry(&mut self, pkg: &Package) -> Result<Entry> {
         if !self.entries.contains_key(&pkg.name) {
-            let path_buf = self.entries_dir().join(&pkg.name);
+            let path_buf = package_file(&self.entries_dir(), &pkg.name);
             let contents = read_to_string(&path_buf)
                 .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
             let entry = serde_json::from_str::<Entry>(&contents)?;
-            ensure!(
-                pkg.repository.as_ref() == Some(&entry.named_url),
-                "`pkg.repository` and `entry.named_url` differ"
-            );
             self.entries.insert(pkg.name.clone(), entry);
         }
         #[allow(clippy::unwrap_used)]
         Ok(self.entries.get(&pkg.name).cloned().unwrap())
     }
 
+    /// Removes the clone of `url`, along with its timestamp and recorded branch, unless a package
+    /// other than `pkg_name` has an entry referring to it
+    ///
+    /// Without this, a package whose repository changes would leave behind a clone that no entry
+    /// refers to.
+    fn remove_unreferenced_repository(&mut self, pkg_name: &str, url: &str) -> Result<()> {
+        let entries_dir = self.entries_dir();
+        let read_dir = read_dir(&entries_dir)
+            .with_context(|| format!("failed to read `{}`", entries_dir.display()))?;
+        for result in read_dir {
+            let dir_entry =
+                result.with_context(|| format!("failed to read `{}`", entries_dir.display()))?;
+            if dir_entry.file_name() == *package_filename(pkg_name)
+                || dir_entry.file_name() == pkg_name
+            {
+                continue;
+            }
+            // smoelius: Ignore any errors that may occur while reading/deserializing.
+            let Some(entry) = read_to_string(dir_entry.path())
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Entry>(&contents).ok())
+            else {
+                continue;
+            };
+            if canonical_url(&entry.cloned_url) == canonical_url(url) {
+                return Ok(());
+            }
+        }
+
+        let _lock: File;
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        if self.tempdir.is_none() {
+            _lock = crate::flock::lock_path(&CACHE_DIRECTORY)
+                .with_context(|| format!("failed to lock `{}`", CACHE_DIRECTORY.display()))?;
+        }
+
+        let digest = url_digest(url);
+        // smoelius: If another process holds the clone's lock, the clone is in use. Leave it.
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        let Some(_repository_lock) = self.try_lock_repository(&digest)?
+        else {
+            return Ok(());
+        };
+
+        self.remove_repository(&digest)
+    }
+
+    /// Evicts the least recently used clones until the clones occupy no more than `limit` bytes
+    ///
+    /// Clones used during this run or locked by another process are not evicted, so the limit may
+    /// still be exceeded afterward. Entries that refer to an evicted clone are removed along
+    /// with it.
+    #[cfg(all(feature = "on-disk-cache", not(windows)))]
+    pub fn evict(&mut self, limit: u64) -> Result<Vec<Eviction>> {
+        let _lock: File;
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        if self.tempdir.is_none() {
+            _lock = crate::flock::lock_path(&CACHE_DIRECTORY)
+                .with_context(|| format!("failed to lock `{}`", CACHE_DIRECTORY.display()))?;
+        }
+
+        let repositories_dir = self.repositories_dir();
+        if !repository_existence(&repositories_dir)? {
+            return Ok(Vec::new());
+        }
+
+        let mut total = 0;
+        let mut candidates = Vec::new();
+        let read_dir = read_dir(&repositories_dir)
+            .with_context(|| format!("failed to read `{}`", repositories_dir.display()))?;
+        for result in read_dir {
+            let dir_entry = result
+                .with_context(|| format!("failed to read `{}`", repositories_dir.display()))?;
+            let repo_dir = dir_entry.path();
+            let size = disk_usage(&repo_dir)?;
+            total += size;
+            let digest = dir_entry.file_name().to_string_lossy().into_owned();
+            if self.used.contains(&digest) {
+                continue;
+            }
+            // smoelius: Clones made before access times were recorded fall back to their
+            // timestamps.
+            let accessed = read_secs(&self.accessed_dir().join(&digest))
+                .or_else(|_| read_secs(&self.repository_timestamps_dir().join(&digest)))
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            candidates.push((accessed, digest, repo_dir, size));
+        }
+        candidates.sort();
+
+        let urls = self.cloned_urls_by_digest()?;
+
+        let mut evictions = Vec::new();
+        for (accessed, digest, repo_dir, size) in candidates {
+            if total <= limit {
+                break;
+            }
+            #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+            let Some(_repository_lock) = self.try_lock_repository(&digest)?
+            else {
+                continue;
+            };
+            self.remove_repository(&digest)?;
+            let mut url = None;
+            for (filename, cloned_url) in urls.get(&digest).into_iter().flatten() {
+                let path_buf = self.entries_dir().join(filename);
+                remove_file(&path_buf)
+                    .with_context(|| format!("failed to remove `{}`", path_buf.display()))?;
+                url = Some(cloned_url.clone());
+            }
+            self.entries
+                .retain(|_, entry| url_digest(&entry.cloned_url) != digest);
+            total -= size;
+            evictions.push(Eviction {
+                url,
+                repo_dir,
+                size,
+                accessed,
+            });
+        }
+
+        Ok(evictions)
+    }
+
+    /// Returns the filenames of the entries that refer to each clone, along with the urls the
+    /// entries refer to, keyed by the clones' digests
+    #[cfg(all(feature = "on-disk-cache", not(windows)))]
+    fn cloned_urls_by_digest(&self) -> Result<HashMap<String, Vec<(String, String)>>> {
+        let mut urls = HashMap::<_, Vec<_>>::new();
+        let entries_dir = self.entries_dir();
+        if !entries_dir.try_exists().unwrap_or_default() {
+            return Ok(urls);
+        }
+        let read_dir = read_dir(&entries_dir)
+            .with_context(|| format!("failed to read `{}`", entries_dir.display()))?;
+        for result in read_dir {
+            let dir_entry =
+                result.with_context(|| format!("failed to read `{}`", entries_dir.display()))?;
+            // smoelius: Ignore any errors that may occur while reading/deserializing.
+            let Some(entry) = read_to_string(dir_entry.path())
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Entry>(&contents).ok())
+            else {
+                continue;
+            };
+            urls.entry(url_digest(&entry.cloned_url))
+                .or_default()
+                .push((
+                    dir_entry.file_name().to_string_lossy().into_owned(),
+                    entry.cloned_url,
+                ));
+        }
+        Ok(urls)
+    }
+
+    /// Removes the clone with digest `digest`, along with its timestamp, access time, and recorded
+    /// branch
+    ///
+    /// The caller is expected to hold the cache's lock.
+    fn remove_repository(&mut self, digest: &str) -> Result<()> {
+        let repo_dir = self.repositories_dir().join(digest);
+        if repository_existence(&repo_dir)? {
+            remove_dir_all(&repo_dir)
+                .with_context(|| format!("failed to remove `{}`", repo_dir.display()))?;
+        }
+        for path_buf in [
+            self.accessed_dir().join(digest),
+            self.repository_timestamps_dir().join(digest),
+            self.branches_dir().join(digest),
+        ] {
+            if path_buf.try_exists().unwrap_or_default() {
+                remove_file(&path_buf)
+                    .with_context(|| format!("failed to remove `{}`", path_buf.display()))?;
+            }
+        }
+        self.repository_timestamps.remove(digest);
+        self.branches.remove(digest);
+
+        Ok(())
+    }
+
+    /// Locks the clone with digest `digest`, waiting for any other process holding the lock
+    ///
+    /// Without the `lock-index` feature, clones are not locked, and callers construct an unlocked
+    /// [`RepositoryLock`] instead.
+    #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+    fn lock_repository(&self, digest: &str) -> Result<RepositoryLock> {
+        if self.tempdir.is_none() {
+            create_dir_all(self.locks_dir()).with_context(|| "failed to create locks directory")?;
+            let path_buf = self.locks_dir().join(digest);
+            let file = crate::flock::lock_file(&path_buf)
+                .with_context(|| format!("failed to lock `{}`", path_buf.display()))?;
+            return Ok(RepositoryLock { _file: Some(file) });
+        }
+        Ok(RepositoryLock { _file: None })
+    }
+
+    /// Locks the clone at `repo_dir`
+    #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+    fn lock_repository_dir(&self, repo_dir: &Path) -> Result<RepositoryLock> {
+        let digest = repo_dir
+            .file_name()
+            .ok_or_else(|| anyhow!("`{}` has no file name", repo_dir.display()))?;
+        self.lock_repository(&digest.to_string_lossy())
+    }
+
+    /// Locks the clone with digest `digest`, unless another process holds the lock, in which case
+    /// `None` is returned
+    #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+    fn try_lock_repository(&self, digest: &str) -> Result<Option<RepositoryLock>> {
+        if self.tempdir.is_none() {
+            create_dir_all(self.locks_dir()).with_context(|| "failed to create locks directory")?;
+            let path_buf = self.locks_dir().join(digest);
+            return match crate::flock::try_lock_file(&path_buf) {
+                Ok(file) => Ok(Some(RepositoryLock { _file: Some(file) })),
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+                Err(error) => Err(anyhow::Error::from(error)
+                    .context(format!("failed to lock `{}`", path_buf.display()))),
+            };
+        }
+        Ok(Some(RepositoryLock { _file: None }))
+    }
+
+    /// Records that the clone with digest `digest` was used during this run
+    fn record_access(&mut self, digest: &str) -> Result<()> {
+        if !self.used.insert(digest.to_owned()) {
+            return Ok(());
+        }
+        create_dir_all(self.accessed_dir())
+            .with_context(|| "failed to create accessed directory")?;
+        let path_buf = self.accessed_dir().join(digest);
+        let duration = clock::real_now().duration_since(SystemTime::UNIX_EPOCH)?;
+        write(&path_buf, duration.as_secs().to_string())
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        Ok(())
+    }
+
     fn repository_is_current(&mut self, url: &str) -> Result<bool> {
         self.repository_timestamp(url).and_then(|timestamp| {
-            let duration = SystemTime::now().duration_since(timestamp)?;
-            Ok(duration.as_secs() < self.refresh_age * SECS_PER_DAY)
+            let duration = clock::real_now().duration_since(timestamp)?;
+            Ok(days::from_secs(duration.as_secs()) < self.refresh_age)
         })
     }
 
@@ -249,35 +1172,323 @@ impl Cache {
         Ok(*self.repository_timestamps.get(&digest).unwrap())
     }
 
-    pub fn fetch_versions(&mut self, name: &str) -> Result<Vec<Version>> {
+    /// Returns true if [`Cache::fetch_versions`] would return cached versions for `name` without
+    /// making a request
+    pub fn versions_are_cached(&mut self, name: &str) -> bool {
+        self.versions(name).is_ok() && self.versions_are_current(name).unwrap_or_default()
+    }
+
+    pub fn fetch_versions(&mut self, name: &str) -> Result<Vec<VersionSummary>> {
         // smoelius: Ignore any errors that may occur while reading/deserializing.
-        if let Ok(versions) = self.versions(name) {
+        let cached_versions = self.versions(name).ok();
+        if let Some(versions) = &cached_versions {
             if self.versions_are_current(name).unwrap_or_default() {
+                metrics::hit(metrics::Cache::OnDiskVersions);
+                return Ok(versions.clone());
+            }
+        }
+        metrics::miss(metrics::Cache::OnDiskVersions);
+
+        let result = retry::with_backoff(
+            "crates.io API request",
+            || fetch_crate_response(name),
+            is_transient,
+        );
+        let crate_response = match (result, cached_versions) {
+            (Ok(crate_response), _) => crate_response,
+            // smoelius: Stale versions are better than none, unless the user asked otherwise.
+            (Err(error), Some(versions)) if !crate::opts::get().require_crates_io => {
+                crate::warn!(
+                    "failed to refresh versions of `{}`; using stale cached versions: {}",
+                    name,
+                    error
+                );
                 return Ok(versions);
             }
+            (Err(error), _) => return Err(error),
+        };
+        self.store_crate_response(name, crate_response)?;
+
+        self.versions(name)
+    }
+
+    /// Fetches the versions of each of `names` that [`Cache::fetch_versions`] would request,
+    /// several at a time (see [`scheduler`])
+    ///
+    /// Failures are not reported here; they are reported when [`Cache::fetch_versions`] is called.
+    pub fn prefetch_versions<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        let urls = names
+            .into_iter()
+            .filter(|name| !self.versions_are_cached(name))
+            .map(|name| (format!("{CRATES_API_URL}{name}"), name))
+            .collect::<BTreeMap<_, _>>();
+        if urls.is_empty() {
+            return;
+        }
+        let results = scheduler::get_all(urls.keys().map(String::as_str), USER_AGENT);
+        for (url, result) in results {
+            let Some(name) = urls.get(&url) else {
+                continue;
+            };
+            let result = result
+                .and_then(|body| parse_crate_response(&body))
+                .and_then(|crate_response| self.store_crate_response(name, crate_response));
+            if let Err(error) = result {
+                log::debug!("failed to prefetch versions of `{name}`: {error}");
+            }
+        }
+    }
+
+    /// Fetches the repository recorded for `name` on crates.io
+    ///
+    /// The recorded repository can differ from the one in the package's manifest, e.g., if the
+    /// package's owner corrected it through the crates.io web interface.
+    /// Reads the findings history recorded for `fingerprint`, applies `f` to it, and writes it back
+    ///
+    /// The cache is locked throughout, so that concurrent runs in the same workspace do not lose
+    /// one another's updates.
+    pub fn update_history<T>(
+        &mut self,
+        fingerprint: &str,
+        f: impl FnOnce(&mut History) -> T,
+    ) -> Result<T> {
+        let _lock: File;
+        #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+        if self.tempdir.is_none() {
+            _lock = crate::flock::lock_path(&CACHE_DIRECTORY)
+                .with_context(|| format!("failed to lock `{}`", CACHE_DIRECTORY.display()))?;
         }
 
-        let crate_response = CRATES_IO_SYNC_CLIENT.get_crate(name)?;
-        // smoelius: Avoid using anything other than `versions` from `CrateResponse`. In particular,
-        // avoid using `crate_data`. The same data should be available in the crates.io index.
+        let path_buf = self.histories_dir().join(digest(fingerprint));
+        // smoelius: Ignore any errors that may occur while reading/deserializing.
+        let mut history = read_to_string(&path_buf)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<History>(&contents).ok())
+            .unwrap_or_default();
+
+        let result = f(&mut history);
+
+        create_dir_all(self.histories_dir())
+            .with_context(|| "failed to create histories directory")?;
+        let json = serde_json::to_string_pretty(&history)?;
+        write(&path_buf, json)
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+
+        Ok(result)
+    }
+
+    /// Returns the baseline recorded by the last fast run in `workspace_root`, if any
+    pub fn baseline(&self, workspace_root: &str) -> Option<Baseline> {
+        // smoelius: Ignore any errors that may occur while reading/deserializing.
+        let path_buf = self.baselines_dir().join(digest(workspace_root));
+        let contents = read_to_string(path_buf).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn write_baseline(&self, workspace_root: &str, baseline: &Baseline) -> Result<()> {
+        create_dir_all(self.baselines_dir())
+            .with_context(|| "failed to create baselines directory")?;
+        let path_buf = self.baselines_dir().join(digest(workspace_root));
+        let json = serde_json::to_string_pretty(baseline)?;
+        write(&path_buf, json)
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        Ok(())
+    }
+
+    pub fn fetch_recorded_repository(&mut self, name: &str) -> Result<Option<String>> {
+        // smoelius: Ignore any errors that may occur while reading/deserializing. Recorded
+        // repositories are fetched along with versions, so they share a timestamp.
+        if let Ok(repository) = self.recorded_repository(name) {
+            if self.versions_are_current(name).unwrap_or_default() {
+                metrics::hit(metrics::Cache::OnDiskRecordedRepositories);
+                return Ok(repository);
+            }
+        }
+        metrics::miss(metrics::Cache::OnDiskRecordedRepositories);
+
+        let crate_response = retry::with_backoff(
+            "crates.io API request",
+            || CRATES_IO_SYNC_CLIENT.get_crate(name).map_err(Into::into),
+            is_transient,
+        )?;
+        let repository = crate_response.crate_data.repository.clone();
+        self.store_crate_response(name, crate_response)?;
+
+        Ok(repository)
+    }
+
+    fn store_crate_response(&mut self, name: &str, crate_response: CrateResponse) -> Result<()> {
+        // smoelius: Avoid using anything other than `versions` and `crate_data.repository` from
+        // `CrateResponse`. The rest of `crate_data` should be available in the crates.io index.
+        // The repository is the exception: the index records only what was published, whereas
+        // crates.io records the repository's current value.
+        metrics::count(metrics::Count::FetchedVersions, 1);
+        let repository = crate_response.crate_data.repository;
+        self.write_recorded_repository(name, repository.as_deref())?;
+        self.recorded_repositories
+            .insert(name.to_owned(), repository);
+
         let versions = crate_response.versions;
         self.write_versions(name, &versions)?;
-        self.versions.insert(name.to_owned(), versions.clone());
+        self.versions.insert(
+            name.to_owned(),
+            versions.into_iter().map(VersionSummary::from).collect(),
+        );
 
-        let timestamp = SystemTime::now();
+        let timestamp = clock::real_now();
         self.write_versions_timestamp(name, timestamp)?;
         self.versions_timestamps.insert(name.to_owned(), timestamp);
 
-        Ok(versions)
+        Ok(())
+    }
+
+    /// Returns the temporary package metadata stored under `key`, if it is current
+    ///
+    /// Metadata that cannot be deserialized (e.g., because it was produced by a different version
+    /// of Cargo) is treated as missing.
+    pub fn temp_package_metadata(&self, key: &str) -> Option<Metadata> {
+        let digest = digest(key);
+        // smoelius: Ignore any errors that may occur while reading/deserializing.
+        let metadata = self
+            .temp_package_metadata_is_current(&digest)
+            .unwrap_or_default()
+            .then(|| {
+                let path_buf = self.temp_package_metadata_dir().join(&digest);
+                let contents = read_to_string(path_buf).ok()?;
+                serde_json::from_str::<Metadata>(&contents).ok()
+            })
+            .flatten();
+        if metadata.is_some() {
+            metrics::hit(metrics::Cache::OnDiskTempPackageMetadata);
+        } else {
+            metrics::miss(metrics::Cache::OnDiskTempPackageMetadata);
+        }
+        metadata
     }
 
-    fn versions(&mut self, name: &str) -> Result<Vec<Version>> {
+    pub fn write_temp_package_metadata(&self, key: &str, metadata: &Metadata) -> Result<()> {
+        let digest = digest(key);
+        create_dir_all(self.temp_package_metadata_dir())
+            .with_context(|| "failed to create temporary package metadata directory")?;
+        let path_buf = self.temp_package_metadata_dir().join(&digest);
+        let json = serde_json::to_string(metadata)?;
+        write(&path_buf, json)
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+
+        create_dir_all(self.temp_package_metadata_timestamps_dir())
+            .with_context(|| "failed to create temporary package metadata timestamps directory")?;
+        let path_buf = self.temp_package_metadata_timestamps_dir().join(&digest);
+        let duration = clock::real_now().duration_since(SystemTime::UNIX_EPOCH)?;
+        write(&path_buf, duration.as_secs().to_string())
+            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        Ok(())
+    }
+
+    fn temp_package_metadata_is_current(&self, digest: &str) -> Result<bool> {
+        let path_buf = self.temp_package_metadata_timestamps_dir().join(digest);
+        let timestamp = read_secs(&path_buf)?;
+        let duration = clock::real_now().duration_since(timestamp)?;
+        Ok(days::from_secs(duration.as_secs()) < self.refresh_age)
+    }
+
+    /// Fetches the last 90 days of `name`'s downloads
+    pub fn fetch_downloads(&mut self, name: &str) -> Result<CrateDownloads> {
+        // smoelius: Ignore any errors that may occur while reading/deserializing.
+        let cached_downloads = self.downloads(name).ok();
+        if let Some(downloads) = &cached_downloads {
+            if self.downloads_are_current(name).unwrap_or_default() {
+                metrics::hit(metrics::Cache::OnDiskDownloads);
+                return Ok(downloads.clone());
+            }
+        }
+        metrics::miss(metrics::Cache::OnDiskDownloads);
+
+        let result = retry::with_backoff(
+            "crates.io API request",
+            || {
+                CRATES_IO_SYNC_CLIENT
+                    .crate_downloads(name)
+                    .map_err(Into::into)
+            },
+            is_transient,
+        );
+        let downloads = match (result, cached_downloads) {
+            (Ok(downloads), _) => downloads,
+            (Err(error), Some(downloads)) => {
+                crate::warn!(
+                    "failed to refresh downloads of `{}`; using stale cached downloads: {}",
+                    name,
+                    error
+                );
+                return Ok(downloads);
+            }
+            (Err(error), None) => return Err(error),
+        };
+        self.write_downloads(name, &downloads)?;
+        self.downloads.insert(name.to_owned(), downloads.clone());
+
+        let timestamp = clock::real_now();
+        self.write_downloads_timestamp(name, timestamp)?;
+        self.downloads_timestamps.insert(name.to_owned(), timestamp);
+
+        Ok(downloads)
+    }
+
+    fn downloads(&mut self, name: &str) -> Result<CrateDownloads> {
+        if !self.downloads.contains_key(name) {
+            let path_buf = package_file(&self.downloads_dir(), name);
+            let contents = read_to_string(&path_buf)
+                .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
+            let downloads = serde_json::from_str::<CrateDownloads>(&contents)?;
+            self.downloads.insert(name.to_owned(), downloads);
+        }
+        #[allow(clippy::unwrap_used)]
+        Ok(self.downloads.get(name).cloned().unwrap())
+    }
+
+    fn downloads_are_current(&mut self, name: &str) -> Result<bool> {
+        self.downloads_timestamp(name).and_then(|timestamp| {
+            let duration = clock::real_now().duration_since(timestamp)?;
+            Ok(days::from_secs(duration.as_secs()) < DOWNLOADS_REFRESH_AGE)
+        })
+    }
+
+    fn downloads_timestamp(&mut self, name: &str) -> Result<SystemTime> {
+        if !self.downloads_timestamps.contains_key(name) {
+            let path_buf = package_file(&self.downloads_timestamps_dir(), name);
+            let contents = read_to_string(&path_buf)
+                .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
+            let secs = u64::from_str(&contents)?;
+            let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+            self.downloads_timestamps.insert(name.to_owned(), timestamp);
+        }
+        #[allow(clippy::unwrap_used)]
+        Ok(*self.downloads_timestamps.get(name).unwrap())
+    }
+
+    fn recorded_repository(&mut self, name: &str) -> Result<Option<String>> {
+        if !self.recorded_repositories.contains_key(name) {
+            let path_buf = package_file(&self.recorded_repositories_dir(), name);
+            let contents = read_to_string(&path_buf)
+                .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
+            let repository = serde_json::from_str::<Option<String>>(&contents)?;
+            self.recorded_repositories
+                .insert(name.to_owned(), repository);
+        }
+        #[allow(clippy::unwrap_used)]
+        Ok(self.recorded_repositories.get(name).cloned().unwrap())
+    }
+
+    fn versions(&mut self, name: &str) -> Result<Vec<VersionSummary>> {
         if !self.versions.contains_key(name) {
-            let path_buf = self.versions_dir().join(name);
+            let path_buf = package_file(&self.versions_dir(), name);
             let contents = read_to_string(&path_buf)
                 .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
             let versions = serde_json::from_str::<Vec<Version>>(&contents)?;
-            self.versions.insert(name.to_owned(), versions);
+            self.versions.insert(
+                name.to_owned(),
+                versions.into_iter().map(VersionSummary::from).collect(),
+            );
         }
         #[allow(clippy::unwrap_used)]
         Ok(self.versions.get(name).cloned().unwrap())
@@ -285,14 +1496,14 @@ impl Cache {
 
     fn versions_are_current(&mut self, url: &str) -> Result<bool> {
         self.versions_timestamp(url).and_then(|timestamp| {
-            let duration = SystemTime::now().duration_since(timestamp)?;
-            Ok(duration.as_secs() < self.refresh_age * SECS_PER_DAY)
+            let duration = clock::real_now().duration_since(timestamp)?;
+            Ok(days::from_secs(duration.as_secs()) < self.refresh_age)
         })
     }
 
     fn versions_timestamp(&mut self, name: &str) -> Result<SystemTime> {
         if !self.versions_timestamps.contains_key(name) {
-            let path_buf = self.versions_timestamps_dir().join(name);
+            let path_buf = package_file(&self.versions_timestamps_dir(), name);
             let contents = read_to_string(&path_buf)
                 .with_context(|| format!("failed to read `{}`", path_buf.display()))?;
             let secs = u64::from_str(&contents)?;
@@ -303,12 +1514,47 @@ impl Cache {
         Ok(*self.versions_timestamps.get(name).unwrap())
     }
 
+    fn write_downloads(&self, name: &str, downloads: &CrateDownloads) -> Result<()> {
+        create_dir_all(self.downloads_dir())
+            .with_context(|| "failed to create downloads directory")?;
+        let json = serde_json::to_string_pretty(downloads)?;
+        write_package_file(&self.downloads_dir(), name, json)?;
+        Ok(())
+    }
+
+    fn write_downloads_timestamp(&self, name: &str, timestamp: SystemTime) -> Result<()> {
+        create_dir_all(self.downloads_timestamps_dir())
+            .with_context(|| "failed to create downloads timestamps directory")?;
+        let duration = timestamp.duration_since(SystemTime::UNIX_EPOCH)?;
+        write_package_file(
+            &self.downloads_timestamps_dir(),
+            name,
+            duration.as_secs().to_string(),
+        )?;
+        Ok(())
+    }
+
     fn write_entry(&self, pkg_name: &str, entry: &Entry) -> Result<()> {
-        create_dir_all(self.entries_dir()).with_context(|| "failed to create entries directory")?;
-        let path_buf = self.entries_dir().join(pkg_name);
+        if !self.entries_dir().try_exists().unwrap_or_default() {
+            let _lock: File;
+            #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+            if self.tempdir.is_none() {
+                _lock = crate::flock::lock_path(&CACHE_DIRECTORY)
+                    .with_context(|| format!("failed to lock `{}`", CACHE_DIRECTORY.display()))?;
+            }
+            create_dir_all(self.entries_dir())
+                .with_context(|| "failed to create entries directory")?;
+        }
         let json = serde_json::to_string_pretty(entry)?;
-        write(&path_buf, json)
-            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        write_package_file(&self.entries_dir(), pkg_name, json)?;
+        Ok(())
+    }
+
+    fn write_recorded_repository(&self, name: &str, repository: Option<&str>) -> Result<()> {
+        create_dir_all(self.recorded_repositories_dir())
+            .with_context(|| "failed to create recorded repositories directory")?;
+        let json = serde_json::to_string(&repository)?;
+        write_package_file(&self.recorded_repositories_dir(), name, json)?;
         Ok(())
     }
 
@@ -325,31 +1571,72 @@ impl Cache {
     fn write_versions(&self, name: &str, versions: &[Version]) -> Result<()> {
         create_dir_all(self.versions_dir())
             .with_context(|| "failed to create versions directory")?;
-        let path_buf = self.versions_dir().join(name);
         let json = serde_json::to_string_pretty(versions)?;
-        write(&path_buf, json)
-            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        write_package_file(&self.versions_dir(), name, json)?;
         Ok(())
     }
 
     fn write_versions_timestamp(&self, name: &str, timestamp: SystemTime) -> Result<()> {
         create_dir_all(self.versions_timestamps_dir())
             .with_context(|| "failed to create versions timestamps directory")?;
-        let path_buf = self.versions_timestamps_dir().join(name);
         let duration = timestamp.duration_since(SystemTime::UNIX_EPOCH)?;
-        write(&path_buf, duration.as_secs().to_string())
-            .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+        write_package_file(
+            &self.versions_timestamps_dir(),
+            name,
+            duration.as_secs().to_string(),
+        )?;
         Ok(())
     }
 
+    fn accessed_dir(&self) -> PathBuf {
+        self.base_dir().join("accessed")
+    }
+
+    fn baselines_dir(&self) -> PathBuf {
+        self.base_dir().join("baselines")
+    }
+
+    fn branches_dir(&self) -> PathBuf {
+        self.base_dir().join("branches")
+    }
+
+    fn downloads_dir(&self) -> PathBuf {
+        self.base_dir().join("downloads")
+    }
+
+    fn downloads_timestamps_dir(&self) -> PathBuf {
+        self.base_dir().join("downloads_timestamps")
+    }
+
     fn entries_dir(&self) -> PathBuf {
         self.base_dir().join("entries")
     }
 
+    fn histories_dir(&self) -> PathBuf {
+        self.base_dir().join("histories")
+    }
+
+    #[cfg(all(feature = "on-disk-cache", feature = "lock-index", not(windows)))]
+    fn locks_dir(&self) -> PathBuf {
+        self.base_dir().join("locks")
+    }
+
+    fn recorded_repositories_dir(&self) -> PathBuf {
+        self.base_dir().join("recorded_repositories")
+    }
+
     fn repositories_dir(&self) -> PathBuf {
         self.base_dir().join("repositories")
     }
 
+    fn temp_package_metadata_dir(&self) -> PathBuf {
+        self.base_dir().join("temp_package_metadata")
+    }
+
+    fn temp_package_metadata_timestamps_dir(&self) -> PathBuf {
+        self.base_dir().join("temp_package_metadata_timestamps")
+    }
+
     // smoelius: FIXME: Rename this directory to "repository_timestamps".
     fn repository_timestamps_dir(&self) -> PathBuf {
         self.base_dir().join("timestamps")
@@ -363,7 +1650,12 @@ impl Cache {
         self.base_dir().join("versions_timestamps")
     }
 
-    fn base_dir(&self) -> &Path {
+    fn version_repositories_dir(&self) -> PathBuf {
+        self.base_dir().join("version_repositories")
+    }
+
+    /// Returns the directory in which the cache is stored
+    pub fn base_dir(&self) -> &Path {
         let base_dir = self.tempdir.as_ref().map(TempDir::path);
 
         #[cfg(all(feature = "on-disk-cache", not(windows)))]
@@ -375,8 +1667,147 @@ impl Cache {
     }
 }
 
+/// Returns the size limit given by `--cache-size-limit` or `CARGO_UNMAINTAINED_CACHE_SIZE_LIMIT`,
+/// if either is set
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+pub(crate) fn size_limit() -> Result<Option<u64>> {
+    if let Some(limit) = crate::opts::get().cache_size_limit {
+        return Ok(Some(limit));
+    }
+    let Ok(value) = std::env::var(CACHE_SIZE_LIMIT_VAR) else {
+        return Ok(None);
+    };
+    u64::from_str(&value).map(Some).with_context(|| {
+        format!("failed to parse {CACHE_SIZE_LIMIT_VAR} value `{value}` as a number of bytes")
+    })
+}
+
+/// Returns those of `tags` that exist in the remote repository of the clone at `repo_dir`,
+/// without fetching them
+pub(crate) fn remote_tags(repo_dir: &Path, tags: &[String]) -> Result<Vec<String>> {
+    let mut command = git::command();
+    command.args(["ls-remote", "--tags", "--refs", "origin"]);
+    command.args(tags.iter().map(|tag| format!("refs/tags/{tag}")));
+    command
+        .current_dir(repo_dir)
+        .env("GCM_INTERACTIVE", "never")
+        .env("GIT_ASKPASS", "echo")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stderr(Stdio::piped());
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    if !output.status.success() {
+        let error = String::from_utf8(output.stderr)?;
+        bail!("failed to list tags: {}", error.trim_end());
+    }
+    let stdout = String::from_utf8(output.stdout)?;
+    let remote_tags = stdout
+        .lines()
+        .filter_map(|line| line.split_once("\trefs/tags/"))
+        .map(|(_, tag)| tag)
+        .collect::<HashSet<_>>();
+    Ok(tags
+        .iter()
+        .filter(|tag| remote_tags.contains(tag.as_str()))
+        .cloned()
+        .collect())
+}
+
+fn is_transient(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<crates_io_api::Error>(),
+        Some(crates_io_api::Error::Http(error)) if error.is_timeout()
+    ) || error
+        .downcast_ref::<curl::Error>()
+        .is_some_and(retry::is_transient_curl_error)
+        || error
+            .downcast_ref::<scheduler::UnexpectedResponse>()
+            .is_some_and(|response| response.code >= 500)
+}
+
+/// Fetches `name`'s crate data and versions from the crates.io API
+fn fetch_crate_response(name: &str) -> Result<CrateResponse> {
+    let url = format!("{CRATES_API_URL}{name}");
+    let body = scheduler::get_all([url.as_str()], USER_AGENT)
+        .remove(&url)
+        .unwrap_or_else(|| Err(anyhow!("no response from `{}`", url)))?;
+    parse_crate_response(&body)
+}
+
+fn parse_crate_response(body: &[u8]) -> Result<CrateResponse> {
+    serde_json::from_slice(body).with_context(|| "failed to parse crates.io API response")
+}
+
+// smoelius: Urls that differ only in, e.g., a trailing `.git` share a clone.
+/// The maximum number of characters of a package's name that are kept in its filename
+const PACKAGE_FILENAME_PREFIX_LEN: usize = 64;
+
+/// The number of hex digits of the hash of a package's name that are appended to its filename
+const PACKAGE_FILENAME_HASH_LEN: usize = 12;
+
+/// Returns the filename under which `name`'s data is stored, e.g., `serde-9a5f0d3a6e1c`
+///
+/// The name is lowercased and truncated, and characters other than ASCII alphanumerics, `-`, and
+/// `_` are replaced with `_`. So the filename is valid on every platform, and the appended hash
+/// keeps names that differ only by case (or by replaced characters) apart. No Windows reserved
+/// name (e.g., `con`) contains `-`, so none is produced.
+pub(crate) fn package_filename(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .take(PACKAGE_FILENAME_PREFIX_LEN)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let mut hash = digest(name);
+    hash.truncate(PACKAGE_FILENAME_HASH_LEN);
+    format!("{sanitized}-{hash}")
+}
+
+/// Returns the path of `name`'s file in `dir`, or of the file written by an earlier version (named
+/// after the package) if only it exists
+pub(crate) fn package_file(dir: &Path, name: &str) -> PathBuf {
+    let path_buf = dir.join(package_filename(name));
+    if !path_buf.try_exists().unwrap_or_default() {
+        if let Some(legacy) = legacy_package_file(dir, name) {
+            if legacy.try_exists().unwrap_or_default() {
+                return legacy;
+            }
+        }
+    }
+    path_buf
+}
+
+/// Writes `contents` to `name`'s file in `dir`, and removes the file written by an earlier version,
+/// if any
+pub(crate) fn write_package_file(dir: &Path, name: &str, contents: impl AsRef<[u8]>) -> Result<()> {
+    let path_buf = dir.join(package_filename(name));
+    write(&path_buf, contents)
+        .with_context(|| format!("failed to write `{}`", path_buf.display()))?;
+    if let Some(legacy) = legacy_package_file(dir, name) {
+        // smoelius: Ignore any errors that may occur while removing.
+        let _: Result<_, _> = remove_file(legacy);
+    }
+    Ok(())
+}
+
+/// Returns the path at which an earlier version stored `name`'s file in `dir`, unless `name` is
+/// not a single path component
+fn legacy_package_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    (Path::new(name).file_name() == Some(name.as_ref())).then(|| dir.join(name))
+}
+
 fn url_digest(url: &str) -> String {
-    sha1_smol::Sha1::from(url).hexdigest()
+    digest(&canonical_url(url))
+}
+
+fn digest(s: &str) -> String {
+    sha1_smol::Sha1::from(s).hexdigest()
 }
 
 fn repository_existence(repo_dir: &Path) -> Result<bool> {
@@ -388,8 +1819,199 @@ fn repository_existence(repo_dir: &Path) -> Result<bool> {
     })
 }
 
+fn read_secs(path: &Path) -> Result<SystemTime> {
+    let contents =
+        read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let secs = u64::from_str(&contents)?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Returns what to do about a clone of `size` bytes
+pub(crate) fn size_check(size: u64, max_repo_size: u64, deny_oversize: bool) -> SizeCheck {
+    if size <= max_repo_size {
+        SizeCheck::Within
+    } else if deny_oversize {
+        SizeCheck::Deny
+    } else {
+        SizeCheck::Warn
+    }
+}
+
+/// Warns if a clone of `url` occupying `size` bytes exceeds `--max-repo-size`, or, with
+/// `--deny-oversize`, returns a [`TooLarge`] error
+fn check_size(url: &str, size: u64) -> Result<()> {
+    let opts = crate::opts::get();
+    match size_check(size, opts.max_repo_size, opts.deny_oversize) {
+        SizeCheck::Within => {}
+        SizeCheck::Warn => {
+            crate::warn!(
+                "clone of `{}` occupies {} bytes, which exceeds --max-repo-size ({} bytes)",
+                url,
+                size,
+                opts.max_repo_size
+            );
+        }
+        SizeCheck::Deny => {
+            return Err(TooLarge {
+                url: url.to_owned(),
+                size,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the sizes that the entries in the cache directory `dir` record for their clones, largest
+/// first
+///
+/// Each clone appears once, even if several packages' entries name it. Entries that cannot be read
+/// or that record no size are skipped.
+#[cfg(all(feature = "on-disk-cache", not(windows)))]
+pub(crate) fn recorded_clone_sizes(dir: &Path) -> Vec<(String, u64)> {
+    let Ok(read_dir) = read_dir(dir.join("entries")) else {
+        return Vec::new();
+    };
+    let mut sizes = BTreeMap::new();
+    for dir_entry in read_dir.flatten() {
+        let Some(entry) = read_to_string(dir_entry.path())
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Entry>(&contents).ok())
+        else {
+            continue;
+        };
+        if let Some(size) = entry.size {
+            sizes.insert(entry.cloned_url, size);
+        }
+    }
+    let mut sizes = sizes.into_iter().collect::<Vec<_>>();
+    sizes.sort_by(|(lhs_url, lhs_size), (rhs_url, rhs_size)| {
+        rhs_size.cmp(lhs_size).then_with(|| lhs_url.cmp(rhs_url))
+    });
+    sizes
+}
+
+/// Returns the total size of the files in `path`, without following symlinks
+pub(crate) fn disk_usage(path: &Path) -> Result<u64> {
+    let metadata =
+        symlink_metadata(path).with_context(|| format!("failed to stat `{}`", path.display()))?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    let read_dir =
+        read_dir(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    for result in read_dir {
+        let dir_entry = result.with_context(|| format!("failed to read `{}`", path.display()))?;
+        size += disk_usage(&dir_entry.path())?;
+    }
+    Ok(size)
+}
+
+/// Clones `url` into `repo_dir`, or, if `exists`, fetches into the existing clone
+///
+/// A connection failure is retried once, after [`RETRY_DELAY`]. Returns git's stderr if the clone
+/// or fetch failed.
+fn clone_or_fetch(url: &str, repo_dir: &Path, exists: bool) -> Result<Option<String>> {
+    let mut retried = false;
+    loop {
+        let mut command = if exists {
+            let branch_name = branch_name(repo_dir)?;
+            let mut command = git::command();
+            command.args([
+                "fetch",
+                "--no-recurse-submodules",
+                "--update-head-ok",
+                "origin",
+                &format!("{branch_name}:{branch_name}"),
+            ]);
+            command.current_dir(repo_dir);
+            command
+        } else {
+            let mut command = git::command();
+            // smoelius: The full repository is no longer checked out.
+            command.args([
+                "clone",
+                "--depth=1",
+                "--no-checkout",
+                "--no-recurse-submodules",
+                "--quiet",
+                url,
+                &repo_dir.to_string_lossy(),
+            ]);
+            command
+        };
+        command
+            .env("GCM_INTERACTIVE", "never")
+            .env("GIT_ASKPASS", "echo")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stderr(Stdio::piped());
+        let output = command
+            .output()
+            .with_context(|| format!("failed to run command: {command:?}"))?;
+        if output.status.success() {
+            network::record(network::Outcome::Reached);
+            return Ok(None);
+        }
+        let error = String::from_utf8(output.stderr)?;
+        let outcome = network::classify_git_stderr(&error);
+        // smoelius: Connection failures are often intermittent (e.g., because of flaky IPv6). But
+        // other failures (e.g., a nonexistent repository) are not worth retrying.
+        if outcome == network::Outcome::ConnectionFailure && !retried {
+            log::debug!(
+                "failed to clone or fetch `{url}`; retrying in {} seconds",
+                RETRY_DELAY.as_secs()
+            );
+            sleep(RETRY_DELAY);
+            retried = true;
+            continue;
+        }
+        network::record(outcome);
+        return Ok(Some(error));
+    }
+}
+
+/// Returns `url` with a `.git` suffix appended or stripped, if `url` is an `https` url
+pub(crate) fn toggle_git_suffix(url: &str) -> Option<String> {
+    if !url.starts_with("https://") {
+        return None;
+    }
+    Some(url.strip_suffix(".git").map_or_else(
+        || format!("{}.git", url.trim_end_matches('/')),
+        ToOwned::to_owned,
+    ))
+}
+
+/// Downloads a tarball of the default branch of the GitHub repository at `url`, and unpacks it at
+/// `repo_dir`, replacing anything there
+fn unpack_tarball(url: Url, repo_dir: &Path) -> Result<()> {
+    let codeload_url = github::codeload_url(url)
+        .ok_or_else(|| anyhow!("no tarball is available for `{}`", url))?;
+    let tempdir = tempdir().with_context(|| "failed to create temporary directory")?;
+    let tarball = tempdir.path().join("repository.tar.gz");
+    crate::curl::download(Url::from(codeload_url.as_str()), &tarball)?;
+    if repository_existence(repo_dir)? {
+        remove_dir_all(repo_dir)
+            .with_context(|| format!("failed to remove `{}`", repo_dir.display()))?;
+    }
+    create_dir_all(repo_dir)
+        .with_context(|| format!("failed to create `{}`", repo_dir.display()))?;
+    let mut command = Command::new("tar");
+    command.arg("-xzf").arg(&tarball).arg("-C").arg(repo_dir);
+    let output = command
+        .output()
+        .with_context(|| format!("failed to run command: {command:?}"))?;
+    if !output.status.success() {
+        // smoelius: A partially unpacked tarball must not be mistaken for a usable one.
+        let _ = remove_dir_all(repo_dir);
+        let error = String::from_utf8(output.stderr)?;
+        bail!("failed to unpack tarball of `{}`: {}", url, error);
+    }
+    Ok(())
+}
+
 fn branch_name(repo_dir: &Path) -> Result<String> {
-    let mut command = Command::new("git");
+    let mut command = git::command();
     command.args(["rev-parse", "--abbrev-ref", "HEAD"]);
     command.current_dir(repo_dir);
     let output = command
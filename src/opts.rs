@@ -2,7 +2,8 @@
 
 use once_cell::sync::OnceCell;
 
-use super::Opts;
+use super::{Cargo, CargoSubCommand, Opts};
+use clap::Parser;
 
 static OPTS: OnceCell<Opts> = OnceCell::new();
 
@@ -10,6 +11,19 @@ pub(crate) fn init(opts: Opts) {
     OPTS.set(opts).unwrap();
 }
 
+/// Initializes the options to their defaults, unless they were already initialized
+///
+/// Used by library entry points (e.g., [`crate::repo_health`]) that can be called without a
+/// command line.
+pub(crate) fn init_default() {
+    OPTS.get_or_init(|| {
+        let Cargo {
+            subcmd: CargoSubCommand::Unmaintained(opts),
+        } = Cargo::parse_from(["cargo", "unmaintained"]);
+        opts
+    });
+}
+
 pub(crate) fn get() -> &'static Opts {
     OPTS.get().unwrap()
 }
@@ -0,0 +1,124 @@
+//! Whether a package's crates.io owners appear inactive (see `--owner-activity`)
+//!
+//! An owner's activity is the later of the last update to any crate they own and their most recent
+//! public GitHub event. If none of a package's owners has been active for
+//! `--owner-inactivity-days`, the package is effectively orphaned, even if its repository is merely
+//! quiet. But owner inactivity alone is weak evidence. So it causes a package to be reported only
+//! in combination with another signal (see [`tips`]).
+
+use super::{clock, days, on_disk_cache, opts, verbose, TOKEN_FOUND};
+use anyhow::Result;
+use std::{cell::RefCell, collections::HashMap, sync::atomic::Ordering, time::SystemTime};
+
+thread_local! {
+    // smoelius: Keyed by crate name. `None` means the owners are active, or that their activity
+    // could not be determined.
+    static INACTIVE_DAYS_CACHE: RefCell<HashMap<String, Option<u64>>> = RefCell::new(HashMap::new());
+    // smoelius: Keyed by owner login. Several packages often share an owner.
+    static ACTIVITY_CACHE: RefCell<HashMap<String, Option<SystemTime>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns true if owner inactivity should cause a package to be reported
+///
+/// A package with a stale repository and outdated dependencies is reported regardless. One with
+/// exactly one of these is reported only if its owners are also inactive. Owner inactivity alone
+/// is never enough.
+pub(crate) fn tips(repo_stale: bool, has_outdated_deps: bool, inactive_days: Option<u64>) -> bool {
+    inactive_days.is_some() && (repo_stale || has_outdated_deps)
+}
+
+/// Returns the number of days since any of crate `name`'s owners was last active, if that is at
+/// least `--owner-inactivity-days`
+///
+/// Returns `None` if `--owner-activity` was not passed, if there is no GitHub token, or if any
+/// owner's activity cannot be determined (e.g., because the owner is a team).
+pub(crate) fn inactive_days(name: &str) -> Option<u64> {
+    if !opts::get().owner_activity || !TOKEN_FOUND.load(Ordering::SeqCst) {
+        return None;
+    }
+    if let Some(inactive_days) = INACTIVE_DAYS_CACHE
+        .with_borrow(|inactive_days_cache| inactive_days_cache.get(name).copied())
+    {
+        return inactive_days;
+    }
+    let inactive_days = inactive_days_uncached(name)
+        .map_err(|error| {
+            crate::warn!(
+                "failed to determine activity of `{}`'s owners: {}",
+                name,
+                error
+            );
+        })
+        .ok()
+        .flatten();
+    INACTIVE_DAYS_CACHE.with_borrow_mut(|inactive_days_cache| {
+        inactive_days_cache.insert(name.to_owned(), inactive_days);
+    });
+    inactive_days
+}
+
+fn inactive_days_uncached(name: &str) -> Result<Option<u64>> {
+    let owners = verbose::wrap!(
+        || on_disk_cache::fetch_owners(name),
+        "owners of `{}` using crates.io API",
+        name
+    )?;
+    let mut activities = Vec::new();
+    for owner in &owners {
+        // smoelius: A team's activity is not easily determined. So a team is assumed to be active.
+        if owner.team {
+            return Ok(None);
+        }
+        activities.push(activity(owner)?);
+    }
+    Ok(inactive_days_from(
+        &activities,
+        clock::now(),
+        opts::get().owner_inactivity_days,
+    ))
+}
+
+/// Returns the time of `owner`'s most recent activity, if any
+fn activity(owner: &on_disk_cache::Owner) -> Result<Option<SystemTime>> {
+    if let Some(activity) =
+        ACTIVITY_CACHE.with_borrow(|activity_cache| activity_cache.get(&owner.login).copied())
+    {
+        return Ok(activity);
+    }
+    let latest_update = verbose::wrap!(
+        || on_disk_cache::fetch_latest_update_by(owner.id),
+        "latest crate update by `{}` using crates.io API",
+        owner.login
+    )?;
+    let latest_event = verbose::wrap!(
+        || super::github::latest_public_event(&owner.login),
+        "latest public event of `{}` using GitHub API",
+        owner.login
+    )?;
+    let activity = latest_update.max(latest_event);
+    ACTIVITY_CACHE.with_borrow_mut(|activity_cache| {
+        activity_cache.insert(owner.login.clone(), activity);
+    });
+    Ok(activity)
+}
+
+/// Returns the number of days between `now` and the most recent of `activities`, if that is at
+/// least `threshold` days
+///
+/// An owner with no known activity has been inactive for as long as can be determined. But if
+/// there are no owners at all, nothing can be concluded.
+pub(crate) fn inactive_days_from(
+    activities: &[Option<SystemTime>],
+    now: SystemTime,
+    threshold: u64,
+) -> Option<u64> {
+    if activities.is_empty() {
+        return None;
+    }
+    let latest = activities.iter().flatten().max().copied();
+    let inactive_days = latest.map_or(u64::MAX, |latest| {
+        // smoelius: If the time is pinned, an activity could be newer than `now`.
+        days::from_secs(now.duration_since(latest).unwrap_or_default().as_secs())
+    });
+    (inactive_days >= threshold).then_some(inactive_days)
+}
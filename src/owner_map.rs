@@ -0,0 +1,99 @@
+//! Labels for findings, by repository owner (see `--owner-map`)
+//!
+//! An owner map is a TOML or JSON file (by extension: `.json` is JSON, anything else is TOML) that
+//! maps patterns to labels, e.g.:
+//!
+//! ```toml
+//! "github.com/tokio-rs" = "networking"
+//! "github.com/mycorp-*" = "platform"
+//! ```
+//!
+//! A pattern has the form `HOST/OWNER`, and may contain `*`, which matches any sequence of
+//! characters other than `/`. A pattern is matched, case-insensitively, against the host and owner
+//! of the canonical form of a package's repository url (see [`canonical_url`]). If several patterns
+//! match, the one with the fewest `*` is used, then the longest, then the first alphabetically.
+
+use super::canonical_url;
+use anyhow::{ensure, Context, Result};
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+pub(crate) struct OwnerMap {
+    /// Patterns and their labels, in the order in which they are tried
+    entries: Vec<(String, String)>,
+}
+
+impl OwnerMap {
+    /// Reads the owner map at `path`
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let contents = read_to_string(path)
+            .with_context(|| format!("failed to read owner map `{}`", path.display()))?;
+        Self::parse(&contents, path.extension().is_some_and(|ext| ext == "json"))
+            .with_context(|| format!("failed to parse owner map `{}`", path.display()))
+    }
+
+    pub(crate) fn parse(contents: &str, json: bool) -> Result<Self> {
+        let map = if json {
+            serde_json::from_str::<BTreeMap<String, String>>(contents)?
+        } else {
+            toml::from_str::<BTreeMap<String, String>>(contents)?
+        };
+        let mut entries = Vec::with_capacity(map.len());
+        for (pattern, label) in map {
+            ensure!(
+                pattern.split('/').count() == 2 && !pattern.split('/').any(str::is_empty),
+                "pattern `{}` is not of the form `HOST/OWNER`",
+                pattern
+            );
+            ensure!(
+                !label.is_empty(),
+                "pattern `{}` has an empty label",
+                pattern
+            );
+            entries.push((pattern.to_lowercase(), label));
+        }
+        // smoelius: `sort_by_key` is stable. So patterns that are equally specific remain in
+        // alphabetical order.
+        entries.sort_by_key(|(pattern, _)| {
+            (
+                pattern.matches('*').count(),
+                std::cmp::Reverse(pattern.len()),
+            )
+        });
+        Ok(Self { entries })
+    }
+
+    /// Returns the label of the most specific pattern that `url`'s host and owner match
+    pub(crate) fn label(&self, url: &str) -> Option<&str> {
+        let owner = owner(url)?;
+        self.entries
+            .iter()
+            .find(|(pattern, _)| glob_matches(pattern.as_bytes(), owner.as_bytes()))
+            .map(|(_, label)| label.as_str())
+    }
+}
+
+/// Returns the lowercased `HOST/OWNER` of `url`'s canonical form
+fn owner(url: &str) -> Option<String> {
+    let url = canonical_url(url).to_lowercase();
+    let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let mut components = rest.split('/');
+    let host = components.next()?;
+    let owner = components.next().filter(|owner| !owner.is_empty())?;
+    Some(format!("{host}/{owner}"))
+}
+
+/// Returns true if `text` matches `pattern`, in which `*` matches any sequence of bytes other than
+/// `/`
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => {
+            // smoelius: Try every split of `text` up to the next `/`.
+            let end = text.iter().position(|&b| b == b'/').unwrap_or(text.len());
+            (0..=end).any(|i| glob_matches(rest, &text[i..]))
+        }
+        Some((&b, rest)) => text
+            .split_first()
+            .is_some_and(|(&c, text)| b == c && glob_matches(rest, text)),
+    }
+}
@@ -1,13 +1,25 @@
 use anyhow::{ensure, Context, Result};
-use std::{fs::OpenOptions, io::Write, process::Command};
+use std::{fs::OpenOptions, io::Write};
 use tempfile::{tempdir, TempDir};
+use toml::{Table, Value};
 
 pub fn temp_package(name: &str) -> Result<TempDir> {
+    temp_package_with_features(name, None, &[], true)
+}
+
+/// Like [`temp_package`], but depends upon exactly `version` of `name` (if given) with `features`
+/// enabled, and with default features enabled only if `default_features` is true
+pub fn temp_package_with_features(
+    name: &str,
+    version: Option<&str>,
+    features: &[String],
+    default_features: bool,
+) -> Result<TempDir> {
     let tempdir = tempdir().with_context(|| "failed to create temporary directory")?;
 
     // smoelius: Passing `--vcs=none` adds a tiny bit of speedup. This is useful when `cargo
     // unmaintained` is called repeatedly, e.g., in the `rustsec_advisories` test.
-    let status = Command::new("cargo")
+    let status = crate::cargo::command()
         .args([
             "init",
             &format!("--name={name}-temp-package"),
@@ -23,9 +35,29 @@ pub fn temp_package(name: &str) -> Result<TempDir> {
     let mut manifest = OpenOptions::new()
         .append(true)
         .open(&path_buf)
-        .with_context(|| format!("failed to open {path_buf:?}"))?;
-    writeln!(manifest, r#"{name} = "*""#)
-        .with_context(|| format!("failed to write to {path_buf:?}"))?;
+        .with_context(|| format!("failed to open `{}`", path_buf.display()))?;
+    writeln!(
+        manifest,
+        "{name} = {}",
+        dependency(version, features, default_features)
+    )
+    .with_context(|| format!("failed to write to `{}`", path_buf.display()))?;
 
     Ok(tempdir)
 }
+
+fn dependency(version: Option<&str>, features: &[String], default_features: bool) -> Value {
+    let version = version.map_or_else(|| String::from("*"), |version| format!("={version}"));
+    if features.is_empty() && default_features {
+        return Value::from(version);
+    }
+    let mut table = Table::new();
+    table.insert(String::from("version"), Value::from(version));
+    if !features.is_empty() {
+        table.insert(String::from("features"), Value::from(features.to_vec()));
+    }
+    if !default_features {
+        table.insert(String::from("default-features"), Value::from(false));
+    }
+    Value::from(table)
+}
@@ -0,0 +1,121 @@
+//! `--check-pins`: find `=`-pinned dependencies that prevent fixes from flowing
+
+use super::{
+    clock, days, find_packages, from_crates_io, latest_version, latest_versions, opts,
+    serialize::SerializablePinnedDep, versions,
+};
+use anyhow::Result;
+use cargo_metadata::{
+    semver::{Op, Version, VersionReq},
+    Dependency, Metadata, Package,
+};
+use std::time::SystemTime;
+
+pub(crate) struct PinnedDep<'a> {
+    pub pkg: &'a Package,
+    pub dep: &'a Dependency,
+    pub version_pinned: &'a Version,
+    pub version_latest: Version,
+    pub age: u64, // seconds
+    pub yanked: bool,
+}
+
+/// Returns the dependencies of every package in `metadata` whose requirements are `=` pins to a
+/// yanked version, or to a version that is older than `max_age` and not the latest
+pub(crate) fn pinned_deps(metadata: &Metadata) -> Result<Vec<PinnedDep<'_>>> {
+    let edges = metadata
+        .packages
+        .iter()
+        .flat_map(|pkg| pkg.dependencies.iter().map(move |dep| (pkg, dep)))
+        .filter(|(_, dep)| is_pin(dep))
+        .collect::<Vec<_>>();
+
+    // smoelius: Look up all of the latest versions at once, so that the index is locked only once.
+    latest_versions(edges.iter().map(|(_, dep)| dep.name.as_str()))?;
+
+    let mut pinned_deps = Vec::new();
+    for (pkg, dep) in edges {
+        let Some(dep_pkg) = find_packages(metadata, dep.into()).next() else {
+            continue;
+        };
+        // smoelius: A pin to, e.g., a git dependency cannot be checked against crates.io.
+        if !from_crates_io(dep_pkg) {
+            continue;
+        }
+        let Ok(version_latest) = latest_version(&dep.name) else {
+            continue;
+        };
+        let versions = match versions(&dep.name) {
+            Ok(versions) => versions,
+            Err(error) => {
+                crate::warn!(
+                    "failed to fetch versions of `{}`; cannot check `{}`'s pin: {}",
+                    dep.name,
+                    pkg.name,
+                    error
+                );
+                continue;
+            }
+        };
+        let Some(version) = versions
+            .iter()
+            .find(|version| Version::parse(&version.num).ok().as_ref() == Some(&dep_pkg.version))
+        else {
+            continue;
+        };
+        let age = age(clock::now(), version.created_at);
+        let old = days::exceeds(age, opts::get().max_age) && dep_pkg.version < version_latest;
+        if version.yanked || old {
+            pinned_deps.push(PinnedDep {
+                pkg,
+                dep,
+                version_pinned: &dep_pkg.version,
+                version_latest,
+                age,
+                yanked: version.yanked,
+            });
+        }
+    }
+
+    pinned_deps
+        .sort_by(|lhs, rhs| (&lhs.pkg.name, &lhs.dep.name).cmp(&(&rhs.pkg.name, &rhs.dep.name)));
+    pinned_deps.dedup_by(|lhs, rhs| lhs.pkg.id == rhs.pkg.id && lhs.dep.name == rhs.dep.name);
+
+    Ok(pinned_deps)
+}
+
+pub(crate) fn display_pinned_deps(pinned_deps: &[SerializablePinnedDep]) {
+    if pinned_deps.is_empty() {
+        return;
+    }
+    println!("\nPinned dependencies:");
+    for pinned_dep in pinned_deps {
+        println!("    {}", pinned_dep.describe());
+    }
+}
+
+/// Returns how long before `now` a version was published, in seconds
+///
+/// A version published after `now` (e.g., because the time is pinned, or because of clock skew) is
+/// treated as published at `now`, rather than aborting the scan.
+pub(crate) fn age(now: SystemTime, created_at: SystemTime) -> u64 {
+    now.duration_since(created_at).unwrap_or_default().as_secs()
+}
+
+fn is_pin(dep: &Dependency) -> bool {
+    // smoelius: Don't check dependencies in private registries or specified by path.
+    if dep.registry.is_some() || dep.path.is_some() {
+        return false;
+    }
+    is_exact(&dep.req)
+}
+
+/// Returns true if `req` matches exactly one version, e.g., `=1.2.3`
+pub(crate) fn is_exact(req: &VersionReq) -> bool {
+    matches!(
+        req.comparators.as_slice(),
+        [comparator] if comparator.op == Op::Exact
+            && comparator.minor.is_some()
+            && comparator.patch.is_some()
+    )
+}
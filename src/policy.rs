@@ -0,0 +1,177 @@
+//! `--policy`: named combinations of `--max-age`, `--outdated-dep-grace`, and `--deny`
+//!
+//! A preset supplies a value for each member not given explicitly on the command line. The
+//! resolved policy is shown at the top of text output, and is recorded in JSON metadata, so that a
+//! report states the policy it was produced under.
+
+use super::{
+    serialize::{SerializableRepoStatus, SerializableUnmaintainedPkg},
+    Kind, Opts,
+};
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// --max-age=365, --outdated-dep-grace=365, and every problem sets the exit status
+    #[default]
+    Default,
+    /// --max-age=180, --outdated-dep-grace=90, and every problem sets the exit status
+    Strict,
+    /// --max-age=365, --outdated-dep-grace=365, and --deny=archived,nonexistent,stale
+    Rustsec,
+    /// --max-age=730, --outdated-dep-grace=730, and --deny=archived,nonexistent
+    Relaxed,
+}
+
+struct Members {
+    max_age: u64,
+    /// `None` means the same as `max_age`
+    outdated_dep_grace: Option<u64>,
+    /// Empty means any problem
+    deny: &'static [Kind],
+}
+
+impl Preset {
+    fn members(self) -> Members {
+        match self {
+            Self::Default => Members {
+                max_age: 365,
+                outdated_dep_grace: None,
+                deny: &[],
+            },
+            Self::Strict => Members {
+                max_age: 180,
+                outdated_dep_grace: Some(90),
+                deny: &[],
+            },
+            // smoelius: RustSec's unmaintained advisories concern packages that are abandoned, not
+            // packages whose dependencies are out of date.
+            Self::Rustsec => Members {
+                max_age: 365,
+                outdated_dep_grace: Some(365),
+                deny: &[Kind::Archived, Kind::Nonexistent, Kind::Stale],
+            },
+            Self::Relaxed => Members {
+                max_age: 730,
+                outdated_dep_grace: Some(730),
+                deny: &[Kind::Archived, Kind::Nonexistent],
+            },
+        }
+    }
+}
+
+/// A fully resolved policy
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Policy {
+    pub preset: Preset,
+    pub max_age: u64,
+    pub outdated_dep_grace: u64,
+    /// The kinds of problems that set the exit status; empty means any problem does
+    pub deny: Vec<Kind>,
+    /// Packages whose repositories match the built-in allowlist are not reported solely for
+    /// staleness
+    ///
+    /// Reports produced before the built-in allowlist existed lack this member, which is read as
+    /// false.
+    #[serde(default)]
+    pub builtin_allowlist: bool,
+}
+
+/// Fills in the members of `opts`'s preset that `matches` shows were not given explicitly
+///
+/// `matches` should be the matches for the `unmaintained` subcommand.
+pub(crate) fn apply(opts: &mut Opts, matches: &ArgMatches) {
+    let members = opts.policy.unwrap_or_default().members();
+    if matches.value_source("max_age") != Some(ValueSource::CommandLine) {
+        opts.max_age = members.max_age;
+    }
+    if opts.outdated_dep_grace.is_none() {
+        opts.outdated_dep_grace = members.outdated_dep_grace;
+    }
+    if opts.deny.is_empty() {
+        opts.deny = members.deny.to_vec();
+    }
+}
+
+impl Policy {
+    pub(crate) fn new(opts: &Opts) -> Self {
+        Self {
+            preset: opts.policy.unwrap_or_default(),
+            max_age: opts.max_age,
+            outdated_dep_grace: opts.outdated_dep_grace.unwrap_or(opts.max_age),
+            deny: opts.deny.clone(),
+            builtin_allowlist: !opts.no_builtin_allowlist,
+        }
+    }
+
+    /// Returns the command-line options that produce this policy without a preset
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![
+            format!("--max-age={}", self.max_age),
+            format!("--outdated-dep-grace={}", self.outdated_dep_grace),
+        ];
+        if !self.deny.is_empty() {
+            let kinds = self
+                .deny
+                .iter()
+                .filter_map(ValueEnum::to_possible_value)
+                .map(|value| value.get_name().to_owned())
+                .collect::<Vec<_>>();
+            args.push(format!("--deny={}", kinds.join(",")));
+        }
+        if !self.builtin_allowlist {
+            args.push(String::from("--no-builtin-allowlist"));
+        }
+        args
+    }
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let preset = self
+            .preset
+            .to_possible_value()
+            .map(|value| value.get_name().to_owned())
+            .unwrap_or_default();
+        write!(f, "Policy: {preset} ({})", self.args().join(" "))?;
+        if self.builtin_allowlist {
+            write!(f, " with built-in allowlist")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if `unmaintained_pkg` has a problem of a kind in `deny`, or if `deny` is empty
+pub(crate) fn is_denied(deny: &[Kind], unmaintained_pkg: &SerializableUnmaintainedPkg) -> bool {
+    deny.is_empty() || kinds(unmaintained_pkg).any(|kind| deny.contains(&kind))
+}
+
+/// Returns the kinds of problems `unmaintained_pkg` has
+pub(crate) fn kinds(unmaintained_pkg: &SerializableUnmaintainedPkg) -> impl Iterator<Item = Kind> {
+    [
+        matches!(
+            unmaintained_pkg.repo_status,
+            SerializableRepoStatus::Archived
+        )
+        .then_some(Kind::Archived),
+        // smoelius: A package absent from the index is as gone as one whose repository does not
+        // exist.
+        matches!(
+            unmaintained_pkg.repo_status,
+            SerializableRepoStatus::Nonexistent | SerializableRepoStatus::AbsentFromIndex
+        )
+        .then_some(Kind::Nonexistent),
+        (!unmaintained_pkg.outdated_deps.is_empty()).then_some(Kind::OutdatedDeps),
+        // smoelius: A package that is no longer released is stale, even if its repository is not.
+        matches!(
+            unmaintained_pkg.repo_status,
+            SerializableRepoStatus::Age(_) | SerializableRepoStatus::Unreleased(_)
+        )
+        .then_some(Kind::Stale),
+    ]
+    .into_iter()
+    .flatten()
+}
@@ -0,0 +1,69 @@
+//! A cheap first pass that determines which packages could be unmaintained (see `--prefilter`)
+//!
+//! Cloning a repository, checking membership in it, and timestamping it dominate the time a scan
+//! takes. But most packages turn out to be maintained, and a package whose dependencies are all
+//! current is reported only if its repository is archived, nonexistent, or does not contain it.
+//! With `--prefilter`, a scan proceeds in two phases. The first runs only checks that require
+//! neither cloning nor timestamping: whether the package is absent from the index, whether it has
+//! outdated dependencies, and its repository's general status (archival status with a GitHub
+//! token, existence otherwise). The second phase checks the remaining packages, i.e., the
+//! candidates, as a scan without `--prefilter` would.
+//!
+//! The cost is that a package with no outdated dependencies is not checked for being unassociated
+//! with or uncloneable from its repository.
+//!
+//! The pre-filter is not applied with `--fast`, `--only`, `--owner-activity`, or `--publish-lag`,
+//! under which a package's repository must be examined regardless of its dependencies.
+
+use super::{
+    absent_from_index, general_status,
+    metrics::{self, Count, Phase},
+    opts, outdated_deps, owner_url_substitute, url,
+};
+use cargo_metadata::{Metadata, Package};
+
+/// Returns true if the pre-filter should be applied
+pub(crate) fn enabled() -> bool {
+    let opts = opts::get();
+    opts.prefilter
+        && !opts.fast
+        && opts.only.is_none()
+        && !opts.owner_activity
+        && opts.publish_lag.is_none()
+}
+
+/// Returns those of `packages` that could be unmaintained
+pub(crate) fn candidates<'a>(metadata: &'a Metadata, packages: &[&'a Package]) -> Vec<&'a Package> {
+    let candidates = packages
+        .iter()
+        .copied()
+        .filter(|pkg| metrics::time(Phase::Prefilter, || is_candidate(metadata, pkg)))
+        .collect::<Vec<_>>();
+    metrics::count(
+        Count::PrefilteredPackages,
+        (packages.len() - candidates.len()) as u64,
+    );
+    candidates
+}
+
+fn is_candidate(metadata: &Metadata, pkg: &Package) -> bool {
+    // smoelius: A package whose checks fail here is left to the second phase, where the failure is
+    // reported.
+    if absent_from_index(pkg) {
+        return true;
+    }
+    let pkg = owner_url_substitute(pkg).map_or(pkg, |(substitute, _)| substitute);
+    if url::is_assumed_fresh(pkg) {
+        return false;
+    }
+    if !outdated_deps(metadata, pkg).is_ok_and(|outdated_deps| outdated_deps.is_empty()) {
+        return true;
+    }
+    let Some(url_string) = url::repository(pkg) else {
+        return false;
+    };
+    if !url_string.starts_with("https://") {
+        return false;
+    }
+    !general_status(&pkg.name, url_string.into()).is_ok_and(|repo_status| repo_status.is_success())
+}
@@ -0,0 +1,38 @@
+//! The `prime` subcommand: populate the caches without producing a report
+//!
+//! Priming scans the workspace exactly as `cargo unmaintained` would, so that the two cannot
+//! diverge in what they fetch, but discards the results. A subsequent scan with the same options
+//! can then be answered from the on-disk cache. Packages that cannot be checked are warned about
+//! and do not affect the exit status; only errors that would abort a scan do.
+
+use super::{containment, metadata, metrics, metrics::Count, scan, Scan};
+use anyhow::Result;
+
+/// `prime` subcommand: populate the caches for the workspace's packages
+#[derive(Debug, clap::Parser)]
+pub(crate) struct Opts {}
+
+pub(crate) fn prime() -> Result<()> {
+    let metadata = metadata()?;
+
+    let Scan {
+        package_errors,
+        n_scanned,
+        ..
+    } = scan(&metadata)?;
+
+    for containment::PackageError { pkg, error } in &package_errors {
+        crate::warn!("failed to prime `{}`: {}", pkg.name, error);
+    }
+
+    println!(
+        "primed {} package{}: {} clones refreshed, {} new, {} version fetches",
+        n_scanned,
+        if n_scanned == 1 { "" } else { "s" },
+        metrics::total(Count::RefreshedRepositories),
+        metrics::total(Count::ClonedRepositories),
+        metrics::total(Count::FetchedVersions)
+    );
+
+    Ok(())
+}
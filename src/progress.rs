@@ -1,5 +1,8 @@
 use crate::flush::Flush;
 use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{io::Write, path::PathBuf};
+use tempfile::NamedTempFile;
 
 pub struct Progress {
     n: usize,
@@ -57,12 +60,8 @@ impl Progress {
 
     fn draw(&mut self, msg: &str) -> Result<()> {
         let width_n = self.width_n;
-        let percent = if self.n == 0 {
-            100
-        } else {
-            self.i * 100 / self.n
-        };
-        let formatted_msg = format!("{:>width_n$}/{} ({percent}%) {msg}", self.i, self.n,);
+        let percent = (self.i * 100).checked_div(self.n).unwrap_or(100);
+        let formatted_msg = format!("{:>width_n$}/{} ({percent}%) {msg}", self.i, self.n);
         let width_to_overwrite = self.width_prev.saturating_sub(formatted_msg.len());
         eprint!("{formatted_msg}{:width_to_overwrite$}\r", "");
         <_ as Flush>::flush(&mut std::io::stderr()).with_context(|| "failed to flush stderr")?;
@@ -71,3 +70,96 @@ impl Progress {
         Ok(())
     }
 }
+
+/// Machine-readable progress, for use by, e.g., editor integrations
+///
+/// The file is rewritten before each package is scanned, and once more when the scan finishes.
+/// Each rewrite goes to a temporary file in the same directory, which is then renamed over the
+/// original. So a reader never sees a partially written document.
+pub struct ProgressFile {
+    path: PathBuf,
+    document: Document,
+}
+
+#[derive(Serialize)]
+struct Document {
+    done: usize,
+    total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<String>,
+    flagged_so_far: Vec<String>,
+    complete: bool,
+}
+
+impl ProgressFile {
+    pub fn new(path: PathBuf, total: usize) -> Result<Self> {
+        let progress_file = Self {
+            path,
+            document: Document {
+                done: 0,
+                total,
+                current: None,
+                flagged_so_far: Vec::new(),
+                complete: false,
+            },
+        };
+        progress_file.write()?;
+        Ok(progress_file)
+    }
+
+    /// Records that `name` is about to be scanned, and that the previous package (if any) was
+    pub fn advance(&mut self, name: &str) -> Result<()> {
+        if self.document.current.is_some() {
+            self.document.done += 1;
+        }
+        self.document.current = Some(name.to_owned());
+        self.write()
+    }
+
+    /// Records that `name` was found to be unmaintained
+    ///
+    /// The file is not rewritten until the next call to [`ProgressFile::advance`] or
+    /// [`ProgressFile::finish`].
+    pub fn flag(&mut self, name: &str) {
+        self.document.flagged_so_far.push(name.to_owned());
+    }
+
+    /// Undoes one call to [`ProgressFile::flag`], e.g., because the package recovered on retry
+    pub fn unflag(&mut self, name: &str) {
+        if let Some(index) = self
+            .document
+            .flagged_so_far
+            .iter()
+            .rposition(|flagged| flagged == name)
+        {
+            self.document.flagged_so_far.remove(index);
+        }
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        // smoelius: As with `Progress::finish`, if --fail-fast was passed, `finish` may be called
+        // before all packages have been scanned.
+        if self.document.current.take().is_some() {
+            self.document.done += 1;
+        }
+        self.document.complete = true;
+        self.write()
+    }
+
+    fn write(&self) -> Result<()> {
+        let parent = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| std::path::Path::new("."));
+        let mut tempfile = NamedTempFile::new_in(parent).with_context(|| {
+            format!("failed to create temporary file in `{}`", parent.display())
+        })?;
+        serde_json::to_writer(&mut tempfile, &self.document)?;
+        writeln!(tempfile)?;
+        tempfile
+            .persist(&self.path)
+            .with_context(|| format!("failed to write `{}`", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Output in Prometheus's text exposition format, written when `--metrics-file` is passed
+//!
+//! All of the metrics are gauges, as each file describes a single scan.
+
+use super::serialize::{SerializableRepoStatus, SerializableUnmaintainedPkg};
+use anyhow::{Context, Result};
+use std::{fmt::Write, fs::write, path::Path, time::Duration};
+
+/// The values of the `status` label, in the order in which they are written
+const STATUSES: &[&str] = &[
+    "unchecked",
+    "uncloneable",
+    "unnamed",
+    "superseded",
+    "stale",
+    "unassociated",
+    "nonexistent",
+    "archived",
+];
+
+/// Summary of a scan
+pub(crate) struct Summary<'a> {
+    pub scanned: usize,
+    pub unmaintained_pkgs: &'a [SerializableUnmaintainedPkg],
+    pub duration: Duration,
+}
+
+pub(crate) fn write_metrics_file(path: &Path, summary: &Summary, per_package: bool) -> Result<()> {
+    let contents = format(summary, per_package)?;
+    write(path, contents).with_context(|| format!("failed to write `{}`", path.display()))
+}
+
+pub(crate) fn format(summary: &Summary, per_package: bool) -> Result<String> {
+    let mut s = String::new();
+
+    writeln!(
+        s,
+        "# HELP cargo_unmaintained_scanned_total Number of packages scanned"
+    )?;
+    writeln!(s, "# TYPE cargo_unmaintained_scanned_total gauge")?;
+    writeln!(s, "cargo_unmaintained_scanned_total {}", summary.scanned)?;
+
+    writeln!(
+        s,
+        "# HELP cargo_unmaintained_flagged_total Number of unmaintained packages found, by \
+         repository status"
+    )?;
+    writeln!(s, "# TYPE cargo_unmaintained_flagged_total gauge")?;
+    // smoelius: Write every status, even those with no packages, so that each series is present in
+    // every scrape.
+    for &status in STATUSES {
+        let n = summary
+            .unmaintained_pkgs
+            .iter()
+            .filter(|unmaintained_pkg| status_label(unmaintained_pkg.repo_status) == status)
+            .count();
+        writeln!(
+            s,
+            "cargo_unmaintained_flagged_total{{status=\"{status}\"}} {n}"
+        )?;
+    }
+
+    writeln!(
+        s,
+        "# HELP cargo_unmaintained_scan_duration_seconds Time taken to scan the packages"
+    )?;
+    writeln!(s, "# TYPE cargo_unmaintained_scan_duration_seconds gauge")?;
+    writeln!(
+        s,
+        "cargo_unmaintained_scan_duration_seconds {}",
+        summary.duration.as_secs_f64()
+    )?;
+
+    // smoelius: One series per package can make for many series. Hence, these are opt-in.
+    if per_package {
+        writeln!(
+            s,
+            "# HELP cargo_unmaintained_package_info Unmaintained package, with its repository \
+             status and repository"
+        )?;
+        writeln!(s, "# TYPE cargo_unmaintained_package_info gauge")?;
+        for unmaintained_pkg in summary.unmaintained_pkgs {
+            writeln!(
+                s,
+                "cargo_unmaintained_package_info{{name=\"{}\",version=\"{}\",status=\"{}\",\
+                 repository=\"{}\"}} 1",
+                escape_label_value(&unmaintained_pkg.name),
+                escape_label_value(&unmaintained_pkg.version.to_string()),
+                status_label(unmaintained_pkg.repo_status),
+                escape_label_value(unmaintained_pkg.repository.as_deref().unwrap_or_default()),
+            )?;
+        }
+    }
+
+    Ok(s)
+}
+
+fn status_label(repo_status: SerializableRepoStatus) -> &'static str {
+    match repo_status {
+        SerializableRepoStatus::Unchecked => "unchecked",
+        SerializableRepoStatus::Uncloneable => "uncloneable",
+        SerializableRepoStatus::TooLarge => "too_large",
+        SerializableRepoStatus::Unnamed => "unnamed",
+        SerializableRepoStatus::Superseded => "superseded",
+        SerializableRepoStatus::Age(_) => "stale",
+        SerializableRepoStatus::Unreleased(_) => "unreleased",
+        SerializableRepoStatus::Unassociated => "unassociated",
+        SerializableRepoStatus::Nonexistent => "nonexistent",
+        SerializableRepoStatus::Archived => "archived",
+        SerializableRepoStatus::AbsentFromIndex => "absent_from_index",
+    }
+}
+
+/// Escapes backslashes, double quotes, and line feeds, as the exposition format requires
+pub(crate) fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,45 @@
+//! How repository statuses were obtained
+//!
+//! A `Nonexistent` or `Uncloneable` status is usually observed directly, e.g., from a 404 or from
+//! git's refusal to clone. But such a status can also be derived from a network failure, e.g., a
+//! timed out HTTP request or a clone whose host could not be reached. The latter may be spurious.
+//! So the urls whose statuses were derived from network failures are recorded, and packages whose
+//! findings depend upon them are re-checked before being reported (see `--no-verify`).
+
+use super::{RepoStatus, Url};
+use std::{cell::RefCell, collections::HashSet};
+
+thread_local! {
+    // smoelius: Keyed by canonical url, like the url-keyed caches in lib.rs.
+    static NETWORK_DERIVED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Provenance {
+    /// The status was observed directly, or is not a failure
+    Observed,
+    /// The status is a failure derived from a network failure
+    NetworkFailure,
+}
+
+/// Records that `url`'s status was derived from a network failure
+pub(crate) fn record_network_failure(url: Url) {
+    NETWORK_DERIVED.with_borrow_mut(|network_derived| network_derived.insert(url.canonical()));
+}
+
+/// Forgets that `url`'s status was derived from a network failure, e.g., before re-checking it
+pub(crate) fn forget(url: Url) {
+    NETWORK_DERIVED.with_borrow_mut(|network_derived| network_derived.remove(&url.canonical()));
+}
+
+pub(crate) fn of<T>(repo_status: &RepoStatus<T>) -> Provenance {
+    match repo_status {
+        RepoStatus::Nonexistent(url) | RepoStatus::Uncloneable(url)
+            if NETWORK_DERIVED
+                .with_borrow(|network_derived| network_derived.contains(&url.canonical())) =>
+        {
+            Provenance::NetworkFailure
+        }
+        _ => Provenance::Observed,
+    }
+}
@@ -0,0 +1,450 @@
+//! `render` subcommand: present a previously produced JSON report without rescanning
+//!
+//! Only presentation-level policy is applied. Fields that cannot be recomputed without a scan
+//! (e.g., ages) are used as recorded.
+
+use super::{
+    allowlist, display_n_hidden, display_report, opts, policy,
+    serialize::{
+        check_schema_version, Report, SerializablePackageError, SerializablePinnedDep,
+        SerializablePublishAnomaly, SerializableRepoStatus, SerializableUnmaintainedPkg,
+        SerializableWarning,
+    },
+};
+use anyhow::{Context, Result};
+use clap::{crate_version, Parser, ValueEnum};
+use serde_json::json;
+use std::{fs::read_to_string, io::Write, path::PathBuf};
+use termcolor::NoColor;
+
+#[derive(Debug, Parser)]
+#[remain::sorted]
+pub(crate) struct Opts {
+    #[clap(
+        long,
+        help = "Output format; github produces workflow commands that GitHub Actions displays as \
+                annotations; compact produces one tab-separated line per package",
+        default_value = "text",
+        value_name = "FORMAT"
+    )]
+    format: Format,
+
+    #[clap(
+        long,
+        help = "Report produced by --json, with or without --json-metadata",
+        value_name = "PATH"
+    )]
+    input: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Text,
+    Github,
+    Sarif,
+    Compact,
+}
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+const INFORMATION_URI: &str = "https://github.com/trailofbits/cargo-unmaintained";
+
+/// Location that SARIF results are attributed to
+///
+/// Findings concern packages, not source lines. But some SARIF consumers (e.g., GitHub code
+/// scanning) require that every result have a location.
+const LOCKFILE: &str = "Cargo.lock";
+
+/// Returns true if the exit status should be set, i.e., if a denied problem is shown
+pub(crate) fn render(render_opts: &Opts) -> Result<bool> {
+    let Contents {
+        mut unmaintained_pkgs,
+        mut pinned_deps,
+        publish_anomalies,
+        package_errors,
+        warnings,
+    } = read_report(render_opts)?;
+
+    // smoelius: A package whose repository was updated within `max_age` days is not unmaintained
+    // (condition 3b). The converse does not hold: a report cannot reveal packages that a scan with
+    // a smaller `max_age` would have flagged. So `--max-age` can only remove packages.
+    let max_age = opts::get().max_age;
+    unmaintained_pkgs.retain(|unmaintained_pkg| !is_younger_than(unmaintained_pkg, max_age));
+    pinned_deps.retain(|pinned_dep| pinned_dep.yanked || pinned_dep.age >= max_age);
+
+    // smoelius: Whether a package was reported solely for staleness is recorded. So the built-in
+    // allowlist can be applied. A workspace's allowlist cannot, as the workspace's metadata is not.
+    unmaintained_pkgs.retain(|unmaintained_pkg| !is_allowlisted(unmaintained_pkg));
+
+    // smoelius: As with a scan, `--min-age-to-report` does not affect the exit status, but packages
+    // that could not be checked do.
+    let found = !package_errors.is_empty()
+        || unmaintained_pkgs
+            .iter()
+            .any(|unmaintained_pkg| policy::is_denied(&opts::get().deny, unmaintained_pkg));
+
+    let n_unfiltered = unmaintained_pkgs.len();
+    if let Some(min_age_to_report) = opts::get().min_age_to_report {
+        unmaintained_pkgs
+            .retain(|unmaintained_pkg| !is_younger_than(unmaintained_pkg, min_age_to_report));
+    }
+    let n_hidden = n_unfiltered - unmaintained_pkgs.len();
+
+    match render_opts.format {
+        Format::Text => display_report(
+            unmaintained_pkgs,
+            &pinned_deps,
+            &publish_anomalies,
+            &package_errors,
+            n_hidden,
+        )?,
+        Format::Github => display_github(&unmaintained_pkgs, &pinned_deps, &package_errors)?,
+        Format::Sarif => {
+            display_sarif(&unmaintained_pkgs, &pinned_deps, &package_errors, &warnings)?;
+        }
+        Format::Compact => display_compact(&unmaintained_pkgs),
+    }
+
+    display_n_hidden(n_hidden);
+
+    Ok(found && !opts::get().no_exit_code)
+}
+
+/// The parts of a report that `render` presents
+struct Contents {
+    unmaintained_pkgs: Vec<SerializableUnmaintainedPkg>,
+    pinned_deps: Vec<SerializablePinnedDep>,
+    publish_anomalies: Vec<SerializablePublishAnomaly>,
+    package_errors: Vec<SerializablePackageError>,
+    warnings: Vec<SerializableWarning>,
+}
+
+fn read_report(render_opts: &Opts) -> Result<Contents> {
+    let path = &render_opts.input;
+    let contents =
+        read_to_string(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+    let value = serde_json::from_str::<serde_json::Value>(&contents)
+        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+    // smoelius: Before schema version 3, a report produced without --json-metadata was just an
+    // array of packages.
+    if value.is_array() {
+        let unmaintained_pkgs = serde_json::from_value(value)
+            .with_context(|| format!("failed to parse packages in `{}`", path.display()))?;
+        Ok(Contents {
+            unmaintained_pkgs,
+            pinned_deps: Vec::new(),
+            publish_anomalies: Vec::new(),
+            package_errors: Vec::new(),
+            warnings: Vec::new(),
+        })
+    } else {
+        // smoelius: Check the schema version first, as a report with a newer schema may not parse.
+        check_schema_version(&value)?;
+        let Report {
+            schema_version: _,
+            metadata: _,
+            packages,
+            allowlisted: _,
+            pinned_deps,
+            publish_anomalies,
+            errors,
+            warnings,
+        } = serde_json::from_value(value)
+            .with_context(|| format!("failed to parse report `{}`", path.display()))?;
+        Ok(Contents {
+            unmaintained_pkgs: packages,
+            pinned_deps,
+            publish_anomalies,
+            package_errors: errors,
+            warnings,
+        })
+    }
+}
+
+fn is_younger_than(unmaintained_pkg: &SerializableUnmaintainedPkg, days: u64) -> bool {
+    matches!(unmaintained_pkg.repo_status, SerializableRepoStatus::Age(age) if age < days)
+}
+
+fn is_allowlisted(unmaintained_pkg: &SerializableUnmaintainedPkg) -> bool {
+    matches!(unmaintained_pkg.repo_status, SerializableRepoStatus::Age(_))
+        && unmaintained_pkg
+            .repository
+            .as_deref()
+            .is_some_and(|repository| allowlist::matching_pattern(&[], repository).is_some())
+}
+
+fn level(unmaintained_pkg: &SerializableUnmaintainedPkg) -> &'static str {
+    if policy::is_denied(&opts::get().deny, unmaintained_pkg) {
+        "error"
+    } else {
+        "warning"
+    }
+}
+
+/// Returns the text output for `unmaintained_pkg`, without colors, paths, or fork suggestions
+fn describe(unmaintained_pkg: &SerializableUnmaintainedPkg) -> Result<String> {
+    let mut buffer = NoColor::new(Vec::new());
+    write!(
+        buffer,
+        "{}@{} (",
+        unmaintained_pkg.name, unmaintained_pkg.version
+    )?;
+    unmaintained_pkg.repo_age()?.write(&mut buffer)?;
+    write!(buffer, ")")?;
+    if let Some(owner_label) = &unmaintained_pkg.owner_label {
+        write!(buffer, " [team:{owner_label}]")?;
+    }
+    if let Some(original_repository) = &unmaintained_pkg.original_repository {
+        write!(
+            buffer,
+            "\n    repository named in manifest: {original_repository}"
+        )?;
+    }
+    if unmaintained_pkg.network_failure {
+        write!(
+            buffer,
+            "\n    status derived from a network failure that persisted on retry"
+        )?;
+    }
+    if unmaintained_pkg.assumed_fresh {
+        write!(
+            buffer,
+            "\n    repository freshness assumed via --assume-fresh"
+        )?;
+    }
+    if let Some(membership_tag) = &unmaintained_pkg.membership_tag {
+        write!(
+            buffer,
+            "\n    package found at tag {membership_tag}, not on default branch"
+        )?;
+    }
+    if unmaintained_pkg.membership_conflict {
+        write!(
+            buffer,
+            "\n    other packages from the same repository were found in it"
+        )?;
+    }
+    if let Some(owners_inactive_days) = unmaintained_pkg.owners_inactive_days {
+        write!(
+            buffer,
+            "\n    owners inactive for {owners_inactive_days} days"
+        )?;
+    }
+    if unmaintained_pkg.tarball_fallback {
+        write!(
+            buffer,
+            "\n    repository checked via tarball; branches, tags, and history not checked"
+        )?;
+    }
+    if let Some(latest_version_repository) = &unmaintained_pkg.latest_version_repository {
+        write!(
+            buffer,
+            "\n    latest version {} (",
+            latest_version_repository.version
+        )?;
+        latest_version_repository
+            .repo_age(&unmaintained_pkg.name)?
+            .write(&mut buffer)?;
+        write!(buffer, ")")?;
+    }
+    if let Some(used_version_repository) = &unmaintained_pkg.used_version_repository {
+        write!(buffer, "\n    used version's repository is gone (")?;
+        used_version_repository
+            .repo_age(&unmaintained_pkg.name)?
+            .write(&mut buffer)?;
+        write!(buffer, "); newer versions are developed at ")?;
+        unmaintained_pkg.repo_age()?.write(&mut buffer)?;
+    }
+    if unmaintained_pkg.outdated_deps_are_siblings() {
+        write!(
+            buffer,
+            "\n    all outdated dependencies are siblings in the same repository"
+        )?;
+    } else {
+        for outdated_dep in &unmaintained_pkg.outdated_deps {
+            write!(buffer, "\n    {}", outdated_dep.describe())?;
+        }
+    }
+    if let Some(latest_version_outdated_deps) =
+        unmaintained_pkg.describe_latest_version_outdated_deps()
+    {
+        write!(buffer, "\n    {latest_version_outdated_deps}")?;
+    }
+    if let Some(download_trend) = unmaintained_pkg.download_trend {
+        write!(buffer, "\n    {download_trend}")?;
+    }
+    if let Some(blast_radius) = unmaintained_pkg.describe_blast_radius() {
+        write!(buffer, "\n    {blast_radius}")?;
+    }
+    for path in unmaintained_pkg.describe_paths() {
+        write!(buffer, "\n    {path}")?;
+    }
+    if let Some(partial_scan) = unmaintained_pkg.describe_partial_scan() {
+        write!(buffer, "\n    {partial_scan}")?;
+    }
+    Ok(String::from_utf8(buffer.into_inner())?)
+}
+
+/// Prints one line per package, with the following tab-separated columns:
+///
+/// ```text
+/// NAME VERSION STATUS AGE_DAYS REPO_URL DIRECT|TRANSITIVE
+/// ```
+///
+/// `STATUS` is the name of a [`SerializableRepoStatus`] variant. Columns with no value (e.g.,
+/// `AGE_DAYS` for an archived repository) are `-`.
+fn display_compact(unmaintained_pkgs: &[SerializableUnmaintainedPkg]) {
+    for unmaintained_pkg in unmaintained_pkgs {
+        println!("{}", compact_line(unmaintained_pkg));
+    }
+}
+
+fn compact_line(unmaintained_pkg: &SerializableUnmaintainedPkg) -> String {
+    let age = match unmaintained_pkg.repo_status {
+        SerializableRepoStatus::Age(age) => age.to_string(),
+        _ => String::from("-"),
+    };
+    [
+        unmaintained_pkg.name.as_str(),
+        &unmaintained_pkg.version.to_string(),
+        unmaintained_pkg.repo_status.name(),
+        &age,
+        unmaintained_pkg.repository.as_deref().unwrap_or("-"),
+        if unmaintained_pkg.direct {
+            "DIRECT"
+        } else {
+            "TRANSITIVE"
+        },
+    ]
+    .join("\t")
+}
+
+fn display_github(
+    unmaintained_pkgs: &[SerializableUnmaintainedPkg],
+    pinned_deps: &[SerializablePinnedDep],
+    package_errors: &[SerializablePackageError],
+) -> Result<()> {
+    for unmaintained_pkg in unmaintained_pkgs {
+        println!(
+            "::{} title={}::{}",
+            level(unmaintained_pkg),
+            escape_property(&format!("{} is unmaintained", unmaintained_pkg.name)),
+            escape_data(&describe(unmaintained_pkg)?)
+        );
+    }
+    for pinned_dep in pinned_deps {
+        println!(
+            "::warning title={}::{}",
+            escape_property(&format!("{} is pinned", pinned_dep.name)),
+            escape_data(&pinned_dep.describe())
+        );
+    }
+    for package_error in package_errors {
+        println!(
+            "::error title={}::{}",
+            escape_property(&format!("{} could not be checked", package_error.name)),
+            escape_data(&package_error.error)
+        );
+    }
+    Ok(())
+}
+
+// smoelius: Escaping rules for workflow commands:
+// https://github.com/actions/toolkit/blob/main/packages/core/src/command.ts
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn display_sarif(
+    unmaintained_pkgs: &[SerializableUnmaintainedPkg],
+    pinned_deps: &[SerializablePinnedDep],
+    package_errors: &[SerializablePackageError],
+    warnings: &[SerializableWarning],
+) -> Result<()> {
+    let mut results = Vec::new();
+    for unmaintained_pkg in unmaintained_pkgs {
+        results.push(sarif_result(
+            "unmaintained",
+            level(unmaintained_pkg),
+            &describe(unmaintained_pkg)?,
+        ));
+    }
+    for pinned_dep in pinned_deps {
+        results.push(sarif_result("pinned", "warning", &pinned_dep.describe()));
+    }
+    for package_error in package_errors {
+        results.push(sarif_result(
+            "unchecked",
+            "error",
+            &format!(
+                "{}@{} could not be checked: {}",
+                package_error.name, package_error.version, package_error.error
+            ),
+        ));
+    }
+    let mut sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargo-unmaintained",
+                    "version": crate_version!(),
+                    "informationUri": INFORMATION_URI,
+                    "rules": [
+                        {
+                            "id": "unmaintained",
+                            "shortDescription": { "text": "Unmaintained package" },
+                        },
+                        {
+                            "id": "pinned",
+                            "shortDescription": {
+                                "text": "Dependency pinned to a yanked or old version",
+                            },
+                        },
+                        {
+                            "id": "unchecked",
+                            "shortDescription": { "text": "Package that could not be checked" },
+                        },
+                    ],
+                },
+            },
+            "results": results,
+        }],
+    });
+    // smoelius: Warnings concern the scan rather than any one package. So they are reported as
+    // notifications of the scan's invocation rather than as results.
+    if !warnings.is_empty() {
+        sarif["runs"][0]["invocations"] = json!([{
+            "executionSuccessful": true,
+            "toolExecutionNotifications": warnings
+                .iter()
+                .map(|warning| json!({
+                    "level": "warning",
+                    "message": { "text": warning.describe() },
+                }))
+                .collect::<Vec<_>>(),
+        }]);
+    }
+    println!("{}", serde_json::to_string_pretty(&sarif)?);
+    Ok(())
+}
+
+fn sarif_result(rule_id: &str, level: &str, text: &str) -> serde_json::Value {
+    json!({
+        "ruleId": rule_id,
+        "level": level,
+        "message": { "text": text },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": LOCKFILE },
+            },
+        }],
+    })
+}
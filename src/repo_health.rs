@@ -0,0 +1,167 @@
+//! Health of a repository, independent of any package
+//!
+//! [`repo_health`] answers "is this repository abandoned?" for a bare url (e.g., a C submodule, or
+//! a tool referenced in documentation) using the same checks and caches as a scan: the repository's
+//! general status (archival status or existence), a shallow clone, and the latest commit's
+//! timestamp. There is no package, so there is no membership check.
+
+use super::{
+    clock, clone_timestamp, clone_url, days, general_status, opts, theme, RepoStatus, Url,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+use termcolor::{StandardStream, WriteColor};
+
+/// Hidden `repo-health` subcommand: evaluate a single repository url
+#[derive(Debug, clap::Parser)]
+pub(crate) struct Opts {
+    #[clap(help = "Url of the repository to evaluate")]
+    url: String,
+}
+
+/// Options for [`repo_health`]
+#[derive(Clone, Debug)]
+pub struct RepoHealthOptions {
+    /// Age in days that the repository's latest commit must not exceed for the repository to be
+    /// considered current
+    pub max_age: u64,
+}
+
+impl Default for RepoHealthOptions {
+    fn default() -> Self {
+        Self { max_age: 365 }
+    }
+}
+
+/// The result of [`repo_health`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RepoHealth {
+    /// The url's canonical form, by which the caches are keyed
+    pub canonical_url: String,
+    pub status: RepoHealthStatus,
+    /// Seconds since the repository's latest commit, if the repository could be cloned
+    pub age: Option<u64>,
+}
+
+/// Statuses that [`repo_health`] can report
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoHealthStatus {
+    Current,
+    Uncloneable,
+    Stale,
+    Nonexistent,
+    Archived,
+}
+
+/// Evaluates the repository at `url` as a scan would evaluate a package's repository
+///
+/// The in-memory and on-disk caches are shared with scans. So a repository that a scan already
+/// cloned is not cloned again, and vice versa.
+///
+/// If the command-line options have not been initialized (i.e., if called as a library function),
+/// they are initialized to their defaults.
+pub fn repo_health(url: &str, options: &RepoHealthOptions) -> Result<RepoHealth> {
+    opts::init_default();
+    clock::init(None)?;
+
+    let url = Url::from(url);
+    let canonical_url = url.canonical();
+
+    let general_status = general_status(url.as_str(), url)?;
+    let status = match general_status {
+        RepoStatus::Archived(_) => Some(RepoHealthStatus::Archived),
+        RepoStatus::Nonexistent(_) => Some(RepoHealthStatus::Nonexistent),
+        _ => None,
+    };
+    if let Some(status) = status {
+        return Ok(RepoHealth {
+            canonical_url,
+            status,
+            age: None,
+        });
+    }
+
+    let repo_status = clone_url(url)?;
+    let Some((url, repo_dir)) = repo_status.as_success() else {
+        let status = if matches!(repo_status, RepoStatus::Nonexistent(_)) {
+            RepoHealthStatus::Nonexistent
+        } else {
+            RepoHealthStatus::Uncloneable
+        };
+        return Ok(RepoHealth {
+            canonical_url,
+            status,
+            age: None,
+        });
+    };
+
+    let timestamp = clone_timestamp(url, repo_dir)?;
+    // smoelius: If the time is pinned, the latest commit could be newer than `now`.
+    let age = clock::now()
+        .duration_since(timestamp)
+        .unwrap_or_default()
+        .as_secs();
+    let status = if days::exceeds(age, options.max_age) {
+        RepoHealthStatus::Stale
+    } else {
+        RepoHealthStatus::Current
+    };
+
+    Ok(RepoHealth {
+        canonical_url,
+        status,
+        age: Some(age),
+    })
+}
+
+/// Returns true if the repository is not current
+#[cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+pub(crate) fn run(repo_health_opts: &Opts) -> Result<bool> {
+    let health = repo_health(
+        &repo_health_opts.url,
+        &RepoHealthOptions {
+            max_age: opts::get().max_age,
+        },
+    )?;
+
+    if opts::get().json {
+        serde_json::to_writer_pretty(std::io::stdout(), &health)?;
+        println!();
+    } else {
+        let mut stdout = StandardStream::stdout(theme::color_choice());
+        let severity = match health.status {
+            RepoHealthStatus::Current => None,
+            RepoHealthStatus::Uncloneable | RepoHealthStatus::Stale => Some(0),
+            RepoHealthStatus::Nonexistent | RepoHealthStatus::Archived => Some(u8::MAX),
+        };
+        if let Some(severity) = severity {
+            write!(
+                stdout,
+                "{}",
+                theme::symbol(opts::get().theme, severity == u8::MAX)
+            )?;
+        }
+        write!(stdout, "{} (", health.canonical_url)?;
+        stdout.set_color(&theme::spec(severity))?;
+        write!(stdout, "{}", status_name(health.status))?;
+        stdout.reset()?;
+        if let Some(age) = health.age {
+            write!(stdout, ", updated {} days ago", days::from_secs(age))?;
+        }
+        writeln!(stdout, ")")?;
+    }
+
+    Ok(health.status != RepoHealthStatus::Current)
+}
+
+fn status_name(status: RepoHealthStatus) -> &'static str {
+    match status {
+        RepoHealthStatus::Current => "current",
+        RepoHealthStatus::Uncloneable => "uncloneable",
+        RepoHealthStatus::Stale => "stale",
+        RepoHealthStatus::Nonexistent => "nonexistent",
+        RepoHealthStatus::Archived => "archived",
+    }
+}
@@ -1,33 +1,72 @@
-use super::{opts, Url, SECS_PER_DAY};
+use super::{days, opts, theme, Url};
 use anyhow::Result;
-use termcolor::{Color, ColorSpec, WriteColor};
+use termcolor::{ColorSpec, WriteColor};
 
 /// Repository statuses with the variants ordered by how "bad" they are.
 ///
-/// A `RepoStatus` has a url only if it's not `Unnamed`. A `RepoStatus` has a value only if
-/// it is `Success`.
+/// A `RepoStatus` has a url only if it's not `Unchecked` or `Unnamed`. A `RepoStatus` has a value
+/// only if it is `Success`.
+///
+/// `Unchecked` means the repository was deliberately not examined, e.g., because of `--only`.
+///
+/// `Superseded` means the repository does not contain the package, but there is evidence that the
+/// package was renamed or split within the repository (see `--detect-superseded`).
+///
+/// `Unreleased` means the repository is current, but the package's newest release is older than
+/// `max_age` and trails the repository's latest commit by at least `--publish-lag` days. Its
+/// `u64` is the age of the newest release in seconds.
+///
+/// `TooLarge` means the repository's clone exceeded `--max-repo-size` and `--deny-oversize` was
+/// passed. So the clone was removed, and the repository was not examined.
+///
+/// `AbsentFromIndex` means the package is from crates.io, but is no longer in the crates.io index,
+/// e.g., because it was deleted. Its repository is not considered.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum RepoStatus<'a, T> {
+    Unchecked,
     Uncloneable(Url<'a>),
+    TooLarge(Url<'a>),
     Unnamed,
+    Superseded(Url<'a>),
     Success(Url<'a>, T),
+    Unreleased(Url<'a>, u64),
     Unassociated(Url<'a>),
     Nonexistent(Url<'a>),
     Archived(Url<'a>),
+    AbsentFromIndex,
 }
 
 impl<'a, T> RepoStatus<'a, T> {
     pub fn as_success(&self) -> Option<(Url<'a>, &T)> {
         match self {
-            Self::Uncloneable(_)
+            Self::Unchecked
+            | Self::Uncloneable(_)
+            | Self::TooLarge(_)
             | Self::Unnamed
+            | Self::Superseded(_)
+            | Self::Unreleased(..)
             | Self::Unassociated(_)
             | Self::Nonexistent(_)
-            | Self::Archived(_) => None,
+            | Self::Archived(_)
+            | Self::AbsentFromIndex => None,
             Self::Success(url, value) => Some((*url, value)),
         }
     }
 
+    pub fn url(&self) -> Option<Url<'a>> {
+        match self {
+            Self::Unchecked | Self::Unnamed | Self::AbsentFromIndex => None,
+            Self::Uncloneable(url)
+            | Self::TooLarge(url)
+            | Self::Superseded(url)
+            | Self::Success(url, _)
+            | Self::Unreleased(url, _)
+            | Self::Unassociated(url)
+            | Self::Nonexistent(url)
+            | Self::Archived(url) => Some(*url),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn is_success(&self) -> bool {
         self.as_success().is_some()
@@ -37,14 +76,20 @@ impl<'a, T> RepoStatus<'a, T> {
         self.as_success().is_none()
     }
 
+    #[cfg(test)]
     pub fn erase_url(self) -> RepoStatus<'static, T> {
         match self {
+            Self::Unchecked => RepoStatus::Unchecked,
             Self::Uncloneable(_) => RepoStatus::Uncloneable(Url::default()),
+            Self::TooLarge(_) => RepoStatus::TooLarge(Url::default()),
             Self::Unnamed => RepoStatus::Unnamed,
+            Self::Superseded(_) => RepoStatus::Superseded(Url::default()),
             Self::Success(_, value) => RepoStatus::Success(Url::default(), value),
+            Self::Unreleased(_, age) => RepoStatus::Unreleased(Url::default(), age),
             Self::Unassociated(_) => RepoStatus::Unassociated(Url::default()),
             Self::Nonexistent(_) => RepoStatus::Nonexistent(Url::default()),
             Self::Archived(_) => RepoStatus::Archived(Url::default()),
+            Self::AbsentFromIndex => RepoStatus::AbsentFromIndex,
         }
     }
 
@@ -54,23 +99,53 @@ impl<'a, T> RepoStatus<'a, T> {
     // is rather insignificant.
     pub fn leak_url(self) -> RepoStatus<'static, T> {
         match self {
+            Self::Unchecked => RepoStatus::Unchecked,
             Self::Uncloneable(url) => RepoStatus::Uncloneable(url.leak()),
+            Self::TooLarge(url) => RepoStatus::TooLarge(url.leak()),
             Self::Unnamed => RepoStatus::Unnamed,
+            Self::Superseded(url) => RepoStatus::Superseded(url.leak()),
             Self::Success(url, value) => RepoStatus::Success(url.leak(), value),
+            Self::Unreleased(url, age) => RepoStatus::Unreleased(url.leak(), age),
             Self::Unassociated(url) => RepoStatus::Unassociated(url.leak()),
             Self::Nonexistent(url) => RepoStatus::Nonexistent(url.leak()),
             Self::Archived(url) => RepoStatus::Archived(url.leak()),
+            Self::AbsentFromIndex => RepoStatus::AbsentFromIndex,
+        }
+    }
+
+    /// Replaces the url, if any, with `url`
+    ///
+    /// Used when a status is found in a cache keyed by canonical url, so that the status refers to
+    /// the url the caller asked about.
+    pub fn with_url(self, url: Url<'_>) -> RepoStatus<'_, T> {
+        match self {
+            Self::Unchecked => RepoStatus::Unchecked,
+            Self::Uncloneable(_) => RepoStatus::Uncloneable(url),
+            Self::TooLarge(_) => RepoStatus::TooLarge(url),
+            Self::Unnamed => RepoStatus::Unnamed,
+            Self::Superseded(_) => RepoStatus::Superseded(url),
+            Self::Success(_, value) => RepoStatus::Success(url, value),
+            Self::Unreleased(_, age) => RepoStatus::Unreleased(url, age),
+            Self::Unassociated(_) => RepoStatus::Unassociated(url),
+            Self::Nonexistent(_) => RepoStatus::Nonexistent(url),
+            Self::Archived(_) => RepoStatus::Archived(url),
+            Self::AbsentFromIndex => RepoStatus::AbsentFromIndex,
         }
     }
 
     pub fn map<U>(self, f: impl Fn(T) -> U) -> RepoStatus<'a, U> {
         match self {
+            Self::Unchecked => RepoStatus::Unchecked,
             Self::Uncloneable(url) => RepoStatus::Uncloneable(url),
+            Self::TooLarge(url) => RepoStatus::TooLarge(url),
             Self::Unnamed => RepoStatus::Unnamed,
+            Self::Superseded(url) => RepoStatus::Superseded(url),
             Self::Success(url, value) => RepoStatus::Success(url, f(value)),
+            Self::Unreleased(url, age) => RepoStatus::Unreleased(url, age),
             Self::Unassociated(url) => RepoStatus::Unassociated(url),
             Self::Nonexistent(url) => RepoStatus::Nonexistent(url),
             Self::Archived(url) => RepoStatus::Archived(url),
+            Self::AbsentFromIndex => RepoStatus::AbsentFromIndex,
         }
     }
 
@@ -83,70 +158,123 @@ impl<'a, T> RepoStatus<'a, T> {
 impl<'a, T, E> RepoStatus<'a, Result<T, E>> {
     pub fn transpose(self) -> Result<RepoStatus<'a, T>, E> {
         match self {
+            Self::Unchecked => Ok(RepoStatus::Unchecked),
             Self::Uncloneable(url) => Ok(RepoStatus::Uncloneable(url)),
+            Self::TooLarge(url) => Ok(RepoStatus::TooLarge(url)),
             Self::Unnamed => Ok(RepoStatus::Unnamed),
+            Self::Superseded(url) => Ok(RepoStatus::Superseded(url)),
             Self::Success(url, Ok(value)) => Ok(RepoStatus::Success(url, value)),
             Self::Success(_, Err(error)) => Err(error),
+            Self::Unreleased(url, age) => Ok(RepoStatus::Unreleased(url, age)),
             Self::Unassociated(url) => Ok(RepoStatus::Unassociated(url)),
             Self::Nonexistent(url) => Ok(RepoStatus::Nonexistent(url)),
             Self::Archived(url) => Ok(RepoStatus::Archived(url)),
+            Self::AbsentFromIndex => Ok(RepoStatus::AbsentFromIndex),
         }
     }
 }
 
-/// Multiples of `max_age` that cause the color to go completely from yellow to red.
+/// Multiples of `max_age` that cause the severity to go completely from least to most severe (e.g.,
+/// from yellow to red).
 const SATURATION_MULTIPLIER: u64 = 3;
 
 impl RepoStatus<'_, u64> {
-    pub fn color(&self) -> Option<Color> {
+    pub fn color_spec(&self) -> ColorSpec {
+        theme::spec(Some(self.severity()))
+    }
+
+    /// Returns the prefix that indicates the status's severity without color (see `--theme`)
+    pub fn symbol(&self) -> &'static str {
+        theme::symbol(
+            opts::get().theme,
+            matches!(
+                self,
+                Self::Unassociated(_)
+                    | Self::Nonexistent(_)
+                    | Self::Archived(_)
+                    | Self::AbsentFromIndex
+            ),
+        )
+    }
+
+    /// Returns the status's severity, from 0 (least severe) to `u8::MAX` (most severe)
+    fn severity(&self) -> u8 {
         let age = match self {
-            // smoelius: `Uncloneable` and `Unnamed` default to yellow.
-            Self::Uncloneable(_) | Self::Unnamed => {
-                return Some(Color::Rgb(u8::MAX, u8::MAX, 0));
+            // smoelius: `Unchecked`, `Uncloneable`, `TooLarge`, `Unnamed`, `Superseded`, and
+            // `Unreleased` default to yellow, i.e., are least severe.
+            Self::Unchecked
+            | Self::Uncloneable(_)
+            | Self::TooLarge(_)
+            | Self::Unnamed
+            | Self::Superseded(_)
+            | Self::Unreleased(..) => {
+                return 0;
             }
             Self::Success(_, age) => age,
-            // smoelius: `Unassociated`, `Nonexistent`, and `Archived` default to red.
-            Self::Unassociated(_) | Self::Nonexistent(_) | Self::Archived(_) => {
-                return Some(Color::Rgb(u8::MAX, 0, 0));
+            // smoelius: `Unassociated`, `Nonexistent`, `Archived`, and `AbsentFromIndex` default to
+            // red, i.e., are most severe.
+            Self::Unassociated(_)
+            | Self::Nonexistent(_)
+            | Self::Archived(_)
+            | Self::AbsentFromIndex => {
+                return u8::MAX;
             }
         };
-        let age_in_days = age / SECS_PER_DAY;
-        let Some(max_age_excess) = age_in_days.checked_sub(opts::get().max_age) else {
-            // smoelius: `age_in_days` should be at least `max_age`. Otherwise, why are we here?
-            debug_assert!(false);
-            return None;
-        };
-        let subtrahend_u64 = if opts::get().max_age == 0 {
+        let max_age = opts::get().max_age;
+        // smoelius: A current repository can still be displayed, e.g., as the repository of a
+        // package's latest version, or of a package reported because its owners are inactive. Such
+        // a repository is least severe.
+        if !days::exceeds(*age, max_age) {
+            return 0;
+        }
+        let severity_u64 = if max_age == 0 {
             u64::MAX
         } else {
-            (max_age_excess * u64::from(u8::MAX)) / (SATURATION_MULTIPLIER * opts::get().max_age)
+            let max_age_excess = days::from_secs(*age) - max_age;
+            (max_age_excess * u64::from(u8::MAX)) / (SATURATION_MULTIPLIER * max_age)
         };
-        Some(Color::Rgb(
-            u8::MAX,
-            u8::MAX.saturating_sub(u8::try_from(subtrahend_u64).unwrap_or(u8::MAX)),
-            0,
-        ))
+        u8::try_from(severity_u64).unwrap_or(u8::MAX)
     }
 
     #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
     #[cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
     pub fn write(&self, stream: &mut impl WriteColor) -> std::io::Result<()> {
         match self {
+            Self::Unchecked => write!(stream, "repository not checked"),
             Self::Uncloneable(url) => {
                 write_url(stream, *url)?;
                 write!(stream, " is uncloneable")?;
                 Ok(())
             }
+            Self::TooLarge(url) => {
+                write_url(stream, *url)?;
+                write!(stream, " skipped: repository too large")?;
+                Ok(())
+            }
             Self::Unnamed => write!(stream, "no repository"),
+            Self::Superseded(url) => {
+                write!(stream, "renamed or split in ")?;
+                write_url(stream, *url)?;
+                Ok(())
+            }
             Self::Success(url, age) => {
                 write_url(stream, *url)?;
                 write!(stream, " updated ")?;
-                stream.set_color(ColorSpec::new().set_fg(self.color()))?;
-                write!(stream, "{}", age / SECS_PER_DAY)?;
+                stream.set_color(&self.color_spec())?;
+                write!(stream, "{}", days::from_secs(*age))?;
                 stream.set_color(ColorSpec::new().set_fg(None))?;
                 write!(stream, " days ago")?;
                 Ok(())
             }
+            Self::Unreleased(url, age) => {
+                write_url(stream, *url)?;
+                write!(
+                    stream,
+                    " active, but no release in {} days",
+                    days::from_secs(*age)
+                )?;
+                Ok(())
+            }
             Self::Unassociated(url) => {
                 write!(stream, "not in ")?;
                 write_url(stream, *url)?;
@@ -162,6 +290,7 @@ impl RepoStatus<'_, u64> {
                 write!(stream, " archived")?;
                 Ok(())
             }
+            Self::AbsentFromIndex => write!(stream, "absent from registry index"),
         }
     }
 }
@@ -169,7 +298,7 @@ impl RepoStatus<'_, u64> {
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
 #[cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
 fn write_url(stream: &mut impl WriteColor, url: Url) -> std::io::Result<()> {
-    stream.set_color(ColorSpec::new().set_fg(Some(Color::Blue)))?;
+    stream.set_color(ColorSpec::new().set_fg(theme::url_color()))?;
     write!(stream, "{url}")?;
     stream.set_color(ColorSpec::new().set_fg(None))?;
     Ok(())
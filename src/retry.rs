@@ -0,0 +1,48 @@
+//! Bounded retries with exponential backoff
+//!
+//! Shared by the crates.io and GitHub clients so that a transient outage of either service does not
+//! silently change a scan's results.
+
+use anyhow::Result;
+use std::{thread::sleep, time::Duration};
+
+const MAX_ATTEMPTS: u32 = 4;
+
+const INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Calls `f` until it succeeds, it fails with an error that `is_transient` rejects, or
+/// [`MAX_ATTEMPTS`] attempts have been made
+///
+/// The delay between attempts starts at [`INITIAL_DELAY`] and doubles after each attempt.
+pub(crate) fn with_backoff<T>(
+    what: &str,
+    mut f: impl FnMut() -> Result<T>,
+    is_transient: impl Fn(&anyhow::Error) -> bool,
+) -> Result<T> {
+    let mut delay = INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < MAX_ATTEMPTS && is_transient(&error) => {
+                log::debug!(
+                    "attempt {attempt} of {what} failed: {error}; retrying in {} seconds",
+                    delay.as_secs()
+                );
+                sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Returns true if a failed curl transfer is worth retrying
+///
+/// Only a timeout is. A host that could not be resolved or connected to (see
+/// [`crate::network::classify_curl_error`]) will not become reachable within the few seconds of
+/// backoff, and retrying would only delay the failure.
+pub(crate) fn is_transient_curl_error(error: &curl::Error) -> bool {
+    error.is_operation_timedout()
+}
@@ -0,0 +1,218 @@
+//! Concurrent HTTP GET requests with adaptive backoff
+//!
+//! At most [`CONCURRENCY`] requests are in flight at once. Rather than wait a fixed interval
+//! between requests, a request is sent as soon as a worker is free, until the server responds with
+//! 429 (Too Many Requests). Then every worker waits for the interval given by the response's
+//! `Retry-After` header or, if there is none, for an interval that doubles with each such response.
+//! Each successful response halves the interval. A url requested more than once in a batch is
+//! fetched once.
+
+use super::{curl, network, Url};
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    sync::{Mutex, MutexGuard, PoisonError},
+    thread::{scope, sleep},
+    time::{Duration, Instant},
+};
+
+pub(crate) const CONCURRENCY: usize = 4;
+
+/// The number of times a request is sent before a 429 response is treated as an error
+const MAX_ATTEMPTS: u32 = 6;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+const MAX_BACKOFF: Duration = Duration::from_mins(1);
+
+// smoelius: The throttle is shared by every request, so that a 429 response slows every worker,
+// not just the one that received it.
+static THROTTLE: Mutex<Throttle> = Mutex::new(Throttle {
+    backoff: Duration::ZERO,
+    not_before: None,
+});
+
+struct Throttle {
+    backoff: Duration,
+    /// The time before which no request should be sent
+    not_before: Option<Instant>,
+}
+
+/// The error returned when a server responds with something other than 200 (OK), or with 429 (Too
+/// Many Requests) more than [`MAX_ATTEMPTS`] times
+#[derive(Debug)]
+pub(crate) struct UnexpectedResponse {
+    pub url: String,
+    pub code: u32,
+}
+
+impl std::fmt::Display for UnexpectedResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unexpected response code from `{}`: {}",
+            self.url, self.code
+        )
+    }
+}
+
+impl std::error::Error for UnexpectedResponse {}
+
+struct Response {
+    code: u32,
+    retry_after: Option<Duration>,
+    body: Vec<u8>,
+}
+
+/// Sends a GET request to each of `urls` and returns the response bodies, keyed by url
+pub(crate) fn get_all<'a>(
+    urls: impl IntoIterator<Item = &'a str>,
+    user_agent: &str,
+) -> BTreeMap<String, Result<Vec<u8>>> {
+    let queue = urls
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<VecDeque<_>>();
+    let n_workers = CONCURRENCY.min(queue.len());
+    let queue = Mutex::new(queue);
+    let results = Mutex::new(BTreeMap::new());
+    let outcomes = Mutex::new(Vec::new());
+    scope(|scope| {
+        for _ in 0..n_workers {
+            scope.spawn(|| {
+                while let Some(url) = lock(&queue).pop_front() {
+                    let result = get(&url, user_agent, &outcomes);
+                    lock(&results).insert(url, result);
+                }
+            });
+        }
+    });
+    // smoelius: Consecutive connection failures are tracked per thread. So record the outcomes on
+    // the calling thread.
+    for outcome in outcomes
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+    {
+        network::record(outcome);
+    }
+    results.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn get(url: &str, user_agent: &str, outcomes: &Mutex<Vec<network::Outcome>>) -> Result<Vec<u8>> {
+    let mut attempt = 1;
+    loop {
+        wait();
+        let response = perform(url, user_agent, outcomes)?;
+        match response.code {
+            200 => {
+                relax();
+                return Ok(response.body);
+            }
+            429 if attempt < MAX_ATTEMPTS => {
+                let backoff = back_off(response.retry_after);
+                log::debug!(
+                    "attempt {attempt} of request to `{url}` was rate limited; retrying in {} \
+                     seconds",
+                    backoff.as_secs()
+                );
+                attempt += 1;
+            }
+            code => {
+                return Err(UnexpectedResponse {
+                    url: url.to_owned(),
+                    code,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+/// Sleeps until requests may be sent
+fn wait() {
+    // smoelius: Another worker may push `not_before` back while this one sleeps. So check again
+    // after waking.
+    while let Some(delay) = lock(&THROTTLE)
+        .not_before
+        .and_then(|not_before| not_before.checked_duration_since(Instant::now()))
+    {
+        sleep(delay);
+    }
+}
+
+/// Delays all requests in response to a 429 response, and returns the delay
+fn back_off(retry_after: Option<Duration>) -> Duration {
+    let mut throttle = lock(&THROTTLE);
+    throttle.backoff =
+        retry_after.unwrap_or_else(|| (throttle.backoff * 2).clamp(INITIAL_BACKOFF, MAX_BACKOFF));
+    let not_before = Instant::now() + throttle.backoff;
+    throttle.not_before = Some(
+        throttle
+            .not_before
+            .map_or(not_before, |previous| previous.max(not_before)),
+    );
+    throttle.backoff
+}
+
+/// Shortens the delay imposed by earlier 429 responses
+fn relax() {
+    lock(&THROTTLE).backoff /= 2;
+}
+
+fn perform(
+    url: &str,
+    user_agent: &str,
+    outcomes: &Mutex<Vec<network::Outcome>>,
+) -> Result<Response> {
+    let mut handle = curl::handle(Url::from(url))?;
+    handle.useragent(user_agent)?;
+    let mut retry_after = None;
+    let mut body = Vec::new();
+    let result = {
+        let mut transfer = handle.transfer();
+        transfer.header_function(|header| {
+            if let Some(duration) = parse_retry_after(header) {
+                retry_after = Some(duration);
+            }
+            true
+        })?;
+        transfer.write_function(|data| {
+            body.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()
+    };
+    lock(outcomes).push(match &result {
+        Ok(()) => network::Outcome::Reached,
+        Err(err) => {
+            let connected = handle
+                .connect_time()
+                .is_ok_and(|connect_time| !connect_time.is_zero());
+            network::classify_curl_error(err, connected)
+        }
+    });
+    result?;
+    Ok(Response {
+        code: handle.response_code()?,
+        retry_after,
+        body,
+    })
+}
+
+/// Parses a `Retry-After` header that gives a number of seconds
+///
+/// A header that gives a date is ignored, and the interval is doubled instead.
+pub(crate) fn parse_retry_after(header: &[u8]) -> Option<Duration> {
+    let header = std::str::from_utf8(header).ok()?;
+    let (name, value) = header.split_once(':')?;
+    if !name.trim().eq_ignore_ascii_case("retry-after") {
+        return None;
+    }
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -1,6 +1,232 @@
-use super::{OutdatedDep, RepoStatus, UnmaintainedPkg, SECS_PER_DAY};
-use cargo_metadata::semver::{Version, VersionReq};
-use serde::Serialize;
+//! JSON output
+//!
+//! Fields of the structures in this module are stable unless documented as *experimental*. A
+//! stable field is not removed, renamed, or given a different meaning without incrementing
+//! [`SCHEMA_VERSION`]. An experimental field may change in any release.
+//!
+//! Packages are ordered by name and version, and each package's outdated dependencies are ordered
+//! by name and the version used. These orders are also stable.
+
+use super::{
+    allowlist,
+    anomalies::{Anomaly, PublishAnomaly},
+    behind, canonical_url, clock,
+    containment::PackageError,
+    days,
+    downloads::Trend,
+    github::Fork,
+    index_freshness,
+    on_disk_cache::CacheState,
+    opts, outdated_dep_grace,
+    pins::PinnedDep,
+    policy::Policy,
+    url,
+    warnings::{self, Warning},
+    AgeSource, Kind, LatestVersionRepository, OutdatedDep, RepoStatus, UnmaintainedPkg, Url,
+    TOKEN_FOUND,
+};
+use anyhow::{anyhow, ensure, Result};
+use cargo_metadata::{
+    semver::{Version, VersionReq},
+    Metadata, Package,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::crate_version;
+use serde::{Deserialize, Serialize};
+use std::{sync::atomic::Ordering, time::SystemTime};
+
+/// The version of the JSON output's schema
+///
+/// Version 1 is that of reports produced before `schema_version` was introduced. Before version 3,
+/// `--json` without `--json-metadata` produced just an array of packages.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// The version of the algorithm by which finding ids are computed (see [`finding_id`])
+///
+/// Incremented whenever a change to the algorithm would change an existing finding's id.
+pub const FINDING_ID_VERSION: u32 = 1;
+
+/// Number of hexadecimal digits in a finding id
+pub(crate) const FINDING_ID_LEN: usize = 16;
+
+/// JSON output produced when `--json` is passed
+#[derive(Deserialize, Serialize)]
+pub struct Report {
+    #[serde(default = "schema_version_1")]
+    pub schema_version: u32,
+    /// Present only if `--json-metadata` was passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ReportMetadata>,
+    pub packages: Vec<SerializableUnmaintainedPkg>,
+    /// *Experimental.*
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_deps: Vec<SerializablePinnedDep>,
+    /// *Experimental.*
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publish_anomalies: Vec<SerializablePublishAnomaly>,
+    /// *Experimental.* Packages not reported because their repositories matched allowlisted
+    /// patterns, ordered by name and version
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowlisted: Vec<SerializableAllowlisted>,
+    /// *Experimental.* Packages that could not be checked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<SerializablePackageError>,
+    /// *Experimental.* The warnings emitted during the scan, in the order in which they were
+    /// emitted; absent from reports produced before warnings were recorded
+    #[serde(default)]
+    pub warnings: Vec<SerializableWarning>,
+}
+
+fn schema_version_1() -> u32 {
+    1
+}
+
+/// Returns an error if `report`'s schema version is newer than [`SCHEMA_VERSION`]
+pub fn check_schema_version(report: &serde_json::Value) -> Result<()> {
+    let schema_version = report
+        .get("schema_version")
+        .map_or(Some(1), serde_json::Value::as_u64)
+        .ok_or_else(|| anyhow!("report's `schema_version` is not a number"))?;
+    ensure!(
+        schema_version <= u64::from(SCHEMA_VERSION),
+        "report has schema version {}, but this version of cargo-unmaintained reads schema \
+         versions up to {}; upgrade cargo-unmaintained",
+        schema_version,
+        SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+/// Information about how a report was produced
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ReportMetadata {
+    tool_version: String,
+    max_age: u64,
+    /// Absent from reports produced before `--outdated-dep-grace` was introduced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outdated_dep_grace: Option<u64>,
+    /// *Experimental.* Absent from reports produced before `--policy` was introduced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    policy: Option<Policy>,
+    github_api: bool,
+    /// *Experimental.*
+    cache_state: CacheState,
+    scan_start: String,
+    scan_end: String,
+    workspace_root: Option<String>,
+    /// *Experimental.* The version of the algorithm by which the report's finding ids were
+    /// computed (see [`FINDING_ID_VERSION`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finding_id_version: Option<u32>,
+    /// *Experimental.* How many seconds old the crates.io index was when the scan consulted it,
+    /// or `None` if the index was not consulted (see `--index-max-age`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    index_age_secs: Option<u64>,
+}
+
+impl Report {
+    pub fn new(
+        metadata: Option<ReportMetadata>,
+        packages: &[UnmaintainedPkg],
+        pinned_deps: &[PinnedDep],
+        publish_anomalies: &[PublishAnomaly],
+        package_errors: &[PackageError],
+    ) -> Self {
+        let mut packages = packages
+            .iter()
+            .map(SerializableUnmaintainedPkg::new)
+            .collect::<Vec<_>>();
+        // smoelius: Package ids embed, e.g., registry paths, which can change between cargo
+        // versions. So sort by name and version instead.
+        packages.sort_by(|lhs, rhs| (&lhs.name, &lhs.version).cmp(&(&rhs.name, &rhs.version)));
+        let mut allowlisted = allowlist::collected()
+            .iter()
+            .map(SerializableAllowlisted::new)
+            .collect::<Vec<_>>();
+        allowlisted.sort_by(|lhs, rhs| (&lhs.name, &lhs.version).cmp(&(&rhs.name, &rhs.version)));
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata,
+            packages,
+            pinned_deps: pinned_deps.iter().map(SerializablePinnedDep::new).collect(),
+            publish_anomalies: publish_anomalies
+                .iter()
+                .map(SerializablePublishAnomaly::new)
+                .collect(),
+            allowlisted,
+            errors: package_errors
+                .iter()
+                .map(SerializablePackageError::new)
+                .collect(),
+            warnings: warnings::collected()
+                .iter()
+                .map(SerializableWarning::new)
+                .collect(),
+        }
+    }
+}
+
+impl ReportMetadata {
+    pub fn new(
+        metadata: &Metadata,
+        cache_state: CacheState,
+        scan_start: SystemTime,
+        scan_end: SystemTime,
+    ) -> Self {
+        Self {
+            tool_version: crate_version!().to_owned(),
+            max_age: opts::get().max_age,
+            outdated_dep_grace: Some(outdated_dep_grace()),
+            policy: Some(Policy::new(opts::get())),
+            github_api: TOKEN_FOUND.load(Ordering::SeqCst),
+            cache_state,
+            scan_start: rfc3339(scan_start),
+            scan_end: rfc3339(scan_end),
+            workspace_root: if opts::get().redact_workspace_root {
+                None
+            } else {
+                Some(metadata.workspace_root.to_string())
+            },
+            finding_id_version: Some(FINDING_ID_VERSION),
+            index_age_secs: index_freshness::recorded_age().map(|age| age.as_secs()),
+        }
+    }
+}
+
+/// Returns an id for a finding that is stable across runs and machines
+///
+/// The id is a truncated SHA-1 hash of the following, separated by NUL bytes:
+/// - [`FINDING_ID_VERSION`]
+/// - the canonical form of the repository url (see [`crate::canonical_url`]), or `unnamed:NAME` if
+///   the package `NAME` names no repository
+/// - the finding's family (see [`SerializableRepoStatus::family`]), or `outdated-dep` or `pinned`
+/// - for an outdated or pinned dependency, the dependency's name
+///
+/// Versions, ages, and paths are deliberately not inputs. So the id of a finding does not change
+/// when, e.g., the repository ages or a newer version of the package is used, but does change when
+/// the underlying problem changes (e.g., a stale repository is archived). One consequence is that
+/// packages from the same repository with the same problem share an id, as they share the problem.
+pub fn finding_id(
+    repository: Option<&str>,
+    name: &str,
+    family: &str,
+    dependency: Option<&str>,
+) -> String {
+    let repository = repository.map_or_else(|| format!("unnamed:{name}"), canonical_url);
+    let mut inputs = vec![
+        FINDING_ID_VERSION.to_string(),
+        repository,
+        family.to_owned(),
+    ];
+    inputs.extend(dependency.map(ToOwned::to_owned));
+    let mut hexdigest = sha1_smol::Sha1::from(inputs.join("\0")).hexdigest();
+    hexdigest.truncate(FINDING_ID_LEN);
+    hexdigest
+}
+
+pub(crate) fn rfc3339(timestamp: SystemTime) -> String {
+    DateTime::<Utc>::from(timestamp).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
 
 impl Serialize for UnmaintainedPkg<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -11,64 +237,849 @@ impl Serialize for UnmaintainedPkg<'_> {
     }
 }
 
-#[derive(Serialize)]
-struct SerializableUnmaintainedPkg<'pkg, 'dep> {
-    name: &'pkg str,
-    version: &'pkg Version,
-    repo_status: SerializableRepoStatus,
-    outdated_deps: Vec<SerializableOutdatedDep<'pkg, 'dep>>,
+/// An [`UnmaintainedPkg`] as it appears in a report
+///
+/// Unlike an [`UnmaintainedPkg`], a `SerializableUnmaintainedPkg` can be read back from a report,
+/// e.g., by the `render` subcommand.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Deserialize, Serialize)]
+pub struct SerializableUnmaintainedPkg {
+    pub name: String,
+    pub version: Version,
+    /// *Experimental.* An id for the finding that is stable across runs (see [`finding_id`]);
+    /// absent from reports produced before finding ids were introduced
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub finding_id: String,
+    pub repo_status: SerializableRepoStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// A newer version that satisfies the requirements on the package is available
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub newer_compatible_version_available: bool,
+    /// A newer, semver-incompatible version is available
+    ///
+    /// Reports produced before this field and the previous one were distinguished recorded
+    /// `newer_version_is_available`, which is read as this field.
+    #[serde(
+        default,
+        alias = "newer_version_is_available",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub newer_major_version_available: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub proc_macro: bool,
+    /// The package's immediate dependents depend upon it only as a build dependency
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub build_only: bool,
+    /// *Experimental.* The package is a path dependency that resolves outside the workspace root
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub path_dependency: bool,
+    /// *Experimental.* The label that the package's repository's owner maps to (see
+    /// `--owner-map`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_label: Option<String>,
+    pub outdated_deps: Vec<SerializableOutdatedDep>,
+    /// *Experimental.*
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggested_forks: Vec<SerializableFork>,
+    /// *Experimental.* Repositories that the package's archived repository's description or
+    /// homepage names as possible successors, the first of which exists and is not archived
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub successor_candidates: Vec<String>,
+    /// *Experimental.* Why the package was scanned though `workspace.metadata.unmaintained.ignore`
+    /// names it, e.g., `ignore entry expired on 2025-09-01 (reason: replacing in Q3)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expired_ignore: Option<String>,
+    /// *Experimental.*
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_trend: Option<Trend>,
+    /// *Experimental.* Branch other than the default on which the package was found in its
+    /// repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub membership_branch: Option<String>,
+    /// *Experimental.* Tag named after the version used at which the package was found in its
+    /// repository, when it was not found on any branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub membership_tag: Option<String>,
+    /// *Experimental.* The package was not found in its repository, though another package from
+    /// the same repository was, which suggests that the membership check is at fault
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub membership_conflict: bool,
+    /// The repository was inferred because the package's repository field names an owner (e.g.,
+    /// `https://github.com/rust-lang`) rather than a repository
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repository_inferred: bool,
+    /// *Experimental.* The repository of the package's latest version, if it differs from the
+    /// repository of the version used, and the latest version was checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version_repository: Option<SerializableLatestVersionRepository>,
+    /// *Experimental.* The package's latest version, if it was checked and its outdated
+    /// dependencies differ from those of the version used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<Version>,
+    /// *Experimental.* The outdated dependencies of `latest_version`. If `latest_version` is
+    /// present and this is empty, upgrading would resolve the package's outdated dependencies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub latest_version_outdated_deps: Vec<SerializableOutdatedDep>,
+    /// *Experimental.* The repository was given by `workspace.metadata.unmaintained.repositories`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repository_overridden: bool,
+    /// *Experimental.* The repository of the version used, if it is gone and the package was
+    /// instead checked against the repository named by a newer version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub used_version_repository: Option<SerializableUsedVersionRepository>,
+    /// *Experimental.* A workspace member depends directly upon the package
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub direct: bool,
+    /// The repository named in the package's manifest, if it does not name the same repository as
+    /// `repository` (e.g., because `repository` was substituted for it, or because `repo_status`
+    /// has no url)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_repository: Option<String>,
+    /// *Experimental.* What the repository's age was computed from (`commit`, `tag`, or
+    /// `release`), if `--age-source` was given something other than `commit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_source: Option<AgeSource>,
+    /// *Experimental.* `repo_status` was derived from a network failure (e.g., a timeout) that
+    /// persisted when the package was re-checked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub network_failure: bool,
+    /// *Experimental.* The package's repository was assumed to be fresh (see `--assume-fresh`),
+    /// and so was not checked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub assumed_fresh: bool,
+    /// *Experimental.* The packages other than workspace members that depend upon the package,
+    /// directly or transitively, nearest first (see `--blast-radius`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blast_radius: Vec<SerializableDependent>,
+    /// *Experimental.* Up to `--tree-paths` shortest paths from workspace members to the package,
+    /// each given as the names of the packages along it, if `--tree` was passed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<Vec<String>>,
+    /// *Experimental.* The number of days since any of the package's owners was last active, if
+    /// the package was reported partly because of that (see `--owner-activity`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners_inactive_days: Option<u64>,
+    /// *Experimental.* The package's repository was checked against a tarball rather than a clone
+    /// (see `--allow-tarball-fallback`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tarball_fallback: bool,
+    /// *Experimental.* The scan stopped at this package before examining every package (see
+    /// `--fail-fast` and `--fail-fast-on`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial_scan: bool,
+    /// *Experimental.* The kind of problem the scan stopped on, if `partial_scan` is true and the
+    /// package has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_fast_kind: Option<Kind>,
+    /// *Experimental.* The number of packages not examined, if `partial_scan` is true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unexamined_packages: Option<usize>,
+    /// *Experimental.* When the finding was first recorded in the workspace's findings history
+    /// (see `--history`), in RFC 3339 format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen: Option<String>,
+    /// *Experimental.* The number of non-yanked releases published after the version used, if the
+    /// package is from crates.io
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releases_behind: Option<u64>,
+    /// *Experimental.* The number of days between the version used's publication and the newest
+    /// non-yanked release's, if the package is from crates.io
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_behind: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializableLatestVersionRepository {
+    pub version: Version,
+    pub repo_status: SerializableRepoStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializableUsedVersionRepository {
+    pub repo_status: SerializableRepoStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializableFork {
+    pub url: String,
+    pub stars: u64,
+    pub age: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SerializableOutdatedDep {
+    pub name: String,
+    /// *Experimental.* An id for the finding that is stable across runs (see [`finding_id`]);
+    /// absent from reports produced before finding ids were introduced
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub finding_id: String,
+    pub req: VersionReq,
+    pub version_used: Version,
+    pub version_latest: Version,
+    /// *Experimental.* The dependency is developed in the same repository as the dependent package
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sibling: bool,
+    /// *Experimental.* The packages through which the dependency was reached, if it is not a
+    /// direct dependency (see `--deep-outdated`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub via: Vec<SerializableVia>,
+    /// *Experimental.* The number of non-yanked releases published after the version used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releases_behind: Option<u64>,
+    /// *Experimental.* The number of days between the version used's publication and the newest
+    /// non-yanked release's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_behind: Option<u64>,
 }
 
-#[derive(Serialize)]
-struct SerializableOutdatedDep<'pkg, 'dep> {
-    name: &'pkg str,
-    req: &'pkg VersionReq,
-    version_used: &'pkg Version,
-    version_latest: &'dep Version,
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SerializableVia {
+    pub name: String,
+    pub version: Version,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, Serialize)]
+pub struct SerializableWarning {
+    pub message: String,
+    /// The name of the package being checked when the warning was emitted, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializableAllowlisted {
+    pub name: String,
+    pub version: Version,
+    pub repository: String,
+    /// The allowlisted pattern that the repository matched
+    pub pattern: String,
+    /// Where the pattern came from, e.g., "the built-in allowlist"
+    pub origin: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializableDependent {
+    pub name: String,
+    pub version: Version,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializablePinnedDep {
+    pub dependent: String,
+    pub name: String,
+    /// *Experimental.* An id for the finding that is stable across runs (see [`finding_id`]);
+    /// absent from reports produced before finding ids were introduced
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub finding_id: String,
+    pub req: VersionReq,
+    pub version_pinned: Version,
+    pub version_latest: Version,
+    pub age: u64,
+    pub yanked: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializablePublishAnomaly {
+    pub name: String,
+    pub version: Version,
+    pub anomaly: Anomaly,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SerializablePackageError {
+    pub name: String,
+    pub version: Version,
+    pub error: String,
+}
+
+#[derive(Clone, Copy, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum SerializableRepoStatus {
+    Unchecked,
     Uncloneable,
+    /// *Experimental.* The repository's clone exceeded `--max-repo-size` and was removed (see
+    /// `--deny-oversize`)
+    TooLarge,
     Unnamed,
+    Superseded,
     Age(u64),
+    /// *Experimental.* The repository is current, but the package's newest release is this many
+    /// days old (see `--publish-lag`)
+    Unreleased(u64),
     Unassociated,
     Nonexistent,
     Archived,
+    /// *Experimental.*
+    AbsentFromIndex,
 }
 
-impl<'pkg, 'dep> SerializableUnmaintainedPkg<'pkg, 'dep> {
-    fn new(value: &'dep UnmaintainedPkg<'pkg>) -> Self {
+impl SerializableUnmaintainedPkg {
+    pub fn new(value: &UnmaintainedPkg) -> Self {
         let UnmaintainedPkg {
             pkg,
             repo_age,
-            newer_version_is_available: _,
+            newer_compatible_version_available,
+            newer_major_version_available,
+            proc_macro,
+            build_only,
+            path_dependency,
+            owner_label,
             outdated_deps,
+            suggested_forks,
+            successor_candidates,
+            expired_ignore,
+            download_trend,
+            membership_branch,
+            membership_tag,
+            membership_conflict,
+            repository_inferred,
+            latest_version_repository,
+            latest_version_outdated_deps,
+            repository_overridden,
+            used_version_repository,
+            direct,
+            original_repository,
+            age_source,
+            network_failure,
+            assumed_fresh,
+            blast_radius,
+            paths,
+            owners_inactive_days,
+            tarball_fallback,
+            fail_fast,
+            first_seen,
+            behind,
         } = value;
-        SerializableUnmaintainedPkg {
-            name: &pkg.name,
-            version: &pkg.version,
+        let mut serializable = SerializableUnmaintainedPkg {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            finding_id: String::new(),
             repo_status: SerializableRepoStatus::from(*repo_age),
-            outdated_deps: outdated_deps
+            repository: repo_age.url().map(|url| url.as_str().to_owned()),
+            newer_compatible_version_available: *newer_compatible_version_available,
+            newer_major_version_available: *newer_major_version_available,
+            proc_macro: *proc_macro,
+            build_only: *build_only,
+            path_dependency: *path_dependency,
+            owner_label: owner_label.clone(),
+            outdated_deps: sorted_outdated_deps(outdated_deps),
+            suggested_forks: suggested_forks.iter().map(SerializableFork::new).collect(),
+            successor_candidates: successor_candidates.clone(),
+            expired_ignore: expired_ignore.clone(),
+            download_trend: *download_trend,
+            membership_branch: membership_branch.clone(),
+            membership_tag: membership_tag.clone(),
+            membership_conflict: *membership_conflict,
+            repository_inferred: *repository_inferred,
+            latest_version_repository: latest_version_repository
+                .as_ref()
+                .map(SerializableLatestVersionRepository::new),
+            latest_version: latest_version_outdated_deps
+                .as_ref()
+                .map(|latest_version_outdated_deps| latest_version_outdated_deps.version.clone()),
+            latest_version_outdated_deps: latest_version_outdated_deps
+                .as_ref()
+                .map(|latest_version_outdated_deps| {
+                    latest_version_outdated_deps.outdated_deps.clone()
+                })
+                .unwrap_or_default(),
+            repository_overridden: *repository_overridden,
+            used_version_repository: used_version_repository
+                .map(SerializableUsedVersionRepository::new),
+            direct: *direct,
+            original_repository: original_repository.clone(),
+            age_source: *age_source,
+            network_failure: *network_failure,
+            assumed_fresh: *assumed_fresh,
+            blast_radius: blast_radius
                 .iter()
-                .map(SerializableOutdatedDep::new)
+                .copied()
+                .map(SerializableDependent::new)
                 .collect(),
+            paths: paths
+                .iter()
+                .map(|path| path.iter().map(|pkg| pkg.name.clone()).collect())
+                .collect(),
+            owners_inactive_days: *owners_inactive_days,
+            tarball_fallback: *tarball_fallback,
+            partial_scan: fail_fast.is_some(),
+            fail_fast_kind: fail_fast.and_then(|fail_fast| fail_fast.kind),
+            unexamined_packages: fail_fast.map(|fail_fast| fail_fast.unexamined),
+            first_seen: first_seen.map(rfc3339),
+            releases_behind: behind.map(|behind| behind.releases),
+            days_behind: behind.map(|behind| behind.days),
+        };
+        serializable.set_finding_ids();
+        serializable
+    }
+
+    /// Sets the finding ids of the package and its outdated dependencies (see [`finding_id`])
+    ///
+    /// The ids are computed from the package's serialized fields alone. So they can also be
+    /// computed for a package read from a report produced before finding ids were introduced.
+    pub fn set_finding_ids(&mut self) {
+        let repository = self.repository.as_deref();
+        self.finding_id = finding_id(repository, &self.name, self.repo_status.family(), None);
+        for outdated_dep in &mut self.outdated_deps {
+            outdated_dep.finding_id = finding_id(
+                repository,
+                &self.name,
+                "outdated-dep",
+                Some(&outdated_dep.name),
+            );
+        }
+    }
+
+    /// Describes the package's blast radius as it appears in text output, e.g., `blast radius: 14
+    /// packages, including hyper-x, tower-y, tokio-z`
+    ///
+    /// Returns `None` if no package other than a workspace member depends upon the package.
+    pub fn describe_blast_radius(&self) -> Option<String> {
+        const NEAREST: usize = 3;
+        if self.blast_radius.is_empty() {
+            return None;
+        }
+        let mut names = Vec::new();
+        for dependent in &self.blast_radius {
+            if !names.contains(&dependent.name.as_str()) {
+                names.push(dependent.name.as_str());
+            }
         }
+        let n = self.blast_radius.len();
+        let noun = if n == 1 { "package" } else { "packages" };
+        if names.len() <= NEAREST {
+            Some(format!("blast radius: {n} {noun} ({})", names.join(", ")))
+        } else {
+            Some(format!(
+                "blast radius: {n} {noun}, including {}",
+                names[..NEAREST].join(", ")
+            ))
+        }
+    }
+
+    /// Describes the package's paths as they appear in text output, e.g., `path: app → serde_derive
+    /// → proc-macro2`
+    pub fn describe_paths(&self) -> impl Iterator<Item = String> + '_ {
+        self.paths
+            .iter()
+            .map(|path| format!("path: {}", path.join(" → ")))
+    }
+
+    /// Describes where the scan stopped as it appears in text output, e.g., `scan stopped here on
+    /// a problem of kind archived; 12 packages not examined`
+    ///
+    /// Returns `None` if the scan did not stop at the package.
+    pub fn describe_partial_scan(&self) -> Option<String> {
+        if !self.partial_scan {
+            return None;
+        }
+        let n = self.unexamined_packages.unwrap_or_default();
+        let noun = if n == 1 { "package" } else { "packages" };
+        Some(self.fail_fast_kind.map_or_else(
+            || format!("scan stopped here; {n} {noun} not examined"),
+            |kind| {
+                format!(
+                    "scan stopped here on a problem of kind {}; {n} {noun} not examined",
+                    kind.name()
+                )
+            },
+        ))
+    }
+
+    /// Describes how long the finding has been outstanding as it appears in text output, e.g.,
+    /// `outstanding for 94 days`
+    ///
+    /// Returns `None` if the finding was first seen less than a day ago, or if its first-seen time
+    /// is unknown.
+    pub fn describe_outstanding(&self) -> Option<String> {
+        let first_seen = DateTime::parse_from_rfc3339(self.first_seen.as_deref()?).ok()?;
+        let secs = clock::now()
+            .duration_since(SystemTime::from(first_seen))
+            .ok()?
+            .as_secs();
+        let n = days::from_secs(secs);
+        if n == 0 {
+            return None;
+        }
+        Some(format!(
+            "outstanding for {n} {}",
+            if n == 1 { "day" } else { "days" }
+        ))
+    }
+
+    /// Describes the package's most likely successor as it appears in text output, e.g., `possible
+    /// successor: github.com/owner/foo2`
+    pub fn describe_successor(&self) -> Option<String> {
+        let url = self.successor_candidates.first()?;
+        Some(format!(
+            "possible successor: {}",
+            url.strip_prefix("https://").unwrap_or(url)
+        ))
+    }
+
+    /// Describes how far the version used is behind the newest release, e.g., `14 releases / 3.2y
+    /// behind` (see [`behind::describe`])
+    pub fn describe_behind(&self) -> Option<String> {
+        behind::describe(self.releases_behind?, self.days_behind?)
+    }
+
+    /// Describes the latest version's outdated dependencies as they appear in text output, e.g.,
+    /// `latest version 2.1.0 also has outdated deps: foo, bar`, or `upgrading to 2.1.0 would
+    /// resolve the outdated dependencies`
+    ///
+    /// Returns `None` if the latest version's outdated dependencies were not recorded.
+    pub fn describe_latest_version_outdated_deps(&self) -> Option<String> {
+        let latest_version = self.latest_version.as_ref()?;
+        if self.latest_version_outdated_deps.is_empty() {
+            return Some(format!(
+                "upgrading to {latest_version} would resolve the outdated dependencies"
+            ));
+        }
+        let names = self
+            .latest_version_outdated_deps
+            .iter()
+            .map(|outdated_dep| outdated_dep.name.as_str())
+            .collect::<Vec<_>>();
+        Some(format!(
+            "latest version {latest_version} also has outdated deps: {}",
+            names.join(", ")
+        ))
+    }
+
+    /// Returns true if the package has outdated dependencies, and all of them are developed in the
+    /// package's repository
+    ///
+    /// In such a case, the dependencies are outdated because the repository's crates stopped being
+    /// released, which is better reported once than once per dependency.
+    pub fn outdated_deps_are_siblings(&self) -> bool {
+        !self.outdated_deps.is_empty()
+            && self
+                .outdated_deps
+                .iter()
+                .all(|outdated_dep| outdated_dep.sibling)
     }
+
+    /// Reconstructs the [`RepoStatus`] from which this package's `repo_status` and `repository`
+    /// were produced
+    ///
+    /// Ages are recorded in days. So the reconstructed age is a whole number of days.
+    pub fn repo_age(&self) -> Result<RepoStatus<'_, u64>> {
+        repo_age(self.repo_status, self.repository.as_deref())
+            .ok_or_else(|| anyhow!("`{}` has no recorded repository", self.name))
+    }
+}
+
+impl SerializableLatestVersionRepository {
+    fn new(value: &LatestVersionRepository) -> Self {
+        let LatestVersionRepository {
+            version,
+            repository: _,
+            repo_age,
+            outdated_deps: _,
+        } = value;
+        SerializableLatestVersionRepository {
+            version: version.clone(),
+            repo_status: SerializableRepoStatus::from(*repo_age),
+            repository: repo_age.url().map(|url| url.as_str().to_owned()),
+        }
+    }
+
+    /// Like [`SerializableUnmaintainedPkg::repo_age`], but for the latest version
+    pub fn repo_age(&self, name: &str) -> Result<RepoStatus<'_, u64>> {
+        repo_age(self.repo_status, self.repository.as_deref())
+            .ok_or_else(|| anyhow!("latest version of `{}` has no recorded repository", name))
+    }
+}
+
+impl SerializableUsedVersionRepository {
+    fn new(repo_age: RepoStatus<u64>) -> Self {
+        SerializableUsedVersionRepository {
+            repo_status: SerializableRepoStatus::from(repo_age),
+            repository: repo_age.url().map(|url| url.as_str().to_owned()),
+        }
+    }
+
+    /// Like [`SerializableUnmaintainedPkg::repo_age`], but for the version used
+    pub fn repo_age(&self, name: &str) -> Result<RepoStatus<'_, u64>> {
+        repo_age(self.repo_status, self.repository.as_deref())
+            .ok_or_else(|| anyhow!("used version of `{}` has no recorded repository", name))
+    }
+}
+
+/// Returns `None` if `repo_status` requires a url and `repository` is `None`
+fn repo_age(
+    repo_status: SerializableRepoStatus,
+    repository: Option<&str>,
+) -> Option<RepoStatus<'_, u64>> {
+    let url = repository.map(Url::from);
+    Some(match repo_status {
+        SerializableRepoStatus::Unchecked => RepoStatus::Unchecked,
+        SerializableRepoStatus::Uncloneable => RepoStatus::Uncloneable(url?),
+        SerializableRepoStatus::TooLarge => RepoStatus::TooLarge(url?),
+        SerializableRepoStatus::Unnamed => RepoStatus::Unnamed,
+        SerializableRepoStatus::Superseded => RepoStatus::Superseded(url?),
+        SerializableRepoStatus::Age(age) => RepoStatus::Success(url?, days::to_secs(age)),
+        SerializableRepoStatus::Unreleased(age) => RepoStatus::Unreleased(url?, days::to_secs(age)),
+        SerializableRepoStatus::Unassociated => RepoStatus::Unassociated(url?),
+        SerializableRepoStatus::Nonexistent => RepoStatus::Nonexistent(url?),
+        SerializableRepoStatus::Archived => RepoStatus::Archived(url?),
+        SerializableRepoStatus::AbsentFromIndex => RepoStatus::AbsentFromIndex,
+    })
 }
 
-impl<'pkg, 'dep> SerializableOutdatedDep<'pkg, 'dep> {
-    fn new(value: &'dep OutdatedDep<'pkg>) -> Self {
+/// Returns `outdated_deps` ordered by name and the version used
+fn sorted_outdated_deps(outdated_deps: &[OutdatedDep]) -> Vec<SerializableOutdatedDep> {
+    let mut outdated_deps = outdated_deps
+        .iter()
+        .map(SerializableOutdatedDep::new)
+        .collect::<Vec<_>>();
+    outdated_deps
+        .sort_by(|lhs, rhs| (&lhs.name, &lhs.version_used).cmp(&(&rhs.name, &rhs.version_used)));
+    outdated_deps
+}
+
+impl SerializableOutdatedDep {
+    /// Describes the dependency as it appears in text output, e.g., `foo (requirement: ^1.0,
+    /// version used: 1.0.0, latest: 2.0.0)`
+    ///
+    /// A dependency reached through other packages is described by the path by which it was
+    /// reached, e.g., `via foo 0.3.0 → cc =1.0.18 (version used: 1.0.18, latest: 1.2.0)`.
+    ///
+    /// Either is followed by how far the version used is behind, if it is known, e.g., `(14
+    /// releases / 3.2y behind)`.
+    pub fn describe(&self) -> String {
+        let mut description = if self.via.is_empty() {
+            format!(
+                "{} (requirement: {}, version used: {}, latest: {})",
+                self.name, self.req, self.version_used, self.version_latest
+            )
+        } else {
+            let path = self
+                .via
+                .iter()
+                .map(|via| format!("{} {}", via.name, via.version))
+                .collect::<Vec<_>>()
+                .join(" → ");
+            format!(
+                "via {path} → {} {} (version used: {}, latest: {})",
+                self.name, self.req, self.version_used, self.version_latest
+            )
+        };
+        if let Some(behind) = self
+            .releases_behind
+            .zip(self.days_behind)
+            .and_then(|(releases, days)| behind::describe(releases, days))
+        {
+            description.push_str(" (");
+            description.push_str(&behind);
+            description.push(')');
+        }
+        description
+    }
+
+    pub(crate) fn new(value: &OutdatedDep) -> Self {
         let OutdatedDep {
             dep,
             version_used,
             version_latest,
+            sibling,
+            via,
+            behind,
         } = value;
         SerializableOutdatedDep {
-            name: &dep.name,
-            req: &dep.req,
-            version_used,
+            name: dep.name.clone(),
+            // smoelius: Set by `SerializableUnmaintainedPkg::set_finding_ids`, which knows the
+            // dependent package's repository.
+            finding_id: String::new(),
+            req: dep.req.clone(),
+            version_used: (*version_used).clone(),
+            version_latest: version_latest.clone(),
+            sibling: *sibling,
+            via: via
+                .iter()
+                .map(|pkg| SerializableVia {
+                    name: pkg.name.clone(),
+                    version: pkg.version.clone(),
+                })
+                .collect(),
+            releases_behind: behind.map(|behind| behind.releases),
+            days_behind: behind.map(|behind| behind.days),
+        }
+    }
+}
+
+impl SerializableAllowlisted {
+    fn new(value: &allowlist::Allowlisted) -> Self {
+        let allowlist::Allowlisted {
+            name,
+            version,
+            repository,
+            pattern,
+            origin,
+        } = value;
+        SerializableAllowlisted {
+            name: name.clone(),
+            version: version.clone(),
+            repository: repository.clone(),
+            pattern: pattern.clone(),
+            origin: (*origin).to_owned(),
+        }
+    }
+}
+
+impl SerializableWarning {
+    fn new(value: &Warning) -> Self {
+        let Warning { message, package } = value;
+        SerializableWarning {
+            message: message.clone(),
+            package: package.clone(),
+        }
+    }
+
+    /// Describes the warning as it appears in SARIF output, followed by the package being checked
+    /// when it was emitted, if any
+    pub fn describe(&self) -> String {
+        match &self.package {
+            Some(package) => format!("{} (while checking {package})", self.message),
+            None => self.message.clone(),
+        }
+    }
+}
+
+impl SerializableDependent {
+    fn new(pkg: &Package) -> Self {
+        SerializableDependent {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+        }
+    }
+}
+
+impl SerializableFork {
+    fn new(value: &Fork) -> Self {
+        let Fork {
+            url,
+            stars,
+            pushed_at,
+        } = value;
+        let age = clock::now().duration_since(*pushed_at).unwrap_or_default();
+        SerializableFork {
+            url: url.clone(),
+            stars: *stars,
+            age: days::from_secs(age.as_secs()),
+        }
+    }
+
+    /// Describes the fork as it appears in text output
+    pub fn describe(&self) -> String {
+        format!(
+            "possible maintained fork: {} (last commit {} days ago, {} stars)",
+            self.url.strip_prefix("https://").unwrap_or(&self.url),
+            self.age,
+            self.stars
+        )
+    }
+}
+
+impl SerializablePinnedDep {
+    /// Describes the pin as it appears in text output
+    pub fn describe(&self) -> String {
+        let reason = if self.yanked {
+            String::from("yanked")
+        } else {
+            format!("{} days old", self.age)
+        };
+        format!(
+            "{} -> {} (requirement: {}, version pinned: {} ({}), latest: {})",
+            self.dependent, self.name, self.req, self.version_pinned, reason, self.version_latest
+        )
+    }
+
+    pub fn new(value: &PinnedDep) -> Self {
+        let PinnedDep {
+            pkg,
+            dep,
+            version_pinned,
             version_latest,
+            age,
+            yanked,
+        } = value;
+        SerializablePinnedDep {
+            dependent: pkg.name.clone(),
+            name: dep.name.clone(),
+            finding_id: finding_id(url::repository(pkg), &pkg.name, "pinned", Some(&dep.name)),
+            req: dep.req.clone(),
+            version_pinned: (*version_pinned).clone(),
+            version_latest: version_latest.clone(),
+            age: days::from_secs(*age),
+            yanked: *yanked,
+        }
+    }
+}
+
+impl SerializablePublishAnomaly {
+    pub fn new(value: &PublishAnomaly) -> Self {
+        let PublishAnomaly { pkg, anomaly } = value;
+        SerializablePublishAnomaly {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            anomaly: anomaly.clone(),
+        }
+    }
+}
+
+impl SerializablePackageError {
+    pub fn new(value: &PackageError) -> Self {
+        let PackageError { pkg, error } = value;
+        SerializablePackageError {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            error: error.clone(),
+        }
+    }
+}
+
+impl SerializableRepoStatus {
+    /// Returns the variant's name, as it appears in JSON output (without an `Age` variant's value)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unchecked => "Unchecked",
+            Self::Uncloneable => "Uncloneable",
+            Self::TooLarge => "TooLarge",
+            Self::Unnamed => "Unnamed",
+            Self::Superseded => "Superseded",
+            Self::Age(_) => "Age",
+            Self::Unreleased(_) => "Unreleased",
+            Self::Unassociated => "Unassociated",
+            Self::Nonexistent => "Nonexistent",
+            Self::Archived => "Archived",
+            Self::AbsentFromIndex => "AbsentFromIndex",
+        }
+    }
+}
+
+impl SerializableRepoStatus {
+    /// Returns the kind of problem the status represents, without any age
+    ///
+    /// Used as an input to [`finding_id`]. So the strings returned must not change without
+    /// incrementing [`FINDING_ID_VERSION`].
+    pub fn family(&self) -> &'static str {
+        match self {
+            Self::Unchecked => "unchecked",
+            Self::Uncloneable => "uncloneable",
+            Self::TooLarge => "too-large",
+            Self::Unnamed => "unnamed",
+            Self::Superseded => "superseded",
+            Self::Age(_) => "stale",
+            Self::Unreleased(_) => "unreleased",
+            Self::Unassociated => "unassociated",
+            Self::Nonexistent => "nonexistent",
+            Self::Archived => "archived",
+            Self::AbsentFromIndex => "absent-from-index",
         }
     }
 }
@@ -76,12 +1087,19 @@ impl<'pkg, 'dep> SerializableOutdatedDep<'pkg, 'dep> {
 impl From<RepoStatus<'_, u64>> for SerializableRepoStatus {
     fn from(value: RepoStatus<'_, u64>) -> Self {
         match value {
+            RepoStatus::Unchecked => SerializableRepoStatus::Unchecked,
             RepoStatus::Uncloneable(_) => SerializableRepoStatus::Uncloneable,
+            RepoStatus::TooLarge(_) => SerializableRepoStatus::TooLarge,
             RepoStatus::Unnamed => SerializableRepoStatus::Unnamed,
-            RepoStatus::Success(_, value) => SerializableRepoStatus::Age(value / SECS_PER_DAY),
+            RepoStatus::Superseded(_) => SerializableRepoStatus::Superseded,
+            RepoStatus::Success(_, value) => SerializableRepoStatus::Age(days::from_secs(value)),
+            RepoStatus::Unreleased(_, age) => {
+                SerializableRepoStatus::Unreleased(days::from_secs(age))
+            }
             RepoStatus::Unassociated(_) => SerializableRepoStatus::Unassociated,
             RepoStatus::Nonexistent(_) => SerializableRepoStatus::Nonexistent,
             RepoStatus::Archived(_) => SerializableRepoStatus::Archived,
+            RepoStatus::AbsentFromIndex => SerializableRepoStatus::AbsentFromIndex,
         }
     }
 }
@@ -0,0 +1,473 @@
+//! `serve` subcommand: answer scan requests over HTTP, reusing warm caches between requests
+//!
+//! Two endpoints are supported:
+//!
+//! - `POST /scan` with a body of `{"path": "..."}` (a workspace directory on the server's machine)
+//!   or `{"lockfile": "..."}` (the contents of a `Cargo.lock` file). The response is the report
+//!   that `--json --json-metadata` would produce.
+//! - `GET /healthz` reports how many scans have been served and the sizes of the in-memory caches.
+//!
+//! Requests are handled one at a time, on the thread that owns the caches. This serializes access
+//! to the crates.io index lock and the on-disk cache lock. Note that data held in memory (e.g., the
+//! crates.io index) is not refreshed while the server runs.
+//!
+//! Because a web page can send requests to a loopback address, a request with an `Origin` header or
+//! a `Host` header naming something other than a loopback address (e.g., after DNS rebinding) is
+//! rejected.
+
+use super::{
+    cargo, lru, on_disk_cache, opts, reset_scan_state, scan, serialize::Report, Scan, FORKS_CACHE,
+    GENERAL_STATUS_CACHE, LATEST_VERSION_CACHE, MEMBERSHIP_BRANCH_CACHE, MEMBERSHIP_TAG_CACHE,
+    REPOSITORY_CACHE, SUPERSEDED_CACHE, TIMESTAMP_CACHE,
+};
+use anyhow::{bail, ensure, Context, Result};
+use cargo_metadata::MetadataCommand;
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::write,
+    io::{BufRead, BufReader, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener},
+    path::PathBuf,
+    time::Duration,
+};
+use tempfile::{tempdir, TempDir};
+use toml::{Table, Value};
+
+#[derive(Debug, Parser)]
+#[remain::sorted]
+pub(crate) struct Opts {
+    #[clap(
+        long,
+        help = "Listen on ADDR, which must be a loopback address (e.g., 127.0.0.1:8080)",
+        value_name = "ADDR"
+    )]
+    listen: Option<SocketAddr>,
+
+    #[cfg(unix)]
+    #[clap(
+        long,
+        help = "Listen on a unix socket at PATH",
+        value_name = "PATH",
+        conflicts_with = "listen"
+    )]
+    socket: Option<PathBuf>,
+}
+
+/// Upper bound on the size of a request body, which is large enough for any reasonable lockfile
+const MAX_BODY_LEN: usize = 16 * 1024 * 1024;
+
+/// Upper bounds on the length of the request line or a header line, and on the number of headers
+const MAX_LINE_LEN: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+// smoelius: Requests are handled one at a time. So a client that stops sending or receiving must
+// not be allowed to block the server indefinitely.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    has_origin: bool,
+    body: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ScanRequest {
+    Path(PathBuf),
+    Lockfile(String),
+}
+
+struct Response {
+    status: &'static str,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Self {
+            status: "200 OK",
+            body,
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+pub(crate) fn serve(serve_opts: &Opts) -> Result<()> {
+    // smoelius: `--package` selects a package within a workspace determined at startup. A server
+    // is not associated with any one workspace.
+    if opts::get().package.is_some() {
+        bail!("`--package` cannot be used with `serve`");
+    }
+
+    let mut n_scans = 0;
+
+    #[cfg(unix)]
+    if let Some(path) = &serve_opts.socket {
+        use std::os::unix::{
+            fs::{FileTypeExt, PermissionsExt},
+            net::UnixListener,
+        };
+
+        // smoelius: A socket left behind by a previous server would cause `bind` to fail. Remove
+        // it, but do not remove anything that is not a socket.
+        if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            std::fs::remove_file(path)
+                .with_context(|| format!("failed to remove `{}`", path.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("failed to bind to `{}`", path.display()))?;
+        // smoelius: Requests are not authenticated. So only the user running the server may
+        // connect.
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("failed to set permissions of `{}`", path.display()))?;
+        eprintln!("Listening on {}", path.display());
+        for stream in listener.incoming() {
+            // smoelius: A failure to accept one connection (e.g., because the client disconnected
+            // first) should not stop the server.
+            let stream = match stream.and_then(|stream| {
+                stream.set_read_timeout(Some(TIMEOUT))?;
+                stream.set_write_timeout(Some(TIMEOUT))?;
+                Ok(stream)
+            }) {
+                Ok(stream) => stream,
+                Err(error) => {
+                    crate::warn!("failed to accept connection: {}", error);
+                    continue;
+                }
+            };
+            handle_connection(stream, &mut n_scans);
+        }
+        return Ok(());
+    }
+
+    let Some(addr) = serve_opts.listen else {
+        bail!("an address to listen on is required (e.g., `--listen 127.0.0.1:8080`)");
+    };
+    // smoelius: Requests are not authenticated, and a request can name any path on the server's
+    // machine. So do not accept connections from other machines.
+    ensure!(
+        addr.ip().is_loopback(),
+        "`{addr}` is not a loopback address"
+    );
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("failed to bind to `{addr}`"))?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream.and_then(|stream| {
+            stream.set_read_timeout(Some(TIMEOUT))?;
+            stream.set_write_timeout(Some(TIMEOUT))?;
+            Ok(stream)
+        }) {
+            Ok(stream) => stream,
+            Err(error) => {
+                crate::warn!("failed to accept connection: {}", error);
+                continue;
+            }
+        };
+        handle_connection(stream, &mut n_scans);
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: impl Read + Write, n_scans: &mut usize) {
+    let response = match read_request(&mut stream) {
+        Ok(request) if request.has_origin => Response::error(
+            "403 Forbidden",
+            "requests with an `Origin` header are not accepted",
+        ),
+        Ok(Request {
+            host: Some(host), ..
+        }) if !is_loopback_host(&host) => {
+            Response::error("403 Forbidden", &format!("`{host}` is not a loopback host"))
+        }
+        Ok(Request {
+            method, path, body, ..
+        }) => route(&method, &path, &body, n_scans),
+        Err(error) => Response::error("400 Bad Request", &format!("{error:#}")),
+    };
+    if let Err(error) = write_response(&mut stream, &response) {
+        crate::warn!("failed to write response: {}", error);
+    }
+}
+
+/// Returns true if `host` (the value of a `Host` header) names a loopback address
+fn is_loopback_host(host: &str) -> bool {
+    let name = if let Some(rest) = host.strip_prefix('[') {
+        rest.split_once(']').map_or(rest, |(name, _)| name)
+    } else {
+        host.rsplit_once(':').map_or(host, |(name, _)| name)
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|addr| addr.is_loopback())
+}
+
+fn route(method: &str, path: &str, body: &[u8], n_scans: &mut usize) -> Response {
+    match (method, path) {
+        ("GET", "/healthz") => Response::ok(health(*n_scans)),
+        ("POST", "/scan") => {
+            let request = match serde_json::from_slice::<ScanRequest>(body) {
+                Ok(request) => request,
+                Err(error) => {
+                    return Response::error(
+                        "400 Bad Request",
+                        &format!(
+                            "expected `{{\"path\": ...}}` or `{{\"lockfile\": ...}}`: {error}"
+                        ),
+                    );
+                }
+            };
+            *n_scans += 1;
+            match scan_request(&request) {
+                Ok(report) => Response::ok(report),
+                Err(error) => Response::error("500 Internal Server Error", &format!("{error:#}")),
+            }
+        }
+        (_, "/healthz" | "/scan") => Response::error("405 Method Not Allowed", method),
+        _ => Response::error("404 Not Found", path),
+    }
+}
+
+fn scan_request(request: &ScanRequest) -> Result<serde_json::Value> {
+    // smoelius: See tests/snapbox.rs for another use of this conditional initialization trick.
+    let tempdir: TempDir;
+
+    let dir = match request {
+        ScanRequest::Path(path) => path.as_path(),
+        ScanRequest::Lockfile(lockfile) => {
+            tempdir = lockfile_package(lockfile)?;
+            tempdir.path()
+        }
+    };
+
+    let metadata = MetadataCommand::new().current_dir(dir).exec()?;
+
+    reset_scan_state();
+
+    let Scan {
+        unmaintained_pkgs,
+        pinned_deps,
+        publish_anomalies,
+        package_errors,
+        report_metadata,
+        ..
+    } = scan(&metadata)?;
+
+    let report = Report::new(
+        Some(report_metadata),
+        &unmaintained_pkgs,
+        &pinned_deps,
+        &publish_anomalies,
+        &package_errors,
+    );
+
+    serde_json::to_value(report).map_err(Into::into)
+}
+
+/// Creates a package that depends upon the registry packages that `lockfile`'s root packages depend
+/// upon
+///
+/// The lockfile is copied into the package so that resolution selects the versions it records. The
+/// registry packages that the root packages do not depend upon are reached transitively, and so are
+/// not reported as direct dependencies.
+fn lockfile_package(lockfile: &str) -> Result<TempDir> {
+    let table = lockfile
+        .parse::<Table>()
+        .with_context(|| "failed to parse lockfile")?;
+    let dependencies = lockfile_dependencies(&table)?;
+
+    let tempdir = tempdir().with_context(|| "failed to create temporary directory")?;
+
+    let status = cargo::command()
+        .args([
+            "init",
+            "--name=lockfile-temp-package",
+            "--quiet",
+            "--vcs=none",
+        ])
+        .current_dir(&tempdir)
+        .status()
+        .with_context(|| "failed to create temporary package")?;
+    ensure!(status.success());
+
+    let manifest = tempdir.path().join("Cargo.toml");
+    let mut contents = std::fs::read_to_string(&manifest)
+        .with_context(|| format!("failed to read `{}`", manifest.display()))?;
+    contents.push_str(&dependencies);
+    write(&manifest, contents)
+        .with_context(|| format!("failed to write `{}`", manifest.display()))?;
+    write(tempdir.path().join("Cargo.lock"), lockfile)
+        .with_context(|| "failed to write lockfile")?;
+
+    Ok(tempdir)
+}
+
+/// Returns `[dependencies]` entries for the registry packages that `lockfile`'s root packages
+/// depend upon
+///
+/// A lockfile does not say which packages are its roots. But a root package has no `source`, and a
+/// registry package always has one.
+pub(crate) fn lockfile_dependencies(lockfile: &Table) -> Result<String> {
+    let packages = lockfile
+        .get("package")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    // smoelius: An element of a package's `dependencies` is of the form `name`, `name version`, or
+    // `name version (source)`. The version is given only when it is needed to disambiguate.
+    let root_dependencies = packages
+        .iter()
+        .filter(|package| package.get("source").is_none())
+        .filter_map(|package| package.get("dependencies").and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|dependency| {
+            let mut parts = dependency.split_whitespace();
+            (parts.next().unwrap_or_default(), parts.next())
+        })
+        .collect::<Vec<_>>();
+
+    let mut dependencies = String::new();
+    for (i, package) in packages.iter().enumerate() {
+        // smoelius: Path and git dependencies cannot be reconstructed from a lockfile alone.
+        let is_registry = package
+            .get("source")
+            .and_then(Value::as_str)
+            .is_some_and(|source| source.starts_with("registry+"));
+        if !is_registry {
+            continue;
+        }
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(Value::as_str),
+            package.get("version").and_then(Value::as_str),
+        ) else {
+            bail!("lockfile package lacks a name or version: {package}");
+        };
+        if !root_dependencies
+            .iter()
+            .any(|&(dependency_name, dependency_version)| {
+                dependency_name == name && dependency_version.is_none_or(|other| other == version)
+            })
+        {
+            continue;
+        }
+        // smoelius: A lockfile can contain multiple versions of a package. So each dependency is
+        // renamed.
+        writeln!(
+            dependencies,
+            "dependency-{i} = {{ package = {}, version = {} }}",
+            Value::from(name),
+            Value::from(format!("={version}"))
+        )?;
+    }
+
+    Ok(dependencies)
+}
+
+fn health(n_scans: usize) -> serde_json::Value {
+    let cache_state = on_disk_cache::with_cache(|cache| cache.state());
+    json!({
+        "status": "ok",
+        "scans": n_scans,
+        "on_disk_cache": cache_state,
+        "in_memory_caches": {
+            "forks": FORKS_CACHE.with_borrow(HashMap::len),
+            "general_status": GENERAL_STATUS_CACHE.with_borrow(HashMap::len),
+            "latest_version": LATEST_VERSION_CACHE.with_borrow(HashMap::len),
+            "membership_branch": MEMBERSHIP_BRANCH_CACHE.with_borrow(HashMap::len),
+            "membership_tag": MEMBERSHIP_TAG_CACHE.with_borrow(HashMap::len),
+            "repository": REPOSITORY_CACHE.with_borrow(lru::Lru::len),
+            "superseded": SUPERSEDED_CACHE.with_borrow(HashMap::len),
+            "timestamp": TIMESTAMP_CACHE.with_borrow(lru::Lru::len),
+        },
+    })
+}
+
+/// Reads an HTTP/1.1 request
+fn read_request(stream: impl Read) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("malformed request line: {request_line:?}");
+    };
+
+    let mut host = None;
+    let mut has_origin = false;
+    let mut content_length = 0;
+    for n_headers in 0.. {
+        let header = read_line(&mut reader)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        ensure!(
+            n_headers < MAX_HEADERS,
+            "request has more than {MAX_HEADERS} headers"
+        );
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_owned());
+            } else if name.eq_ignore_ascii_case("origin") {
+                has_origin = true;
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid content length: {value:?}"))?;
+            }
+        }
+    }
+    ensure!(
+        content_length <= MAX_BODY_LEN,
+        "request body exceeds {MAX_BODY_LEN} bytes"
+    );
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        host,
+        has_origin,
+        body,
+    })
+}
+
+/// Reads a line of at most [`MAX_LINE_LEN`] bytes
+fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    reader
+        .take(u64::try_from(MAX_LINE_LEN)? + 1)
+        .read_line(&mut line)?;
+    ensure!(
+        line.len() <= MAX_LINE_LEN,
+        "request line or header exceeds {MAX_LINE_LEN} bytes"
+    );
+    Ok(line)
+}
+
+fn write_response(mut stream: impl Write, response: &Response) -> Result<()> {
+    let body = serde_json::to_string_pretty(&response.body)?;
+    let message = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    );
+    stream.write_all(message.as_bytes()).map_err(Into::into)
+}
@@ -0,0 +1,158 @@
+//! What a repository is checked against: a shallow clone, or, with `--allow-tarball-fallback`, an
+//! unpacked tarball of the repository's default branch
+//!
+//! A tarball has no history, branches, or tags. So only its timestamp and the manifests it contains
+//! can be checked. The timestamp is the modification time of the tarball's top-level directory,
+//! which GitHub sets to the time of the commit the tarball was made from.
+
+use super::{
+    commit_timestamp, git,
+    manifests::{self, Manifest},
+    show, Url,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs::{metadata, read_dir, read_to_string},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// smoelius: Paths in the output of `git status --porcelain`, for a clone made with
+// `--no-checkout`, are preceded by this prefix.
+const LINE_PREFIX: &str = "D  ";
+
+thread_local! {
+    // smoelius: Canonical urls of repositories that were checked against tarballs.
+    static TARBALL_URLS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+pub(crate) enum Snapshot<'a> {
+    /// A shallow clone at the given directory
+    Git(&'a Path),
+    /// An unpacked tarball whose top-level directory is the given path
+    Tarball(PathBuf),
+}
+
+impl<'a> Snapshot<'a> {
+    /// Opens the clone or unpacked tarball at `repo_dir`
+    pub(crate) fn open(repo_dir: &'a Path) -> Result<Self> {
+        if !is_tarball(repo_dir) {
+            return Ok(Self::Git(repo_dir));
+        }
+        let mut entries = read_dir(repo_dir)
+            .with_context(|| format!("failed to read `{}`", repo_dir.display()))?
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("failed to read `{}`", repo_dir.display()))?;
+        let (Some(entry), None) = (entries.pop(), entries.pop()) else {
+            bail!(
+                "expected a single top-level directory in `{}`",
+                repo_dir.display()
+            );
+        };
+        Ok(Self::Tarball(entry.path()))
+    }
+
+    pub(crate) fn is_tarball(&self) -> bool {
+        matches!(self, Self::Tarball(_))
+    }
+
+    /// Returns the time of the commit the snapshot was made from
+    pub(crate) fn timestamp(&self) -> Result<SystemTime> {
+        match self {
+            Self::Git(repo_dir) => commit_timestamp(repo_dir, "HEAD"),
+            Self::Tarball(root) => metadata(root)
+                .and_then(|metadata| metadata.modified())
+                .with_context(|| format!("failed to get mtime of `{}`", root.display())),
+        }
+    }
+
+    /// Returns the manifests in the snapshot
+    pub(crate) fn manifests(&self) -> Result<Vec<Manifest>> {
+        match self {
+            Self::Git(repo_dir) => {
+                check_clone(repo_dir)?;
+                manifests::in_rev(repo_dir, "HEAD")
+            }
+            Self::Tarball(root) => {
+                let mut manifests = Vec::new();
+                find_manifests(root, Path::new(""), &mut manifests)?;
+                Ok(manifests)
+            }
+        }
+    }
+
+    /// Reads the file at `path`, relative to the snapshot's root
+    pub(crate) fn read(&self, path: &Path) -> Result<String> {
+        match self {
+            Self::Git(repo_dir) => show(repo_dir, "HEAD", path),
+            Self::Tarball(root) => {
+                let path_buf = root.join(path);
+                read_to_string(&path_buf)
+                    .with_context(|| format!("failed to read `{}`", path_buf.display()))
+            }
+        }
+    }
+}
+
+/// Panics if the clone at `repo_dir` has been modified (e.g., a file has been staged)
+fn check_clone(repo_dir: &Path) -> Result<()> {
+    let mut command = git::command();
+    command.args(["status", "--porcelain"]);
+    command.current_dir(repo_dir);
+    let output = command
+        .output()
+        .with_context(|| format!("command failed: {command:?}"))?;
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| format!("failed to read `{}`", repo_dir.display()))?;
+    for line in stdout.lines() {
+        assert!(
+            line.starts_with(LINE_PREFIX),
+            "cache is corrupt at `{}`",
+            repo_dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns true if `repo_dir` holds an unpacked tarball rather than a clone
+pub(crate) fn is_tarball(repo_dir: &Path) -> bool {
+    repo_dir.try_exists().unwrap_or_default()
+        && !repo_dir.join(".git").try_exists().unwrap_or_default()
+}
+
+/// Records that `url`'s repository was checked against a tarball
+pub(crate) fn record_tarball(url: Url) {
+    TARBALL_URLS.with_borrow_mut(|tarball_urls| {
+        tarball_urls.insert(url.canonical());
+    });
+}
+
+/// Returns true if `url`'s repository was checked against a tarball
+pub(crate) fn used_tarball(url: Url) -> bool {
+    TARBALL_URLS.with_borrow(|tarball_urls| tarball_urls.contains(&url.canonical()))
+}
+
+fn find_manifests(root: &Path, dir: &Path, manifests: &mut Vec<Manifest>) -> Result<()> {
+    let path_buf = root.join(dir);
+    let read_dir =
+        read_dir(&path_buf).with_context(|| format!("failed to read `{}`", path_buf.display()))?;
+    for result in read_dir {
+        let entry = result.with_context(|| format!("failed to read `{}`", path_buf.display()))?;
+        let file_type = entry
+            .file_type()
+            .with_context(|| format!("failed to stat `{}`", entry.path().display()))?;
+        let path = dir.join(entry.file_name());
+        if file_type.is_dir() {
+            find_manifests(root, &path, manifests)?;
+        } else if file_type.is_file() && entry.file_name() == "Cargo.toml" {
+            let size = entry
+                .metadata()
+                .with_context(|| format!("failed to stat `{}`", entry.path().display()))?
+                .len();
+            manifests.push(Manifest { path, size });
+        }
+    }
+    Ok(())
+}
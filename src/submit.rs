@@ -0,0 +1,228 @@
+//! Submission of reports to an aggregation endpoint (see `--submit`)
+//!
+//! Nothing is sent unless `--submit` is passed or `CARGO_UNMAINTAINED_SUBMIT_URL` is set. What is
+//! sent is the report that `--json --json-metadata` would print, except that the workspace's path
+//! is replaced by `--submit-label` wherever it appears, and so are other local directories (the
+//! cache, cargo's home, the temporary directory, and the user's home) by placeholders. A failed
+//! submission produces a warning and does not affect the exit status, unless `--require-submit` is
+//! passed.
+
+use super::{curl, on_disk_cache, on_disk_cache::USER_AGENT, opts, serialize, Url};
+use anyhow::{bail, ensure, Result};
+use flate2::{write::GzEncoder, Compression};
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const SUBMIT_URL_VAR: &str = "CARGO_UNMAINTAINED_SUBMIT_URL";
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of bytes of an unexpected response's body to include in the error
+const RESPONSE_EXCERPT_LEN: usize = 200;
+
+/// Returns the url given by `--submit` or `CARGO_UNMAINTAINED_SUBMIT_URL`, if either is set
+pub(crate) fn url() -> Option<String> {
+    opts::get().submit.clone().or_else(|| {
+        std::env::var(SUBMIT_URL_VAR)
+            .ok()
+            .filter(|url| !url.is_empty())
+    })
+}
+
+/// Returns an error if a `--submit-header` is not of the form `Name: value`
+///
+/// Checked before scanning, so that a typo is not discovered only after a long scan.
+pub(crate) fn check_headers() -> Result<()> {
+    for header in &opts::get().submit_header {
+        check_header(header)?;
+    }
+    Ok(())
+}
+
+/// Submits `report` to `url`
+///
+/// `workspace_root` is replaced by `--submit-label` wherever it appears in the report. A failure is
+/// returned as an error only if `--require-submit` was passed.
+pub(crate) fn submit(url: &str, report: &serialize::Report, workspace_root: &str) -> Result<()> {
+    let result = body(report, workspace_root).and_then(|body| post(url, &body));
+    match result {
+        Ok(code) => {
+            crate::verbose::note!("submitted report to `{}` (response code {})", url, code);
+            Ok(())
+        }
+        Err(error) if opts::get().require_submit => {
+            Err(error.context(format!("failed to submit report to `{url}`")))
+        }
+        Err(error) => {
+            crate::warn!("failed to submit report to `{}`: {:#}", url, error);
+            Ok(())
+        }
+    }
+}
+
+/// Returns the gzip-compressed body of the request that submits `report`
+fn body(report: &serialize::Report, workspace_root: &str) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(report)?;
+    relabel(
+        &mut value,
+        workspace_root,
+        opts::get().submit_label.as_deref(),
+    );
+    replace_local_dirs(&mut value, &local_dirs())?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &value)?;
+    encoder.finish().map_err(Into::into)
+}
+
+/// Sets the report's workspace root to `label`, and replaces `workspace_root` with `label` (or, if
+/// there is no label, with `.`) in every other string in `value`
+pub(crate) fn relabel(value: &mut Value, workspace_root: &str, label: Option<&str>) {
+    if !workspace_root.is_empty() {
+        let to = label.unwrap_or(".");
+        replace_in_strings(value, &|string| string.replace(workspace_root, to));
+    }
+    if let Some(metadata) = value.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.insert(
+            String::from("workspace_root"),
+            label.map_or(Value::Null, Value::from),
+        );
+    }
+}
+
+/// Returns the local directories that could appear in a report (e.g., in a warning), each with the
+/// placeholder that replaces it
+fn local_dirs() -> Vec<(PathBuf, &'static str)> {
+    let mut dirs = vec![
+        (
+            on_disk_cache::with_cache(|cache| cache.base_dir().to_path_buf()),
+            "<cache>",
+        ),
+        (std::env::temp_dir(), "<tmp>"),
+    ];
+    dirs.extend(home::cargo_home().ok().map(|dir| (dir, "<cargo-home>")));
+    dirs.extend(home::home_dir().map(|dir| (dir, "<home>")));
+    // smoelius: A path could appear in either its given or its canonical form (e.g., on macOS,
+    // `/var/folders/...` is a symlink to `/private/var/folders/...`).
+    let canonical = dirs
+        .iter()
+        .filter_map(|(dir, placeholder)| {
+            dir.canonicalize()
+                .ok()
+                .filter(|canonical| canonical != dir)
+                .map(|canonical| (canonical, *placeholder))
+        })
+        .collect::<Vec<_>>();
+    dirs.extend(canonical);
+    dirs
+}
+
+/// Replaces each of `dirs` with its placeholder in every string in `value`
+///
+/// Longer paths are replaced first, so that, e.g., a cache directory within the home directory is
+/// replaced by the cache's placeholder.
+pub(crate) fn replace_local_dirs(value: &mut Value, dirs: &[(PathBuf, &str)]) -> Result<()> {
+    let mut dirs = dirs
+        .iter()
+        .map(|(dir, placeholder)| (trim_separators(dir), *placeholder))
+        // smoelius: A directory of `/` would match every absolute path.
+        .filter(|(dir, _)| !dir.is_empty())
+        .collect::<Vec<_>>();
+    dirs.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.len()));
+    for (dir, placeholder) in dirs {
+        // smoelius: Match only whole path components, so that, e.g., `/home/alice` does not match
+        // `/home/alicesmith`.
+        let re = Regex::new(&format!(r"{}([/\\\s\x22'`]|$)", regex::escape(&dir)))?;
+        replace_in_strings(value, &|string| {
+            re.replace_all(string, format!("{placeholder}$1"))
+                .into_owned()
+        });
+    }
+    Ok(())
+}
+
+fn trim_separators(dir: &Path) -> String {
+    dir.to_string_lossy()
+        .trim_end_matches(['/', '\\'])
+        .to_owned()
+}
+
+fn replace_in_strings(value: &mut Value, f: &impl Fn(&str) -> String) {
+    match value {
+        Value::String(string) => {
+            *string = f(string);
+        }
+        Value::Array(values) => {
+            for value in values {
+                replace_in_strings(value, f);
+            }
+        }
+        Value::Object(map) => {
+            for value in map.values_mut() {
+                replace_in_strings(value, f);
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+/// POSTs `body` to `url`, and returns the response code
+fn post(url: &str, body: &[u8]) -> Result<u32> {
+    let mut headers = ::curl::easy::List::new();
+    headers.append("Content-Type: application/json")?;
+    headers.append("Content-Encoding: gzip")?;
+    // smoelius: Without this, curl may wait for a `100 Continue` response before sending a large
+    // body. Some servers never send one.
+    headers.append("Expect:")?;
+    for header in &opts::get().submit_header {
+        headers.append(header)?;
+    }
+
+    let mut handle = curl::handle(Url::from(url))?;
+    handle.useragent(USER_AGENT)?;
+    handle.timeout(TIMEOUT)?;
+    handle.connect_timeout(CONNECT_TIMEOUT)?;
+    // smoelius: A redirect could send the report, and the headers given by --submit-header, to a
+    // server other than the one named. So a redirect is treated as a failed submission.
+    handle.follow_location(false)?;
+    handle.post(true)?;
+    handle.post_fields_copy(body)?;
+    handle.http_headers(headers)?;
+
+    let mut response = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            response.extend_from_slice(data);
+            Ok(data.len())
+        })?;
+        transfer.perform()?;
+    }
+    let code = handle.response_code()?;
+    if !(200..300).contains(&code) {
+        let excerpt =
+            String::from_utf8_lossy(&response[..response.len().min(RESPONSE_EXCERPT_LEN)])
+                .trim()
+                .to_owned();
+        if excerpt.is_empty() {
+            bail!("unexpected response code {code}");
+        }
+        bail!("unexpected response code {code}: {excerpt}");
+    }
+    Ok(code)
+}
+
+// smoelius: The header itself is not included in the error, as it may contain a secret.
+fn check_header(header: &str) -> Result<()> {
+    let name = header.split_once(':').map(|(name, _)| name.trim());
+    ensure!(
+        name.is_some_and(|name| !name.is_empty() && !name.contains(char::is_whitespace)),
+        "--submit-header must be of the form `Name: value`"
+    );
+    Ok(())
+}
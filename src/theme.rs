@@ -0,0 +1,87 @@
+//! `--theme` and the `NO_COLOR` convention
+//!
+//! The default theme colors findings on a yellow-to-red gradient, which deuteranopic users cannot
+//! distinguish. The `colorblind` theme uses a blue-to-orange ramp instead, and the `mono` theme
+//! uses no color at all. Both prefix findings with symbols, so that color is never the only
+//! indication of severity.
+
+use super::opts;
+use clap::ValueEnum;
+use std::ffi::OsStr;
+use termcolor::{Color, ColorChoice, ColorSpec};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub(crate) enum Theme {
+    Default,
+    Colorblind,
+    Mono,
+}
+
+/// Ends of the `colorblind` theme's ramp, from the Okabe-Ito palette
+const BLUE: (u8, u8, u8) = (0, 114, 178);
+const ORANGE: (u8, u8, u8) = (230, 159, 0);
+
+/// Returns the color choice with which to write to stdout
+///
+/// See <https://no-color.org/>.
+pub(crate) fn color_choice() -> ColorChoice {
+    color_choice_with(opts::get().color, std::env::var_os("NO_COLOR").as_deref())
+}
+
+pub(crate) fn color_choice_with(color: ColorChoice, no_color: Option<&OsStr>) -> ColorChoice {
+    // smoelius: Only an explicit `--color=always` overrides `NO_COLOR`.
+    if color == ColorChoice::Auto && no_color.is_some_and(|value| !value.is_empty()) {
+        ColorChoice::Never
+    } else {
+        color
+    }
+}
+
+/// Returns the color for a finding of `severity`, which ranges from 0 (least severe) to
+/// `u8::MAX` (most severe)
+pub(crate) fn ramp(theme: Theme, severity: u8) -> Option<Color> {
+    match theme {
+        Theme::Default => Some(Color::Rgb(u8::MAX, u8::MAX - severity, 0)),
+        Theme::Colorblind => {
+            let lerp = |from: u8, to: u8| {
+                let value = (u32::from(from) * u32::from(u8::MAX - severity)
+                    + u32::from(to) * u32::from(severity))
+                    / u32::from(u8::MAX);
+                u8::try_from(value).unwrap_or(u8::MAX)
+            };
+            Some(Color::Rgb(
+                lerp(BLUE.0, ORANGE.0),
+                lerp(BLUE.1, ORANGE.1),
+                lerp(BLUE.2, ORANGE.2),
+            ))
+        }
+        Theme::Mono => None,
+    }
+}
+
+/// Returns the spec with which to write a finding of `severity`
+///
+/// Without color, the `mono` theme uses bold.
+pub(crate) fn spec(severity: Option<u8>) -> ColorSpec {
+    let theme = opts::get().theme;
+    let mut spec = ColorSpec::new();
+    spec.set_fg(severity.and_then(|severity| ramp(theme, severity)))
+        .set_bold(theme == Theme::Mono && severity.is_some());
+    spec
+}
+
+/// Returns the color with which to write urls
+pub(crate) fn url_color() -> Option<Color> {
+    (opts::get().theme != Theme::Mono).then_some(Color::Blue)
+}
+
+/// Returns the prefix for a finding, which is empty with the default theme
+///
+/// `error` is true for findings that default to red, e.g., archived or nonexistent repositories.
+pub(crate) fn symbol(theme: Theme, error: bool) -> &'static str {
+    match theme {
+        Theme::Default => "",
+        Theme::Colorblind | Theme::Mono if error => "✗ ",
+        Theme::Colorblind | Theme::Mono => "! ",
+    }
+}
@@ -1,9 +1,52 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::{cell::RefCell, collections::HashMap};
 
 #[allow(clippy::unwrap_used)]
 static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^https://[^/]*/[^/]*/[^/]*").unwrap());
 
+// smoelius: Each of the following matches a url naming an owner (i.e., a user or organization)
+// rather than a repository. crates.io logins and team names are GitHub logins and organizations.
+#[allow(clippy::unwrap_used)]
+static OWNER_RES: Lazy<[(Regex, Host); 5]> = Lazy::new(|| {
+    [
+        (r"^https://github\.com/([^/]+)$", Host::GitHub),
+        (r"^https://github\.com/orgs/([^/]+)(/.*)?$", Host::GitHub),
+        (r"^https://gitlab\.com/([^/]+)$", Host::GitLab),
+        (
+            r"^https://crates\.io/teams/github:([^:/]+):[^/]+$",
+            Host::GitHub,
+        ),
+        (r"^https://crates\.io/users/([^/]+)$", Host::GitHub),
+    ]
+    .map(|(re, host)| (Regex::new(re).unwrap(), host))
+});
+
+thread_local! {
+    static REPOSITORY_OVERRIDES: RefCell<HashMap<String, Vec<&'static str>>> = RefCell::new(HashMap::new());
+    static ASSUMED_FRESH_PREFIXES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// smoelius: Hosts whose repository paths are case-insensitive.
+const CASE_INSENSITIVE_HOSTS: &[&str] = &["github.com", "gitlab.com"];
+
+// smoelius: Path components that begin a location within a repository, e.g.,
+// `https://github.com/owner/repo/tree/master/crate` or `https://gitlab.com/owner/repo/-/tree/main`.
+const IN_REPOSITORY_COMPONENTS: &[&str] = &["/-/", "/blob/", "/tree/"];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Host {
+    GitHub,
+    GitLab,
+}
+
+/// An owner (i.e., a user or organization) named by a url in place of a repository
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Owner<'a> {
+    pub host: Host,
+    pub name: &'a str,
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub(crate) struct Url<'a>(&'a str);
 
@@ -22,9 +65,105 @@ impl<'a> Url<'a> {
             .map(|captures| captures.get(0).unwrap().as_str().into())
     }
 
+    /// Returns the owner that the url names, if the url names an owner rather than a repository,
+    /// e.g., `https://github.com/rust-lang`
+    #[allow(clippy::unwrap_used)]
+    pub(crate) fn owner(self) -> Option<Owner<'a>> {
+        let url = self.trim_trailing_slash();
+        OWNER_RES.iter().find_map(|(re, host)| {
+            re.captures(url.0).map(|captures| Owner {
+                host: *host,
+                name: captures.get(1).unwrap().as_str(),
+            })
+        })
+    }
+
     pub(crate) fn trim_trailing_slash(self) -> Self {
         self.0.strip_suffix('/').map_or(self, Self::from)
     }
+
+    /// Returns the url's canonical form; see [`canonical_url`]
+    pub(crate) fn canonical(self) -> String {
+        canonical_url(self.0)
+    }
+}
+
+/// Returns a canonical form of a repository url, so that urls that name the same repository can be
+/// recognized as such
+///
+/// The canonical form is obtained by:
+/// - decoding percent-encoded unreserved characters (e.g., `%2D` becomes `-`)
+/// - lowercasing the scheme and host, and, for hosts known to treat paths case-insensitively (e.g.,
+///   github.com), the path
+/// - removing any query or fragment
+/// - for `http` and `https` urls, removing any location within the repository (e.g.,
+///   `/tree/master/crate`)
+/// - removing trailing slashes and a trailing `.git`
+///
+/// The canonical form is meant for comparing urls. It is not necessarily a url that can be cloned.
+/// For example, a `file://` url to a bare repository typically ends with `.git`.
+#[must_use]
+pub fn canonical_url(url: &str) -> String {
+    let url = percent_decode_unreserved(url);
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let (host, mut path) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+    let scheme = scheme.to_lowercase();
+    let host = host.to_lowercase();
+
+    // smoelius: A local path could legitimately contain, e.g., `/tree/`.
+    if scheme == "http" || scheme == "https" {
+        if let Some(i) = IN_REPOSITORY_COMPONENTS
+            .iter()
+            .filter_map(|component| path.find(component))
+            .min()
+        {
+            path = &path[..i];
+        }
+    }
+    path = path.trim_end_matches('/');
+    path = path.strip_suffix(".git").unwrap_or(path);
+    path = path.trim_end_matches('/');
+
+    let path = if CASE_INSENSITIVE_HOSTS.contains(&host.as_str()) {
+        path.to_lowercase()
+    } else {
+        path.to_owned()
+    };
+
+    if scheme.is_empty() {
+        format!("{host}{path}")
+    } else {
+        format!("{scheme}://{host}{path}")
+    }
+}
+
+/// Decodes percent-encoded characters that need no encoding, i.e., those that RFC 3986 calls
+/// "unreserved"
+///
+/// Decoding other characters (e.g., `%2F`, i.e., `/`) could change the url's meaning.
+fn percent_decode_unreserved(url: &str) -> String {
+    let mut decoded = String::with_capacity(url.len());
+    let mut rest = url;
+    while let Some(i) = rest.find('%') {
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let c = rest
+            .get(1..3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .map(char::from)
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+        if let Some(c) = c {
+            decoded.push(c);
+            rest = &rest[3..];
+        } else {
+            decoded.push('%');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+    decoded
 }
 
 impl std::fmt::Display for Url<'_> {
@@ -39,23 +178,106 @@ impl<'a> From<&'a str> for Url<'a> {
     }
 }
 
-/// Returns up to two urls associated with `pkg`:
+/// Sets the urls to try before a package's repository url, keyed by package name
 ///
+/// The urls come from `workspace.metadata.unmaintained.repositories`.
+pub(crate) fn set_repository_overrides(overrides: HashMap<String, Vec<String>>) {
+    // smoelius: The urls are leaked so that `urls` can return them alongside urls borrowed from a
+    // `Package`. They are set once per scan, so the effect of leaking them is insignificant.
+    let overrides = overrides
+        .into_iter()
+        .map(|(name, urls)| {
+            let urls = urls
+                .into_iter()
+                .map(|url| &*String::leak(url))
+                .collect::<Vec<_>>();
+            (name, urls)
+        })
+        .collect();
+    REPOSITORY_OVERRIDES.with_borrow_mut(|repository_overrides| {
+        *repository_overrides = overrides;
+    });
+}
+
+/// Returns true if `url` is one of the urls given for `pkg` in the workspace metadata
+pub(crate) fn is_repository_override(pkg: &cargo_metadata::Package, url: Url) -> bool {
+    repository_overrides(pkg)
+        .into_iter()
+        .any(|url_string| Url::from(url_string).canonical() == url.canonical())
+}
+
+/// Sets the url prefixes of repositories assumed to be fresh
+///
+/// The prefixes come from `--assume-fresh` and `workspace.metadata.unmaintained.assume_fresh`.
+pub(crate) fn set_assumed_fresh_prefixes(prefixes: Vec<String>) {
+    ASSUMED_FRESH_PREFIXES.with_borrow_mut(|assumed_fresh_prefixes| {
+        *assumed_fresh_prefixes = prefixes;
+    });
+}
+
+/// Returns true if `pkg`'s repository is assumed to be fresh
+pub(crate) fn is_assumed_fresh(pkg: &cargo_metadata::Package) -> bool {
+    let Some(url_string) = repository(pkg) else {
+        return false;
+    };
+    ASSUMED_FRESH_PREFIXES.with_borrow(|assumed_fresh_prefixes| {
+        assumed_fresh_prefixes
+            .iter()
+            .any(|prefix| has_prefix(url_string, prefix))
+    })
+}
+
+/// Returns true if `url`'s canonical form begins with `prefix`'s, where the prefix ends at a path
+/// component boundary
+///
+/// For example, `https://github.com/Owner` is a prefix of `https://github.com/owner/repo.git`, but
+/// `https://github.com/own` is not.
+pub(crate) fn has_prefix(url: &str, prefix: &str) -> bool {
+    let url = canonical_url(url);
+    let prefix = canonical_url(prefix);
+    url.strip_prefix(&prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn repository_overrides(pkg: &cargo_metadata::Package) -> Vec<&'static str> {
+    REPOSITORY_OVERRIDES.with_borrow(|repository_overrides| {
+        repository_overrides
+            .get(&pkg.name)
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+/// Returns the url of `pkg`'s repository, i.e., the first url given for `pkg` in the workspace
+/// metadata, if any, and the repository url stored in the [`cargo_metadata::Package`] otherwise
+pub(crate) fn repository(pkg: &cargo_metadata::Package) -> Option<&str> {
+    repository_overrides(pkg)
+        .first()
+        .copied()
+        .or(pkg.repository.as_deref())
+}
+
+/// Returns the urls associated with `pkg`, in the order they should be tried:
+///
+/// - the urls given for `pkg` in the workspace metadata, if any
 /// - the repository url stored in the [`cargo_metadata::Package`]
-/// - a "shortened" url consisting of just the domain and two fragments
-pub(crate) fn urls(pkg: &cargo_metadata::Package) -> impl IntoIterator<Item = Url> {
-    let mut urls = Vec::new();
+///
+/// Each url is followed by a "shortened" url consisting of just the domain and two fragments, if
+/// the two differ.
+pub(crate) fn urls(pkg: &cargo_metadata::Package) -> impl IntoIterator<Item = Url<'_>> {
+    let mut urls = Vec::<Url>::new();
 
-    if let Some(url_string) = &pkg.repository {
+    for url_string in repository_overrides(pkg)
+        .into_iter()
+        .chain(pkg.repository.as_deref())
+    {
         // smoelius: Without the use of `trim_trailing_slash`, whether a timestamp was obtained via
         // the GitHub API or a shallow clone would be distinguishable.
-        let url = Url::from(url_string.as_str()).trim_trailing_slash();
-
-        urls.push(url);
+        let url = Url::from(url_string).trim_trailing_slash();
 
-        if let Some(shortened_url) = url.shorten() {
-            if url != shortened_url {
-                urls.push(shortened_url);
+        for url in std::iter::once(url).chain(url.shorten()) {
+            if !urls.contains(&url) {
+                urls.push(url);
             }
         }
     }
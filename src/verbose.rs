@@ -62,6 +62,13 @@ macro_rules! wrap {
     }};
 }
 
+macro_rules! note {
+    ($fmt:expr, $($arg:tt)*) => {{
+        $crate::verbose::newline!();
+        $crate::verbose::__eprintln!(concat!("    ", $fmt), $($arg)*);
+    }};
+}
+
 #[allow(unused_macros)]
 macro_rules! update {
     ($fmt:expr) => {
@@ -77,4 +84,4 @@ macro_rules! update {
 }
 
 // smoelius: "The trick": https://stackoverflow.com/a/31749071
-pub(crate) use {__eprint, __eprintln, newline, wrap};
+pub(crate) use {__eprint, __eprintln, newline, note, wrap};
@@ -0,0 +1,54 @@
+//! Warnings collected for JSON reports (see `--json`)
+//!
+//! Every warning emitted through [`crate::warn!`] is recorded here as well as printed, so that a
+//! report can say what a scan warned about, e.g., that a package's latest version could not be
+//! determined. Warnings are recorded even with `--no-warnings`, which affects only what is printed.
+//!
+//! A warning emitted while a package is being checked is associated with that package.
+
+use std::{
+    cell::RefCell,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+#[derive(Clone, Debug)]
+pub(crate) struct Warning {
+    pub message: String,
+    /// The name of the package being checked when the warning was emitted, if any
+    pub package: Option<String>,
+}
+
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static PACKAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Records a warning, associating it with the package being checked, if any
+pub(crate) fn record(message: String) {
+    let package = PACKAGE.with_borrow(Clone::clone);
+    lock().push(Warning { message, package });
+}
+
+/// Calls `f`, associating the warnings it emits with the package `name`
+pub(crate) fn with_package<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let previous = PACKAGE.replace(Some(name.to_owned()));
+    let result = f();
+    PACKAGE.set(previous);
+    result
+}
+
+/// Returns the warnings recorded so far, in the order in which they were emitted
+pub(crate) fn collected() -> Vec<Warning> {
+    lock().clone()
+}
+
+/// Forgets the warnings recorded so far, e.g., before a scan that `serve` performs
+#[cfg(feature = "server")]
+pub(crate) fn clear() {
+    lock().clear();
+}
+
+fn lock() -> MutexGuard<'static, Vec<Warning>> {
+    WARNINGS.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,176 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{fs::write, path::Path, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+const OLD_DATE: &str = "2015-01-01T00:00:00Z";
+
+// smoelius: `dependency`'s repository has an old tagged commit followed by a fresh, untagged one.
+// By commit, the repository is current. By tag, it is years old.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn tag_age_diverges_from_commit_age() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("file://{}", origin.display());
+
+    write_package(
+        &repo,
+        "dependency",
+        &format!("repository = \"{repository}\"\n"),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git_at(OLD_DATE, &repo, &["commit", "--quiet", "--message=Release"])?;
+    git(&repo, &["tag", "v0.1.0"])?;
+    write(repo.join("README.md"), "")?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Bump dependencies"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--age-source=commit"],
+    )?;
+    ensure!(output.status.success(), "{output:#?}");
+
+    for age_source in ["tag", "release"] {
+        let output = cargo_unmaintained(
+            &dependent,
+            &cache_home,
+            &[
+                "--only=stale",
+                &format!("--age-source={age_source}"),
+                "--verbose",
+            ],
+        )?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+        ensure!(
+            stdout
+                .lines()
+                .any(|line| line.starts_with(&format!("dependency ({repository} updated "))),
+            "{stdout}"
+        );
+        ensure!(
+            stderr.contains(&format!(
+                "age of `{repository}` computed from tag `v0.1.0` (2015-01-01T00:00:00Z)"
+            )),
+            "{stderr}"
+        );
+
+        let output = cargo_unmaintained(
+            &dependent,
+            &cache_home,
+            &[
+                "--only=stale",
+                &format!("--age-source={age_source}"),
+                "--json",
+            ],
+        )?;
+        let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+        ensure!(report["packages"][0]["age_source"] == "tag", "{report}");
+    }
+
+    Ok(())
+}
+
+// smoelius: A repository with no tags falls back to its latest commit.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn untagged_falls_back_to_commit() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(
+        &repo,
+        "dependency",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git_at(
+        OLD_DATE,
+        &repo,
+        &["commit", "--quiet", "--message=Initial commit"],
+    )?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--age-source=tag", "--json"],
+    )?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(report["packages"][0]["age_source"] == "commit", "{report}");
+
+    Ok(())
+}
+
+/// Runs `git` in `dir` with the author and committer dates set to `date`
+fn git_at(date: &str, dir: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+        ])
+        .args(args)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .current_dir(dir)
+        .status()?;
+    ensure!(status.success());
+    Ok(())
+}
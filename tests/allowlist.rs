@@ -0,0 +1,127 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{create_dir_all, OpenOptions},
+    io::Write,
+    process::Command,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: `dependency`'s repository is stale. Once the workspace allowlists the directory
+// containing the repository, `dependency` is no longer reported.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn workspace_allowlist() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origins = tempdir.path().join("origins");
+    let origin = origins.join("dependency.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("file://{}", origin.display());
+
+    write_package(
+        &repo,
+        "dependency",
+        &format!("repository = \"{repository}\"\n"),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+            "commit",
+            "--quiet",
+            "--message=Initial commit",
+        ])
+        .env("GIT_AUTHOR_DATE", "2015-01-01T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2015-01-01T00:00:00Z")
+        .current_dir(&repo)
+        .status()?;
+    ensure!(status.success());
+    create_dir_all(&origins)?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--no-builtin-allowlist"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(1), "{stdout}");
+    ensure!(
+        stdout.starts_with(
+            "Policy: default (--max-age=365 --outdated-dep-grace=365 --no-builtin-allowlist)\n"
+        ),
+        "{stdout}"
+    );
+
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(dependent.join("Cargo.toml"))?;
+    writeln!(
+        manifest,
+        "\n[workspace.metadata.unmaintained]\nallowlist = [\"file://{}/*\"]",
+        origins.display()
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--only=stale", "--verbose"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stdout}{stderr}");
+    ensure!(stdout.is_empty(), "{stdout}");
+    ensure!(
+        stderr.contains(&format!(
+            "`dependency` appears unmaintained, but its repository `{repository}` matches \
+             `file://{}/*` from workspace metadata",
+            origins.display()
+        )),
+        "{stderr}"
+    );
+
+    // smoelius: In JSON output, the package is listed among those allowlisted.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--only=stale", "--json"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.success(), "{stdout}");
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    ensure!(
+        report["packages"] == serde_json::json!([])
+            && report["allowlisted"]
+                == serde_json::json!([{
+                    "name": "dependency",
+                    "version": "0.1.0",
+                    "repository": repository,
+                    "pattern": format!("file://{}/*", origins.display()),
+                    "origin": "workspace metadata",
+                }]),
+        "{report}"
+    );
+
+    Ok(())
+}
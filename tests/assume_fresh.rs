@@ -0,0 +1,100 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained, write_package};
+
+// smoelius: `unreachable`'s repository does not exist, as though it were on a host that cannot be
+// reached. So `unreachable` is reported unless its repository is assumed fresh.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn assume_fresh_flag() -> Result<()> {
+    let (tempdir, dependent) = create_test_workspace()?;
+    let prefix = format!("file://{}/unreachable-host", tempdir.path().display());
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache"), &[])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache"),
+        &["--verbose", &format!("--assume-fresh={prefix}")],
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(!stderr.contains("shallow clone"), "{stderr}");
+
+    // smoelius: A prefix must end at a path component boundary.
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache"),
+        &[&format!("--assume-fresh={prefix}/own")],
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(
+        stderr.lines().any(|line| line
+            == format!(
+                "warning: repositories beginning with `{prefix}/own` are assumed fresh, but no \
+                 package's repository begins with `{prefix}/own`"
+            )),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn assume_fresh_metadata() -> Result<()> {
+    let (tempdir, dependent) = create_test_workspace()?;
+    // smoelius: Canonical urls are compared, so a trailing slash does not matter.
+    let prefix = format!(
+        "file://{}/unreachable-host/owner/",
+        tempdir.path().display()
+    );
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(dependent.join("Cargo.toml"))?;
+    writeln!(
+        file,
+        "\n[workspace.metadata.unmaintained]\nassume_fresh = [\"{prefix}\"]"
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache"), &[])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+
+    Ok(())
+}
+
+fn create_test_workspace() -> Result<(TempDir, PathBuf)> {
+    let tempdir = tempdir()?;
+
+    let dependency = tempdir.path().join("unreachable");
+    write_package(
+        &dependency,
+        "unreachable",
+        &format!(
+            "repository = \"file://{}/unreachable-host/owner/repo.git\"\n",
+            tempdir.path().display()
+        ),
+    )?;
+
+    let dependent = tempdir.path().join("dependent");
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nunreachable = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    Ok((tempdir, dependent))
+}
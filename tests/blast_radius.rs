@@ -0,0 +1,95 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: `flagged`'s repository does not contain a package named `flagged`. So `flagged` is
+// reported. `a` and `b` depend upon `flagged` directly, and `c` depends upon it through `a`. The
+// workspace member `dependent` depends upon `b` and `c`, but is not counted.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn blast_radius() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let cache_home = tempdir.path().join("cache");
+
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &tempdir.path().join("flagged"),
+        "flagged",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    write_package(
+        &tempdir.path().join("a"),
+        "a",
+        &path_dependencies(&["flagged"]),
+    )?;
+    write_package(
+        &tempdir.path().join("b"),
+        "b",
+        &path_dependencies(&["flagged"]),
+    )?;
+    write_package(&tempdir.path().join("c"), "c", &path_dependencies(&["a"]))?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!("publish = false\n{}", path_dependencies(&["b", "c"])),
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(!stdout.contains("blast radius"), "{stdout}");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--blast-radius"])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line == "    blast radius: 3 packages (a, b, c)"),
+        "{stdout}"
+    );
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--blast-radius", "--json"])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    let names = report["packages"][0]["blast_radius"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|dependent| dependent["name"].as_str())
+        .collect::<Vec<_>>();
+    ensure!(names == [Some("a"), Some("b"), Some("c")], "{report}");
+
+    Ok(())
+}
+
+/// Returns a `[dependencies]` table naming each of `deps`, which are siblings of the package
+fn path_dependencies(deps: &[&str]) -> String {
+    let dependencies = deps
+        .iter()
+        .map(|dep| format!("{dep} = {{ path = \"../{dep}\" }}\n"))
+        .collect::<String>();
+    format!("\n[dependencies]\n{dependencies}")
+}
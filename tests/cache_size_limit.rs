@@ -0,0 +1,84 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::fs::read_dir;
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, package_filename, write_package};
+
+// smoelius: Each of two dependents depends on a package with its own repository. The first
+// dependent is checked, and then the second is checked with a tiny size limit. The first
+// dependent's repository is the older and was not used during the second run, so it should be
+// evicted. The second dependent's repository was used during the second run, so it should be kept
+// even though the limit is still exceeded.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn evict_least_recently_used() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+
+    let mut dependents = Vec::new();
+    for name in ["older", "newer"] {
+        let repo = tempdir.path().join(format!("{name}-repo"));
+        let origin = tempdir.path().join(format!("{name}.git"));
+        write_package(
+            &repo,
+            name,
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        let dependent = tempdir.path().join(format!("{name}-dependent"));
+        write_package(
+            &dependent,
+            &format!("{name}-dependent"),
+            &format!(
+                "publish = false\n\n[dependencies]\n{name} = {{ path = \"{}\" }}\n",
+                repo.display()
+            ),
+        )?;
+        dependents.push(dependent);
+    }
+
+    let output = cargo_unmaintained(&dependents[0], &cache_home, &[])?;
+    ensure!(output.status.success(), "{output:#?}");
+
+    let cache_dir = cache_home.join("cargo-unmaintained/v2");
+    ensure!(read_dir(cache_dir.join("repositories"))?.count() == 1);
+
+    let output = cargo_unmaintained(
+        &dependents[1],
+        &cache_home,
+        &["--cache-size-limit=1", "--verbose"],
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains(&format!(
+            "Evicted `file://{}` from the on-disk cache",
+            tempdir.path().join("older.git").display()
+        )),
+        "{stderr}"
+    );
+
+    ensure!(read_dir(cache_dir.join("repositories"))?.count() == 1);
+    let entries_dir = cache_dir.join("entries");
+    ensure!(!entries_dir.join(package_filename("older")).try_exists()?);
+    ensure!(entries_dir.join(package_filename("newer")).try_exists()?);
+
+    Ok(())
+}
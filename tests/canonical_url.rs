@@ -0,0 +1,82 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir, read_dir},
+    process::Command,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{git, write_package};
+
+// smoelius: The repository contains two packages. One names the repository with a trailing `.git`
+// and one without (via a symlink to the bare repository). Both urls have the same canonical form.
+// So the repository should be cloned only once.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn one_clone_per_canonical_url() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    write_package(
+        &repo.join("first"),
+        "first",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    write_package(
+        &repo.join("second"),
+        "second",
+        &format!(
+            "repository = \"file://{}\"\n",
+            origin.with_extension("").display()
+        ),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&origin, origin.with_extension(""))?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nfirst = {{ path = \"{}\" }}\nsecond = {{ path = \
+             \"{}\" }}\n",
+            repo.join("first").display(),
+            repo.join("second").display()
+        ),
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never"])
+        .env("XDG_CACHE_HOME", &cache_home)
+        .current_dir(&dependent)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stdout}{stderr}");
+
+    let repositories = read_dir(cache_home.join("cargo-unmaintained/v2/repositories"))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    ensure!(repositories.len() == 1, "{repositories:#?}");
+
+    Ok(())
+}
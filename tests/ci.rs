@@ -1,7 +1,11 @@
 use assert_cmd::Command;
 use regex::Regex;
 use similar_asserts::SimpleDiff;
-use std::{env::remove_var, fs::read_to_string, path::Path};
+use std::{
+    env::{remove_var, var},
+    fs::{read_to_string, write},
+    path::Path,
+};
 use tempfile::tempdir;
 
 mod util;
@@ -191,7 +195,20 @@ fn below_cut_line(s: &str) -> Option<&str> {
     split_at_cut_line(s).map(|(_, below)| below)
 }
 
+// smoelius: The README's usage block is the `--help` output of a build with default features.
+// Other features add options (e.g., `serve` and `--compare-rustsec`), which are documented in the
+// README's other sections instead.
 #[cfg_attr(target_os = "windows", ignore)]
+#[cfg_attr(
+    any(
+        not(feature = "on-disk-cache"),
+        not(feature = "lock-index"),
+        feature = "keyring",
+        feature = "rustsec",
+        feature = "server",
+    ),
+    ignore = "the usage block reflects a build with default features"
+)]
 #[test]
 fn readme_contains_usage() {
     let readme = read_to_string("README.md").unwrap();
@@ -215,6 +232,47 @@ fn readme_contains_usage() {
     );
 }
 
+// smoelius: Like the README's usage block, the committed man page is generated by a build with
+// default features.
+#[cfg_attr(target_os = "windows", ignore)]
+#[cfg_attr(
+    any(
+        not(feature = "on-disk-cache"),
+        not(feature = "lock-index"),
+        feature = "keyring",
+        feature = "rustsec",
+        feature = "server",
+    ),
+    ignore = "the committed man page reflects a build with default features"
+)]
+#[test]
+fn man_page_is_current() {
+    const PATH: &str = "cargo-unmaintained.1";
+
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join(PATH);
+
+    Command::cargo_bin("cargo-unmaintained")
+        .unwrap()
+        .args(["unmaintained", "--generate-man"])
+        .arg(&path)
+        .assert()
+        .success();
+
+    let actual = read_to_string(path).unwrap();
+
+    if var("BLESS").is_ok() {
+        write(PATH, actual).unwrap();
+    } else {
+        let expected = read_to_string(PATH).unwrap();
+        assert!(
+            expected == actual,
+            "{}",
+            SimpleDiff::from_str(&expected, &actual, "left", "right")
+        );
+    }
+}
+
 #[test]
 fn readme_reference_links_are_sorted() {
     let re = Regex::new(r"^\[[^\]]*\]:").unwrap();
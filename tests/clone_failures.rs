@@ -0,0 +1,72 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fmt::Write,
+    fs::{create_dir, write},
+    process::Command,
+};
+use tempfile::tempdir;
+
+// smoelius: Each dependency names the same nonexistent repository. No network access is required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn clone_failures() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+    let origin = tempdir.path().join("missing.git");
+
+    let mut dependencies = String::new();
+    for name in ["a", "b", "c"] {
+        let dir = tempdir.path().join(name);
+        create_dir(&dir)?;
+        write(
+            dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \
+                 \"2021\"\nrepository = \"file://{}\"\n",
+                origin.display()
+            ),
+        )?;
+        create_dir(dir.join("src"))?;
+        write(dir.join("src/lib.rs"), "")?;
+        writeln!(dependencies, "{name} = {{ path = \"{}\" }}", dir.display())?;
+    }
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+             false\n\n[dependencies]\n{dependencies}"
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never", "--only=stale"])
+        .env("XDG_CACHE_HOME", &cache_home)
+        .current_dir(&dependent)
+        .output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+
+    let warnings = stderr
+        .lines()
+        .filter(|line| line.starts_with("warning: failed to clone"))
+        .collect::<Vec<_>>();
+    ensure!(
+        warnings
+            == [format!(
+                "warning: failed to clone `file://{}` (affects 3 packages): [",
+                origin.display()
+            )],
+        "{stderr}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir, create_dir_all, write, OpenOptions},
+    io::Write,
+    process::{Command, Output},
+};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2000-0001"
+package = "unused-dependency"
+date = "2000-01-01"
+informational = "unmaintained"
+
+[versions]
+patched = []
+```
+
+# `unused-dependency` is unmaintained
+"#;
+
+// smoelius: The dependency's repository does not contain a package named `renamed-dependency`. So
+// the dependency is flagged by cargo-unmaintained. The advisory database contains an advisory for
+// a package that the workspace does not use. No network access is required.
+struct Fixture {
+    tempdir: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let tempdir = tempdir()?;
+        let repo = tempdir.path().join("repo");
+        let origin = tempdir.path().join("origin.git");
+        let dependency = tempdir.path().join("dependency");
+        let dependent = tempdir.path().join("dependent");
+        let advisory_db = tempdir.path().join("advisory-db/crates/unused-dependency");
+
+        create_dir(&repo)?;
+        write_package(&repo, "original-name", "")?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        create_dir(&dependency)?;
+        write_package(
+            &dependency,
+            "renamed-dependency",
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+
+        create_dir(&dependent)?;
+        write_package(
+            &dependent,
+            "dependent",
+            &format!(
+                "publish = false\n\n[dependencies]\nrenamed-dependency = {{ path = \"{}\" }}\n",
+                dependency.display()
+            ),
+        )?;
+
+        create_dir_all(&advisory_db)?;
+        write(advisory_db.join("RUSTSEC-2000-0001.md"), ADVISORY)?;
+
+        Ok(Self { tempdir })
+    }
+
+    fn cargo_unmaintained(&self, args: &[&str]) -> Result<Output> {
+        let output = cargo_unmaintained_command(
+            &self.tempdir.path().join("dependent"),
+            &self.tempdir.path().join("cache"),
+        )
+        .args(["--compare-rustsec", "--rustsec-db"])
+        .arg(self.tempdir.path().join("advisory-db"))
+        .args(args)
+        .output()?;
+        ensure!(
+            output.status.code() == Some(1),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(output)
+    }
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn compare_rustsec() -> Result<()> {
+    let fixture = Fixture::new()?;
+
+    let output = fixture.cargo_unmaintained(&[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let origin = fixture.tempdir.path().join("origin.git");
+    ensure!(
+        stdout
+            == format!(
+                "Flagged by both (0):
+Flagged by an advisory only (0):
+Flagged by cargo-unmaintained \
+                 only (1):
+    renamed-dependency@0.1.0 (file://{})
+",
+                origin.display()
+            ),
+        "{stdout}"
+    );
+
+    let output = fixture.cargo_unmaintained(&["--json"])?;
+    let comparison = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(comparison["both"] == serde_json::json!([]));
+    ensure!(comparison["advisory_only"] == serde_json::json!([]));
+    ensure!(comparison["heuristic_only"][0]["name"] == "renamed-dependency");
+
+    Ok(())
+}
+
+// smoelius: The fixture's packages are path dependencies, to which no advisory can apply. So the
+// ignored advisories apply to none of the workspace's packages, and a warning is emitted for each,
+// whether it is ignored by the workspace metadata or on the command line.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn ignore_advisories() -> Result<()> {
+    let fixture = Fixture::new()?;
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(fixture.tempdir.path().join("dependent/Cargo.toml"))?;
+    writeln!(
+        manifest,
+        r#"
+[workspace.metadata.unmaintained]
+ignore_advisories = ["RUSTSEC-2000-0001"]
+"#
+    )?;
+
+    let output = fixture.cargo_unmaintained(&["--ignore-advisory=RUSTSEC-2000-0002"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(!stdout.contains("Suppressed advisories"), "{stdout}");
+    for id in ["RUSTSEC-2000-0001", "RUSTSEC-2000-0002"] {
+        ensure!(
+            stderr.lines().any(|line| line
+                == format!(
+                    "warning: advisory `{id}` is ignored, but applies to none of the workspace's \
+                     packages"
+                )),
+            "{stderr}"
+        );
+    }
+
+    let output = fixture.cargo_unmaintained(&["--json"])?;
+    let comparison = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(comparison["suppressed"] == serde_json::json!([]));
+
+    Ok(())
+}
+
+// smoelius: An advisory id in a cargo-deny configuration is mapped to the package the advisory
+// concerns, which is then ignored.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn import_advisory_ignores() -> Result<()> {
+    let fixture = Fixture::new()?;
+    let advisory_db = fixture.tempdir.path().join("advisory-db");
+    let deny_toml = fixture.tempdir.path().join("deny.toml");
+
+    create_dir_all(advisory_db.join("crates/renamed-dependency"))?;
+    write(
+        advisory_db.join("crates/renamed-dependency/RUSTSEC-2000-0002.md"),
+        ADVISORY
+            .replace("RUSTSEC-2000-0001", "RUSTSEC-2000-0002")
+            .replace("unused-dependency", "renamed-dependency"),
+    )?;
+    write(
+        &deny_toml,
+        "[advisories]\nignore = [\"RUSTSEC-2000-0002\", \"RUSTSEC-2000-0009\"]\n",
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never", "--import-ignores-from"])
+        .arg(&deny_toml)
+        .arg("--rustsec-db")
+        .arg(&advisory_db)
+        .env("XDG_CACHE_HOME", fixture.tempdir.path().join("cache"))
+        .current_dir(fixture.tempdir.path().join("dependent"))
+        .output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.lines().any(|line| line
+            == "    renamed-dependency (reason: advisory RUSTSEC-2000-0002; ignored via \
+                deny.toml)"),
+        "{stderr}"
+    );
+    ensure!(
+        stderr.lines().any(|line| line
+            == "warning: `deny.toml` ignores advisory `RUSTSEC-2000-0009`, which is not in the \
+                advisory database"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fmt::Write,
+    fs::read_dir,
+    path::Path,
+    process::{Child, Command, Stdio},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{git, write_package};
+
+const N_PACKAGES: usize = 8;
+
+// smoelius: Two processes share a cache. Each depends on six packages with their own repositories.
+// Four of the repositories are used by both processes, and two are used by only one. Both
+// processes should succeed, every clone should pass `git fsck`, and a third run should succeed
+// using the clones as is.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn concurrent_runs_share_cache() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+
+    let mut dependencies = Vec::new();
+    for i in 0..N_PACKAGES {
+        let name = format!("dependency-{i}");
+        let repo = tempdir.path().join(format!("{name}-repo"));
+        let origin = tempdir.path().join(format!("{name}.git"));
+        write_package(
+            &repo,
+            &name,
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+        dependencies.push(format!("{name} = {{ path = \"{}\" }}\n", repo.display()));
+    }
+
+    let mut dependents = Vec::new();
+    for (name, range) in [("first", 0..N_PACKAGES - 2), ("second", 2..N_PACKAGES)] {
+        let dependent = tempdir.path().join(name);
+        let mut manifest = String::from("publish = false\n\n[dependencies]\n");
+        for dependency in &dependencies[range] {
+            write!(manifest, "{dependency}")?;
+        }
+        write_package(&dependent, name, &manifest)?;
+        dependents.push(dependent);
+    }
+
+    let children = dependents
+        .iter()
+        .map(|dependent| spawn_cargo_unmaintained(dependent, &cache_home))
+        .collect::<Result<Vec<_>>>()?;
+    for child in children {
+        let output = child.wait_with_output()?;
+        ensure!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let repositories_dir = cache_home.join("cargo-unmaintained/v2/repositories");
+    let repositories = read_dir(&repositories_dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    ensure!(repositories.len() == N_PACKAGES, "{repositories:#?}");
+    for repository in repositories {
+        git(&repository.path(), &["fsck", "--no-progress"])?;
+    }
+
+    let output = spawn_cargo_unmaintained(&dependents[0], &cache_home)?.wait_with_output()?;
+    ensure!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(())
+}
+
+fn spawn_cargo_unmaintained(dir: &Path, cache_home: &Path) -> Result<Child> {
+    Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never"])
+        .env("XDG_CACHE_HOME", cache_home)
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(Into::into)
+}
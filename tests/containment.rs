@@ -0,0 +1,125 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::canonical_url;
+use std::{fmt::Write, fs::write};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: Each dependency has its own repository. After a first run, a file is staged in the
+// cached clone of `corrupt`'s repository, which makes the clone appear corrupt. The second
+// run should report `corrupt` as not checked, and should still report `renamed`, whose repository
+// does not contain a package of that name.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn corrupt_clone_does_not_abort_scan() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+
+    let mut dependencies = String::new();
+    for (name, name_in_repo) in [("corrupt", "corrupt"), ("renamed", "original-name")] {
+        let repo = tempdir.path().join(format!("{name}-repo"));
+        let origin = tempdir.path().join(format!("{name}.git"));
+        let repository = format!("file://{}", origin.display());
+        write_package(
+            &repo,
+            name_in_repo,
+            &format!("repository = \"{repository}\"\n"),
+        )?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        let dependency = tempdir.path().join(name);
+        write_package(
+            &dependency,
+            name,
+            &format!("repository = \"{repository}\"\n"),
+        )?;
+        writeln!(
+            dependencies,
+            "{name} = {{ path = \"{}\" }}",
+            dependency.display()
+        )?;
+    }
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!("publish = false\n\n[dependencies]\n{dependencies}"),
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    ensure!(output.status.code() == Some(1));
+
+    let repository = format!("file://{}", tempdir.path().join("corrupt.git").display());
+    let digest = sha1_smol::Sha1::from(canonical_url(&repository)).hexdigest();
+    let clone = cache_home
+        .join("cargo-unmaintained/v2/repositories")
+        .join(digest);
+    // smoelius: `git status --porcelain` lists paths in order. Name the file so that it is listed
+    // before `Cargo.toml`.
+    write(clone.join("0.txt"), "")?;
+    git(&clone, &["add", "0.txt"])?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(stdout.contains("\nrenamed ("), "{stdout}{stderr}");
+    ensure!(
+        stdout.contains(
+            "\nPackages that could not be checked:\n    corrupt 0.1.0: error: panicked: cache is \
+             corrupt at "
+        ),
+        "{stdout}{stderr}"
+    );
+    ensure!(
+        stderr.contains("warning: failed to check `corrupt`; continuing with the next package"),
+        "{stderr}"
+    );
+
+    // smoelius: The error and the warning about it are recorded in the JSON output even though
+    // `--no-warnings` hides the warning from stderr, and even without `--json-metadata`.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--json", "--no-warnings"])?;
+    ensure!(output.status.code() == Some(1));
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(report["errors"][0]["name"] == "corrupt", "{report}");
+    ensure!(
+        report["errors"][0]["error"]
+            .as_str()
+            .is_some_and(|error| error.starts_with("panicked: cache is corrupt at ")),
+        "{report}"
+    );
+    let warnings = report["warnings"].as_array().cloned().unwrap_or_default();
+    ensure!(
+        warnings
+            .iter()
+            .any(|warning| warning["package"] == "corrupt"
+                && warning["message"]
+                    .as_str()
+                    .is_some_and(|message| message.starts_with("failed to check `corrupt`"))),
+        "{report}"
+    );
+    ensure!(
+        !String::from_utf8(output.stderr)?.contains("warning:"),
+        "`--no-warnings` did not hide warnings"
+    );
+    ensure!(report["packages"][0]["name"] == "renamed", "{report}");
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::fmt::Write;
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: With `--only stale --max-age=0`, each dependency is unmaintained. Two of the
+// dependencies declare themselves finished, one with a badge and one with a metadata marker. No
+// network access is required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn declared_stable() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+
+    let mut dependencies = String::new();
+    for (name, extra) in [
+        (
+            "badged",
+            "\n[badges]\nmaintenance = { status = \"passively-maintained\" }\n",
+        ),
+        (
+            "marked",
+            "\n[package.metadata.cargo-unmaintained]\nstable = true\n",
+        ),
+        ("unmarked", ""),
+    ] {
+        let repo = tempdir.path().join(name);
+        let origin = tempdir.path().join(format!("{name}.git"));
+        write_package(
+            &repo,
+            name,
+            &format!("repository = \"file://{}\"\n{extra}", origin.display()),
+        )?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+        writeln!(dependencies, "{name} = {{ path = \"{}\" }}", repo.display())?;
+    }
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!("publish = false\n\n[dependencies]\n{dependencies}"),
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--only=stale", "--max-age=0"])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(names(&stdout) == ["unmarked"], "{stdout}");
+    ensure!(
+        stderr.contains(
+            "note: `badged` appears unmaintained, but declares itself finished with `[badges] \
+             maintenance = { status = \"passively-maintained\" }`"
+        ),
+        "{stderr}"
+    );
+    ensure!(
+        stderr.contains(
+            "note: `marked` appears unmaintained, but declares itself finished with \
+             `package.metadata.cargo-unmaintained.stable = true`"
+        ),
+        "{stderr}"
+    );
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--max-age=0", "--strict"],
+    )?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        names(&stdout) == ["badged", "marked", "unmarked"],
+        "{stdout}"
+    );
+    ensure!(!stderr.contains("note:"), "{stderr}");
+
+    Ok(())
+}
+
+/// Returns the names of the packages reported in `stdout`
+fn names(stdout: &str) -> Vec<&str> {
+    let mut names = stdout
+        .lines()
+        .filter(|line| line.contains(" (file://"))
+        .filter_map(|line| line.split_whitespace().next())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
@@ -0,0 +1,242 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{anyhow, ensure, Context, Result};
+use serde_json::{json, Value};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir_all, write, OpenOptions},
+    io::Write,
+    path::Path,
+    process::Command,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+// smoelius: The packages are published to a local registry that replaces crates.io, so no network
+// access is required. Package ids still name crates.io, so the packages are checked as though they
+// came from there. The dependency graph is:
+//
+//     aaa ---------> bbb ---> ccc ^1 (2.0.0 is available)
+//      |              ^  |
+//      +---> fff -----+  +---> eee ---> ddd ^1 (2.0.0 is available)
+//
+// `bbb` is reachable from `aaa` both directly and through `fff`, but should be visited only once.
+//
+// The packages have no repositories. Versions' release dates are still requested from the crates.io
+// API, and those requests fail without network access. So `--keep-going` is passed, and
+// `--outdated-dep-grace=0` ensures that no release dates are needed to determine outdatedness.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn deep_outdated() -> Result<()> {
+    let tempdir = tempdir()?;
+    let registry = tempdir.path().join("registry");
+    let cargo_home = tempdir.path().join("cargo-home");
+    let dependent = tempdir.path().join("dependent");
+
+    for (name, version, deps) in [
+        ("ccc", "1.0.0", &[][..]),
+        ("ccc", "2.0.0", &[]),
+        ("ddd", "1.0.0", &[]),
+        ("ddd", "2.0.0", &[]),
+        ("eee", "1.0.0", &[("ddd", "^1")]),
+        ("bbb", "1.0.0", &[("ccc", "^1"), ("eee", "^1")]),
+        ("fff", "1.0.0", &[("bbb", "^1")]),
+        ("aaa", "1.0.0", &[("bbb", "^1"), ("fff", "^1")]),
+    ] {
+        publish(&registry, name, version, deps)?;
+    }
+    write(
+        registry.join("index/config.json"),
+        json!({ "dl": format!("file://{}/crates/{{crate}}-{{version}}.crate", registry.display()) })
+            .to_string(),
+    )?;
+    let index = registry.join("index");
+    git(&index, &["init", "--quiet"])?;
+    git(&index, &["add", "."])?;
+    git(&index, &["commit", "--quiet", "--message=Initial commit"])?;
+
+    create_dir_all(&cargo_home)?;
+    write(
+        cargo_home.join("config.toml"),
+        format!(
+            "[source.crates-io]\nreplace-with = \"local\"\n\n[source.local]\nregistry = \
+             \"file://{}\"\n",
+            index.display()
+        ),
+    )?;
+
+    create_dir_all(dependent.join("src"))?;
+    write(
+        dependent.join("Cargo.toml"),
+        "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+         false\n\n[dependencies]\naaa = \"1\"\n",
+    )?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let scan = |depth: &str| -> Result<Vec<Value>> {
+        let output = Command::new(cargo_bin("cargo-unmaintained"))
+            .args([
+                "unmaintained",
+                "--json",
+                "--keep-going",
+                "--outdated-dep-grace=0",
+                "--token-source=none",
+                &format!("--deep-outdated={depth}"),
+            ])
+            .env("CARGO_HOME", &cargo_home)
+            .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+            .current_dir(&dependent)
+            .output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        ensure!(output.status.code() == Some(1), "{stderr}");
+        let report =
+            serde_json::from_slice::<Value>(&output.stdout).with_context(|| stderr.to_string())?;
+        Ok(report["packages"].as_array().cloned().unwrap_or_default())
+    };
+
+    // smoelius: By default, `aaa` has no outdated dependencies.
+    let report = scan("1")?;
+    ensure!(outdated_deps(&report, "aaa")?.is_empty(), "{report:#?}");
+    ensure!(
+        outdated_deps(&report, "bbb")? == [(String::from("ccc"), Vec::new())],
+        "{report:#?}"
+    );
+
+    let report = scan("2")?;
+    ensure!(
+        outdated_deps(&report, "aaa")? == [(String::from("ccc"), vec![String::from("bbb")])],
+        "{report:#?}"
+    );
+
+    let report = scan("3")?;
+    ensure!(
+        outdated_deps(&report, "aaa")?
+            == [
+                (String::from("ccc"), vec![String::from("bbb")]),
+                (
+                    String::from("ddd"),
+                    vec![String::from("bbb"), String::from("eee")]
+                ),
+            ],
+        "{report:#?}"
+    );
+
+    Ok(())
+}
+
+/// Returns the names of package `name`'s outdated dependencies in `report`, and the names of the
+/// packages through which each was reached, sorted
+fn outdated_deps(report: &[Value], name: &str) -> Result<Vec<(String, Vec<String>)>> {
+    let Some(package) = report.iter().find(|package| package["name"] == name) else {
+        return Ok(Vec::new());
+    };
+    let mut outdated_deps = package["outdated_deps"]
+        .as_array()
+        .ok_or_else(|| anyhow!("`{name}` has no `outdated_deps`"))?
+        .iter()
+        .map(|outdated_dep| {
+            let via = outdated_dep["via"]
+                .as_array()
+                .map(|via| {
+                    via.iter()
+                        .filter_map(|pkg| pkg["name"].as_str().map(ToOwned::to_owned))
+                        .collect()
+                })
+                .unwrap_or_default();
+            (
+                outdated_dep["name"].as_str().unwrap_or_default().to_owned(),
+                via,
+            )
+        })
+        .collect::<Vec<_>>();
+    outdated_deps.sort();
+    Ok(outdated_deps)
+}
+
+/// Writes a `.crate` file for `name` at `version` to `registry`, and adds it to `registry`'s index
+///
+/// Names must be three characters long, which determines where they go in the index.
+fn publish(registry: &Path, name: &str, version: &str, deps: &[(&str, &str)]) -> Result<()> {
+    assert_eq!(3, name.len());
+
+    let src = registry.join("src").join(format!("{name}-{version}"));
+    create_dir_all(src.join("src"))?;
+    let dependencies = deps
+        .iter()
+        .map(|(dep, req)| format!("{dep} = \"{req}\"\n"))
+        .collect::<String>();
+    write(
+        src.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"{version}\"\nedition = \
+             \"2021\"\n\n[dependencies]\n{dependencies}"
+        ),
+    )?;
+    write(src.join("src/lib.rs"), "")?;
+
+    let crates = registry.join("crates");
+    create_dir_all(&crates)?;
+    let krate = crates.join(format!("{name}-{version}.crate"));
+    let status = Command::new("tar")
+        .arg("czf")
+        .arg(&krate)
+        .arg(format!("{name}-{version}"))
+        .current_dir(registry.join("src"))
+        .status()?;
+    ensure!(status.success());
+
+    let dir = registry.join("index/3").join(&name[..1]);
+    create_dir_all(&dir)?;
+    let deps = deps
+        .iter()
+        .map(|(dep, req)| {
+            json!({
+                "name": dep,
+                "req": req,
+                "features": [],
+                "optional": false,
+                "default_features": true,
+                "target": null,
+                "kind": "normal",
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(dir.join(name))?;
+    writeln!(
+        file,
+        "{}",
+        json!({
+            "name": name,
+            "vers": version,
+            "deps": deps,
+            "cksum": sha256(&krate)?,
+            "features": {},
+            "yanked": false,
+        })
+    )?;
+
+    Ok(())
+}
+
+fn sha256(path: &Path) -> Result<String> {
+    // smoelius: `sha256sum` is not installed on macOS by default, but `shasum` is.
+    let output = Command::new("sha256sum").arg(path).output().or_else(|_| {
+        Command::new("shasum")
+            .args(["-a", "256"])
+            .arg(path)
+            .output()
+    })?;
+    ensure!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    stdout
+        .split_whitespace()
+        .next()
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| anyhow!("unexpected output: {stdout:?}"))
+}
@@ -0,0 +1,102 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+#[cfg(feature = "testing-mock")]
+use cargo_unmaintained::github::mock::FIXTURES_VAR;
+use serde_json::Value;
+use std::{path::Path, process::Output};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, write_package};
+
+// smoelius: The network checks depend upon the environment, so only the local checks are required
+// to pass. Whatever their outcomes, the exit status is nonzero exactly when some check fails.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn doctor() -> Result<()> {
+    let tempdir = tempdir()?;
+    let package = tempdir.path().join("a");
+    write_package(&package, "a", "publish = false\n")?;
+
+    let output = cargo_unmaintained(&package, &tempdir.path().join("cache"), &[])?;
+    let checks = parse_checks(&output)?;
+    for name in ["git", "cache directory", "cargo workspace"] {
+        ensure!(status(&checks, name) == Some("PASS"), "{checks:#?}");
+    }
+    ensure!(
+        status(&checks, "GitHub token") == Some("WARN"),
+        "{checks:#?}"
+    );
+    let failed = checks.iter().any(|check| check["status"] == "FAIL");
+    ensure!(output.status.success() != failed, "{output:#?}");
+
+    // smoelius: Outside of a workspace, the workspace check warns but does not fail.
+    let output = cargo_unmaintained(tempdir.path(), &tempdir.path().join("cache"), &[])?;
+    let checks = parse_checks(&output)?;
+    ensure!(
+        status(&checks, "cargo workspace") == Some("WARN"),
+        "{checks:#?}"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "testing-mock")]
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn doctor_token() -> Result<()> {
+    let tempdir = tempdir()?;
+    let package = tempdir.path().join("a");
+    let fixtures = tempdir.path().join("fixtures.json");
+    write_package(&package, "a", "publish = false\n")?;
+    std::fs::write(&fixtures, "{}")?;
+
+    let output = cargo_unmaintained(
+        &package,
+        &tempdir.path().join("cache"),
+        &[
+            ("GITHUB_TOKEN", "ghp_mock"),
+            (FIXTURES_VAR, &fixtures.to_string_lossy()),
+        ],
+    )?;
+    let checks = parse_checks(&output)?;
+    ensure!(
+        status(&checks, "GitHub token") == Some("PASS"),
+        "{checks:#?}"
+    );
+
+    Ok(())
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path, envs: &[(&str, &str)]) -> Result<Output> {
+    let token_source = if envs.iter().any(|&(key, _)| key == "GITHUB_TOKEN") {
+        "env"
+    } else {
+        "none"
+    };
+    cargo_unmaintained_command(dir, cache_home)
+        .args([
+            &format!("--token-source={token_source}"),
+            "--json",
+            "doctor",
+        ])
+        .envs(envs.iter().copied())
+        .output()
+        .map_err(Into::into)
+}
+
+fn parse_checks(output: &Output) -> Result<Vec<Value>> {
+    let report = serde_json::from_slice::<Value>(&output.stdout)?;
+    let checks = report["checks"].as_array().cloned().unwrap_or_default();
+    ensure!(!checks.is_empty(), "{output:#?}");
+    Ok(checks)
+}
+
+fn status<'a>(checks: &'a [Value], name: &str) -> Option<&'a str> {
+    checks
+        .iter()
+        .find(|check| check["name"] == name)
+        .and_then(|check| check["status"].as_str())
+}
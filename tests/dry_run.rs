@@ -0,0 +1,92 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::canonical_url;
+use std::{fs::write, process::Output};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: A dry run against an empty cache should predict a clone. After a real run populates
+// the cache, a dry run should predict that the clone is used as is. After the clone's timestamp is
+// made old, a dry run should predict a refresh. No dry run should clone anything.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn predict_clones_and_refreshes() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("file://{}", origin.display());
+
+    write_package(&repo, "dep", &format!("repository = \"{repository}\"\n"))?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndep = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    let cache_dir = cache_home.join("cargo-unmaintained/v2");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--dry-run"])?;
+    ensure_plan(
+        &output,
+        "0 repos cached (fresh), 0 cached (stale, would refresh), 1 would be cloned",
+    )?;
+    ensure!(!cache_dir.join("repositories").try_exists()?);
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    ensure!(output.status.success(), "{output:#?}");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--dry-run"])?;
+    ensure_plan(
+        &output,
+        "1 repos cached (fresh), 0 cached (stale, would refresh), 0 would be cloned",
+    )?;
+
+    let digest = sha1_smol::Sha1::from(canonical_url(&repository)).hexdigest();
+    write(cache_dir.join("timestamps").join(digest), "0")?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--dry-run"])?;
+    ensure_plan(
+        &output,
+        "0 repos cached (fresh), 1 cached (stale, would refresh), 0 would be cloned",
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--dry-run", "--only=archived"])?;
+    ensure_plan(&output, "no repos would be cloned")?;
+    ensure!(String::from_utf8(output.stdout)?.ends_with("index update: not required\n"));
+
+    Ok(())
+}
+
+fn ensure_plan(output: &Output, repositories: &str) -> Result<()> {
+    let stdout = String::from_utf8(output.stdout.clone())?;
+    let stderr = String::from_utf8(output.stderr.clone())?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stdout.starts_with(&format!("1 packages; {repositories}; ")),
+        "{stdout}"
+    );
+    Ok(())
+}
@@ -0,0 +1,137 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{read_dir, read_to_string},
+    path::Path,
+    process::{Command, Output},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+// smoelius: `dependency`'s repository is stale, so `dependency` is flagged and a bundle is written
+// for it. The home directory is set to the temporary directory. So the repository url in the
+// dependency's manifest lies within the home directory, and must be redacted.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn evidence_bundle() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let evidence_dir = tempdir.path().join("evidence");
+
+    write_package(
+        &repo,
+        "dependency",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+            "commit",
+            "--quiet",
+            "--message=Initial commit",
+        ])
+        .env("GIT_AUTHOR_DATE", "2015-01-01T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2015-01-01T00:00:00Z")
+        .current_dir(&repo)
+        .status()?;
+    ensure!(status.success());
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    let head = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(&repo)
+        .output()?;
+    ensure!(head.status.success());
+    let head = String::from_utf8(head.stdout)?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, tempdir.path(), &evidence_dir)?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+
+    let bundle = evidence_dir.join("dependency-0.1.0");
+
+    let summary =
+        serde_json::from_str::<serde_json::Value>(&read_to_string(bundle.join("summary.json"))?)?;
+    ensure!(summary["package"] == "dependency", "{summary}");
+    ensure!(summary["clone"]["head"] == head.trim_end(), "{summary}");
+    ensure!(summary["manifest"]["path"] == "Cargo.toml", "{summary}");
+    ensure!(summary["generated_at"].is_string(), "{summary}");
+    ensure!(
+        summary["files"] == serde_json::json!(["git_log.txt", "Cargo.toml"]),
+        "{summary}"
+    );
+
+    let git_log = read_to_string(bundle.join("git_log.txt"))?;
+    ensure!(git_log.starts_with(&format!("commit {head}")), "{git_log}");
+    ensure!(git_log.contains("Initial commit"), "{git_log}");
+
+    let manifest = read_to_string(bundle.join("Cargo.toml"))?;
+    ensure!(
+        manifest.contains("repository = \"file://~/origin.git\""),
+        "{manifest}"
+    );
+
+    // smoelius: No file in the bundle may mention the home directory.
+    for entry in read_dir(&bundle)? {
+        let path = entry?.path();
+        let contents = read_to_string(&path)?;
+        ensure!(
+            !contents.contains(&*tempdir.path().to_string_lossy()),
+            "{}: {contents}",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn cargo_unmaintained(
+    dir: &Path,
+    cache_home: &Path,
+    home: &Path,
+    evidence_dir: &Path,
+) -> Result<Output> {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    command
+        .args(["--only=stale", "--evidence-dir"])
+        .arg(evidence_dir)
+        .env("HOME", home);
+    // smoelius: Changing the home directory must not change where cargo and rustup are found.
+    if let Ok(cargo_home) = home::cargo_home() {
+        command.env("CARGO_HOME", cargo_home);
+    }
+    if let Ok(rustup_home) = home::rustup_home() {
+        command.env("RUSTUP_HOME", rustup_home);
+    }
+    command.output().map_err(Into::into)
+}
@@ -0,0 +1,171 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::github::mock::{RepoFixture, FIXTURES_VAR};
+use std::{collections::HashMap, fs::write, path::Path, process::Output};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+// smoelius: Packages are checked in order by name. `a-renamed`'s repository contains no package of
+// that name, so `a-renamed` is unassociated with it, which is not a kind of problem that
+// `--fail-fast-on` accepts. `b-archived`'s repository is archived according to the fixtures file.
+// `c-fresh`'s repository's only commit is from the time the test is run.
+//
+// smoelius: A stale repository is reported only if its package has outdated dependencies, which
+// requires the crates.io index. So no package here is merely stale.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn fail_fast_on() -> Result<()> {
+    let tempdir = tempdir()?;
+    let fixtures = tempdir.path().join("fixtures.json");
+    let dependent = tempdir.path().join("dependent");
+
+    write_fixture(tempdir.path(), &fixtures, &dependent)?;
+
+    for (args, expected) in [
+        (&["--fail-fast"][..], &[("a-renamed", Some((None, 2)))][..]),
+        (&["--fail-fast-on=any"], &[("a-renamed", Some((None, 2)))]),
+        (
+            &["--fail-fast-on=archived"],
+            &[
+                ("a-renamed", None),
+                ("b-archived", Some((Some("archived"), 1))),
+            ],
+        ),
+        (
+            &["--fail-fast-on=nonexistent,stale"],
+            &[("a-renamed", None), ("b-archived", None)],
+        ),
+    ] {
+        let cache_home = tempdir.path().join(format!("cache-{}", args.join("-")));
+
+        let output = cargo_unmaintained(&dependent, &cache_home, &fixtures, args, true)?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(
+            output.status.code() == Some(1),
+            "{args:?}: {stdout}{stderr}"
+        );
+        let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+        let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+        ensure!(packages.len() == expected.len(), "{args:?}: {stdout}");
+        for (package, &(name, partial_scan)) in packages.iter().zip(expected) {
+            ensure!(package["name"] == name, "{args:?}: {stdout}");
+            match partial_scan {
+                Some((kind, unexamined)) => {
+                    ensure!(package["partial_scan"] == true, "{args:?}: {stdout}");
+                    ensure!(
+                        package["fail_fast_kind"].as_str() == kind,
+                        "{args:?}: {stdout}"
+                    );
+                    ensure!(
+                        package["unexamined_packages"] == unexamined,
+                        "{args:?}: {stdout}"
+                    );
+                }
+                None => {
+                    ensure!(package.get("partial_scan").is_none(), "{args:?}: {stdout}");
+                }
+            }
+        }
+
+        // smoelius: The marker also appears in text output.
+        let output = cargo_unmaintained(&dependent, &cache_home, &fixtures, args, false)?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stopped = expected.iter().find_map(|&(_, partial_scan)| partial_scan);
+        if let Some((kind, unexamined)) = stopped {
+            let noun = if unexamined == 1 {
+                "package"
+            } else {
+                "packages"
+            };
+            let on = kind.map_or_else(String::new, |kind| format!(" on a problem of kind {kind}"));
+            ensure!(
+                stdout.contains(&format!(
+                    "    scan stopped here{on}; {unexamined} {noun} not examined\n"
+                )),
+                "{args:?}: {stdout}"
+            );
+        } else {
+            ensure!(!stdout.contains("scan stopped here"), "{args:?}: {stdout}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the fixtures file and the packages described above, with `dependent` depending upon
+/// each of them, in `root`
+fn write_fixture(root: &Path, fixtures: &Path, dependent: &Path) -> Result<()> {
+    write(
+        fixtures,
+        serde_json::to_string(&HashMap::from([(
+            "owner/b-archived",
+            RepoFixture {
+                archived: true,
+                ..Default::default()
+            },
+        )]))?,
+    )?;
+
+    write_package(
+        &root.join("b-archived"),
+        "b-archived",
+        "repository = \"https://github.com/owner/b-archived\"\n",
+    )?;
+    for (name, name_in_repo) in [("a-renamed", "original-name"), ("c-fresh", "c-fresh")] {
+        let repo = root.join(name);
+        let origin = root.join(format!("{name}.git"));
+        let repository = format!("repository = \"file://{}\"\n", origin.display());
+        write_package(&repo, name_in_repo, &repository)?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            root,
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+        write_package(&repo, name, &repository)?;
+    }
+
+    let dependencies = ["a-renamed", "b-archived", "c-fresh"]
+        .iter()
+        .map(|name| format!("{name} = {{ path = \"{}\" }}\n", root.join(name).display()))
+        .collect::<String>();
+    write_package(
+        dependent,
+        "dependent",
+        &format!("publish = false\n\n[dependencies]\n{dependencies}"),
+    )?;
+
+    Ok(())
+}
+
+fn cargo_unmaintained(
+    dir: &Path,
+    cache_home: &Path,
+    fixtures: &Path,
+    args: &[&str],
+    json: bool,
+) -> Result<Output> {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    if json {
+        command.arg("--json");
+    }
+    command
+        .args(args)
+        .env("GITHUB_TOKEN", "mock")
+        .env(FIXTURES_VAR, fixtures)
+        .output()
+        .map_err(Into::into)
+}
@@ -0,0 +1,52 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::fs::write;
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, write_package};
+
+// smoelius: Both packages are workspace members, so nothing is scanned. No network access is
+// required.
+#[test]
+fn fail_if_empty() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+
+    write(
+        tempdir.path().join("Cargo.toml"),
+        "[workspace]\nmembers = [\"a\", \"b\"]\nresolver = \"2\"\n",
+    )?;
+    write_package(
+        &tempdir.path().join("a"),
+        "a",
+        "publish = false\n\n[dependencies]\nb = { path = \"../b\" }\n",
+    )?;
+    write_package(&tempdir.path().join("b"), "b", "publish = false\n")?;
+
+    let output = cargo_unmaintained(tempdir.path(), &cache_home, &[])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stderr}");
+    ensure!(
+        stderr.contains(
+            "No packages were scanned. Packages excluded from the scan:
+    workspace members (including path dependencies within the workspace): 2
+    ignored by `workspace.metadata.unmaintained.ignore`: 0
+    not dependencies when building for the targets (see --target): 0
+    older versions of packages also used at newer versions: 0"
+        ),
+        "{stderr}"
+    );
+
+    let output = cargo_unmaintained(tempdir.path(), &cache_home, &["--fail-if-empty"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(2), "{stderr}");
+    ensure!(
+        stderr.contains("Error: no packages were scanned"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fmt::Write,
+    fs::{create_dir, write},
+    path::Path,
+    process::Command,
+};
+use tempfile::tempdir;
+
+// smoelius: The dependencies' repositories are `file://` urls, whose general statuses can be
+// determined without network access.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn fast() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+
+    write_dependent(tempdir.path(), &dependent, &["a", "b"])?;
+
+    let stderr = run_fast(&dependent, &cache_home, &[])?;
+    ensure!(
+        stderr.contains(
+            "fast mode: partial checks (archival status and existence only; 2 of 2 packages are \
+             new since the last check)"
+        ),
+        "{stderr}"
+    );
+    // smoelius: Fast mode never clones.
+    ensure!(
+        !cache_home
+            .join("cargo-unmaintained/v2/repositories")
+            .try_exists()?,
+        "{stderr}"
+    );
+
+    let stderr = run_fast(&dependent, &cache_home, &[])?;
+    ensure!(
+        stderr.contains(
+            "fast mode: partial checks: `Cargo.lock` is unchanged since the last check, which \
+             found nothing"
+        ),
+        "{stderr}"
+    );
+
+    // smoelius: Only `c` is new. But with no requests allowed, no package is checked.
+    write_dependent(tempdir.path(), &dependent, &["a", "b", "c"])?;
+    let stderr = run_fast(&dependent, &cache_home, &["--fast-max-requests=0"])?;
+    ensure!(
+        stderr.contains("1 of 3 packages are new since the last check"),
+        "{stderr}"
+    );
+    ensure!(
+        stderr.contains(
+            "fast mode: partial checks: 3 packages were not checked because --fast-max-requests \
+             was reached"
+        ),
+        "{stderr}"
+    );
+
+    // smoelius: Because not every package was checked, the next run checks again. `c` is still new.
+    let stderr = run_fast(&dependent, &cache_home, &[])?;
+    ensure!(
+        stderr.contains("1 of 3 packages are new since the last check"),
+        "{stderr}"
+    );
+    ensure!(!stderr.contains("not checked"), "{stderr}");
+
+    let stderr = run_fast(&dependent, &cache_home, &[])?;
+    ensure!(stderr.contains("`Cargo.lock` is unchanged"), "{stderr}");
+
+    // smoelius: `--fast-max-requests` requires `--fast`.
+    let output = command(&dependent, &cache_home)
+        .args(["--fast-max-requests=1"])
+        .output()?;
+    ensure!(output.status.code() == Some(2));
+
+    Ok(())
+}
+
+fn write_dependent(dir: &Path, dependent: &Path, names: &[&str]) -> Result<()> {
+    let mut dependencies = String::new();
+    for name in names {
+        let dep = dir.join(name);
+        if !dep.try_exists()? {
+            create_dir(&dep)?;
+            write(
+                dep.join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \
+                     \"2021\"\nrepository = \"file://{}\"\n",
+                    dir.join(format!("{name}.git")).display()
+                ),
+            )?;
+            create_dir(dep.join("src"))?;
+            write(dep.join("src/lib.rs"), "")?;
+        }
+        writeln!(dependencies, "{name} = {{ path = \"{}\" }}", dep.display())?;
+    }
+
+    if !dependent.try_exists()? {
+        create_dir(dependent)?;
+        create_dir(dependent.join("src"))?;
+        write(dependent.join("src/lib.rs"), "")?;
+    }
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+             false\n\n[dependencies]\n{dependencies}"
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn run_fast(dependent: &Path, cache_home: &Path, args: &[&str]) -> Result<String> {
+    let output = command(dependent, cache_home)
+        .arg("--fast")
+        .args(args)
+        .output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    Ok(stderr)
+}
+
+fn command(dependent: &Path, cache_home: &Path) -> Command {
+    let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+    command
+        .args(["unmaintained", "--color=never"])
+        .env("XDG_CACHE_HOME", cache_home)
+        .current_dir(dependent);
+    command
+}
@@ -0,0 +1,299 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::github::mock::{RepoFixture, FIXTURES_VAR, REJECTED_TOKEN};
+use snapbox::cmd::cargo_bin;
+use std::{collections::HashMap, fs::write, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::write_package;
+
+// smoelius: `archived`'s repository is archived according to the fixtures file. Its archival status
+// is determined without the network, and before any attempt to clone the repository. So the scan
+// runs fully offline.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn archived_fixture() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependency = tempdir.path().join("archived");
+    let dependent = tempdir.path().join("dependent");
+    let fixtures = tempdir.path().join("fixtures.json");
+
+    write(
+        &fixtures,
+        serde_json::to_string(&HashMap::from([(
+            "owner/archived",
+            RepoFixture {
+                archived: true,
+                ..Default::default()
+            },
+        )]))?,
+    )?;
+
+    write_package(
+        &dependency,
+        "archived",
+        "repository = \"https://github.com/owner/archived\"\n",
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\narchived = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never"])
+        .env("XDG_CACHE_HOME", &cache_home)
+        .env("GITHUB_TOKEN", "mock")
+        .env(FIXTURES_VAR, &fixtures)
+        .current_dir(&dependent)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+    ensure!(
+        stdout.contains(
+            "\narchived (https://github.com/owner/archived archived) [path dependency]\n"
+        ),
+        "{stdout}{stderr}"
+    );
+
+    Ok(())
+}
+
+// smoelius: `archived`'s repository's description names its successor, which exists and is not
+// archived. Naming a successor does not change the exit status.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn successor_fixture() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependency = tempdir.path().join("archived");
+    let dependent = tempdir.path().join("dependent");
+    let fixtures = tempdir.path().join("fixtures.json");
+
+    write(
+        &fixtures,
+        serde_json::to_string(&HashMap::from([
+            (
+                "owner/archived",
+                RepoFixture {
+                    archived: true,
+                    description: Some(String::from(
+                        "Superseded by https://github.com/owner/archived2.",
+                    )),
+                    ..Default::default()
+                },
+            ),
+            ("owner/archived2", RepoFixture::default()),
+        ]))?,
+    )?;
+
+    write_package(
+        &dependency,
+        "archived",
+        "repository = \"https://github.com/owner/archived\"\n",
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\narchived = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    for json in [false, true] {
+        let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+        command
+            .args(["unmaintained", "--color=never"])
+            .env("XDG_CACHE_HOME", &cache_home)
+            .env("GITHUB_TOKEN", "mock")
+            .env(FIXTURES_VAR, &fixtures)
+            .current_dir(&dependent);
+        if json {
+            command.arg("--json");
+        }
+        let output = command.output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+        if json {
+            let value = serde_json::from_str::<serde_json::Value>(&stdout)?;
+            ensure!(
+                value["packages"][0]["successor_candidates"]
+                    == serde_json::json!(["https://github.com/owner/archived2"]),
+                "{stdout}"
+            );
+        } else {
+            ensure!(
+                stdout.contains("\n    possible successor: github.com/owner/archived2\n"),
+                "{stdout}{stderr}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// smoelius: `archived`'s repository has three forks. Only `owner2/maintained` was pushed to more
+// recently than the upstream and has enough stars. So it is suggested, and the others are not.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn suggested_fork_fixture() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependency = tempdir.path().join("archived");
+    let dependent = tempdir.path().join("dependent");
+    let fixtures = tempdir.path().join("fixtures.json");
+
+    let fork = |stargazers_count, pushed_at: &str| RepoFixture {
+        stargazers_count,
+        pushed_at: Some(pushed_at.to_owned()),
+        ..Default::default()
+    };
+    write(
+        &fixtures,
+        serde_json::to_string(&HashMap::from([
+            (
+                "owner/archived",
+                RepoFixture {
+                    archived: true,
+                    pushed_at: Some(String::from("2020-01-01T00:00:00Z")),
+                    forks: vec![
+                        String::from("owner2/maintained"),
+                        String::from("owner3/few-stars"),
+                        String::from("owner4/older"),
+                    ],
+                    ..Default::default()
+                },
+            ),
+            ("owner2/maintained", fork(340, "2024-01-01T00:00:00Z")),
+            ("owner3/few-stars", fork(1, "2024-01-01T00:00:00Z")),
+            ("owner4/older", fork(500, "2019-01-01T00:00:00Z")),
+        ]))?,
+    )?;
+
+    write_package(
+        &dependency,
+        "archived",
+        "repository = \"https://github.com/owner/archived\"\n",
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\narchived = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    for json in [false, true] {
+        let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+        command
+            .args(["unmaintained", "--color=never", "--suggest-forks"])
+            .env("XDG_CACHE_HOME", &cache_home)
+            .env("GITHUB_TOKEN", "mock")
+            .env(FIXTURES_VAR, &fixtures)
+            .current_dir(&dependent);
+        if json {
+            command.arg("--json");
+        }
+        let output = command.output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+        if json {
+            let value = serde_json::from_str::<serde_json::Value>(&stdout)?;
+            let suggested_forks = value["packages"][0]["suggested_forks"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            ensure!(suggested_forks.len() == 1, "{stdout}");
+            ensure!(
+                suggested_forks[0]["url"] == "https://github.com/owner2/maintained"
+                    && suggested_forks[0]["stars"] == 340,
+                "{stdout}"
+            );
+        } else {
+            ensure!(
+                stdout.contains("\n    possible maintained fork: github.com/owner2/maintained ("),
+                "{stdout}{stderr}"
+            );
+            ensure!(stdout.contains("340 stars)"), "{stdout}{stderr}");
+        }
+    }
+
+    Ok(())
+}
+
+// smoelius: A rejected token produces one warning, and is then disregarded, as though no token had
+// been found. `--suggest-forks` requires a token, so it warns only when the token was disregarded.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn rejected_token() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependency = tempdir.path().join("abandoned");
+    let dependent = tempdir.path().join("dependent");
+    let fixtures = tempdir.path().join("fixtures.json");
+
+    write(&fixtures, "{}")?;
+
+    write_package(
+        &dependency,
+        "abandoned",
+        &format!(
+            "repository = \"file://{}\"\n",
+            tempdir.path().join("missing").display()
+        ),
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nabandoned = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    for (token, rejected) in [(REJECTED_TOKEN, true), ("mock", false)] {
+        let output = Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "--color=never", "--suggest-forks"])
+            .env("XDG_CACHE_HOME", &cache_home)
+            .env("GITHUB_TOKEN", token)
+            .env(FIXTURES_VAR, &fixtures)
+            .current_dir(&dependent)
+            .output()?;
+        let stderr = String::from_utf8(output.stderr)?;
+        let n_rejected = stderr
+            .lines()
+            .filter(|line| line.contains("GitHub token rejected"))
+            .count();
+        ensure!(
+            n_rejected == usize::from(rejected),
+            "{token}: {n_rejected}\n{stderr}"
+        );
+        if rejected {
+            ensure!(
+                stderr
+                    .lines()
+                    .any(|line| line
+                        == "warning: GitHub token rejected (401); archival checks disabled"),
+                "{stderr}"
+            );
+        }
+        ensure!(
+            stderr.contains("--suggest-forks requires a personal access token") == rejected,
+            "{token}: {stderr}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: `renamed` names a repository containing no package of that name. So it is unassociated
+// with its repository. The finding appears in the first run, persists in the second, and is
+// resolved in the third, in which `dependent` no longer depends upon `renamed`.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn history() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let renamed = tempdir.path().join("renamed");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    write_package(
+        &renamed,
+        "renamed",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    let dependencies = format!(
+        "publish = false\n\n[dependencies]\nrenamed = {{ path = \"{}\" }}\n",
+        renamed.display()
+    );
+    write_package(&dependent, "dependent", &dependencies)?;
+
+    let cache_home = tempdir.path().join("cache");
+
+    // smoelius: First run: the finding is new.
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--now=2030-01-01T00:00:00Z", "--json"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(1), "{stdout}");
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    ensure!(
+        packages.iter().any(|package| package["name"] == "renamed"
+            && package["first_seen"] == "2030-01-01T00:00:00Z"),
+        "{stdout}"
+    );
+
+    // smoelius: Second run: the finding is still outstanding, and nothing has been resolved.
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--now=2030-04-02T00:00:00Z", "--history=365"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(1), "{stdout}");
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("renamed ") && line.ends_with("(outstanding for 91 days)")),
+        "{stdout}"
+    );
+    ensure!(
+        stdout.contains("No findings resolved in the last 365 days"),
+        "{stdout}"
+    );
+
+    // smoelius: Third run: the dependency is removed, so the finding is resolved.
+    write_package(&dependent, "dependent", "publish = false\n")?;
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--now=2030-05-02T00:00:00Z", "--history=30"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(0), "{stdout}");
+    ensure!(
+        stdout.contains(
+            "Findings resolved in the last 30 days:\n    renamed (unassociated): resolved on \
+             2030-05-02, after 121 days\n"
+        ),
+        "{stdout}"
+    );
+
+    // smoelius: With `--no-cache`, no history is kept.
+    write_package(&dependent, "dependent", &dependencies)?;
+    for now in ["--now=2030-06-01T00:00:00Z", "--now=2030-09-01T00:00:00Z"] {
+        let output = cargo_unmaintained(&dependent, &cache_home, &[now, "--no-cache"])?;
+        let stdout = String::from_utf8(output.stdout)?;
+        ensure!(output.status.code() == Some(1), "{stdout}");
+        ensure!(!stdout.contains("outstanding"), "{stdout}");
+    }
+
+    Ok(())
+}
@@ -6,6 +6,9 @@ use snapbox::cmd::cargo_bin;
 use std::{fs::OpenOptions, io::Write, path::Path, process::Command};
 use tempfile::{tempdir, TempDir};
 
+mod util;
+use util::write_package;
+
 const NAME: &str = "bigint";
 
 #[test]
@@ -47,6 +50,138 @@ fn warn_not_depended_upon() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn warn_unknown_key() -> Result<()> {
+    let tempdir = create_test_package()?;
+
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(tempdir.path().join("Cargo.toml"))?;
+    writeln!(
+        manifest,
+        r#"
+[workspace.metadata.unmaintained]
+ignored = ["{NAME}"]
+"#
+    )?;
+
+    // smoelius: The misspelled key is reported, but the scan proceeds as usual.
+    let output = cargo_unmaintained(tempdir.path()).output()?;
+    ensure!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.lines().any(|line| line
+            == "warning: unknown key `ignored` in `workspace.metadata.unmaintained`; did you \
+                mean `ignore`? (supported keys: allowlist, assume_fresh, ignore, \
+                ignore_advisories, import_ignores_from, repositories)"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.lines().any(|line| line.starts_with("Scanning ")),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+// smoelius: `abandoned`'s repository url names a repository that does not exist. So `abandoned` is
+// flagged unless it is ignored.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn ignore_table() -> Result<()> {
+    let tempdir = create_abandoned_dependent(
+        r#"{ name = "abandoned", reason = "replacing in Q3", until = "2025-09-01" }"#,
+    )?;
+    let dependent = tempdir.path().join("dependent");
+
+    let output = cargo_unmaintained(&dependent)
+        .arg("--now=2025-08-01T00:00:00Z")
+        .output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains(
+            "Ignored by `workspace.metadata.unmaintained.ignore`:\n    abandoned (reason: \
+             replacing in Q3; until 2025-09-01)\n"
+        ),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn ignore_expired() -> Result<()> {
+    let tempdir = create_abandoned_dependent(
+        r#"{ name = "abandoned", reason = "replacing in Q3", until = "2025-09-01" }"#,
+    )?;
+    let dependent = tempdir.path().join("dependent");
+
+    let output = cargo_unmaintained(&dependent)
+        .arg("--now=2025-09-02T00:00:00Z")
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line == "    ignore entry expired on 2025-09-01 (reason: replacing in Q3)"),
+        "{stdout}"
+    );
+    ensure!(!stderr.contains("Ignored by"), "{stderr}");
+
+    Ok(())
+}
+
+// smoelius: A plain string and a table without a date are never expired.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn ignore_without_until() -> Result<()> {
+    for entry in [r#""abandoned""#, r#"{ name = "abandoned" }"#] {
+        let tempdir = create_abandoned_dependent(entry)?;
+        let dependent = tempdir.path().join("dependent");
+
+        let output = cargo_unmaintained(&dependent)
+            .arg("--now=2100-01-01T00:00:00Z")
+            .output()?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.success(), "{stderr}");
+        ensure!(
+            stderr.lines().any(|line| line == "    abandoned"),
+            "{stderr}"
+        );
+    }
+
+    Ok(())
+}
+
+fn create_abandoned_dependent(entry: &str) -> Result<TempDir> {
+    let tempdir = tempdir()?;
+    let url = format!("file://{}", tempdir.path().join("missing.git").display());
+    let dependency = tempdir.path().join("abandoned");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(
+        &dependency,
+        "abandoned",
+        &format!("repository = \"{url}\"\n"),
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nabandoned = {{ path = \"{}\" \
+             }}\n\n[workspace.metadata.unmaintained]\nignore = [{entry}]\n",
+            dependency.display()
+        ),
+    )?;
+
+    Ok(tempdir)
+}
+
 fn create_test_package() -> Result<TempDir> {
     let tempdir = tempdir()?;
 
@@ -0,0 +1,153 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{copy, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained_command, write_package};
+
+// smoelius: `abandoned`'s repository url names a repository that does not exist. So `abandoned` is
+// flagged unless it is ignored. The sample configuration ignores it by name.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn import_ignores() -> Result<()> {
+    let tempdir = create_abandoned_dependent()?;
+
+    let output = cargo_unmaintained(&tempdir, &["--import-ignores-from"], Some(&deny_toml()))?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr
+            .lines()
+            .any(|line| line == "    abandoned (reason: replacing in Q3; ignored via deny.toml)"),
+        "{stderr}"
+    );
+
+    // smoelius: The configuration's other package specs name packages the workspace does not use.
+    for name in ["duplicated", "legacy", "vendored"] {
+        ensure!(
+            stderr.lines().any(|line| line
+                == format!(
+                    "warning: deny.toml says to ignore `{name}`, but workspace does not depend \
+                     upon `{name}`"
+                )),
+            "{stderr}"
+        );
+    }
+
+    #[cfg(not(feature = "rustsec"))]
+    ensure!(
+        stderr.lines().any(|line| {
+            line == "warning: `deny.toml` ignores advisory `RUSTSEC-2000-0003`, but mapping \
+                     advisories to packages requires the `rustsec` feature; disregarding it"
+        }),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn import_ignores_from_metadata() -> Result<()> {
+    let tempdir = create_abandoned_dependent()?;
+    let dependent = tempdir.path().join("dependent");
+
+    copy(deny_toml(), dependent.join("deny.toml"))?;
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(dependent.join("Cargo.toml"))?;
+    writeln!(
+        manifest,
+        r#"
+[workspace.metadata.unmaintained]
+import_ignores_from = "deny.toml"
+"#
+    )?;
+
+    let output = cargo_unmaintained(&tempdir, &[], None)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains("abandoned (reason: replacing in Q3; ignored via deny.toml)"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+// smoelius: A package named with `--package` is checked even though the configuration ignores it.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn package_conflict() -> Result<()> {
+    let tempdir = create_abandoned_dependent()?;
+
+    // smoelius: `--package` resolves the package in the current workspace only if the workspace
+    // has a lockfile.
+    let status = Command::new("cargo")
+        .args(["generate-lockfile", "--offline", "--quiet"])
+        .current_dir(tempdir.path().join("dependent"))
+        .status()?;
+    ensure!(status.success());
+
+    let output = cargo_unmaintained(
+        &tempdir,
+        &["--package=abandoned", "--import-ignores-from"],
+        Some(&deny_toml()),
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(
+        stderr.lines().any(|line| line
+            == "warning: `abandoned` is ignored via deny.toml, but is named by --package; \
+                checking it anyway"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+fn deny_toml() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/deny.toml")
+}
+
+fn cargo_unmaintained(tempdir: &TempDir, args: &[&str], path: Option<&Path>) -> Result<Output> {
+    cargo_unmaintained_command(
+        &tempdir.path().join("dependent"),
+        &tempdir.path().join("cache"),
+    )
+    .args(args)
+    .args(path)
+    .output()
+    .map_err(Into::into)
+}
+
+fn create_abandoned_dependent() -> Result<TempDir> {
+    let tempdir = tempdir()?;
+    let url = format!("file://{}", tempdir.path().join("missing.git").display());
+    let dependency = tempdir.path().join("abandoned");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(
+        &dependency,
+        "abandoned",
+        &format!("repository = \"{url}\"\n"),
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nabandoned = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    Ok(tempdir)
+}
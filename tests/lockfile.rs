@@ -0,0 +1,89 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{remove_file, File},
+    process::Command,
+    time::{Duration, SystemTime},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, write_package};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// smoelius: The only dependency is a path dependency, so no release dates can be fetched, and the
+// lockfile's modification time is used. No network access is required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn lockfile() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependency = tempdir.path().join("dependency");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(&dependency, "dependency", "publish = false\n")?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    let output = Command::new("cargo")
+        .arg("generate-lockfile")
+        .current_dir(&dependent)
+        .output()?;
+    ensure!(output.status.success());
+
+    // smoelius: Add an hour so that rounding does not affect the number of days reported.
+    let modified =
+        SystemTime::now() - Duration::from_secs(730 * SECS_PER_DAY) - Duration::from_hours(1);
+    File::options()
+        .write(true)
+        .open(dependent.join("Cargo.lock"))?
+        .set_modified(modified)?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stderr}");
+    ensure!(
+        stderr.contains(
+            "warning: `Cargo.lock` appears stale (last modified 730 days ago); findings may \
+             reflect your lockfile rather than upstream maintenance; consider `cargo update`"
+        ),
+        "{stderr}"
+    );
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--require-fresh-lockfile=1000"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stderr}");
+    ensure!(!stderr.contains("stale"), "{stderr}");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--require-fresh-lockfile=365"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(2), "{stderr}");
+    ensure!(
+        stderr.contains(
+            "Error: `Cargo.lock` is stale (last modified 730 days ago, which exceeds \
+             --require-fresh-lockfile=365); consider `cargo update`"
+        ),
+        "{stderr}"
+    );
+
+    // smoelius: A missing lockfile is warned about, but is not stale.
+    remove_file(dependent.join("Cargo.lock"))?;
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--require-fresh-lockfile=365"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stderr}");
+    ensure!(
+        stderr.contains("/Cargo.lock` does not exist; versions will be resolved as of now"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, write},
+    path::Path,
+    process::Output,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+const FILLERS: usize = 10;
+
+// smoelius: The repository contains:
+// - `big/Cargo.toml`, which names `big-dep` but is larger than the default --max-manifest-size
+// - `fillers/*/Cargo.toml`, which name packages no one depends on
+// - `third_party/named-dep/Cargo.toml`, which is vendored, but whose path names `named-dep`
+// - `vendor/other/Cargo.toml`, which is vendored and names `vendored-dep`
+// - `zz/Cargo.toml`, which names `late-dep` and is listed after the fillers
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn manifest_guards() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("repository = \"file://{}\"\n", origin.display());
+
+    create_dir_all(&repo)?;
+    write(repo.join("README.md"), "")?;
+    write_package(
+        &repo.join("big"),
+        "big-dep",
+        &format!("{repository}{}\n", "#".repeat(1_100_000)),
+    )?;
+    for i in 0..FILLERS {
+        write_package(
+            &repo.join(format!("fillers/f{i}")),
+            &format!("filler-{i}"),
+            &repository,
+        )?;
+    }
+    write_package(
+        &repo.join("third_party/named-dep"),
+        "named-dep",
+        &repository,
+    )?;
+    write_package(&repo.join("vendor/other"), "vendored-dep", &repository)?;
+    write_package(&repo.join("zz"), "late-dep", &repository)?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    let dependencies = ["big", "third_party/named-dep", "vendor/other", "zz"]
+        .iter()
+        .zip(["big-dep", "named-dep", "vendored-dep", "late-dep"])
+        .map(|(dir, name)| format!("{name} = {{ path = \"{}\" }}\n", repo.join(dir).display()))
+        .collect::<String>();
+    write_package(
+        &dependent,
+        "dependent",
+        &format!("publish = false\n\n[dependencies]\n{dependencies}"),
+    )?;
+
+    let (names, stderr) = unassociated(&dependent, &tempdir.path().join("cache-0"), &[])?;
+    ensure!(
+        names == BTreeSet::from(["big-dep".to_owned(), "vendored-dep".to_owned()]),
+        "{names:?}\n{stderr}"
+    );
+    ensure!(stderr.contains("exceeds --max-manifest-size"), "{stderr}");
+    ensure!(stderr.contains("manifests skipped for size: "), "{stderr}");
+    ensure!(
+        stderr.contains("manifests skipped as vendored: "),
+        "{stderr}"
+    );
+
+    let (names, stderr) = unassociated(
+        &dependent,
+        &tempdir.path().join("cache-1"),
+        &["--scan-vendored", "--max-manifest-size=2000000"],
+    )?;
+    ensure!(names.is_empty(), "{names:?}\n{stderr}");
+
+    let (names, stderr) = unassociated(
+        &dependent,
+        &tempdir.path().join("cache-2"),
+        &["--max-manifests=5"],
+    )?;
+    ensure!(
+        names
+            == BTreeSet::from([
+                "big-dep".to_owned(),
+                "late-dep".to_owned(),
+                "vendored-dep".to_owned()
+            ]),
+        "{names:?}\n{stderr}"
+    );
+    ensure!(
+        stderr.contains(&format!(
+            "has {} manifests; examining only 5 of them for `late-dep`",
+            FILLERS + 1
+        )),
+        "{stderr}"
+    );
+    ensure!(
+        stderr.contains("repositories with too many manifests: "),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Runs `cargo unmaintained` and returns the names of the packages found to be unassociated with
+/// their repositories, along with standard error
+fn unassociated(
+    dir: &Path,
+    cache_home: &Path,
+    args: &[&str],
+) -> Result<(BTreeSet<String>, String)> {
+    let output = cargo_unmaintained(dir, cache_home, args)?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)
+        .map_err(|error| anyhow::anyhow!("{error}: {stdout}\n{stderr}"))?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+    let mut names = BTreeSet::new();
+    for package in packages {
+        ensure!(package["repo_status"] == "Unassociated", "{package}");
+        names.insert(package["name"].as_str().unwrap_or_default().to_owned());
+    }
+    Ok((names, stderr))
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path, args: &[&str]) -> Result<Output> {
+    cargo_unmaintained_command(dir, cache_home)
+        .args(["--json", "--verbose"])
+        .args(args)
+        .output()
+        .map_err(Into::into)
+}
@@ -0,0 +1,100 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::fs::read_dir;
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: Any clone, even one of a repository with a single empty file, occupies more than 1000
+// bytes.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn max_repo_size() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(
+        &repo,
+        "large",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nlarge = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    // smoelius: By default, an oversized clone produces only a warning.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--max-repo-size=1000"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.lines().any(|line| line.starts_with(&format!(
+            "warning: clone of `file://{}` occupies ",
+            origin.display()
+        )) && line
+            .ends_with("bytes, which exceeds --max-repo-size (1000 bytes)")),
+        "{stderr}"
+    );
+
+    // smoelius: The warning is repeated on a warm run, using the size recorded in the entry.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--max-repo-size=1000"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(stderr.contains("exceeds --max-repo-size"), "{stderr}");
+
+    // smoelius: With `--deny-oversize`, the package is reported, and its clone is removed.
+    let cache_home = tempdir.path().join("cold-cache");
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--max-repo-size=1000", "--deny-oversize"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(1), "{stdout}");
+    ensure!(
+        stdout.lines().any(|line| line
+            == format!(
+                "large (file://{} skipped: repository too large) [path dependency]",
+                origin.display()
+            )),
+        "{stdout}"
+    );
+    let repositories = cache_home.join("cargo-unmaintained/v2/repositories");
+    ensure!(read_dir(repositories)?.next().is_none());
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--max-repo-size=1000", "--deny-oversize", "--json"],
+    )?;
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(
+        report["packages"][0]["repo_status"] == "TooLarge",
+        "{report}"
+    );
+
+    Ok(())
+}
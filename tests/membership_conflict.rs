@@ -0,0 +1,91 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::fs::{create_dir_all, write};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: The monorepo contains two packages. In the commit pushed to the origin, the second is
+// spelled `second_member`, whereas the working copy that the dependent uses spells it
+// `second-member`. So `first-member` is found in the repository, but `second-member` is not.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn membership_conflict() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("repository = \"file://{}\"\n", origin.display());
+
+    create_dir_all(&repo)?;
+    write(
+        repo.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"first\", \"second\"]\n",
+    )?;
+    write_package(&repo.join("first"), "first-member", &repository)?;
+    write_package(&repo.join("second"), "second_member", &repository)?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    write_package(&repo.join("second"), "second-member", &repository)?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nfirst-member = {{ path = \"{}\" }}\nsecond-member \
+             = {{ path = \"{}\" }}\n",
+            repo.join("first").display(),
+            repo.join("second").display()
+        ),
+    )?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--json"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+
+    let warnings = stderr
+        .lines()
+        .filter(|line| line.contains("disagree about membership"))
+        .collect::<Vec<_>>();
+    ensure!(warnings.len() == 1, "{stderr}");
+    ensure!(
+        warnings[0].contains(
+            "found in the repository: first-member@0.1.0; not found: second-member@0.1.0"
+        ),
+        "{stderr}"
+    );
+
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    ensure!(packages.len() == 1, "{stdout}");
+    ensure!(packages[0]["name"] == "second-member", "{stdout}");
+    ensure!(packages[0]["repo_status"] == "Unassociated", "{stdout}");
+    ensure!(packages[0]["membership_conflict"] == true, "{stdout}");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains("other packages from the same repository were found in it"),
+        "{stdout}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use serde_json::Value;
+use std::{
+    fs::{create_dir, read_dir, read_to_string, write},
+    path::Path,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, package_filename};
+
+// smoelius: The dependency's `repository` field is changed between two runs that share a cache.
+// The second run should replace the dependency's entry and remove the first run's clone.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn moved_repository_leaves_no_orphans() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin_1 = tempdir.path().join("origin-1.git");
+    let origin_2 = tempdir.path().join("origin-2.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    write_manifest(&repo, &origin_1)?;
+    create_dir(repo.join("src"))?;
+    write(repo.join("src/lib.rs"), "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    for origin in [&origin_1, &origin_2] {
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+    }
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "dependent"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[dependencies]
+moved-dependency = {{ path = "{}" }}
+"#,
+            repo.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let output = cargo_unmaintained_command(&dependent, &cache_home).output()?;
+    ensure!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    write_manifest(&repo, &origin_2)?;
+
+    let output = cargo_unmaintained_command(&dependent, &cache_home).output()?;
+    ensure!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let base_dir = cache_home.join("cargo-unmaintained/v2");
+    for subdir in ["repositories", "timestamps"] {
+        let n = read_dir(base_dir.join(subdir))?.count();
+        ensure!(n == 1, "`{subdir}` has {n} entries");
+    }
+
+    let entry = read_to_string(
+        base_dir
+            .join("entries")
+            .join(package_filename("moved-dependency")),
+    )?;
+    let entry = serde_json::from_str::<Value>(&entry)?;
+    let origin_1_url = format!("file://{}", origin_1.display());
+    let origin_2_url = format!("file://{}", origin_2.display());
+    ensure!(
+        entry["named_url"] == origin_2_url.as_str(),
+        "unexpected entry: {entry}"
+    );
+    ensure!(
+        entry["previous_urls"] == serde_json::json!([origin_1_url]),
+        "unexpected entry: {entry}"
+    );
+
+    Ok(())
+}
+
+fn write_manifest(repo: &Path, origin: &Path) -> Result<()> {
+    write(
+        repo.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "moved-dependency"
+version = "0.1.0"
+edition = "2021"
+repository = "file://{}"
+"#,
+            origin.display()
+        ),
+    )
+    .map_err(Into::into)
+}
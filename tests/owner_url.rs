@@ -0,0 +1,71 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir, write},
+    process::Command,
+};
+use tempfile::tempdir;
+
+// smoelius: The dependency's repository field names an organization. Nothing should be cloned, so
+// no network access is required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn owner_url() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+    let dep = tempdir.path().join("a");
+
+    create_dir(&dep)?;
+    write(
+        dep.join("Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrepository = \
+         \"https://github.com/rust-lang\"\n",
+    )?;
+    create_dir(dep.join("src"))?;
+    write(dep.join("src/lib.rs"), "")?;
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+             false\n\n[dependencies]\na = {{ path = \"{}\" }}\n",
+            dep.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    for args in [&[][..], &["--only=stale", "--max-age=0"]] {
+        let output = Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "--color=never", "--token-source=env"])
+            .args(args)
+            .env("XDG_CACHE_HOME", &cache_home)
+            .env_remove("GITHUB_TOKEN")
+            .env_remove("GITHUB_TOKEN_PATH")
+            .current_dir(&dependent)
+            .output()?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.success(), "{args:?}: {stderr}");
+        ensure!(
+            stderr.contains(
+                "note: `a`'s repository field `https://github.com/rust-lang` names an owner, not \
+                 a repository; treating `a` as having no repository"
+            ),
+            "{args:?}: {stderr}"
+        );
+        ensure!(!stderr.contains("failed to clone"), "{args:?}: {stderr}");
+        ensure!(
+            !cache_home
+                .join("cargo-unmaintained/v2/repositories")
+                .try_exists()?,
+            "{args:?}: {stderr}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{fs::OpenOptions, io::Write, path::Path, process::Command};
+use tempfile::tempdir;
+
+// smoelius: `chrono`'s `rkyv-32` feature enables a dependency on `rkyv` 0.7, whereas the latest
+// version of `rkyv` is 0.8. So whether `chrono` has outdated dependencies depends on its features.
+const NAME: &str = "chrono";
+const FEATURE: &str = "rkyv-32";
+const OPTIONAL_DEPENDENCY: &str = "rkyv";
+
+#[test]
+fn package_and_workspace_modes_agree() -> Result<()> {
+    let tempdir = tempdir()?;
+
+    let status = Command::new("cargo")
+        .args(["init", "--name=test-package"])
+        .current_dir(&tempdir)
+        .status()?;
+    ensure!(status.success());
+
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(tempdir.path().join("Cargo.toml"))?;
+    writeln!(
+        manifest,
+        r#"{NAME} = {{ version = "*", features = ["{FEATURE}"] }}"#
+    )?;
+
+    let workspace = outdated_deps(tempdir.path(), &[])?;
+    ensure!(workspace.contains(&OPTIONAL_DEPENDENCY.to_owned()));
+
+    // smoelius: `--latest` causes `NAME` to be evaluated in a temporary package, even though the
+    // workspace depends upon it.
+    let package = outdated_deps(
+        tempdir.path(),
+        &["--package", NAME, "--latest", "--features", FEATURE],
+    )?;
+    ensure!(package == workspace);
+
+    // smoelius: Without the feature, `--package --latest` evaluates a different dependency graph.
+    let package = outdated_deps(tempdir.path(), &["--package", NAME, "--latest"])?;
+    ensure!(!package.contains(&OPTIONAL_DEPENDENCY.to_owned()));
+
+    Ok(())
+}
+
+#[test]
+fn banner() -> Result<()> {
+    let tempdir = tempdir()?;
+
+    let output = cargo_unmaintained(
+        tempdir.path(),
+        &[
+            "--package",
+            &format!("{NAME}@0.4.30"),
+            "--no-default-features",
+            "--features",
+            "serde",
+        ],
+    )
+    .output()?;
+
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        stderr
+            .lines()
+            .any(|line| line == format!("Evaluating {NAME}@0.4.30 with features: serde")),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Returns the names of `NAME`'s outdated dependencies
+fn outdated_deps(dir: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = cargo_unmaintained(dir, &[&["--json"], args].concat()).output()?;
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    let mut outdated_deps = packages
+        .iter()
+        .filter(|package| package["name"] == NAME)
+        .flat_map(|package| {
+            package["outdated_deps"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+        })
+        .filter_map(|outdated_dep| outdated_dep["name"].as_str().map(ToOwned::to_owned))
+        .collect::<Vec<_>>();
+    outdated_deps.sort();
+    Ok(outdated_deps)
+}
+
+fn cargo_unmaintained(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+    command
+        .args(["unmaintained", "--only=outdated-deps"])
+        .args(args)
+        .current_dir(dir);
+    command
+}
@@ -0,0 +1,138 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{path::Path, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+const NOTICE: &str =
+    "note: package not found in current workspace; evaluating latest published version";
+
+// smoelius: `dependency`'s repository is stale, and `dependent` depends upon `dependency` by path.
+// So `--package dependency` should check the instance of `dependency` resolved in `dependent`'s
+// workspace, rather than look for `dependency` on crates.io.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn package_in_workspace() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = fixture(tempdir.path())?;
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--package", "dependency"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+    ensure!(
+        stderr.lines().any(|line| line
+            == "Evaluating dependency@0.1.0 with no features (as resolved in the current \
+                workspace)"),
+        "{stderr}"
+    );
+    ensure!(!stderr.contains(NOTICE), "{stderr}");
+    ensure!(stdout.contains("dependency"), "{stdout}");
+
+    Ok(())
+}
+
+// smoelius: A package that the workspace does not depend upon is evaluated in a temporary package,
+// as is a package that it does depend upon if `--latest` is passed. Only the former produces the
+// notice. Whether the temporary package can be built (which requires crates.io) is not checked.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn package_not_in_workspace() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = fixture(tempdir.path())?;
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &[
+            "--only=stale",
+            "--package",
+            "cargo-unmaintained-nonexistent-package",
+        ],
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(stderr.lines().any(|line| line == NOTICE), "{stderr}");
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &cache_home,
+        &["--only=stale", "--package", "dependency", "--latest"],
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(!stderr.contains(NOTICE), "{stderr}");
+    ensure!(
+        !stderr.contains("(as resolved in the current workspace)"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Creates a stale package `dependency` and a package `dependent` that depends upon it, and
+/// returns the path of `dependent`
+fn fixture(dir: &Path) -> Result<std::path::PathBuf> {
+    let repo = dir.join("repo");
+    let origin = dir.join("origin.git");
+    let dependent = dir.join("dependent");
+
+    write_package(
+        &repo,
+        "dependency",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+            "commit",
+            "--quiet",
+            "--message=Initial commit",
+        ])
+        .env("GIT_AUTHOR_DATE", "2015-01-01T00:00:00Z")
+        .env("GIT_COMMITTER_DATE", "2015-01-01T00:00:00Z")
+        .current_dir(&repo)
+        .status()?;
+    ensure!(status.success());
+    git(
+        dir,
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ndependency = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    // smoelius: The workspace is used only if its lockfile is up to date.
+    let status = Command::new("cargo")
+        .args(["generate-lockfile", "--offline", "--quiet"])
+        .current_dir(&dependent)
+        .status()?;
+    ensure!(status.success());
+
+    Ok(dependent)
+}
@@ -0,0 +1,88 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{path::Path, process::Output};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+// smoelius: `inner` resides in the dependent's directory, and `outer` resides outside of it. Both
+// name a repository containing no package of their names. So both are unassociated with their
+// repositories, but only `outer` is a path dependency that resolves outside the workspace root.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn path_dependency() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("repository = \"file://{}\"\n", origin.display());
+
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    write_package(&dependent.join("inner"), "inner", &repository)?;
+    write_package(&tempdir.path().join("outer"), "outer", &repository)?;
+    write_package(
+        &dependent,
+        "dependent",
+        "publish = false\n\n[dependencies]\ninner = { path = \"inner\" }\nouter = { path = \
+         \"../outer\" }\n",
+    )?;
+
+    let cache_home = tempdir.path().join("cache");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, true)?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    ensure!(packages.len() == 2, "{stdout}");
+    for package in &packages {
+        let external = package["name"] == "outer";
+        ensure!(package["repo_status"] == "Unassociated", "{stdout}");
+        ensure!(
+            package.get("path_dependency").is_some() == external,
+            "{stdout}"
+        );
+    }
+
+    let output = cargo_unmaintained(&dependent, &cache_home, false)?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("outer (") && line.ends_with(" [path dependency]")),
+        "{stdout}"
+    );
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("inner (") && !line.contains("[path dependency]")),
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path, json: bool) -> Result<Output> {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    if json {
+        command.arg("--json");
+    }
+    command.output().map_err(Into::into)
+}
@@ -0,0 +1,105 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir, write},
+    path::Path,
+    process::{Command, Output},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+// smoelius: The dependency's only commit is fresh. So it is stale only if the time is pinned far
+// enough in the future. No network access is required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn pinned_now() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+    let repo = tempdir.path().join("a");
+    let origin = tempdir.path().join("a.git");
+
+    create_dir(&repo)?;
+    write(
+        repo.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrepository = \
+             \"file://{}\"\n",
+            origin.display()
+        ),
+    )?;
+    create_dir(repo.join("src"))?;
+    write(repo.join("src/lib.rs"), "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+             false\n\n[dependencies]\na = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    for (args, env, expected) in [
+        (&[][..], None, 0),
+        (&["--now", "2100-01-01T00:00:00Z"][..], None, 1),
+        (&[][..], Some("2100-01-01T00:00:00Z"), 1),
+        // smoelius: `--now` takes precedence over the environment variable.
+        (
+            &["--now", "2000-01-01T00:00:00Z"][..],
+            Some("2100-01-01T00:00:00Z"),
+            0,
+        ),
+    ] {
+        let output = run(&dependent, &cache_home, args, env)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(
+            output.status.code() == Some(expected),
+            "{args:?}, {env:?}: {stderr}"
+        );
+    }
+
+    let output = run(&dependent, &cache_home, &["--now", "yesterday"], None)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        stderr.contains("Error: failed to parse --now value `yesterday` as an RFC 3339 time"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+fn run(dir: &Path, cache_home: &Path, args: &[&str], env: Option<&str>) -> Result<Output> {
+    let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+    command
+        .args(["unmaintained", "--color=never", "--only=stale"])
+        .args(args)
+        .env("XDG_CACHE_HOME", cache_home)
+        .env_remove("CARGO_UNMAINTAINED_NOW")
+        .current_dir(dir);
+    if let Some(now) = env {
+        command.env("CARGO_UNMAINTAINED_NOW", now);
+    }
+    command.output().map_err(Into::into)
+}
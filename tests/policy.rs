@@ -0,0 +1,201 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{anyhow, ensure, Result};
+use std::{
+    fs::{create_dir, write},
+    process::Output,
+};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: The dependency's repository does not contain a package named `renamed-dependency`. So
+// the dependency is reported as unassociated with its repository, which is not a problem of any
+// kind that `--deny` accepts. No network access is required.
+const PRESETS: &[&str] = &["default", "strict", "rustsec", "relaxed"];
+
+struct Fixture {
+    tempdir: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let tempdir = tempdir()?;
+        let repo = tempdir.path().join("repo");
+        let origin = tempdir.path().join("origin.git");
+        let dependency = tempdir.path().join("dependency");
+        let dependent = tempdir.path().join("dependent");
+
+        create_dir(&repo)?;
+        write_package(&repo, "original-name", "")?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        create_dir(&dependency)?;
+        write_package(
+            &dependency,
+            "renamed-dependency",
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+
+        create_dir(&dependent)?;
+        write_package(
+            &dependent,
+            "dependent",
+            &format!(
+                "publish = false\n\n[dependencies]\nrenamed-dependency = {{ path = \"{}\" }}\n",
+                dependency.display()
+            ),
+        )?;
+
+        Ok(Self { tempdir })
+    }
+
+    fn cargo_unmaintained(&self, args: &[&str]) -> Result<Output> {
+        let output = cargo_unmaintained(
+            &self.tempdir.path().join("dependent"),
+            &self.tempdir.path().join("cache"),
+            args,
+        )?;
+        ensure!(
+            output.status.code().is_some_and(|code| code <= 1),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(output)
+    }
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn presets() -> Result<()> {
+    let fixture = Fixture::new()?;
+
+    let output = fixture.cargo_unmaintained(&[])?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with(
+            "Policy: default (--max-age=365 --outdated-dep-grace=365) with built-in allowlist\n"
+        ),
+        "{stdout}"
+    );
+
+    // smoelius: The dependency is still reported, but the relaxed preset does not deny it.
+    let output = fixture.cargo_unmaintained(&["--policy=relaxed"])?;
+    ensure!(output.status.code() == Some(0));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with(
+            "Policy: relaxed (--max-age=730 --outdated-dep-grace=730 --deny=archived,nonexistent) \
+             with built-in allowlist\nNot found in their repositories:\nrenamed-dependency "
+        ),
+        "{stdout}"
+    );
+
+    // smoelius: Explicit options override the preset's members.
+    let output = fixture.cargo_unmaintained(&[
+        "--policy=relaxed",
+        "--max-age=1000",
+        "--deny=stale,archived",
+    ])?;
+    ensure!(output.status.code() == Some(0));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with(
+            "Policy: relaxed (--max-age=1000 --outdated-dep-grace=730 --deny=stale,archived) with \
+             built-in allowlist\n"
+        ),
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn round_trip() -> Result<()> {
+    let fixture = Fixture::new()?;
+
+    for preset in PRESETS {
+        let with_preset = fixture.cargo_unmaintained(&[&format!("--policy={preset}")])?;
+        let stdout_with_preset = String::from_utf8(with_preset.stdout)?;
+        let (header, report_with_preset) = stdout_with_preset
+            .split_once('\n')
+            .ok_or_else(|| anyhow!("no header: {stdout_with_preset}"))?;
+        let args = header
+            .strip_prefix(&format!("Policy: {preset} ("))
+            .and_then(|rest| rest.strip_suffix(") with built-in allowlist"))
+            .ok_or_else(|| anyhow!("unexpected header: {header}"))?
+            .split(' ')
+            .collect::<Vec<_>>();
+
+        let explicit = fixture.cargo_unmaintained(&args)?;
+        let stdout_explicit = String::from_utf8(explicit.stdout)?;
+        let (_, report_explicit) = stdout_explicit
+            .split_once('\n')
+            .ok_or_else(|| anyhow!("no header: {stdout_explicit}"))?;
+
+        ensure!(
+            with_preset.status.code() == explicit.status.code(),
+            "{preset}"
+        );
+        ensure!(report_with_preset == report_explicit, "{preset}");
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn json_and_render() -> Result<()> {
+    let fixture = Fixture::new()?;
+
+    let output = fixture.cargo_unmaintained(&["--json", "--json-metadata", "--policy=rustsec"])?;
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(
+        report["metadata"]["policy"]
+            == serde_json::json!({
+                "preset": "rustsec",
+                "max_age": 365,
+                "outdated_dep_grace": 365,
+                "deny": ["archived", "nonexistent", "stale"],
+                "builtin_allowlist": true,
+            }),
+        "{report}"
+    );
+
+    let input = fixture.tempdir.path().join("report.json");
+    write(&input, &output.stdout)?;
+    let output = fixture.cargo_unmaintained(&[
+        "render",
+        "--input",
+        &input.to_string_lossy(),
+        "--policy=relaxed",
+        "--max-age=1000",
+    ])?;
+    ensure!(output.status.code() == Some(0));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with(
+            "Policy: relaxed (--max-age=1000 --outdated-dep-grace=730 \
+             --deny=archived,nonexistent) with built-in allowlist\n"
+        ),
+        "{stdout}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{path::Path, process::Output};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+// smoelius: `renamed` names a repository containing no package of that name, and has no
+// dependencies. So it is unassociated with its repository, but has no outdated dependencies, and
+// its repository's url is not one whose general status is checked. Without `--prefilter`, it is
+// reported. With `--prefilter`, it is ruled out in the first phase, and its repository is never
+// cloned.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn prefilter() -> Result<()> {
+    let tempdir = tempdir()?;
+    let dependent = tempdir.path().join("dependent");
+    write_fixture(tempdir.path(), &dependent)?;
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache-0"), &[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    ensure!(packages.len() == 1, "{stdout}");
+    ensure!(packages[0]["repo_status"] == "Unassociated", "{stdout}");
+    ensure!(!stderr.contains("pre-filter"), "{stderr}");
+    ensure!(
+        stderr.contains("    package check (second phase): 1 calls"),
+        "{stderr}"
+    );
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache-1"),
+        &["--prefilter"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stdout}{stderr}");
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    ensure!(report["packages"] == serde_json::json!([]), "{stdout}");
+    for expected in [
+        "Pre-filter: 0 of 1 packages could be unmaintained\n",
+        "    pre-filter (first phase): 1 calls",
+        "    packages ruled out by pre-filter: 1\n",
+    ] {
+        ensure!(stderr.contains(expected), "{stderr}");
+    }
+    ensure!(!stderr.contains("package check (second phase)"), "{stderr}");
+    ensure!(!stderr.contains("    clone: "), "{stderr}");
+
+    Ok(())
+}
+
+fn write_fixture(root: &Path, dependent: &Path) -> Result<()> {
+    let repo = root.join("repo");
+    let origin = root.join("origin.git");
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        root,
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    let renamed = root.join("renamed");
+    write_package(
+        &renamed,
+        "renamed",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    write_package(
+        dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nrenamed = {{ path = \"{}\" }}\n",
+            renamed.display()
+        ),
+    )
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path, args: &[&str]) -> Result<Output> {
+    cargo_unmaintained_command(dir, cache_home)
+        .args(["--json", "--timings"])
+        .args(args)
+        .output()
+        .map_err(Into::into)
+}
@@ -0,0 +1,83 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: `renamed` names a repository containing no package of that name. So it is unassociated
+// with its repository, and a scan exits with 1. Priming exits with 0 regardless.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn prime() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let renamed = tempdir.path().join("renamed");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    write_package(
+        &renamed,
+        "renamed",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nrenamed = {{ path = \"{}\" }}\n",
+            renamed.display()
+        ),
+    )?;
+
+    let cache_home = tempdir.path().join("cache");
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["prime"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(0), "{stderr}");
+    ensure!(
+        stdout == "primed 1 package: 0 clones refreshed, 1 new, 0 version fetches\n",
+        "{stdout}"
+    );
+
+    // smoelius: The scan is answered from the cache: no repository is cloned or refreshed.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--timings"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(stdout.contains("renamed"), "{stdout}");
+    ensure!(
+        stderr.contains("repositories (on-disk): 1 hits, 0 misses"),
+        "{stderr}"
+    );
+    ensure!(!stderr.contains("repositories cloned"), "{stderr}");
+    ensure!(!stderr.contains("repositories refreshed"), "{stderr}");
+
+    // smoelius: Priming again finds everything cached.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["prime"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(output.status.code() == Some(0), "{stdout}");
+    ensure!(
+        stdout == "primed 1 package: 0 clones refreshed, 0 new, 0 version fetches\n",
+        "{stdout}"
+    );
+
+    Ok(())
+}
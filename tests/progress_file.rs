@@ -0,0 +1,107 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fmt::Write,
+    fs::{create_dir, read_to_string, write},
+    process::Command,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::git;
+
+// smoelius: With `--only stale --max-age=0`, each dependency is unmaintained. No network access is
+// required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn progress_file() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let dependent = tempdir.path().join("dependent");
+    let progress_file = tempdir.path().join("progress.json");
+
+    let mut dependencies = String::new();
+    for name in ["a", "b"] {
+        let repo = tempdir.path().join(name);
+        let origin = tempdir.path().join(format!("{name}.git"));
+        create_dir(&repo)?;
+        write(
+            repo.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \
+                 \"2021\"\nrepository = \"file://{}\"\n",
+                origin.display()
+            ),
+        )?;
+        create_dir(repo.join("src"))?;
+        write(repo.join("src/lib.rs"), "")?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+        writeln!(dependencies, "{name} = {{ path = \"{}\" }}", repo.display())?;
+    }
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+             false\n\n[dependencies]\n{dependencies}"
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args([
+            "unmaintained",
+            "--color=never",
+            "--only=stale",
+            "--max-age=0",
+            "--progress-file",
+        ])
+        .arg(&progress_file)
+        .env("XDG_CACHE_HOME", &cache_home)
+        .current_dir(&dependent)
+        .output()?;
+    ensure!(
+        output.status.code() == Some(1),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let contents = read_to_string(&progress_file)?;
+    let document = serde_json::from_str::<serde_json::Value>(&contents)?;
+    ensure!(
+        document
+            == serde_json::json!({
+                "done": 2,
+                "total": 2,
+                "flagged_so_far": ["a", "b"],
+                "complete": true,
+            }),
+        "{contents}"
+    );
+
+    // smoelius: Only the progress file should remain, i.e., no temporary files.
+    let n_files = std::fs::read_dir(tempdir.path())?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .count();
+    ensure!(n_files == 1, "{n_files}");
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{create_dir, read_dir, read_to_string, write},
+    path::Path,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git};
+
+// smoelius: The dependency's repository's default branch holds a rewrite with a different package
+// name. The dependency itself is published from the `release` branch. So the dependency should be
+// found on that branch, rather than be considered unassociated with its repository.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn member_of_release_branch() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    create_dir(repo.join("src"))?;
+    write(repo.join("src/lib.rs"), "")?;
+    git(&repo, &["init", "--quiet", "--initial-branch=main"])?;
+    write_manifest(&repo, "release-dependency", &origin)?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Release"])?;
+    git(&repo, &["branch", "release"])?;
+    write_manifest(&repo, "rewrite", &origin)?;
+    git(&repo, &["commit", "--quiet", "--all", "--message=Rewrite"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    // smoelius: The dependency is checked out from the `release` branch.
+    git(&repo, &["checkout", "--quiet", "release"])?;
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "dependent"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[dependencies]
+release-dependency = {{ path = "{}" }}
+"#,
+            repo.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--verbose"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains("membership of `release-dependency` on branch `release`...ok"),
+        "{stderr}"
+    );
+
+    let branches = cache_home.join("cargo-unmaintained/v2/branches");
+    let entries = read_dir(branches)?.collect::<std::io::Result<Vec<_>>>()?;
+    ensure!(entries.len() == 1);
+    ensure!(read_to_string(entries[0].path())? == "release");
+
+    // smoelius: On a warm run, the recorded branch is tried first.
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--verbose"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(!stderr.contains("on branch `v0.x`"), "{stderr}");
+
+    Ok(())
+}
+
+fn write_manifest(dir: &Path, name: &str, origin: &Path) -> Result<()> {
+    write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+repository = "file://{}"
+"#,
+            origin.display()
+        ),
+    )?;
+    Ok(())
+}
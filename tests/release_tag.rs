@@ -0,0 +1,95 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{create_dir, write},
+    path::Path,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained, git};
+
+// smoelius: The dependency's repository's default branch holds a rewrite with a different package
+// name, and no other branch holds the dependency. But the commit from which the dependency was
+// published is tagged `v0.1.0`. So the dependency should be found at that tag, rather than be
+// considered unassociated with its repository.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn member_at_release_tag() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    create_dir(repo.join("src"))?;
+    write(repo.join("src/lib.rs"), "")?;
+    git(&repo, &["init", "--quiet", "--initial-branch=main"])?;
+    write_manifest(&repo, "tagged-dependency", &origin)?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Release"])?;
+    git(&repo, &["tag", "v0.1.0"])?;
+    write_manifest(&repo, "rewrite", &origin)?;
+    git(&repo, &["commit", "--quiet", "--all", "--message=Rewrite"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    // smoelius: The dependency is checked out from the tag.
+    git(&repo, &["checkout", "--quiet", "v0.1.0"])?;
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "dependent"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[dependencies]
+tagged-dependency = {{ path = "{}" }}
+"#,
+            repo.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home, &["--verbose"])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains("membership of `tagged-dependency` at tag `v0.1.0`...ok"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+fn write_manifest(dir: &Path, name: &str, origin: &Path) -> Result<()> {
+    write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+repository = "file://{}"
+"#,
+            origin.display()
+        ),
+    )?;
+    Ok(())
+}
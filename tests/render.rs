@@ -0,0 +1,409 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{create_dir, read_to_string, write},
+    process::{Command, Output},
+};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: The dependency's repository does not contain a package named `renamed-dependency`. So
+// the dependency is reported as unassociated with its repository. No network access is required.
+struct Fixture {
+    tempdir: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let tempdir = tempdir()?;
+        let repo = tempdir.path().join("repo");
+        let origin = tempdir.path().join("origin.git");
+        let dependency = tempdir.path().join("dependency");
+        let dependent = tempdir.path().join("dependent");
+
+        create_dir(&repo)?;
+        write_package(&repo, "original-name", "")?;
+        git(&repo, &["init", "--quiet"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        create_dir(&dependency)?;
+        write_package(
+            &dependency,
+            "renamed-dependency",
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+
+        create_dir(&dependent)?;
+        write_package(
+            &dependent,
+            "dependent",
+            &format!(
+                "publish = false\n\n[dependencies]\nrenamed-dependency = {{ path = \"{}\" }}\n",
+                dependency.display()
+            ),
+        )?;
+
+        Ok(Self { tempdir })
+    }
+
+    fn cargo_unmaintained(&self, args: &[&str]) -> Result<Output> {
+        let output = cargo_unmaintained(
+            &self.tempdir.path().join("dependent"),
+            &self.tempdir.path().join("cache"),
+            args,
+        )?;
+        ensure!(
+            output.status.code().is_some_and(|code| code <= 1),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(output)
+    }
+
+    fn report(&self, args: &[&str]) -> Result<String> {
+        let output = self.cargo_unmaintained(&[&["--json"], args].concat())?;
+        let path = self.tempdir.path().join("report.json");
+        write(&path, output.stdout)?;
+        Ok(path.to_string_lossy().to_string())
+    }
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn round_trip() -> Result<()> {
+    let fixture = Fixture::new()?;
+
+    let direct = fixture.cargo_unmaintained(&[])?;
+    ensure!(direct.status.code() == Some(1));
+    let stdout = String::from_utf8(direct.stdout)?;
+    ensure!(
+        stdout.contains("\nrenamed-dependency (not in file://"),
+        "{stdout}"
+    );
+
+    for args in [&[][..], &["--json-metadata"]] {
+        let input = fixture.report(args)?;
+        let rendered = fixture.cargo_unmaintained(&["render", "--input", &input])?;
+        ensure!(rendered.status.code() == Some(1));
+        ensure!(String::from_utf8(rendered.stdout)? == stdout);
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn deny() -> Result<()> {
+    let fixture = Fixture::new()?;
+    let input = fixture.report(&[])?;
+
+    // smoelius: An unassociated package has none of the problems that `--deny` accepts.
+    let output = fixture
+        .cargo_unmaintained(&["render", "--input", &input, "--deny", "archived", "stale"])?;
+    ensure!(output.status.code() == Some(0));
+
+    let output = fixture.cargo_unmaintained(&[
+        "render",
+        "--input",
+        &input,
+        "--deny",
+        "archived",
+        "--format=github",
+    ])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with("::warning title=renamed-dependency is unmaintained::"),
+        "{stdout}"
+    );
+
+    let output = fixture.cargo_unmaintained(&["render", "--input", &input, "--format=sarif"])?;
+    ensure!(output.status.code() == Some(1));
+    let sarif = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(sarif["runs"][0]["results"][0]["level"] == "error");
+
+    Ok(())
+}
+
+// smoelius: `tests/reports/v1.json` was produced with the schema that preceded `schema_version`.
+// If it can no longer be rendered as `tests/reports/v1.stdout`, the schema changed in a way that
+// breaks existing reports.
+#[test]
+fn previous_schema() -> Result<()> {
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args([
+            "unmaintained",
+            "render",
+            "--color=never",
+            "--input",
+            "tests/reports/v1.json",
+        ])
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout == read_to_string("tests/reports/v1.stdout")?,
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+// smoelius: The compact format's columns are a contract with shell pipelines. So the expected
+// output is kept in a fixture.
+#[test]
+fn compact() -> Result<()> {
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args([
+            "unmaintained",
+            "render",
+            "--format=compact",
+            "--input",
+            "tests/reports/compact.json",
+        ])
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout == read_to_string("tests/reports/compact.stdout")?,
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+// smoelius: The version used names a repository that no longer exists. The latest version names a
+// different repository, which was also checked. Both should be shown.
+#[test]
+fn latest_version_repository() -> Result<()> {
+    let tempdir = tempdir()?;
+    let input = tempdir.path().join("report.json");
+    write(
+        &input,
+        r#"[
+  {
+    "name": "moved",
+    "version": "1.0.0",
+    "repo_status": "Nonexistent",
+    "repository": "https://github.com/old-owner/moved",
+    "newer_major_version_available": true,
+    "outdated_deps": [],
+    "latest_version_repository": {
+      "version": "2.0.0",
+      "repo_status": "Archived",
+      "repository": "https://github.com/new-owner/moved"
+    }
+  }
+]"#,
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--color=never", "--input"])
+        .arg(&input)
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains(
+            "\nmoved (https://github.com/old-owner/moved does not exist)*\n    latest version 2.0.0 \
+             (https://github.com/new-owner/moved archived)\n"
+        ),
+        "{stdout}"
+    );
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--format=github", "--input"])
+        .arg(&input)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout
+            .contains("%0A    latest version 2.0.0 (https://github.com/new-owner/moved archived)"),
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+// smoelius: The version used names a repository that no longer exists, so the package was checked
+// against the repository named by a newer version. Both repositories should be shown.
+#[test]
+fn used_version_repository() -> Result<()> {
+    let tempdir = tempdir()?;
+    let input = tempdir.path().join("report.json");
+    write(
+        &input,
+        r#"[
+  {
+    "name": "moved",
+    "version": "1.0.0",
+    "repo_status": { "Age": 400 },
+    "repository": "https://github.com/new-owner/moved",
+    "newer_compatible_version_available": true,
+    "outdated_deps": [],
+    "used_version_repository": {
+      "repo_status": "Nonexistent",
+      "repository": "https://github.com/old-owner/moved"
+    }
+  }
+]"#,
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--color=never", "--input"])
+        .arg(&input)
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains(
+            "\nmoved (https://github.com/new-owner/moved updated 400 days ago)+\n    try `cargo \
+             update -p moved@1.0.0`\n    used version's repository is gone \
+             (https://github.com/old-owner/moved does not exist); newer versions are developed at \
+             https://github.com/new-owner/moved updated 400 days ago\n"
+        ),
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+// smoelius: `foo-cli`'s only outdated dependency is its sibling `foo-core`, so the dependency is
+// not listed. `bar`'s outdated dependencies include a non-sibling, so both are listed.
+#[test]
+fn sibling_outdated_deps() -> Result<()> {
+    let tempdir = tempdir()?;
+    let input = tempdir.path().join("report.json");
+    write(
+        &input,
+        r#"[
+  {
+    "name": "foo-cli",
+    "version": "1.0.0",
+    "repo_status": { "Age": 400 },
+    "repository": "https://github.com/owner/foo",
+    "outdated_deps": [
+      {
+        "name": "foo-core",
+        "req": "^1.0",
+        "version_used": "1.0.0",
+        "version_latest": "2.0.0",
+        "sibling": true
+      }
+    ]
+  },
+  {
+    "name": "bar",
+    "version": "1.0.0",
+    "repo_status": { "Age": 400 },
+    "repository": "https://github.com/owner/bar",
+    "outdated_deps": [
+      {
+        "name": "bar-core",
+        "req": "^1.0",
+        "version_used": "1.0.0",
+        "version_latest": "2.0.0",
+        "sibling": true
+      },
+      {
+        "name": "baz",
+        "req": "^1.0",
+        "version_used": "1.0.0",
+        "version_latest": "2.0.0"
+      }
+    ]
+  }
+]"#,
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--color=never", "--input"])
+        .arg(&input)
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains(
+            "\nfoo-cli (https://github.com/owner/foo updated 400 days ago)\n    all outdated \
+             dependencies are siblings in the same repository\n"
+        ),
+        "{stdout}"
+    );
+    ensure!(
+        stdout.contains(
+            "\nbar (https://github.com/owner/bar updated 400 days ago)\n    bar-core \
+             (requirement: ^1.0, version used: 1.0.0, latest: 2.0.0)\n    baz (requirement: ^1.0, \
+             version used: 1.0.0, latest: 2.0.0)\n"
+        ),
+        "{stdout}"
+    );
+
+    Ok(())
+}
+
+// smoelius: A package absent from the registry index has no repository status of its own. It
+// should be listed first, under its own header.
+#[test]
+fn absent_from_index() -> Result<()> {
+    let tempdir = tempdir()?;
+    let input = tempdir.path().join("report.json");
+    write(
+        &input,
+        r#"[
+  {
+    "name": "stale",
+    "version": "1.0.0",
+    "repo_status": { "Age": 400 },
+    "repository": "https://github.com/owner/stale",
+    "outdated_deps": []
+  },
+  {
+    "name": "vanished",
+    "version": "1.0.0",
+    "repo_status": "AbsentFromIndex",
+    "outdated_deps": []
+  }
+]"#,
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--color=never", "--input"])
+        .arg(&input)
+        .output()?;
+    ensure!(output.status.code() == Some(1));
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains(
+            "Absent from the registry index:\nvanished (absent from registry index)\n\nStale \
+             repositories:\nstale (https://github.com/owner/stale updated 400 days ago)\n"
+        ),
+        "{stdout}"
+    );
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--format=github", "--input"])
+        .arg(&input)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.contains("::vanished@1.0.0 (absent from registry index)"),
+        "{stdout}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::{repo_health, RepoHealthOptions, RepoHealthStatus};
+use std::{
+    env::set_var,
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git};
+
+const OLD_DATE: &str = "2015-01-01T00:00:00Z";
+
+// smoelius: `repo_health` is called as a library function, so the command-line options are never
+// parsed. A repository whose only commit is old is stale, unless `max_age` is large enough. A
+// repository that does not exist cannot be cloned.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn library() -> Result<()> {
+    let tempdir = tempdir()?;
+    set_var("XDG_CACHE_HOME", tempdir.path().join("cache"));
+    let origin = init_origin(tempdir.path())?;
+    let url = format!("file://{}", origin.display());
+
+    let health = repo_health(&url, &RepoHealthOptions::default())?;
+    ensure!(health.status == RepoHealthStatus::Stale, "{health:?}");
+    ensure!(
+        health.canonical_url == url.strip_suffix(".git").unwrap_or(&url),
+        "{health:?}"
+    );
+    ensure!(health.age.is_some(), "{health:?}");
+
+    let health = repo_health(&url, &RepoHealthOptions { max_age: 365_000 })?;
+    ensure!(health.status == RepoHealthStatus::Current, "{health:?}");
+
+    let missing = format!("file://{}", tempdir.path().join("missing.git").display());
+    let health = repo_health(&missing, &RepoHealthOptions::default())?;
+    ensure!(health.status == RepoHealthStatus::Uncloneable, "{health:?}");
+    ensure!(health.age.is_none(), "{health:?}");
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn subcommand() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let origin = init_origin(tempdir.path())?;
+    let url = format!("file://{}", origin.display());
+
+    let output = cargo_unmaintained(&cache_home, &["repo-health", &url])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.starts_with(&format!(
+            "{} (stale, updated ",
+            url.strip_suffix(".git").unwrap_or(&url)
+        )),
+        "{stdout}"
+    );
+
+    // smoelius: With a theme other than the default, severity is also indicated by a prefix, even
+    // without color.
+    let output = cargo_unmaintained(&cache_home, &["--theme=mono", "repo-health", &url])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(stdout.starts_with("! "), "{stdout}");
+
+    let output = cargo_unmaintained(
+        &cache_home,
+        &["--max-age=365000", "--json", "repo-health", &url],
+    )?;
+    ensure!(output.status.success(), "{output:#?}");
+    let health = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(health["status"] == "current", "{health}");
+
+    Ok(())
+}
+
+/// Creates a repository with one old commit, and returns the path of a bare clone of it
+fn init_origin(dir: &Path) -> Result<PathBuf> {
+    let repo = dir.join("repo");
+    let origin = dir.join("origin.git");
+    create_dir_all(&repo)?;
+    write(repo.join("README.md"), "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+            "commit",
+            "--quiet",
+            "--message=Initial commit",
+        ])
+        .env("GIT_AUTHOR_DATE", OLD_DATE)
+        .env("GIT_COMMITTER_DATE", OLD_DATE)
+        .current_dir(&repo)
+        .status()?;
+    ensure!(status.success());
+    git(
+        dir,
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    Ok(origin)
+}
+
+fn cargo_unmaintained(cache_home: &Path, args: &[&str]) -> Result<Output> {
+    cargo_unmaintained_command(Path::new("."), cache_home)
+        .arg("--token-source=none")
+        .args(args)
+        .output()
+        .map_err(Into::into)
+}
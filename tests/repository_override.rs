@@ -0,0 +1,133 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{fs::OpenOptions, io::Write, path::Path};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained, git, write_package};
+
+// smoelius: `moved`'s repository field names a repository that does not exist. Its code actually
+// lives in `moved.git`, which the workspace metadata names.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn override_nonexistent_repository() -> Result<()> {
+    let (tempdir, dependent) = create_test_workspace()?;
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache"), &[])?;
+    ensure!(output.status.code() == Some(1), "{output:#?}");
+
+    override_repository(&dependent, "moved", &tempdir.path().join("moved.git"))?;
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache"), &[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stdout}{stderr}");
+
+    // smoelius: With `--only=stale --max-age=0`, `moved` is reported, and the override is noted.
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache"),
+        &["--only=stale", "--max-age=0"],
+    )?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(
+        stdout.lines().any(|line| line.starts_with(&format!(
+            "moved (file://{} updated 0 days ago) [path dependency] (repository overridden by \
+             workspace metadata)",
+            tempdir.path().join("moved.git").display()
+        ))),
+        "{stdout}"
+    );
+    let gone = format!("file://{}", tempdir.path().join("gone.git").display());
+    ensure!(
+        stdout
+            .lines()
+            .any(|line| line == format!("    repository named in manifest: {gone}")),
+        "{stdout}"
+    );
+
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache"),
+        &["--only=stale", "--max-age=0", "--json"],
+    )?;
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    ensure!(
+        report["packages"][0]["original_repository"] == gone.as_str(),
+        "{report}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn warn_override_not_depended_upon() -> Result<()> {
+    let (tempdir, dependent) = create_test_workspace()?;
+
+    override_repository(&dependent, "absent", &tempdir.path().join("moved.git"))?;
+
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache"), &[])?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        stderr.lines().any(|line| line
+            == "warning: workspace metadata gives a repository for `absent`, but workspace does \
+                not depend upon `absent`"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+fn create_test_workspace() -> Result<(TempDir, std::path::PathBuf)> {
+    let tempdir = tempdir()?;
+
+    let repo = tempdir.path().join("moved-repo");
+    write_package(
+        &repo,
+        "moved",
+        &format!(
+            "repository = \"file://{}\"\n",
+            tempdir.path().join("gone.git").display()
+        ),
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &tempdir.path().join("moved.git").to_string_lossy(),
+        ],
+    )?;
+
+    let dependent = tempdir.path().join("dependent");
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nmoved = {{ path = \"{}\" }}\n",
+            repo.display()
+        ),
+    )?;
+
+    Ok((tempdir, dependent))
+}
+
+fn override_repository(dir: &Path, name: &str, origin: &Path) -> Result<()> {
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(dir.join("Cargo.toml"))?;
+    writeln!(
+        manifest,
+        "\n[workspace.metadata.unmaintained.repositories]\n{name} = \"file://{}\"",
+        origin.display()
+    )?;
+    Ok(())
+}
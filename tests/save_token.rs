@@ -41,7 +41,9 @@ fn save_token() {
     let stderr = String::from_utf8(output.stderr).unwrap();
     assert_eq!(
         stdout,
-        "adler (https://github.com/jonas-schievink/adler.git archived)\n"
+        "Policy: default (--max-age=365 --outdated-dep-grace=365) with built-in \
+         allowlist\nArchived or nonexistent repositories:\nadler \
+         (https://github.com/jonas-schievink/adler.git archived)\n"
     );
     assert_eq!(
         stderr,
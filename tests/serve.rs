@@ -0,0 +1,395 @@
+#![cfg(unix)]
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    fs::{copy, create_dir, write},
+    io::{Read, Write},
+    os::unix::{fs::PermissionsExt, net::UnixStream},
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{git, write_package};
+
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+// smoelius: The dependency's repository does not contain a package named `renamed-dependency`. So
+// the dependency is reported as unassociated with its repository. No network access is required.
+#[test]
+fn scan_and_healthz() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependency = tempdir.path().join("dependency");
+    let dependent = tempdir.path().join("dependent");
+    let socket = tempdir.path().join("socket");
+
+    create_dir(&repo)?;
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    create_dir(&dependency)?;
+    write_package(
+        &dependency,
+        "renamed-dependency",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+
+    create_dir(&dependent)?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nrenamed-dependency = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    let _server = Server(
+        Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "serve", "--socket"])
+            .arg(&socket)
+            .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+            .stderr(Stdio::null())
+            .spawn()?,
+    );
+    while !socket.exists() {
+        sleep(Duration::from_millis(100));
+    }
+
+    let (status, body) = request(&socket, "GET", "/healthz", "")?;
+    ensure!(status == "200 OK", "{body}");
+    ensure!(body["scans"] == 0, "{body}");
+
+    // smoelius: The server has answered a request. So the socket's permissions have been set.
+    let mode = socket.metadata()?.permissions().mode();
+    ensure!(mode & 0o777 == 0o600, "{mode:o}");
+
+    let (status, body) = request(
+        &socket,
+        "POST",
+        "/scan",
+        &serde_json::json!({ "path": dependent }).to_string(),
+    )?;
+    ensure!(status == "200 OK", "{body}");
+    ensure!(
+        body["packages"][0]["name"] == "renamed-dependency",
+        "{body}"
+    );
+    ensure!(body["metadata"]["tool_version"].is_string(), "{body}");
+
+    let (status, body) = request(&socket, "POST", "/scan", "{}")?;
+    ensure!(status == "400 Bad Request", "{body}");
+
+    let (status, body) = request(&socket, "GET", "/healthz", "")?;
+    ensure!(status == "200 OK", "{body}");
+    ensure!(body["scans"] == 1, "{body}");
+    ensure!(body["in_memory_caches"]["repository"] == 1, "{body}");
+
+    Ok(())
+}
+
+// smoelius: A web page can send requests to a loopback address. Such requests carry an `Origin`
+// header, or, after DNS rebinding, a `Host` header naming the attacker's domain.
+#[test]
+fn reject_untrusted_requests() -> Result<()> {
+    let tempdir = tempdir()?;
+    let socket = tempdir.path().join("socket");
+
+    let _server = Server(
+        Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "serve", "--socket"])
+            .arg(&socket)
+            .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+            .stderr(Stdio::null())
+            .spawn()?,
+    );
+    while !socket.exists() {
+        sleep(Duration::from_millis(100));
+    }
+
+    for host in ["localhost:8080", "127.0.0.1", "[::1]:8080"] {
+        let (status, body) = raw_request(
+            &socket,
+            &format!("GET /healthz HTTP/1.1\r\nHost: {host}\r\n\r\n"),
+        )?;
+        ensure!(status == "200 OK", "{host}: {body}");
+    }
+
+    let (status, body) = raw_request(
+        &socket,
+        "GET /healthz HTTP/1.1\r\nHost: attacker.example:8080\r\n\r\n",
+    )?;
+    ensure!(status == "403 Forbidden", "{body}");
+
+    let (status, body) = raw_request(
+        &socket,
+        "POST /scan HTTP/1.1\r\nHost: 127.0.0.1:8080\r\nOrigin: \
+         https://attacker.example\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\n{}",
+    )?;
+    ensure!(status == "403 Forbidden", "{body}");
+
+    // smoelius: The server stops reading an oversized request and closes the connection, so the
+    // client may see a reset (or a broken pipe) rather than the response.
+    for message in [
+        format!(
+            "GET /healthz HTTP/1.1\r\nX-Long: {}\r\n\r\n",
+            "x".repeat(16 * 1024)
+        ),
+        format!(
+            "GET /healthz HTTP/1.1\r\n{}\r\n",
+            "X-Header: x\r\n".repeat(1000)
+        ),
+    ] {
+        match raw_request(&socket, &message) {
+            Ok((status, body)) => ensure!(status == "400 Bad Request", "{body}"),
+            Err(error) => ensure!(
+                error
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|error| matches!(
+                        error.kind(),
+                        std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset
+                    )),
+                "{error}"
+            ),
+        }
+    }
+
+    // smoelius: The server still answers requests after rejecting the above.
+    let (status, body) = request(&socket, "GET", "/healthz", "")?;
+    ensure!(status == "200 OK", "{body}");
+
+    Ok(())
+}
+
+// smoelius: Only the first workspace imports ignores, which ignore `abandoned`. `abandoned`'s
+// repository does not exist. So `abandoned` is reported in the second workspace's scan.
+#[test]
+fn ignores_are_per_workspace() -> Result<()> {
+    let tempdir = tempdir()?;
+    let abandoned = tempdir.path().join("abandoned");
+    let ignoring = tempdir.path().join("ignoring");
+    let not_ignoring = tempdir.path().join("not-ignoring");
+    let socket = tempdir.path().join("socket");
+
+    create_dir(&abandoned)?;
+    write_package(
+        &abandoned,
+        "abandoned",
+        &format!(
+            "repository = \"file://{}\"\n",
+            tempdir.path().join("missing.git").display()
+        ),
+    )?;
+
+    for (dir, rest) in [
+        (
+            &ignoring,
+            "\n[workspace.metadata.unmaintained]\nimport_ignores_from = \"deny.toml\"\n",
+        ),
+        (&not_ignoring, ""),
+    ] {
+        create_dir(dir)?;
+        write_package(
+            dir,
+            "dependent",
+            &format!(
+                "publish = false\n\n[dependencies]\nabandoned = {{ path = \"{}\" }}\n{rest}",
+                abandoned.display()
+            ),
+        )?;
+    }
+    copy(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/deny.toml"),
+        ignoring.join("deny.toml"),
+    )?;
+
+    let _server = Server(
+        Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "serve", "--socket"])
+            .arg(&socket)
+            .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+            .stderr(Stdio::null())
+            .spawn()?,
+    );
+    while !socket.exists() {
+        sleep(Duration::from_millis(100));
+    }
+
+    let (status, body) = request(
+        &socket,
+        "POST",
+        "/scan",
+        &serde_json::json!({ "path": ignoring }).to_string(),
+    )?;
+    ensure!(status == "200 OK", "{body}");
+    ensure!(body["packages"] == serde_json::json!([]), "{body}");
+
+    let (status, body) = request(
+        &socket,
+        "POST",
+        "/scan",
+        &serde_json::json!({ "path": not_ignoring }).to_string(),
+    )?;
+    ensure!(status == "200 OK", "{body}");
+    ensure!(body["packages"][0]["name"] == "abandoned", "{body}");
+
+    Ok(())
+}
+
+// smoelius: The repository contains `first-member` but not `second-member` (see
+// tests/membership_conflict.rs). The first workspace depends upon both, so their disagreement is
+// noted. The second workspace depends upon `second-member` only, so there is nothing to disagree
+// with.
+#[test]
+fn membership_results_are_per_scan() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let both = tempdir.path().join("both");
+    let second_only = tempdir.path().join("second-only");
+    let socket = tempdir.path().join("socket");
+    let repository = format!("repository = \"file://{}\"\n", origin.display());
+
+    create_dir(&repo)?;
+    write(
+        repo.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"first\", \"second\"]\n",
+    )?;
+    for (dir, name) in [("first", "first-member"), ("second", "second_member")] {
+        create_dir(repo.join(dir))?;
+        write_package(&repo.join(dir), name, &repository)?;
+    }
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    write(
+        repo.join("second/Cargo.toml"),
+        format!(
+            "[package]\nname = \"second-member\"\nversion = \"0.1.0\"\nedition = \
+             \"2021\"\n{repository}"
+        ),
+    )?;
+
+    for (dir, names) in [
+        (&both, ["first", "second"].as_slice()),
+        (&second_only, ["second"].as_slice()),
+    ] {
+        create_dir(dir)?;
+        let dependencies = names
+            .iter()
+            .map(|name| {
+                format!(
+                    "{name}-member = {{ path = \"{}\" }}\n",
+                    repo.join(name).display()
+                )
+            })
+            .collect::<String>();
+        write_package(
+            dir,
+            "dependent",
+            &format!("publish = false\n\n[dependencies]\n{dependencies}"),
+        )?;
+    }
+
+    let _server = Server(
+        Command::new(cargo_bin("cargo-unmaintained"))
+            .args(["unmaintained", "serve", "--socket"])
+            .arg(&socket)
+            .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+            .stderr(Stdio::null())
+            .spawn()?,
+    );
+    while !socket.exists() {
+        sleep(Duration::from_millis(100));
+    }
+
+    for (dir, membership_conflict) in [(&both, true), (&second_only, false)] {
+        let (status, body) = request(
+            &socket,
+            "POST",
+            "/scan",
+            &serde_json::json!({ "path": dir }).to_string(),
+        )?;
+        ensure!(status == "200 OK", "{body}");
+        ensure!(body["packages"][0]["name"] == "second-member", "{body}");
+        // smoelius: `membership_conflict` is omitted when false.
+        ensure!(
+            body["packages"][0]["membership_conflict"]
+                .as_bool()
+                .unwrap_or_default()
+                == membership_conflict,
+            "{body}"
+        );
+    }
+
+    Ok(())
+}
+
+fn request(
+    socket: &Path,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Result<(String, serde_json::Value)> {
+    raw_request(
+        socket,
+        &format!(
+            "{method} {path} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        ),
+    )
+}
+
+fn raw_request(socket: &Path, message: &str) -> Result<(String, serde_json::Value)> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(message.as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| anyhow::anyhow!("malformed response: {response:?}"))?;
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("HTTP/1.1 "))
+        .unwrap_or_default()
+        .to_owned();
+    Ok((status, serde_json::from_str(body)?))
+}
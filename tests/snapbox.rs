@@ -17,10 +17,14 @@ use std::{
     io::{stderr, Write},
     path::{Path, PathBuf},
     process::Command,
+    time::Instant,
 };
 
 mod util;
-use util::{enabled, tee, Tee};
+use util::{
+    cache_state, check_timing_budget, enabled, record_timing, tee, timing_report_path,
+    write_timing_report, CaseTiming, Tee,
+};
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -34,6 +38,20 @@ struct Test {
     /// Repo revision; `None` (the default) means the head of the default branch
     #[serde(default)]
     rev: Option<String>,
+
+    /// Additional arguments to pass to `cargo unmaintained`
+    #[serde(default)]
+    args: Vec<String>,
+
+    /// Time to pin the scan to (see `CARGO_UNMAINTAINED_NOW`), e.g., so that the lockfile does not
+    /// appear stale
+    #[serde(default)]
+    now: Option<String>,
+
+    /// GitHub mock fixtures, mapping `owner/repo` to a `RepoFixture`; if set, GitHub's API is not
+    /// contacted, and the case is skipped unless the `testing-mock` feature is enabled
+    #[serde(default)]
+    github_fixtures: Option<toml::Table>,
 }
 
 #[cfg_attr(dylint_lib = "supplementary", allow(commented_code))]
@@ -44,8 +62,11 @@ fn snapbox() -> Result<()> {
 
     let test_cases = Path::new("tests/cases");
 
-    let test_paths = if let Ok(testname) = var("TESTNAME") {
-        vec![test_cases.join(testname).with_extension("toml")]
+    let test_paths = if let Ok(testnames) = var("TESTNAME") {
+        testnames
+            .split(',')
+            .map(|testname| test_cases.join(testname).with_extension("toml"))
+            .collect()
     } else {
         let mut read_dir = read_dir(test_cases)?;
 
@@ -59,10 +80,16 @@ fn snapbox() -> Result<()> {
         })?
     };
 
-    test_paths
+    let report_path = timing_report_path();
+
+    let timings = test_paths
         .into_par_iter()
         .panic_fuse()
-        .try_for_each(|input_path| {
+        .map(|input_path| {
+            let name = input_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
             let stderr_path = input_path.with_extension("stderr");
             let json_path = input_path.with_extension("json");
 
@@ -70,6 +97,12 @@ fn snapbox() -> Result<()> {
 
             let test: Test = toml::from_str(&raw).unwrap();
 
+            if test.github_fixtures.is_some() && !cfg!(feature = "testing-mock") {
+                #[allow(clippy::explicit_write)]
+                writeln!(stderr(), "skipping `{name}`: requires `testing-mock`").unwrap();
+                return Ok(None);
+            }
+
             // smoelius: I learned this conditional initialization trick from Solana's source code:
             // https://github.com/solana-labs/rbpf/blob/f52bfa0f4912d5f6eaa364de7c42b6ee6be50a88/src/elf.rs#L401
             let tempdir: tempfile::TempDir;
@@ -77,22 +110,7 @@ fn snapbox() -> Result<()> {
                 (Some(path), None) => PathBuf::from(path),
                 (None, Some(url)) => {
                     tempdir = tempfile::tempdir()?;
-
-                    // smoelius: Perform the checkout as a separate step so that errors that occur
-                    // in it can be ignored.
-                    let mut command = SnapboxCommand::new("git").args([
-                        "clone",
-                        "--no-checkout",
-                        &url,
-                        &tempdir.path().to_string_lossy(),
-                    ]);
-                    if test.rev.is_none() {
-                        command = command.arg("--depth=1");
-                    }
-                    command.assert().success();
-
-                    checkout(tempdir.path(), test.rev.as_deref()).unwrap();
-
+                    clone(&url, test.rev.as_deref(), tempdir.path());
                     tempdir.path().to_owned()
                 }
                 (_, _) => {
@@ -103,32 +121,55 @@ fn snapbox() -> Result<()> {
             let path_buf = dir.join("Cargo.lock");
             assert!(path_buf.exists(), "{path_buf:?} does not exist");
 
+            let cache_state = cache_state();
+            let start = Instant::now();
+
             let mut command = Command::new(cargo_bin("cargo-unmaintained"));
             command
                 .args(["unmaintained", "--color=never", "--json"])
+                .args(&test.args)
                 .current_dir(dir);
+            if let Some(now) = &test.now {
+                command.env("CARGO_UNMAINTAINED_NOW", now);
+            }
+
+            // smoelius: `mock_dir` must outlive `command`.
+            let mock_dir = tempfile::tempdir()?;
+            if let Some(github_fixtures) = &test.github_fixtures {
+                use_github_mock(&mut command, github_fixtures, mock_dir.path())?;
+            }
 
-            let stdout_actual = if enabled("VERBOSE") {
+            let (stdout_actual, stderr_actual) = if enabled("VERBOSE") {
                 // smoelius If `VERBOSE` is enabled, don't bother comparing stderr, because it won't
                 // match.
                 command.arg("--verbose");
 
                 let output = tee(command, Tee::Stdout)?;
 
-                output.captured
+                (output.captured, None)
             } else {
                 let output = command.output()?;
 
                 let stderr_actual = String::from_utf8(output.stderr)?;
 
-                // smoelius: Compare stderr before stdout so that you can see any errors that
-                // occurred.
-                assert_data_eq!(stderr_actual, Data::read_from(&stderr_path, None));
+                (output.stdout, Some(stderr_actual))
+            };
 
-                output.stdout
+            // smoelius: Record the timing before comparing outputs, so that a case that both fails
+            // and is slow is still reported.
+            let timing = CaseTiming {
+                name,
+                secs: start.elapsed().as_secs_f64(),
+                cache_state,
             };
+            record_timing(&report_path, &timing)?;
+
+            // smoelius: Compare stderr before stdout so that you can see any errors that occurred.
+            if let Some(stderr_actual) = stderr_actual {
+                assert_data_eq!(stderr_actual, Data::read_from(&stderr_path, None));
+            }
 
-            let mut json = serde_json::from_slice(&stdout_actual)?;
+            let mut json = serde_json::from_slice::<serde_json::Value>(&stdout_actual)?;
             visit_key_value_pairs(&mut json, &mut redact);
             let json_pretty = serde_json::to_string_pretty(&json).unwrap() + "\n";
 
@@ -138,8 +179,35 @@ fn snapbox() -> Result<()> {
                 assert_data_eq!(json_pretty, Data::read_from(&json_path, None));
             }
 
-            Ok(())
+            Ok(Some(timing))
         })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    write_timing_report(&report_path)?;
+
+    // smoelius: Check only this run's timings. The report may include timings from other shards.
+    check_timing_budget(&timings)
+}
+
+/// Directs `command` to use the GitHub mock with `github_fixtures`, which are written to `mock_dir`
+///
+/// The token is read from a file so that no `GITHUB_TOKEN` warning is emitted.
+fn use_github_mock(
+    command: &mut Command,
+    github_fixtures: &toml::Table,
+    mock_dir: &Path,
+) -> Result<()> {
+    let fixtures_path = mock_dir.join("fixtures.json");
+    let token_path = mock_dir.join("token");
+    write(&fixtures_path, serde_json::to_string(github_fixtures)?)?;
+    write(&token_path, "mock")?;
+    command
+        .env("CARGO_UNMAINTAINED_GITHUB_FIXTURES", fixtures_path)
+        .env("GITHUB_TOKEN_PATH", token_path);
+    Ok(())
 }
 
 static GIT_CONFIG: Lazy<tempfile::NamedTempFile> = Lazy::new(|| {
@@ -154,6 +222,23 @@ static GIT_CONFIG: Lazy<tempfile::NamedTempFile> = Lazy::new(|| {
     tempfile
 });
 
+fn clone(url: &str, rev: Option<&str>, repo_dir: &Path) {
+    // smoelius: Perform the checkout as a separate step so that errors that occur in it can be
+    // ignored.
+    let mut command = SnapboxCommand::new("git").args([
+        "clone",
+        "--no-checkout",
+        url,
+        &repo_dir.to_string_lossy(),
+    ]);
+    if rev.is_none() {
+        command = command.arg("--depth=1");
+    }
+    command.assert().success();
+
+    checkout(repo_dir, rev).unwrap();
+}
+
 fn checkout(repo_dir: &Path, rev: Option<&str>) -> Result<()> {
     for second_attempt in [false, true] {
         let mut command = Command::new("git");
@@ -208,8 +293,22 @@ fn visit_key_value_pairs(
     }
 }
 
+const VOLATILE_KEYS: &[&str] = &[
+    "Age",
+    "cache_state",
+    "days_behind",
+    "first_seen",
+    "index_age_secs",
+    "releases_behind",
+    "scan_end",
+    "scan_start",
+    "tool_version",
+    "version_latest",
+    "workspace_root",
+];
+
 fn redact(key: &str, value: &mut serde_json::Value) {
-    if key == "Age" || key == "version_latest" {
+    if VOLATILE_KEYS.contains(&key) {
         *value = serde_json::Value::Null;
     }
 }
@@ -0,0 +1,110 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{create_dir, rename, write},
+    path::Path,
+    process::Command,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git};
+
+// smoelius: The dependency's repository has an `.lfsconfig` and a `.gitattributes` that routes all
+// files through the LFS filter. On a warm run, the dependency's repository is made unfetchable (by
+// moving it). The existing clone should still be used, i.e., the dependency should not be
+// considered uncloneable.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn warm_run_uses_stale_clone() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let moved = tempdir.path().join("moved.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    write(
+        repo.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "lfs-dependency"
+version = "0.1.0"
+edition = "2021"
+repository = "file://{}"
+"#,
+            origin.display()
+        ),
+    )?;
+    create_dir(repo.join("src"))?;
+    write(repo.join("src/lib.rs"), "")?;
+    write(
+        repo.join(".lfsconfig"),
+        "[lfs]\n\turl = https://lfs.invalid/\n",
+    )?;
+    write(
+        repo.join(".gitattributes"),
+        "* filter=lfs diff=lfs merge=lfs\n",
+    )?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    create_dir(&dependent)?;
+    write(
+        dependent.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "dependent"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[dependencies]
+lfs-dependency = {{ path = "{}" }}
+"#,
+            repo.display()
+        ),
+    )?;
+    create_dir(dependent.join("src"))?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    // smoelius: `--max-age=0` causes the on-disk cache to be refreshed on every run.
+    let output = cargo_unmaintained(&dependent, &cache_home).output()?;
+    ensure!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    rename(&origin, &moved)?;
+
+    let output = cargo_unmaintained(&dependent, &cache_home).output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.success(), "{stderr}");
+    ensure!(
+        stderr.contains("using existing clone"),
+        "expected a stale-clone warning: {stderr}"
+    );
+
+    Ok(())
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path) -> Command {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    command.arg("--max-age=0");
+    command
+}
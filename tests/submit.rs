@@ -0,0 +1,248 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{anyhow, ensure, Result};
+use flate2::read::GzDecoder;
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::TcpListener,
+    path::Path,
+    process::Output,
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+const LABEL: &str = "team/app";
+
+const SERVER_TIMEOUT: Duration = Duration::from_mins(1);
+
+struct Request {
+    head: String,
+    body: Vec<u8>,
+}
+
+// smoelius: `renamed` names a repository containing no package of that name. So it is unassociated
+// with its repository, and `cargo unmaintained` exits with 1 whether or not the report is
+// submitted.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn submit() -> Result<()> {
+    let tempdir = tempdir()?;
+    let dependent = tempdir.path().join("dependent");
+    write_fixture(tempdir.path(), &dependent)?;
+
+    // smoelius: A successful submission, with the url given by the command line.
+    let (url, server) = serve(200)?;
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache-0"),
+        &[
+            &format!("--submit={url}"),
+            "--submit-header=Authorization: Bearer secret",
+            &format!("--submit-label={LABEL}"),
+        ],
+        None,
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(!stderr.contains("failed to submit"), "{stderr}");
+    let request = join(server)?;
+    ensure!(request.head.starts_with("POST / "), "{}", request.head);
+    for header in [
+        "authorization: bearer secret",
+        "content-encoding: gzip",
+        "content-type: application/json",
+    ] {
+        ensure!(
+            request.head.to_lowercase().contains(header),
+            "{}",
+            request.head
+        );
+    }
+    let mut body = String::new();
+    GzDecoder::new(request.body.as_slice()).read_to_string(&mut body)?;
+    // smoelius: The tempdir contains the workspace, the dependency's repository, and the cache.
+    ensure!(
+        !body.contains(&*tempdir.path().to_string_lossy()),
+        "local path leaked: {body}"
+    );
+    let report = serde_json::from_str::<serde_json::Value>(&body)?;
+    ensure!(report["metadata"]["workspace_root"] == LABEL, "{body}");
+    ensure!(report["packages"][0]["name"] == "renamed", "{body}");
+
+    // smoelius: A failed submission, with the url given by the environment. The failure is
+    // reported, but does not change the exit status.
+    let (url, server) = serve(500)?;
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache-1"), &[], Some(&url))?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(
+        stderr.contains(&format!(
+            "warning: failed to submit report to `{url}`: unexpected response code 500"
+        )),
+        "{stderr}"
+    );
+    join(server)?;
+
+    // smoelius: A redirect is not followed, and is reported as a failure.
+    let (url, server) = serve(307)?;
+    let output = cargo_unmaintained(&dependent, &tempdir.path().join("cache-2"), &[], Some(&url))?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stderr}");
+    ensure!(
+        stderr.contains(&format!(
+            "warning: failed to submit report to `{url}`: unexpected response code 307"
+        )),
+        "{stderr}"
+    );
+    join(server)?;
+
+    // smoelius: With `--require-submit`, a failed submission is an error.
+    let (url, server) = serve(500)?;
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache-3"),
+        &["--require-submit"],
+        Some(&url),
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(2), "{stderr}");
+    ensure!(
+        stderr.contains(&format!("Error: failed to submit report to `{url}`")),
+        "{stderr}"
+    );
+    join(server)?;
+
+    // smoelius: A malformed header is rejected before scanning, and its value is not echoed.
+    let output = cargo_unmaintained(
+        &dependent,
+        &tempdir.path().join("cache-4"),
+        &["--submit=http://127.0.0.1:1/", "--submit-header=secret"],
+        None,
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(2), "{stderr}");
+    ensure!(
+        stderr.contains("--submit-header must be of the form `Name: value`"),
+        "{stderr}"
+    );
+    ensure!(!stderr.contains("Scanning"), "{stderr}");
+    ensure!(!stderr.contains("secret"), "{stderr}");
+
+    Ok(())
+}
+
+/// Starts a server that responds to one request with `code`, and returns its url
+fn serve(code: u32) -> Result<(String, JoinHandle<Result<Request>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    let server = spawn(move || {
+        let start = Instant::now();
+        let stream = loop {
+            match listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    ensure!(start.elapsed() < SERVER_TIMEOUT, "no request was received");
+                    sleep(Duration::from_millis(100));
+                }
+                Err(error) => return Err(error.into()),
+            }
+        };
+        stream.set_nonblocking(false)?;
+        let mut reader = BufReader::new(&stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            if line.trim_end().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let content_length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .ok_or_else(|| anyhow!("request has no content length: {head}"))?;
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        // smoelius: A redirect points back at the server, which will not answer a second request.
+        let location = if (300..400).contains(&code) {
+            format!("Location: http://{}/elsewhere\r\n", listener.local_addr()?)
+        } else {
+            String::new()
+        };
+        (&stream).write_all(
+            format!(
+                "HTTP/1.1 {code} Status\r\n{location}Content-Length: 0\r\nConnection: \
+                 close\r\n\r\n"
+            )
+            .as_bytes(),
+        )?;
+        Ok(Request { head, body })
+    });
+    Ok((url, server))
+}
+
+fn join(server: JoinHandle<Result<Request>>) -> Result<Request> {
+    server
+        .join()
+        .map_err(|_| anyhow!("server thread panicked"))?
+}
+
+fn write_fixture(root: &Path, dependent: &Path) -> Result<()> {
+    let repo = root.join("repo");
+    let origin = root.join("origin.git");
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        root,
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+    let renamed = root.join("renamed");
+    write_package(
+        &renamed,
+        "renamed",
+        &format!("repository = \"file://{}\"\n", origin.display()),
+    )?;
+    write_package(
+        dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nrenamed = {{ path = \"{}\" }}\n",
+            renamed.display()
+        ),
+    )
+}
+
+fn cargo_unmaintained(
+    dir: &Path,
+    cache_home: &Path,
+    args: &[&str],
+    submit_url: Option<&str>,
+) -> Result<Output> {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    command
+        .args(args)
+        .env_remove("CARGO_UNMAINTAINED_SUBMIT_URL");
+    if let Some(url) = submit_url {
+        command.env("CARGO_UNMAINTAINED_SUBMIT_URL", url);
+    }
+    command.output().map_err(Into::into)
+}
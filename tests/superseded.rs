@@ -0,0 +1,190 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use std::{
+    fs::{create_dir, write},
+    process::Output,
+};
+use tempfile::{tempdir, TempDir};
+
+mod util;
+use util::{cargo_unmaintained_command, git};
+
+// smoelius: Each test creates a repository that no longer contains a package named `icu`, as
+// happened when `icu` was split into `icu_provider`, `icu_locid`, etc. The dependency named `icu`
+// points to that repository. No network access is required.
+struct Fixture {
+    tempdir: TempDir,
+}
+
+impl Fixture {
+    /// Creates a repository whose history is given by `commits`, each of which is a list of
+    /// (path, contents) pairs written before committing
+    fn new(commits: &[&[(&str, &str)]]) -> Result<Self> {
+        let tempdir = tempdir()?;
+        let repo = tempdir.path().join("repo");
+        let origin = tempdir.path().join("origin.git");
+        let dependency = tempdir.path().join("dependency");
+        let dependent = tempdir.path().join("dependent");
+
+        create_dir(&repo)?;
+        git(&repo, &["init", "--quiet"])?;
+        for (i, files) in commits.iter().enumerate() {
+            for (path, contents) in *files {
+                let path = repo.join(path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                write(path, contents)?;
+            }
+            git(&repo, &["add", "--all"])?;
+            git(
+                &repo,
+                &["commit", "--quiet", &format!("--message=Commit {i}")],
+            )?;
+        }
+        git(
+            tempdir.path(),
+            &[
+                "clone",
+                "--bare",
+                "--quiet",
+                &repo.to_string_lossy(),
+                &origin.to_string_lossy(),
+            ],
+        )?;
+
+        create_dir(&dependency)?;
+        create_dir(dependency.join("src"))?;
+        write(dependency.join("src/lib.rs"), "")?;
+        write(
+            dependency.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"icu\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrepository \
+                 = \"file://{}\"\n",
+                origin.display()
+            ),
+        )?;
+
+        create_dir(&dependent)?;
+        create_dir(dependent.join("src"))?;
+        write(dependent.join("src/lib.rs"), "")?;
+        write(
+            dependent.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \
+                 \"2021\"\npublish = false\n\n[dependencies]\nicu = {{ path = \"{}\" }}\n",
+                dependency.display()
+            ),
+        )?;
+
+        Ok(Self { tempdir })
+    }
+
+    fn cargo_unmaintained(&self, args: &[&str]) -> Result<Output> {
+        let output = cargo_unmaintained_command(
+            &self.tempdir.path().join("dependent"),
+            &self.tempdir.path().join("cache"),
+        )
+        .arg("--verbose")
+        .args(args)
+        .output()?;
+        ensure!(
+            output.status.code() == Some(1),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(output)
+    }
+}
+
+const SPLIT_MANIFESTS: &[(&str, &str)] = &[
+    ("Cargo.toml", "[workspace]\nmembers = [\"components/*\"]\n"),
+    (
+        "components/locid/Cargo.toml",
+        "[package]\nname = \"icu_locid\"\nversion = \"0.2.0\"\n",
+    ),
+    (
+        "components/provider/Cargo.toml",
+        "[package]\nname = \"icu_provider\"\nversion = \"0.2.0\"\n",
+    ),
+];
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn readme() -> Result<()> {
+    let fixture = Fixture::new(&[&[
+        SPLIT_MANIFESTS,
+        &[(
+            "README.md",
+            "# ICU4X\n\nThe `icu` crate has been split into `icu_locid` and `icu_provider`.\n",
+        )],
+    ]
+    .concat()])?;
+
+    let output = fixture.cargo_unmaintained(&[])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(stdout.contains("\nicu (not in file://"), "{stdout}");
+
+    let output = fixture.cargo_unmaintained(&["--detect-superseded"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        stdout.contains("\nicu (renamed or split in file://"),
+        "{stdout}"
+    );
+    ensure!(
+        stderr.contains("    `README.md` line 3: The `icu` crate has been split"),
+        "{stderr}"
+    );
+
+    // smoelius: A pattern that does not match leaves the package unassociated.
+    let output = fixture.cargo_unmaintained(&[
+        "--detect-superseded",
+        "--superseded-pattern",
+        r"{name} is retired",
+    ])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(stdout.contains("\nicu (not in file://"), "{stdout}");
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn history() -> Result<()> {
+    let fixture = Fixture::new(&[
+        &[(
+            "Cargo.toml",
+            "[package]\nname = \"icu\"\nversion = \"0.1.0\"\n",
+        )],
+        SPLIT_MANIFESTS,
+    ])?;
+
+    let output = fixture.cargo_unmaintained(&["--detect-superseded"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(
+        stdout.contains("\nicu (renamed or split in file://"),
+        "{stdout}"
+    );
+    ensure!(
+        stderr.contains("    `Cargo.toml` named `icu` before commit "),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn unrelated() -> Result<()> {
+    let fixture = Fixture::new(&[SPLIT_MANIFESTS])?;
+
+    let output = fixture.cargo_unmaintained(&["--detect-superseded"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(stdout.contains("\nicu (not in file://"), "{stdout}");
+
+    Ok(())
+}
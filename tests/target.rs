@@ -0,0 +1,123 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{fs::create_dir, path::Path, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::{git, write_package};
+
+// smoelius: Neither dependency's repository contains a package with the dependency's name. So each
+// dependency that is scanned is reported as unassociated with its repository. No network access is
+// required.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn target() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir(&repo)?;
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    for name in ["everywhere", "windows-only"] {
+        let dependency = tempdir.path().join(name);
+        create_dir(&dependency)?;
+        write_package(
+            &dependency,
+            name,
+            &format!("repository = \"file://{}\"\n", origin.display()),
+        )?;
+    }
+
+    create_dir(&dependent)?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\neverywhere = {{ path = \"{}\" \
+             }}\n\n[target.'cfg(windows)'.dependencies]\nwindows-only = {{ path = \"{}\" }}\n",
+            tempdir.path().join("everywhere").display(),
+            tempdir.path().join("windows-only").display()
+        ),
+    )?;
+
+    // smoelius: By default, the dependencies for the host are scanned. This test is ignored on
+    // Windows. So the host is not Windows.
+    let names = scanned(&dependent, &cache_home, &[])?;
+    ensure!(names == ["everywhere"], "{names:?}");
+
+    let names = scanned(&dependent, &cache_home, &["--target=all"])?;
+    ensure!(names == ["everywhere", "windows-only"], "{names:?}");
+
+    let names = scanned(
+        &dependent,
+        &cache_home,
+        &["--target=x86_64-unknown-linux-gnu"],
+    )?;
+    ensure!(names == ["everywhere"], "{names:?}");
+
+    let names = scanned(&dependent, &cache_home, &["--target=host"])?;
+    ensure!(names == ["everywhere"], "{names:?}");
+
+    let names = scanned(
+        &dependent,
+        &cache_home,
+        &["--target=x86_64-pc-windows-msvc"],
+    )?;
+    ensure!(names == ["everywhere", "windows-only"], "{names:?}");
+
+    // smoelius: With multiple targets, packages that are dependencies for any target are scanned.
+    let names = scanned(
+        &dependent,
+        &cache_home,
+        &[
+            "--target=x86_64-unknown-linux-gnu",
+            "--target=x86_64-pc-windows-msvc",
+        ],
+    )?;
+    ensure!(names == ["everywhere", "windows-only"], "{names:?}");
+
+    Ok(())
+}
+
+/// Returns the names of the packages reported when scanning `dir` with `args`
+fn scanned(dir: &Path, cache_home: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never", "--json"])
+        .args(args)
+        .env("XDG_CACHE_HOME", cache_home)
+        .current_dir(dir)
+        .output()?;
+    ensure!(
+        output.status.code() == Some(1),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+    let mut names = packages
+        .iter()
+        .filter_map(|package| package["name"].as_str().map(ToOwned::to_owned))
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
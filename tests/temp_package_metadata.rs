@@ -0,0 +1,66 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{anyhow, ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+use tempfile::tempdir;
+
+const PACKAGE: &str = "chrono@0.4.30";
+
+const CACHE: &str = "temporary package metadata (on-disk)";
+
+// smoelius: The first run resolves `PACKAGE`'s dependency graph and caches the resulting metadata.
+// The second run should reuse the cached metadata rather than run `cargo metadata` again.
+#[test]
+fn temp_package_metadata_is_cached() -> Result<()> {
+    let tempdir = tempdir()?;
+    let cache_home = tempdir.path().join("cache");
+
+    let (cold_elapsed, cold_hits, cold_misses) = run(tempdir.path(), &cache_home)?;
+    ensure!(cold_hits == 0, "{cold_hits} hits on cold run");
+    ensure!(cold_misses >= 1, "{cold_misses} misses on cold run");
+
+    let (warm_elapsed, warm_hits, _) = run(tempdir.path(), &cache_home)?;
+    ensure!(warm_hits >= 1, "{warm_hits} hits on warm run");
+    ensure!(
+        warm_elapsed < cold_elapsed,
+        "warm run ({warm_elapsed:?}) was not faster than cold run ({cold_elapsed:?})"
+    );
+
+    Ok(())
+}
+
+/// Runs `cargo unmaintained --package PACKAGE --timings` and returns the time the run took, along
+/// with the numbers of hits and misses reported for the temporary package metadata cache
+fn run(dir: &Path, cache_home: &Path) -> Result<(Duration, u64, u64)> {
+    let start = Instant::now();
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args([
+            "unmaintained",
+            "--only=outdated-deps",
+            "--timings",
+            "--package",
+            PACKAGE,
+        ])
+        .env("XDG_CACHE_HOME", cache_home)
+        .current_dir(dir)
+        .output()?;
+    let elapsed = start.elapsed();
+
+    let stderr = String::from_utf8(output.stderr)?;
+    let line = stderr
+        .lines()
+        .find_map(|line| line.trim_start().strip_prefix(&format!("{CACHE}: ")))
+        .ok_or_else(|| anyhow!("failed to find `{CACHE}` in:\n{stderr}"))?;
+    let (hits, misses) = line
+        .strip_suffix(" misses")
+        .and_then(|line| line.split_once(" hits, "))
+        .ok_or_else(|| anyhow!("unexpected line: {line}"))?;
+
+    Ok((elapsed, hits.parse()?, misses.parse()?))
+}
@@ -0,0 +1,130 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use cargo_unmaintained::github::mock::FIXTURES_VAR;
+use std::{
+    env::{join_paths, split_paths, var_os},
+    fs::{create_dir_all, remove_file, write},
+    path::Path,
+    process::Output,
+};
+use tempfile::tempdir;
+
+mod util;
+use util::cargo_unmaintained_command;
+
+// smoelius: A fake `gh` on `PATH` reports a token and records that it was run. `gh` should be
+// consulted only after the environment, and should not be consulted at all when another source is
+// pinned.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn token_source_order() -> Result<()> {
+    let tempdir = tempdir()?;
+    let bin = tempdir.path().join("bin");
+    let marker = tempdir.path().join("gh-ran");
+    let dependent = tempdir.path().join("dependent");
+
+    create_dir_all(&bin)?;
+    write_executable(
+        &bin.join("gh"),
+        &format!(
+            "#!/bin/sh\ntouch '{}'\necho mock\n",
+            marker.to_string_lossy()
+        ),
+    )?;
+
+    create_dir_all(dependent.join("src"))?;
+    write(
+        dependent.join("Cargo.toml"),
+        "[package]\nname = \"dependent\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = \
+         false\n",
+    )?;
+    write(dependent.join("src/lib.rs"), "")?;
+
+    // smoelius: An empty fixtures file enables the mock, so that any token is accepted.
+    write(tempdir.path().join("fixtures.json"), "{}")?;
+
+    let run = |args: &[&str], github_token: Option<&str>| -> Result<String> {
+        if marker.try_exists()? {
+            remove_file(&marker)?;
+        }
+        let output = cargo_unmaintained(&dependent, tempdir.path(), &bin, args, github_token)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        ensure!(output.status.success(), "{stderr}");
+        Ok(stderr)
+    };
+
+    let stderr = run(&[], None)?;
+    ensure!(
+        stderr.contains("GitHub token read from `gh auth token`\n"),
+        "{stderr}"
+    );
+    ensure!(marker.try_exists()?);
+
+    let stderr = run(&[], Some("mock"))?;
+    ensure!(
+        stderr.contains("GitHub token read from `GITHUB_TOKEN`\n"),
+        "{stderr}"
+    );
+    ensure!(!marker.try_exists()?);
+
+    let stderr = run(&["--token-source=gh"], Some("mock"))?;
+    ensure!(
+        stderr.contains("GitHub token read from `gh auth token`\n"),
+        "{stderr}"
+    );
+
+    let stderr = run(&["--token-source=none"], None)?;
+    ensure!(!stderr.contains("GitHub token read from"), "{stderr}");
+    ensure!(!stderr.contains("archival statuses"), "{stderr}");
+    ensure!(!marker.try_exists()?);
+
+    let stderr = run(&["--token-source=file"], None)?;
+    ensure!(
+        stderr.contains(
+            "warning: no token was found using --token-source=file; archival statuses will not be \
+             checked"
+        ),
+        "{stderr}"
+    );
+    ensure!(!marker.try_exists()?);
+
+    Ok(())
+}
+
+fn cargo_unmaintained(
+    dir: &Path,
+    home: &Path,
+    bin: &Path,
+    args: &[&str],
+    github_token: Option<&str>,
+) -> Result<Output> {
+    let path = var_os("PATH").unwrap_or_default();
+    let path = join_paths(std::iter::once(bin.to_path_buf()).chain(split_paths(&path)))?;
+    let mut command = cargo_unmaintained_command(dir, &home.join("cache"));
+    command
+        .arg("--verbose")
+        .args(args)
+        .env("PATH", path)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("CI", "1")
+        .env(FIXTURES_VAR, home.join("fixtures.json"))
+        .env_remove("GITHUB_TOKEN_PATH");
+    if let Some(github_token) = github_token {
+        command.env("GITHUB_TOKEN", github_token);
+    } else {
+        command.env_remove("GITHUB_TOKEN");
+    }
+    command.output().map_err(Into::into)
+}
+
+fn write_executable(path: &Path, contents: &str) -> Result<()> {
+    write(path, contents)?;
+    #[cfg(unix)]
+    {
+        use std::{fs::set_permissions, os::unix::fs::PermissionsExt};
+        set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{
+    path::Path,
+    process::{Command, Output},
+};
+use tempfile::tempdir;
+
+mod util;
+use util::{cargo_unmaintained_command, git, write_package};
+
+// smoelius: `inner-macro` is a proc-macro used both by `dependent` and by `outer-macro`, another
+// proc-macro used by `dependent`. `inner-macro` names a repository containing no package of that
+// name. So it is unassociated with its repository. `cargo tree --invert` does not print paths
+// through proc-macros used by proc-macros, which is why `--tree` computes paths itself.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn tree() -> Result<()> {
+    let tempdir = tempdir()?;
+    let repo = tempdir.path().join("repo");
+    let origin = tempdir.path().join("origin.git");
+    let dependent = tempdir.path().join("dependent");
+    let repository = format!("repository = \"file://{}\"\n", origin.display());
+
+    write_package(&repo, "original-name", "")?;
+    git(&repo, &["init", "--quiet"])?;
+    git(&repo, &["add", "."])?;
+    git(&repo, &["commit", "--quiet", "--message=Initial commit"])?;
+    git(
+        tempdir.path(),
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &repo.to_string_lossy(),
+            &origin.to_string_lossy(),
+        ],
+    )?;
+
+    let inner = tempdir.path().join("inner-macro");
+    let outer = tempdir.path().join("outer-macro");
+    write_package(
+        &inner,
+        "inner-macro",
+        &format!("{repository}\n[lib]\nproc-macro = true\n"),
+    )?;
+    write_package(
+        &outer,
+        "outer-macro",
+        &format!(
+            "{repository}\n[lib]\nproc-macro = true\n\n[dependencies]\ninner-macro = {{ path = \
+             \"{}\" }}\n",
+            inner.display()
+        ),
+    )?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\ninner-macro = {{ path = \"{}\" }}\nouter-macro = \
+             {{ path = \"{}\" }}\n",
+            inner.display(),
+            outer.display()
+        ),
+    )?;
+
+    let cache_home = tempdir.path().join("cache");
+
+    for (args, expected) in [
+        (
+            &[][..],
+            &[
+                &["dependent", "inner-macro"][..],
+                &["dependent", "outer-macro", "inner-macro"],
+            ][..],
+        ),
+        (&["--tree-paths=1"], &[&["dependent", "inner-macro"]]),
+    ] {
+        let output = cargo_unmaintained(&dependent, &cache_home, args, true)?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+        let packages = report["packages"].as_array().cloned().unwrap_or_default();
+
+        let package = packages
+            .iter()
+            .find(|package| package["name"] == "inner-macro");
+        ensure!(
+            package.is_some_and(|package| package["paths"] == serde_json::json!(expected)),
+            "{args:?}: {stdout}"
+        );
+
+        let output = cargo_unmaintained(&dependent, &cache_home, args, false)?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let stderr = String::from_utf8(output.stderr)?;
+        for path in expected {
+            ensure!(
+                stdout.contains(&format!("\n    path: {}\n", path.join(" → "))),
+                "{args:?}: {stdout}"
+            );
+        }
+        ensure!(
+            !stderr.contains("could not be printed"),
+            "{args:?}: {stderr}"
+        );
+    }
+
+    // smoelius: Without `--tree`, no paths are computed.
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never", "--json"])
+        .env("XDG_CACHE_HOME", &cache_home)
+        .current_dir(&dependent)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    ensure!(!stdout.contains("\"paths\""), "{stdout}");
+
+    Ok(())
+}
+
+fn cargo_unmaintained(dir: &Path, cache_home: &Path, args: &[&str], json: bool) -> Result<Output> {
+    let mut command = cargo_unmaintained_command(dir, cache_home);
+    command.arg("--tree");
+    if json {
+        command.arg("--json");
+    }
+    command.args(args).output().map_err(Into::into)
+}
@@ -1,10 +1,14 @@
 #![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
 #![allow(dead_code)]
 
-use anyhow::{Context, Result};
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use snapbox::cmd::cargo_bin;
 use std::{
     env::var,
+    fs::{create_dir_all, read_dir, read_to_string, write},
     io::Read,
+    path::{Path, PathBuf},
     process::{Command, ExitStatus, Stdio},
 };
 
@@ -60,7 +64,7 @@ pub fn tee(mut command: Command, which: Tee) -> Result<Output> {
 
 #[must_use]
 pub fn enabled(key: &str) -> bool {
-    var(key).map_or(false, |value| value != "0")
+    var(key).is_ok_and(|value| value != "0")
 }
 
 #[must_use]
@@ -70,3 +74,167 @@ pub fn split_at_cut_line(s: &str) -> Option<(&str, &str)> {
     s.find(CUT_LINE)
         .map(|i| (&s[..=i], &s[i + CUT_LINE.len()..]))
 }
+
+/// Runs `git` in `dir` with a fixed user name and email
+pub fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=cargo-unmaintained",
+            "-c",
+            "user.email=cargo-unmaintained@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .with_context(|| "failed to run `git`")?;
+    ensure!(status.success());
+    Ok(())
+}
+
+/// Writes a package named `name` to `dir`, with `extra` appended to its manifest
+pub fn write_package(dir: &Path, name: &str, extra: &str) -> Result<()> {
+    create_dir_all(dir.join("src"))?;
+    write(
+        dir.join("Cargo.toml"),
+        format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n{extra}"),
+    )?;
+    write(dir.join("src/lib.rs"), "")?;
+    Ok(())
+}
+
+/// Returns a command that runs `cargo unmaintained --color=never` in `dir`, with `cache_home` as
+/// `XDG_CACHE_HOME`
+#[must_use]
+pub fn cargo_unmaintained_command(dir: &Path, cache_home: &Path) -> Command {
+    let mut command = Command::new(cargo_bin("cargo-unmaintained"));
+    command
+        .args(["unmaintained", "--color=never"])
+        .env("XDG_CACHE_HOME", cache_home)
+        .current_dir(dir);
+    command
+}
+
+/// Runs `cargo unmaintained --color=never` with `args` in `dir`, with `cache_home` as
+/// `XDG_CACHE_HOME`
+pub fn cargo_unmaintained(
+    dir: &Path,
+    cache_home: &Path,
+    args: &[&str],
+) -> Result<std::process::Output> {
+    cargo_unmaintained_command(dir, cache_home)
+        .args(args)
+        .output()
+        .map_err(Into::into)
+}
+
+/// Returns the name of the file in which `cargo-unmaintained`'s on-disk cache stores package
+/// `name`'s data (e.g., its entry)
+///
+/// Mirrors `package_filename` in `src/on_disk_cache.rs`.
+#[must_use]
+pub fn package_filename(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .take(64)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let mut hash = sha1_smol::Sha1::from(name).hexdigest();
+    hash.truncate(12);
+    format!("{sanitized}-{hash}")
+}
+
+/// How long a test case took, and whether the on-disk cache was warm when it started
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CaseTiming {
+    pub name: String,
+    pub secs: f64,
+    pub cache_state: String,
+}
+
+/// Returns "warm" if `cargo-unmaintained`'s on-disk cache has entries, and "cold" otherwise
+///
+/// Mirrors how `cargo-unmaintained` determines the `cache_state` it reports.
+#[must_use]
+pub fn cache_state() -> String {
+    let cache_home = var("XDG_CACHE_HOME").map_or_else(
+        |_| Path::new(&var("HOME").unwrap_or_default()).join(".cache"),
+        PathBuf::from,
+    );
+    let warm = read_dir(cache_home.join("cargo-unmaintained/v2/entries"))
+        .is_ok_and(|mut read_dir| read_dir.next().is_some());
+    String::from(if warm { "warm" } else { "cold" })
+}
+
+/// Returns the path of the timing report, which can be set with `SNAPBOX_TIMINGS`
+///
+/// Each case's timing is written to a file of its own in a directory alongside the report, so that
+/// tests run in parallel (e.g., in separate CI shards sharing a target directory) do not clobber
+/// one another's timings.
+#[must_use]
+pub fn timing_report_path() -> PathBuf {
+    var("SNAPBOX_TIMINGS").map_or_else(
+        |_| {
+            let target_dir = var("CARGO_TARGET_DIR").unwrap_or_else(|_| String::from("target"));
+            Path::new(&target_dir).join("snapbox-timings.json")
+        },
+        PathBuf::from,
+    )
+}
+
+fn timing_dir(report_path: &Path) -> PathBuf {
+    report_path.with_extension("d")
+}
+
+pub fn record_timing(report_path: &Path, timing: &CaseTiming) -> Result<()> {
+    let dir = timing_dir(report_path);
+    create_dir_all(&dir)?;
+    write(
+        dir.join(&timing.name).with_extension("json"),
+        serde_json::to_string(timing)?,
+    )?;
+    Ok(())
+}
+
+/// Merges the recorded timings into the report, slowest first, and returns them
+pub fn write_timing_report(report_path: &Path) -> Result<Vec<CaseTiming>> {
+    let mut timings = Vec::new();
+    for entry in read_dir(timing_dir(report_path))? {
+        let path = entry?.path();
+        let contents = read_to_string(&path)?;
+        let timing = serde_json::from_str::<CaseTiming>(&contents)
+            .with_context(|| format!("failed to parse `{}`", path.display()))?;
+        timings.push(timing);
+    }
+    timings.sort_by(|lhs, rhs| rhs.secs.total_cmp(&lhs.secs));
+    write(report_path, serde_json::to_string_pretty(&timings)? + "\n")?;
+    Ok(timings)
+}
+
+/// Fails if any of `timings` exceeds `SNAPBOX_MAX_CASE_SECS`, if set
+pub fn check_timing_budget(timings: &[CaseTiming]) -> Result<()> {
+    let Ok(max_case_secs) = var("SNAPBOX_MAX_CASE_SECS") else {
+        return Ok(());
+    };
+    let max_case_secs = max_case_secs
+        .parse::<f64>()
+        .with_context(|| format!("failed to parse `SNAPBOX_MAX_CASE_SECS`: {max_case_secs}"))?;
+    let over_budget = timings
+        .iter()
+        .filter(|timing| timing.secs > max_case_secs)
+        .map(|timing| format!("{} ({:.1}s)", timing.name, timing.secs))
+        .collect::<Vec<_>>();
+    if !over_budget.is_empty() {
+        bail!(
+            "cases exceeded the budget of {max_case_secs}s: {}",
+            over_budget.join(", ")
+        );
+    }
+    Ok(())
+}
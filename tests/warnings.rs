@@ -0,0 +1,85 @@
+#![cfg_attr(dylint_lib = "general", allow(crate_wide_allow))]
+#![cfg_attr(dylint_lib = "try_io_result", allow(try_io_result))]
+
+use anyhow::{anyhow, ensure, Result};
+use snapbox::cmd::cargo_bin;
+use std::{fs::write, process::Command};
+use tempfile::tempdir;
+
+mod util;
+use util::write_package;
+
+// smoelius: `broken`'s repository url names a repository that does not exist. So checking `broken`
+// produces warnings, which should appear both on stderr and in the report.
+#[cfg_attr(target_os = "windows", ignore)]
+#[test]
+fn warnings_in_report() -> Result<()> {
+    let tempdir = tempdir()?;
+    let url = format!("file://{}", tempdir.path().join("missing.git").display());
+    let dependency = tempdir.path().join("broken");
+    let dependent = tempdir.path().join("dependent");
+
+    write_package(&dependency, "broken", &format!("repository = \"{url}\"\n"))?;
+    write_package(
+        &dependent,
+        "dependent",
+        &format!(
+            "publish = false\n\n[dependencies]\nbroken = {{ path = \"{}\" }}\n",
+            dependency.display()
+        ),
+    )?;
+
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "--color=never", "--json", "--json-metadata"])
+        .env("XDG_CACHE_HOME", tempdir.path().join("cache"))
+        .current_dir(&dependent)
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let stderr = String::from_utf8(output.stderr)?;
+    ensure!(output.status.code() == Some(1), "{stdout}{stderr}");
+
+    let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+    let warnings = report["warnings"]
+        .as_array()
+        .ok_or_else(|| anyhow!("report has no warnings array: {stdout}"))?;
+
+    // smoelius: Every warning in the report was also printed.
+    for warning in warnings {
+        let message = warning["message"].as_str().unwrap_or_default();
+        ensure!(
+            stderr.contains(&format!("warning: {message}")),
+            "{message:?} not in {stderr}"
+        );
+    }
+
+    ensure!(
+        warnings.iter().any(|warning| warning["message"]
+            .as_str()
+            .is_some_and(|message| message.starts_with(&format!("failed to clone `{url}`")))),
+        "{stdout}"
+    );
+
+    // smoelius: A warning emitted while `broken` was being checked is associated with it.
+    ensure!(
+        warnings
+            .iter()
+            .any(|warning| warning["package"] == "broken"),
+        "{stdout}"
+    );
+
+    // smoelius: Rendered as SARIF, the warnings are notifications of the scan's invocation.
+    let input = tempdir.path().join("report.json");
+    write(&input, &stdout)?;
+    let output = Command::new(cargo_bin("cargo-unmaintained"))
+        .args(["unmaintained", "render", "--format=sarif", "--input"])
+        .arg(&input)
+        .output()?;
+    let sarif = serde_json::from_slice::<serde_json::Value>(&output.stdout)?;
+    let notifications = &sarif["runs"][0]["invocations"][0]["toolExecutionNotifications"];
+    ensure!(
+        notifications.as_array().map(Vec::len) == Some(warnings.len()),
+        "{sarif}"
+    );
+
+    Ok(())
+}